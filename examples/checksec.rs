@@ -0,0 +1,134 @@
+//! Reports the hardening features of ELF files, like `checksec --file`
+//!
+//! The report is computed from the files alone, so untrusted binaries and
+//! binaries for other architectures can be audited safely. With `--verbose`
+//! the fortified functions are listed by name.
+
+use std::io::{self, BufWriter, Write};
+use std::process::ExitCode;
+
+use anyhow::{Context, Result};
+use elfling::file::ElfFile;
+use elfling::security::{Pie, Relro, SecurityReport, fortified_functions};
+
+const USAGE: &str = "\
+Usage: checksec [-v|--verbose] <file(s)>
+ Displays the hardening features of ELF files
+  -v --verbose    List the fortified functions";
+
+fn main() -> ExitCode {
+    let mut verbose = false;
+    let mut files = Vec::new();
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "-v" | "--verbose" => verbose = true,
+            _ if arg.starts_with('-') => {
+                eprintln!("checksec: unrecognized option '{arg}'");
+                eprintln!("{USAGE}");
+                return ExitCode::FAILURE;
+            }
+            _ => files.push(arg),
+        }
+    }
+    if files.is_empty() {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    }
+
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let mut status = ExitCode::SUCCESS;
+
+    for (index, path) in files.iter().enumerate() {
+        if index != 0 {
+            let _ = writeln!(out);
+        }
+        let result = std::fs::read(path)
+            .with_context(|| format!("'{path}'"))
+            .and_then(|data| {
+                let elf = ElfFile::parse(&data).with_context(|| format!("'{path}'"))?;
+                print_report(&mut out, path, &elf, verbose)
+            });
+        if let Err(err) = result {
+            let _ = out.flush();
+            eprintln!("checksec: {err:#}");
+            status = ExitCode::FAILURE;
+        }
+    }
+
+    if let Err(err) = out.flush() {
+        eprintln!("checksec: {err}");
+        status = ExitCode::FAILURE;
+    }
+    status
+}
+
+fn print_report(out: &mut impl Write, path: &str, elf: &ElfFile<'_>, verbose: bool) -> Result<()> {
+    let report = SecurityReport::from_file(elf);
+    let yes_no = |value: bool| if value { "yes" } else { "no" };
+    let path_list = |value: Option<&[u8]>| match value {
+        Some(value) => String::from_utf8_lossy(value).into_owned(),
+        None => "none".into(),
+    };
+
+    let relro = match report.relro {
+        Relro::Full => "full",
+        Relro::Partial => "partial (no BIND_NOW)",
+        Relro::None => "none",
+    };
+    let nx = match report.nx {
+        Some(true) => "enabled",
+        Some(false) => "disabled",
+        None => "not applicable",
+    };
+    let pie = match report.pie {
+        Pie::Enabled => "enabled",
+        Pie::Disabled => "disabled (ET_EXEC)",
+        Pie::SharedObject => "shared object (ET_DYN without DF_1_PIE)",
+        Pie::NotApplicable => "not applicable",
+    };
+
+    let control_flow = report.control_flow;
+    let mut properties = Vec::new();
+    for (enabled, name) in [
+        (control_flow.ibt, "IBT"),
+        (control_flow.shstk, "SHSTK"),
+        (control_flow.bti, "BTI"),
+        (control_flow.pac, "PAC"),
+    ] {
+        if enabled {
+            properties.push(name);
+        }
+    }
+
+    writeln!(out, "{path}:")?;
+    writeln!(out, "  RELRO:          {relro}")?;
+    writeln!(out, "  NX:             {nx}")?;
+    writeln!(out, "  PIE:            {pie}")?;
+    writeln!(out, "  Stack canary:   {}", yes_no(report.stack_canary))?;
+    writeln!(
+        out,
+        "  FORTIFY:        {} fortified function{}",
+        report.fortified,
+        if report.fortified == 1 { "" } else { "s" }
+    )?;
+    if verbose {
+        for name in fortified_functions(elf) {
+            writeln!(out, "                    {}", String::from_utf8_lossy(name))?;
+        }
+    }
+    writeln!(out, "  RPATH:          {}", path_list(report.rpath))?;
+    writeln!(out, "  RUNPATH:        {}", path_list(report.runpath))?;
+    writeln!(
+        out,
+        "  Control flow:   {}",
+        if properties.is_empty() {
+            "none".into()
+        } else {
+            properties.join(", ")
+        }
+    )?;
+    writeln!(out, "  Stripped:       {}", yes_no(report.stripped))?;
+
+    Ok(())
+}
@@ -0,0 +1,637 @@
+//! Compares two ELF files structurally
+//!
+//! Rather than a byte diff, which is mostly noise once anything moves, the
+//! files are compared area by area: the ELF header, the segment layout, the
+//! section lists with their attributes, the symbol tables, the dynamic
+//! entries and the notes. Sections whose contents differ are reported with
+//! a hash of each side and the offset of the first differing byte.
+//!
+//! Like `cmp`, the exit status is 0 for identical structures, 1 when a
+//! difference was found and 2 on errors.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+use std::io::{self, BufWriter, Write};
+use std::process::ExitCode;
+
+use anyhow::{Context, Result, bail};
+use elfling::dynamic::DynamicTag;
+use elfling::file::ElfFile;
+use elfling::note::{GnuNoteType, Note};
+use elfling::program_header::{
+    ProgramHeader64, SEGMENT_FLAG_EXECUTE, SEGMENT_FLAG_READ, SEGMENT_FLAG_WRITE,
+};
+use elfling::section_header::{SectionHeader64, SectionType};
+use elfling::symbol::{SectionIndex, Symbol64, SymbolType};
+
+const USAGE: &str = "\
+Usage: elfdiff [-v|--verbose] <file1> <file2>
+ Compares the structure of two ELF files
+  -v --verbose    List the symbols that only moved";
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(err) => {
+            eprintln!("elfdiff: {err:#}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Compares the files given on the command line, returning true if no
+/// difference was found
+fn run() -> Result<bool> {
+    let mut verbose = false;
+    let mut files = Vec::new();
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "-v" | "--verbose" => verbose = true,
+            _ if arg.starts_with('-') => bail!("unrecognized option '{arg}'\n{USAGE}"),
+            _ => files.push(arg),
+        }
+    }
+    let [path_a, path_b] = files.as_slice() else {
+        bail!("expected two files\n{USAGE}");
+    };
+
+    let data_a = std::fs::read(path_a).with_context(|| format!("'{path_a}'"))?;
+    let data_b = std::fs::read(path_b).with_context(|| format!("'{path_b}'"))?;
+    let a = ElfFile::parse(&data_a).with_context(|| format!("'{path_a}'"))?;
+    let b = ElfFile::parse(&data_b).with_context(|| format!("'{path_b}'"))?;
+
+    let areas = [
+        ("ELF header", compare_headers(&a, &b)),
+        ("Segments", compare_segments(&a, &b)),
+        ("Sections", compare_sections(&a, &b)),
+        (
+            "Symbols (.symtab)",
+            compare_symbols(&a, &b, SectionType::SYMTAB, verbose),
+        ),
+        (
+            "Dynamic symbols (.dynsym)",
+            compare_symbols(&a, &b, SectionType::DYNSYM, verbose),
+        ),
+        ("Dynamic entries", compare_dynamic(&a, &b)),
+        ("Notes", compare_notes(&a, &b)),
+    ];
+
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    writeln!(out, "--- {path_a}")?;
+    writeln!(out, "+++ {path_b}")?;
+
+    let mut differences = 0;
+    for (title, lines) in &areas {
+        if lines.is_empty() {
+            continue;
+        }
+        writeln!(out, "\n{title}:")?;
+        for line in lines {
+            writeln!(out, "  {line}")?;
+        }
+        differences += lines.len();
+    }
+
+    if differences == 0 {
+        writeln!(out, "\nNo structural differences")?;
+    } else {
+        writeln!(
+            out,
+            "\n{differences} difference{}",
+            if differences == 1 { "" } else { "s" }
+        )?;
+    }
+    out.flush()?;
+
+    Ok(differences == 0)
+}
+
+/// Records a difference in a field shared by both files
+fn field<T: PartialEq + Display>(lines: &mut Vec<String>, name: &str, a: T, b: T) {
+    if a != b {
+        lines.push(format!("{name}: {a} -> {b}"));
+    }
+}
+
+fn compare_headers(a: &ElfFile<'_>, b: &ElfFile<'_>) -> Vec<String> {
+    let (a, b) = (a.header(), b.header());
+    let mut lines = Vec::new();
+
+    let debug = |value: &dyn std::fmt::Debug| format!("{value:?}");
+    field(&mut lines, "class", debug(&a.class), debug(&b.class));
+    field(&mut lines, "data", debug(&a.encoding), debug(&b.encoding));
+    field(&mut lines, "OS/ABI", debug(&a.os_abi), debug(&b.os_abi));
+    field(&mut lines, "ABI version", a.abi_version, b.abi_version);
+    field(
+        &mut lines,
+        "type",
+        debug(&a.object_type),
+        debug(&b.object_type),
+    );
+    field(&mut lines, "machine", debug(&a.machine), debug(&b.machine));
+    field(&mut lines, "version", debug(&a.version), debug(&b.version));
+    field(
+        &mut lines,
+        "entry point",
+        hex(a.entry_point),
+        hex(b.entry_point),
+    );
+    field(
+        &mut lines,
+        "program headers offset",
+        hex(a.program_header_offset),
+        hex(b.program_header_offset),
+    );
+    field(
+        &mut lines,
+        "section headers offset",
+        hex(a.section_header_offset),
+        hex(b.section_header_offset),
+    );
+    field(
+        &mut lines,
+        "flags",
+        hex(a.flags.into()),
+        hex(b.flags.into()),
+    );
+    field(&mut lines, "header size", a.header_size, b.header_size);
+    field(
+        &mut lines,
+        "program header size",
+        a.program_header_entry_size,
+        b.program_header_entry_size,
+    );
+    field(
+        &mut lines,
+        "program header count",
+        a.program_header_count,
+        b.program_header_count,
+    );
+    field(
+        &mut lines,
+        "section header size",
+        a.section_header_entry_size,
+        b.section_header_entry_size,
+    );
+    field(
+        &mut lines,
+        "section header count",
+        a.section_header_count,
+        b.section_header_count,
+    );
+    field(
+        &mut lines,
+        "section names index",
+        a.section_header_string_table_index,
+        b.section_header_string_table_index,
+    );
+
+    lines
+}
+
+fn compare_segments(a: &ElfFile<'_>, b: &ElfFile<'_>) -> Vec<String> {
+    let segments_a: Vec<ProgramHeader64> = a.program_headers().collect();
+    let segments_b: Vec<ProgramHeader64> = b.program_headers().collect();
+    let mut lines = Vec::new();
+
+    for index in 0..segments_a.len().max(segments_b.len()) {
+        match (segments_a.get(index), segments_b.get(index)) {
+            (Some(a), Some(b)) => {
+                let mut changes = Vec::new();
+                field(
+                    &mut changes,
+                    "type",
+                    format!("{:?}", a.p_type),
+                    format!("{:?}", b.p_type),
+                );
+                field(&mut changes, "offset", hex(a.p_offset), hex(b.p_offset));
+                field(&mut changes, "address", hex(a.p_vaddr), hex(b.p_vaddr));
+                field(
+                    &mut changes,
+                    "physical address",
+                    hex(a.p_paddr),
+                    hex(b.p_paddr),
+                );
+                field(&mut changes, "file size", hex(a.p_filesz), hex(b.p_filesz));
+                field(&mut changes, "memory size", hex(a.p_memsz), hex(b.p_memsz));
+                field(
+                    &mut changes,
+                    "flags",
+                    segment_flags(a.p_flags),
+                    segment_flags(b.p_flags),
+                );
+                field(&mut changes, "align", hex(a.p_align), hex(b.p_align));
+
+                let name = a.p_type.as_human_string();
+                lines.extend(
+                    changes
+                        .into_iter()
+                        .map(|change| format!("[{index}] {name} {change}")),
+                );
+            }
+            (Some(segment), None) => lines.push(format!("- {}", describe_segment(index, segment))),
+            (None, Some(segment)) => lines.push(format!("+ {}", describe_segment(index, segment))),
+            (None, None) => unreachable!(),
+        }
+    }
+
+    lines
+}
+
+fn describe_segment(index: usize, phdr: &ProgramHeader64) -> String {
+    format!(
+        "[{index}] {} offset {} address {} file size {} memory size {} {}",
+        phdr.p_type.as_human_string(),
+        hex(phdr.p_offset),
+        hex(phdr.p_vaddr),
+        hex(phdr.p_filesz),
+        hex(phdr.p_memsz),
+        segment_flags(phdr.p_flags)
+    )
+}
+
+/// Returns the sections of a file by name, repeated names being numbered
+/// from their second occurrence like `.group#2`
+fn sections_by_name<'a>(elf: &ElfFile<'a>) -> Vec<(String, SectionHeader64)> {
+    let mut seen: BTreeMap<String, usize> = BTreeMap::new();
+
+    elf.section_headers()
+        .skip(1)
+        .map(|shdr| {
+            let name =
+                String::from_utf8_lossy(elf.section_name(&shdr).unwrap_or_default()).into_owned();
+            let count = seen.entry(name.clone()).or_default();
+            *count += 1;
+            let key = match *count {
+                1 => name,
+                count => format!("{name}#{count}"),
+            };
+            (key, shdr)
+        })
+        .collect()
+}
+
+fn compare_sections(a: &ElfFile<'_>, b: &ElfFile<'_>) -> Vec<String> {
+    let sections_a = sections_by_name(a);
+    let sections_b = sections_by_name(b);
+    let names_a: BTreeSet<&str> = sections_a.iter().map(|(name, _)| name.as_str()).collect();
+    let names_b: BTreeSet<&str> = sections_b.iter().map(|(name, _)| name.as_str()).collect();
+    let mut lines = Vec::new();
+
+    for (name, shdr) in &sections_a {
+        if !names_b.contains(name.as_str()) {
+            lines.push(format!("- {name} ({} bytes)", shdr.sh_size));
+        }
+    }
+    for (name, shdr) in &sections_b {
+        if !names_a.contains(name.as_str()) {
+            lines.push(format!("+ {name} ({} bytes)", shdr.sh_size));
+        }
+    }
+
+    let order_a: Vec<&str> = sections_a
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .filter(|name| names_b.contains(name))
+        .collect();
+    let order_b: Vec<&str> = sections_b
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .filter(|name| names_a.contains(name))
+        .collect();
+    if order_a != order_b {
+        lines.push("order of the common sections differs".into());
+    }
+
+    let by_name_b: BTreeMap<&str, &SectionHeader64> = sections_b
+        .iter()
+        .map(|(name, shdr)| (name.as_str(), shdr))
+        .collect();
+
+    for (name, shdr_a) in &sections_a {
+        let Some(shdr_b) = by_name_b.get(name.as_str()) else {
+            continue;
+        };
+
+        let mut changes = Vec::new();
+        field(
+            &mut changes,
+            "type",
+            format!("{:?}", shdr_a.sh_type),
+            format!("{:?}", shdr_b.sh_type),
+        );
+        field(
+            &mut changes,
+            "flags",
+            hex(shdr_a.sh_flags),
+            hex(shdr_b.sh_flags),
+        );
+        field(
+            &mut changes,
+            "address",
+            hex(shdr_a.sh_addr),
+            hex(shdr_b.sh_addr),
+        );
+        field(&mut changes, "size", shdr_a.sh_size, shdr_b.sh_size);
+        field(
+            &mut changes,
+            "entry size",
+            shdr_a.sh_entsize,
+            shdr_b.sh_entsize,
+        );
+        field(
+            &mut changes,
+            "align",
+            shdr_a.sh_addralign,
+            shdr_b.sh_addralign,
+        );
+
+        if shdr_a.sh_type != SectionType::NOBITS
+            && shdr_b.sh_type != SectionType::NOBITS
+            && let (Ok(data_a), Ok(data_b)) = (a.section_data(shdr_a), b.section_data(shdr_b))
+            && data_a != data_b
+        {
+            let first = data_a
+                .iter()
+                .zip(data_b)
+                .position(|(a, b)| a != b)
+                .unwrap_or(data_a.len().min(data_b.len()));
+            changes.push(format!(
+                "contents differ: hash {:016x} -> {:016x}, first difference at offset {}",
+                fnv1a(data_a),
+                fnv1a(data_b),
+                hex(first as u64)
+            ));
+        }
+
+        lines.extend(
+            changes
+                .into_iter()
+                .map(|change| format!("{name}: {change}")),
+        );
+    }
+
+    lines
+}
+
+/// The attributes of a symbol that are compared
+struct SymbolInfo {
+    value: u64,
+    size: u64,
+    symbol_type: String,
+    binding: String,
+    visibility: String,
+    section: String,
+}
+
+/// Returns the symbols of the first table of type `table_type` by name,
+/// repeated names being numbered from their second occurrence
+fn symbols_by_name(
+    elf: &ElfFile<'_>,
+    table_type: SectionType,
+) -> Option<BTreeMap<String, SymbolInfo>> {
+    let symtab = elf
+        .section_headers()
+        .find(|shdr| shdr.sh_type == table_type)?;
+    let symbols = elf.symbols(&symtab).ok()?;
+    let strings = elf.linked_strings(&symtab).ok()?;
+
+    let mut by_name = BTreeMap::new();
+    let mut seen: BTreeMap<String, usize> = BTreeMap::new();
+
+    for symbol in symbols.iter().skip(1) {
+        if symbol.symbol_type() == SymbolType::SECTION {
+            continue;
+        }
+
+        let name =
+            String::from_utf8_lossy(strings.get(symbol.st_name).unwrap_or_default()).into_owned();
+        let count = seen.entry(name.clone()).or_default();
+        *count += 1;
+        let key = match *count {
+            1 => name,
+            count => format!("{name}#{count}"),
+        };
+
+        by_name.insert(
+            key,
+            SymbolInfo {
+                value: symbol.st_value,
+                size: symbol.st_size,
+                symbol_type: symbol.symbol_type().as_human_string().into(),
+                binding: symbol.binding().as_human_string().into(),
+                visibility: symbol.visibility().as_human_string().into(),
+                section: symbol_section(elf, &symbol),
+            },
+        );
+    }
+
+    Some(by_name)
+}
+
+fn symbol_section(elf: &ElfFile<'_>, symbol: &Symbol64) -> String {
+    match symbol.st_shndx {
+        SectionIndex::UNDEF | SectionIndex::ABS | SectionIndex::COMMON => {
+            symbol.st_shndx.as_human_string().into()
+        }
+        ref index => elf
+            .section_header(index.as_raw() as usize)
+            .and_then(|shdr| elf.section_name(&shdr))
+            .map(|name| String::from_utf8_lossy(name).into_owned())
+            .unwrap_or_else(|| format!("[{}]", index.as_raw())),
+    }
+}
+
+fn compare_symbols(
+    a: &ElfFile<'_>,
+    b: &ElfFile<'_>,
+    table_type: SectionType,
+    verbose: bool,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    let (symbols_a, symbols_b) = match (
+        symbols_by_name(a, table_type.clone()),
+        symbols_by_name(b, table_type),
+    ) {
+        (Some(symbols_a), Some(symbols_b)) => (symbols_a, symbols_b),
+        (Some(_), None) => return vec!["- symbol table".into()],
+        (None, Some(_)) => return vec!["+ symbol table".into()],
+        (None, None) => return lines,
+    };
+
+    for (name, symbol) in &symbols_a {
+        if !symbols_b.contains_key(name) {
+            lines.push(format!(
+                "- {name} ({} {})",
+                symbol.symbol_type, symbol.section
+            ));
+        }
+    }
+    for (name, symbol) in &symbols_b {
+        if !symbols_a.contains_key(name) {
+            lines.push(format!(
+                "+ {name} ({} {})",
+                symbol.symbol_type, symbol.section
+            ));
+        }
+    }
+
+    let mut moved = 0;
+    for (name, a) in &symbols_a {
+        let Some(b) = symbols_b.get(name) else {
+            continue;
+        };
+
+        let mut changes = Vec::new();
+        field(&mut changes, "type", &a.symbol_type, &b.symbol_type);
+        field(&mut changes, "binding", &a.binding, &b.binding);
+        field(&mut changes, "visibility", &a.visibility, &b.visibility);
+        field(&mut changes, "section", &a.section, &b.section);
+        field(&mut changes, "size", a.size, b.size);
+
+        if a.value != b.value {
+            if verbose || !changes.is_empty() {
+                changes.push(format!("value: {} -> {}", hex(a.value), hex(b.value)));
+            } else {
+                moved += 1;
+            }
+        }
+
+        lines.extend(
+            changes
+                .into_iter()
+                .map(|change| format!("{name}: {change}")),
+        );
+    }
+
+    if moved != 0 {
+        lines.push(format!(
+            "{moved} symbol{} only moved (use --verbose to list them)",
+            if moved == 1 { "" } else { "s" }
+        ));
+    }
+
+    lines
+}
+
+/// Describes the dynamic entries of a file, one line each, with the strings
+/// they refer to
+fn dynamic_entries(elf: &ElfFile<'_>) -> Vec<String> {
+    let Ok(dynamic) = elf.dynamic() else {
+        return Vec::new();
+    };
+    let strings = elf.dynamic_strings().ok();
+
+    dynamic
+        .entries()
+        .take_while(|entry| entry.d_tag != DynamicTag::NULL)
+        .map(|entry| {
+            let tag = match entry.d_tag.as_human_string() {
+                "unknown" => format!("{:#x}", entry.d_tag.as_raw()),
+                name => name.to_string(),
+            };
+            let string = match entry.d_tag {
+                DynamicTag::NEEDED
+                | DynamicTag::SONAME
+                | DynamicTag::RPATH
+                | DynamicTag::RUNPATH => {
+                    strings.and_then(|strings| strings.get(u32::try_from(entry.d_val).ok()?))
+                }
+                _ => None,
+            };
+            match string {
+                Some(string) => format!("{tag} {}", String::from_utf8_lossy(string)),
+                None => format!("{tag} {}", hex(entry.d_val)),
+            }
+        })
+        .collect()
+}
+
+fn compare_dynamic(a: &ElfFile<'_>, b: &ElfFile<'_>) -> Vec<String> {
+    diff_lines(&dynamic_entries(a), &dynamic_entries(b))
+}
+
+/// Describes the notes of a file, one line each, from the note sections or
+/// from the note segments when there are no sections
+fn notes(elf: &ElfFile<'_>) -> Vec<String> {
+    let describe = |note: Note<'_>| {
+        let owner = String::from_utf8_lossy(note.name);
+        let note_type = match GnuNoteType::from_raw(note.n_type).as_human_string() {
+            name if note.name == Note::GNU && name != "unknown" => name.to_string(),
+            _ => format!("type {:#x}", note.n_type),
+        };
+        let desc: String = note.desc.iter().map(|b| format!("{b:02x}")).collect();
+        format!("{owner} {note_type}: {desc}")
+    };
+
+    let has_sections = elf
+        .section_headers()
+        .any(|shdr| shdr.sh_type == SectionType::NOTE);
+    if has_sections {
+        elf.section_notes().map(describe).collect()
+    } else {
+        elf.notes().map(describe).collect()
+    }
+}
+
+fn compare_notes(a: &ElfFile<'_>, b: &ElfFile<'_>) -> Vec<String> {
+    diff_lines(&notes(a), &notes(b))
+}
+
+/// Diffs two lists of lines, returning the removed lines prefixed with `-`
+/// and the added ones with `+`
+fn diff_lines(a: &[String], b: &[String]) -> Vec<String> {
+    // Longest common subsequence of every pair of suffixes
+    let mut common = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            common[i][j] = if a[i] == b[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            i += 1;
+            j += 1;
+        } else if j == b.len() || (i < a.len() && common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(format!("- {}", a[i]));
+            i += 1;
+        } else {
+            lines.push(format!("+ {}", b[j]));
+            j += 1;
+        }
+    }
+    lines
+}
+
+fn segment_flags(flags: u32) -> String {
+    [
+        (SEGMENT_FLAG_READ, 'R'),
+        (SEGMENT_FLAG_WRITE, 'W'),
+        (SEGMENT_FLAG_EXECUTE, 'E'),
+    ]
+    .iter()
+    .filter(|&&(flag, _)| flags & flag != 0)
+    .map(|&(_, letter)| letter)
+    .collect()
+}
+
+fn hex(value: u64) -> String {
+    format!("{value:#x}")
+}
+
+/// 64-bit FNV-1a hash, enough to tell contents apart in a report
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
@@ -0,0 +1,232 @@
+//! Lists the shared libraries ELF files depend on, like ldd
+//!
+//! Unlike ldd, the files are never run: the dependencies are resolved by
+//! reading `DT_NEEDED`, `DT_RPATH` and `DT_RUNPATH` and searching the library
+//! paths, `/etc/ld.so.cache` and the default paths the way the glibc dynamic
+//! loader does. This works on untrusted files and on files for other
+//! architectures, whose libraries can be looked up in a `--sysroot`. As the
+//! files are not loaded, no load addresses are shown.
+
+use std::collections::BTreeSet;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::process::ExitCode;
+
+use anyhow::{Context, Result, bail};
+use elfling::dependencies::{DependencyResolver, DependencyTree};
+
+const USAGE: &str = "\
+Usage: ldd [option(s)] <file(s)>
+ Displays the shared libraries ELF files depend on
+ The options are:
+  --sysroot=<dir>           Look up absolute paths below <dir>
+  -L <dirs>                 Search the colon-separated <dirs> first, like
+  --library-path=<dirs>     LD_LIBRARY_PATH
+  --no-cache                Do not search /etc/ld.so.cache
+  --platform=<name>         Expand $PLATFORM to <name>
+  --lib=<name>              Expand $LIB to <name>
+  -t --tree                 Print the dependency tree";
+
+#[derive(Debug, Default)]
+struct Options {
+    sysroot: Option<String>,
+    library_paths: Vec<String>,
+    no_cache: bool,
+    platform: Option<String>,
+    lib: Option<String>,
+    tree: bool,
+    files: Vec<String>,
+}
+
+impl Options {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut options = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            if let Some(long) = arg.strip_prefix("--") {
+                let (name, value) = match long.split_once('=') {
+                    Some((name, value)) => (name, Some(value.to_string())),
+                    None => (long, None),
+                };
+                let mut value = || -> Result<String> {
+                    match value.clone() {
+                        Some(value) => Ok(value),
+                        None => args
+                            .next()
+                            .with_context(|| format!("option '--{name}' requires an argument")),
+                    }
+                };
+                match name {
+                    "sysroot" => options.sysroot = Some(value()?),
+                    "library-path" => options.library_paths.push(value()?),
+                    "no-cache" => options.no_cache = true,
+                    "platform" => options.platform = Some(value()?),
+                    "lib" => options.lib = Some(value()?),
+                    "tree" => options.tree = true,
+                    _ => bail!("unrecognized option '{arg}'"),
+                }
+            } else if let Some(paths) = arg.strip_prefix("-L") {
+                let paths = match paths {
+                    "" => args.next().context("option '-L' requires an argument")?,
+                    paths => paths.to_string(),
+                };
+                options.library_paths.push(paths);
+            } else if arg == "-t" {
+                options.tree = true;
+            } else if arg.starts_with('-') && arg != "-" {
+                bail!("unrecognized option '{arg}'");
+            } else {
+                options.files.push(arg);
+            }
+        }
+
+        if options.files.is_empty() {
+            bail!("missing file arguments");
+        }
+        Ok(options)
+    }
+
+    fn resolver(&self) -> DependencyResolver {
+        let mut resolver = DependencyResolver::new();
+        if let Some(sysroot) = &self.sysroot {
+            resolver.sysroot(sysroot);
+        }
+        resolver
+            .library_paths(
+                self.library_paths
+                    .iter()
+                    .flat_map(|paths| paths.split(':'))
+                    .filter(|path| !path.is_empty()),
+            )
+            .use_cache(!self.no_cache);
+        if let Some(platform) = &self.platform {
+            resolver.platform(platform);
+        }
+        if let Some(lib) = &self.lib {
+            resolver.lib(lib);
+        }
+        resolver
+    }
+}
+
+fn main() -> ExitCode {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("ldd: {err}");
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let resolver = options.resolver();
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let mut status = ExitCode::SUCCESS;
+
+    for path in &options.files {
+        let result = resolver
+            .resolve(Path::new(path))
+            .with_context(|| format!("'{path}'"))
+            .and_then(|tree| {
+                if options.files.len() > 1 {
+                    writeln!(out, "{path}:")?;
+                }
+                if options.tree {
+                    print_tree(&mut out, &tree)?;
+                } else {
+                    print_list(&mut out, &tree)?;
+                }
+                Ok(tree.missing().next().is_none())
+            });
+
+        match result {
+            Ok(true) => {}
+            Ok(false) => status = ExitCode::FAILURE,
+            Err(err) => {
+                let _ = out.flush();
+                eprintln!("ldd: {err:#}");
+                status = ExitCode::FAILURE;
+            }
+        }
+    }
+
+    if let Err(err) = out.flush() {
+        eprintln!("ldd: {err}");
+        status = ExitCode::FAILURE;
+    }
+    status
+}
+
+/// Prints the libraries in load order like ldd, with the interpreter last
+fn print_list(out: &mut impl Write, tree: &DependencyTree) -> Result<()> {
+    let mut listed = BTreeSet::from([0]);
+    if let Some(interpreter) = tree.interpreter {
+        listed.insert(interpreter);
+    }
+    let mut missing = BTreeSet::new();
+
+    if tree.root().needed.is_empty() {
+        writeln!(out, "\tnot a dynamic executable")?;
+        return Ok(());
+    }
+
+    for object in &tree.objects {
+        for needed in &object.needed {
+            match needed.object {
+                Some(index) if listed.insert(index) => {
+                    let library = &tree.objects[index];
+                    writeln!(out, "\t{} => {}", needed.name, library.path.display())?;
+                }
+                Some(_) => {}
+                None if missing.insert(&needed.name) => {
+                    writeln!(out, "\t{} => not found", needed.name)?;
+                }
+                None => {}
+            }
+        }
+    }
+
+    if let Some(interpreter) = tree.interpreter {
+        writeln!(out, "\t{}", tree.objects[interpreter].path.display())?;
+    }
+    Ok(())
+}
+
+/// Prints the dependency tree, each library under the first object that
+/// needs it
+fn print_tree(out: &mut impl Write, tree: &DependencyTree) -> Result<()> {
+    let mut visited = BTreeSet::new();
+    print_subtree(out, tree, 0, 0, &mut visited)
+}
+
+fn print_subtree(
+    out: &mut impl Write,
+    tree: &DependencyTree,
+    index: usize,
+    depth: usize,
+    visited: &mut BTreeSet<usize>,
+) -> Result<()> {
+    let object = &tree.objects[index];
+    if depth == 0 {
+        writeln!(out, "{}", object.path.display())?;
+    }
+    visited.insert(index);
+
+    let indent = "    ".repeat(depth + 1);
+    for needed in &object.needed {
+        match needed.object {
+            Some(child) if visited.contains(&child) => {
+                writeln!(out, "{indent}{} (already listed)", needed.name)?;
+            }
+            Some(child) => {
+                let library = &tree.objects[child];
+                writeln!(out, "{indent}{} => {}", needed.name, library.path.display())?;
+                print_subtree(out, tree, child, depth + 1, visited)?;
+            }
+            None => writeln!(out, "{indent}{} => not found", needed.name)?,
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,483 @@
+//! Lists the symbols of ELF files, in the output format of GNU nm
+//!
+//! Symbols are shown with their value and the GNU type letter, which is
+//! uppercase for global symbols and lowercase for local ones:
+//!
+//! - `T`/`t`: code, `D`/`d`: initialized data, `R`/`r`: read-only data
+//! - `B`/`b`: uninitialized data, `C`: common symbols
+//! - `U`: undefined, `W`/`w`: weak, `V`/`v`: weak objects, the lowercase
+//!   letters marking undefined weak symbols
+//! - `i`: indirect functions, `u`: unique globals, `A`/`a`: absolute
+//!
+//! Names are demangled with `-C` when the `demangle` feature is enabled.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::process::ExitCode;
+
+use anyhow::{Context, Result, bail};
+use elfling::archive::{ARCHIVE_MAGIC, Archive, THIN_ARCHIVE_MAGIC};
+use elfling::file::ElfFile;
+use elfling::section_header::{
+    SECTION_FLAG_ALLOC, SECTION_FLAG_EXECINSTR, SECTION_FLAG_WRITE, SectionHeader64, SectionType,
+};
+use elfling::symbol::{SectionIndex, Symbol64, SymbolBinding, SymbolType};
+use elfling::version::{VersionDefinitionIter, VersionNeedIter, VersionTable};
+
+const USAGE: &str = "\
+Usage: nm [option(s)] [file(s)]
+ List symbols in [file(s)] (a.out by default).
+ The options are:
+  -C, --demangle         Decode mangled/processed symbol names
+      --no-demangle      Do not demangle low-level symbol names
+  -D, --dynamic          Display dynamic symbols instead of normal symbols
+      --defined-only     Display only defined symbols
+  -n, -v, --numeric-sort Sort symbols numerically by address
+  -p, --no-sort          Do not sort the symbols
+  -r, --reverse-sort     Reverse the sense of the sort
+  -S, --print-size       Print size of defined symbols
+      --size-sort        Sort symbols by size
+  -u, --undefined-only   Display only undefined symbols";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum SortOrder {
+    #[default]
+    Name,
+    Address,
+    Size,
+    None,
+}
+
+#[derive(Debug, Default)]
+struct Options {
+    dynamic: bool,
+    defined_only: bool,
+    undefined_only: bool,
+    print_size: bool,
+    sort: SortOrder,
+    reverse: bool,
+    demangle: bool,
+    files: Vec<String>,
+}
+
+impl Options {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut options = Self::default();
+
+        for arg in args {
+            if let Some(long) = arg.strip_prefix("--") {
+                match long {
+                    "demangle" => options.demangle = true,
+                    "no-demangle" => options.demangle = false,
+                    "dynamic" => options.dynamic = true,
+                    "defined-only" => options.defined_only = true,
+                    "undefined-only" => options.undefined_only = true,
+                    "numeric-sort" => options.sort = SortOrder::Address,
+                    "no-sort" => options.sort = SortOrder::None,
+                    "reverse-sort" => options.reverse = true,
+                    "print-size" => options.print_size = true,
+                    "size-sort" => options.sort = SortOrder::Size,
+                    _ => bail!("unrecognized option '{arg}'"),
+                }
+            } else if let Some(short) = arg.strip_prefix('-')
+                && !short.is_empty()
+            {
+                for flag in short.chars() {
+                    match flag {
+                        'C' => options.demangle = true,
+                        'D' => options.dynamic = true,
+                        'n' | 'v' => options.sort = SortOrder::Address,
+                        'p' => options.sort = SortOrder::None,
+                        'r' => options.reverse = true,
+                        'S' => options.print_size = true,
+                        'u' => options.undefined_only = true,
+                        _ => bail!("invalid option -- '{flag}'"),
+                    }
+                }
+            } else {
+                options.files.push(arg);
+            }
+        }
+
+        if options.demangle && !cfg!(feature = "demangle") {
+            bail!("demangling needs the `demangle` feature");
+        }
+        if options.defined_only && options.undefined_only {
+            bail!("cannot combine --defined-only and --undefined-only");
+        }
+        if options.files.is_empty() {
+            options.files.push("a.out".into());
+        }
+
+        Ok(options)
+    }
+}
+
+fn main() -> ExitCode {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("nm: {err}");
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let mut status = ExitCode::SUCCESS;
+
+    for path in &options.files {
+        let result = std::fs::read(path)
+            .with_context(|| format!("'{path}'"))
+            .and_then(|data| {
+                if data.starts_with(ARCHIVE_MAGIC) || data.starts_with(THIN_ARCHIVE_MAGIC) {
+                    list_archive(path, &data, &options, &mut out)
+                } else {
+                    let elf = ElfFile::parse(&data).with_context(|| path.to_string())?;
+                    if options.files.len() > 1 {
+                        writeln!(out, "\n{path}:")?;
+                    }
+                    list_symbols(path, &elf, &options, &mut out)
+                }
+            });
+
+        if let Err(err) = result {
+            let _ = out.flush();
+            eprintln!("nm: {err:#}");
+            status = ExitCode::FAILURE;
+        }
+    }
+
+    if out.flush().is_err() {
+        status = ExitCode::FAILURE;
+    }
+    status
+}
+
+/// Lists the symbols of every member of an archive, each preceded by its name
+fn list_archive(path: &str, data: &[u8], options: &Options, out: &mut impl Write) -> Result<()> {
+    let archive = Archive::parse(data).with_context(|| path.to_string())?;
+
+    for member in archive.members() {
+        let member = member.with_context(|| path.to_string())?;
+        let name = String::from_utf8_lossy(member.name);
+        writeln!(out, "\n{name}:")?;
+
+        let contents = member
+            .read(Path::new(path))
+            .with_context(|| format!("{path}({name})"))?;
+        let elf = ElfFile::parse(&contents).with_context(|| format!("{path}({name})"))?;
+        list_symbols(&name, &elf, options, &mut *out)?;
+    }
+
+    Ok(())
+}
+
+/// A symbol as listed
+struct Entry<'a> {
+    name: &'a [u8],
+    /// Version suffix, with its `@` or `@@` separator
+    version: Option<String>,
+    value: u64,
+    size: u64,
+    letter: char,
+}
+
+impl Entry<'_> {
+    fn is_undefined(&self) -> bool {
+        matches!(self.letter, 'U' | 'w' | 'v')
+    }
+}
+
+fn list_symbols(
+    name: &str,
+    elf: &ElfFile<'_>,
+    options: &Options,
+    out: &mut impl Write,
+) -> Result<()> {
+    let table_type = if options.dynamic {
+        SectionType::DYNSYM
+    } else {
+        SectionType::SYMTAB
+    };
+    let symtab = elf
+        .section_headers()
+        .find(|shdr| shdr.sh_type == table_type);
+    let symbols = symtab
+        .as_ref()
+        .map(|symtab| elf.symbols(symtab))
+        .transpose()?;
+
+    // The first entry of a symbol table is a placeholder
+    let (Some(symtab), Some(symbols)) = (symtab, symbols.filter(|symbols| symbols.len() > 1))
+    else {
+        out.flush()?;
+        eprintln!("nm: {name}: no symbols");
+        return Ok(());
+    };
+
+    let strings = elf.linked_strings(&symtab)?;
+    let versions = if options.dynamic {
+        SymbolVersions::new(elf)
+    } else {
+        SymbolVersions::default()
+    };
+
+    let mut entries = Vec::new();
+    for (index, symbol) in symbols.iter().enumerate().skip(1) {
+        if matches!(symbol.symbol_type(), SymbolType::SECTION | SymbolType::FILE) {
+            continue;
+        }
+
+        let entry = Entry {
+            name: strings.get(symbol.st_name).unwrap_or(b"<corrupt>"),
+            version: versions.suffix(index, &symbol),
+            // Like BFD, common symbols show their size rather than their
+            // alignment
+            value: if symbol.st_shndx == SectionIndex::COMMON {
+                symbol.st_size
+            } else {
+                symbol.st_value
+            },
+            size: symbol.st_size,
+            letter: type_letter(elf, &symbol),
+        };
+
+        if (options.defined_only && entry.is_undefined())
+            || (options.undefined_only && !entry.is_undefined())
+        {
+            continue;
+        }
+        entries.push(entry);
+    }
+
+    match options.sort {
+        SortOrder::Name => entries.sort_by(|a, b| a.name.cmp(b.name)),
+        SortOrder::Address => entries.sort_by(|a, b| {
+            b.is_undefined()
+                .cmp(&a.is_undefined())
+                .then(a.value.cmp(&b.value))
+                .then(a.name.cmp(b.name))
+        }),
+        SortOrder::Size => {
+            entries.retain(|entry| !entry.is_undefined() && entry.size != 0);
+            entries.sort_by(|a, b| a.size.cmp(&b.size).then(a.name.cmp(b.name)));
+        }
+        SortOrder::None => {}
+    }
+    if options.reverse {
+        entries.reverse();
+    }
+
+    for entry in &entries {
+        if entry.is_undefined() {
+            write!(out, "{:16}", "")?;
+        } else if options.sort == SortOrder::Size && !options.print_size {
+            write!(out, "{:016x}", entry.size)?;
+        } else {
+            write!(out, "{:016x}", entry.value)?;
+            if options.print_size && entry.size != 0 {
+                write!(out, " {:016x}", entry.size)?;
+            }
+        }
+
+        let name = display_name(entry.name, options.demangle);
+        writeln!(
+            out,
+            " {} {name}{}",
+            entry.letter,
+            entry.version.as_deref().unwrap_or_default()
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Returns the GNU nm type letter of a symbol
+fn type_letter(elf: &ElfFile<'_>, symbol: &Symbol64) -> char {
+    let binding = symbol.binding();
+    let symbol_type = symbol.symbol_type();
+    let is_weak = binding == SymbolBinding::WEAK;
+    let is_object = symbol_type == SymbolType::OBJECT;
+
+    match symbol.st_shndx {
+        SectionIndex::COMMON => return 'C',
+        SectionIndex::UNDEF => {
+            return match (is_weak, is_object) {
+                (true, true) => 'v',
+                (true, false) => 'w',
+                (false, _) => 'U',
+            };
+        }
+        _ => {}
+    }
+
+    if symbol_type == SymbolType::GNU_IFUNC {
+        return 'i';
+    }
+    if is_weak {
+        return if is_object { 'V' } else { 'W' };
+    }
+    if binding == SymbolBinding::GNU_UNIQUE {
+        return 'u';
+    }
+
+    let letter = match symbol.st_shndx {
+        SectionIndex::ABS => 'a',
+        ref index => match elf.section_header(index.as_raw() as usize) {
+            Some(shdr) if index.as_raw() < SectionIndex::LORESERVE.as_raw() => {
+                section_letter(elf, &shdr)
+            }
+            _ => '?',
+        },
+    };
+
+    match binding {
+        SymbolBinding::GLOBAL => letter.to_ascii_uppercase(),
+        SymbolBinding::LOCAL => letter,
+        _ => '?',
+    }
+}
+
+/// Returns the lowercase type letter of the symbols defined in a section
+fn section_letter(elf: &ElfFile<'_>, shdr: &SectionHeader64) -> char {
+    let flags = shdr.sh_flags;
+    let has_contents = shdr.sh_type != SectionType::NOBITS;
+
+    if flags & SECTION_FLAG_ALLOC != 0 {
+        if flags & SECTION_FLAG_EXECINSTR != 0 {
+            return 't';
+        }
+        if has_contents {
+            return if flags & SECTION_FLAG_WRITE == 0 {
+                'r'
+            } else {
+                'd'
+            };
+        }
+    }
+
+    if !has_contents {
+        return 'b';
+    }
+
+    let name = elf.section_name(shdr).unwrap_or_default();
+    let is_debug = [&b".debug"[..], b".zdebug", b".stab", b".line"]
+        .iter()
+        .any(|prefix| name.starts_with(prefix));
+    if is_debug {
+        'N'
+    } else if flags & SECTION_FLAG_WRITE == 0 {
+        'n'
+    } else {
+        '?'
+    }
+}
+
+/// Versions of the dynamic symbols, from the GNU symbol versioning sections
+#[derive(Default)]
+struct SymbolVersions<'a> {
+    versions: Option<VersionTable<'a>>,
+    /// Defined versions by index, with the string table offset of their name
+    defined: BTreeMap<u16, (u32, &'a [u8])>,
+    /// Versions needed from other objects, by index
+    needed: BTreeMap<u16, &'a [u8]>,
+}
+
+impl<'a> SymbolVersions<'a> {
+    fn new(elf: &ElfFile<'a>) -> Self {
+        let mut versions = Self::default();
+
+        for shdr in elf.section_headers() {
+            let Ok(data) = elf.section_data(&shdr) else {
+                continue;
+            };
+            let count = shdr.sh_info as usize;
+
+            match shdr.sh_type {
+                SectionType::GNU_VERSYM => versions.versions = Some(VersionTable::new(data)),
+                SectionType::GNU_VERDEF => {
+                    let Ok(strings) = elf.linked_strings(&shdr) else {
+                        continue;
+                    };
+                    for definition in VersionDefinitionIter::new(data, count, strings) {
+                        if let Some(name) = definition.name
+                            && let Some((aux, _)) = definition.auxiliary.clone().next()
+                        {
+                            versions
+                                .defined
+                                .insert(definition.definition.vd_ndx, (aux.vda_name, name));
+                        }
+                    }
+                }
+                SectionType::GNU_VERNEED => {
+                    let Ok(strings) = elf.linked_strings(&shdr) else {
+                        continue;
+                    };
+                    for need in VersionNeedIter::new(data, count, strings) {
+                        for (aux, name) in need.auxiliary {
+                            if let Some(name) = name {
+                                versions.needed.insert(aux.vna_other, name);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        versions
+    }
+
+    /// Returns the version suffix of the symbol at `index`: `@@VERSION` for
+    /// the default version of a definition and `@VERSION` otherwise
+    fn suffix(&self, index: usize, symbol: &Symbol64) -> Option<String> {
+        let version = self.versions.as_ref()?.get(index)?;
+        if version.index() <= 1 {
+            return None;
+        }
+
+        if !symbol.is_undefined()
+            && let Some(&(offset, name)) = self.defined.get(&version.index())
+        {
+            // The symbols naming the versions themselves are shown bare
+            if offset == symbol.st_name {
+                return None;
+            }
+            let separator = if version.is_hidden() { "@" } else { "@@" };
+            return Some(format!("{separator}{}", String::from_utf8_lossy(name)));
+        }
+
+        let name = self.needed.get(&version.index())?;
+        Some(format!("@{}", String::from_utf8_lossy(name)))
+    }
+}
+
+/// Returns the name to show for a symbol, demangling it when requested
+///
+/// A version suffix in the name is kept as is.
+fn display_name(name: &[u8], demangle: bool) -> Cow<'_, str> {
+    if demangle {
+        let (base, version) = match name.iter().position(|&b| b == b'@') {
+            Some(at) => name.split_at(at),
+            None => (name, &b""[..]),
+        };
+        if let Some(demangled) = demangle_name(base) {
+            return Cow::Owned(demangled + &String::from_utf8_lossy(version));
+        }
+    }
+    String::from_utf8_lossy(name)
+}
+
+#[cfg(feature = "demangle")]
+fn demangle_name(name: &[u8]) -> Option<String> {
+    elfling::symbol::demangle(name)
+}
+
+#[cfg(not(feature = "demangle"))]
+fn demangle_name(_name: &[u8]) -> Option<String> {
+    None
+}
@@ -1,100 +1,2251 @@
-use anyhow::{Result, anyhow, bail};
-use elfling::Header64;
-use std::fs::File;
-use zerocopy::FromBytes;
+//! Displays information about ELF files, in the output format of GNU readelf
+//!
+//! The supported options are a subset of readelf's: `-h`, `-l`, `-S`, `-s`,
+//! `--dyn-syms`, `-r`, `-d`, `-n`, `-V`, `-A`, `-x` and `-W`, with `-a`
+//! standing for all of them but `-x`. Their output follows the layout of GNU
+//! readelf closely enough for the two to be diffed, except that `-a` leaves
+//! out the section groups, unwind information and bucket list histogram.
+//! Archive members are displayed one after the other.
 
-fn main() -> Result<()> {
-    let path = std::env::args()
-        .skip(1)
-        .next()
-        .ok_or(anyhow!("Please provide a file path"))?;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::process::ExitCode;
 
-    let elf_file = File::open(path)?;
+use anyhow::{Context, Result, bail};
+use elfling::archive::{ARCHIVE_MAGIC, Archive, THIN_ARCHIVE_MAGIC};
+use elfling::dynamic::{
+    DYNAMIC_FLAG_1_PIE, DYNAMIC_FLAG_BIND_NOW, DYNAMIC_FLAG_ORIGIN, DYNAMIC_FLAG_STATIC_TLS,
+    DYNAMIC_FLAG_SYMBOLIC, DYNAMIC_FLAG_TEXTREL, Dynamic64, DynamicTag,
+};
+use elfling::file::ElfFile;
+use elfling::header::{Machine, ObjectType, OsAbi};
+use elfling::note::{CoreNoteType, GnuNoteType, Note, NoteIter};
+use elfling::program_header::{
+    ProgramHeader64, ProgramType, SEGMENT_FLAG_EXECUTE, SEGMENT_FLAG_READ, SEGMENT_FLAG_WRITE,
+};
+use elfling::relocation::{RelTable, RelaTable, relocation_type_name};
+use elfling::section_header::{
+    SECTION_FLAG_ALLOC, SECTION_FLAG_COMPRESSED, SECTION_FLAG_EXCLUDE, SECTION_FLAG_EXECINSTR,
+    SECTION_FLAG_GROUP, SECTION_FLAG_INFO_LINK, SECTION_FLAG_LINK_ORDER, SECTION_FLAG_MERGE,
+    SECTION_FLAG_OS_NONCONFORMING, SECTION_FLAG_STRINGS, SECTION_FLAG_TLS, SECTION_FLAG_WRITE,
+    SectionHeader64, SectionType,
+};
+use elfling::string_table::StringTable;
+use elfling::symbol::{SectionIndex, Symbol64, SymbolBinding, SymbolType};
+use elfling::table::Table;
+use elfling::version::{
+    VERSION_FLAG_BASE, VERSION_FLAG_WEAK, VersionDefinitionIter, VersionIndex, VersionNeedIter,
+    VersionTable,
+};
 
-    let header = Header64::read_from_io(elf_file)?;
+const USAGE: &str = "\
+Usage: readelf <option(s)> elf-file(s)
+ Display information about the contents of ELF format files
+ Options are:
+  -a --all               Equivalent to: -h -l -S -s -r -d -V -A -n
+  -h --file-header       Display the ELF file header
+  -l --program-headers   Display the program headers
+     --segments          An alias for --program-headers
+  -S --section-headers   Display the sections' header
+     --sections          An alias for --section-headers
+  -e --headers           Equivalent to: -h -l -S
+  -s --syms              Display the symbol table
+     --symbols           An alias for --syms
+     --dyn-syms          Display the dynamic symbol table
+  -n --notes             Display the core notes (if present)
+  -r --relocs            Display the relocations (if present)
+  -d --dynamic           Display the dynamic section (if present)
+  -V --version-info      Display the version sections (if present)
+  -A --arch-specific     Display architecture specific information (if any)
+  -x --hex-dump=<number|name>
+                         Dump the contents of section <number|name> as bytes
+  -W --wide              Allow output width to exceed 80 characters";
 
-    if !header.magic.is_valid() {
-        bail!("Invalid magic!")
+/// Section flag of the GNU ABI keeping a section from garbage collection
+const SECTION_FLAG_GNU_RETAIN: u64 = 0x200000;
+/// Section flag of the GNU ABI binding a section to a memory policy
+const SECTION_FLAG_GNU_MBIND: u64 = 0x1000000;
+/// Section flag of the x86-64 psABI marking sections beyond 2 GiB
+const SECTION_FLAG_X86_64_LARGE: u64 = 0x10000000;
+const SECTION_FLAG_MASKOS: u64 = 0x0ff00000;
+const SECTION_FLAG_MASKPROC: u64 = 0xf0000000;
+
+/// Section type of the RISC-V attributes (`.riscv.attributes`)
+const SECTION_TYPE_RISCV_ATTRIBUTES: u32 = 0x70000003;
+
+#[derive(Debug, Default)]
+struct Options {
+    file_header: bool,
+    segments: bool,
+    sections: bool,
+    symbols: bool,
+    dynamic_symbols: bool,
+    relocations: bool,
+    dynamic: bool,
+    notes: bool,
+    versions: bool,
+    arch_specific: bool,
+    wide: bool,
+    hex_dumps: Vec<String>,
+    files: Vec<String>,
+}
+
+impl Options {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut options = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            if let Some(long) = arg.strip_prefix("--") {
+                let (name, value) = match long.split_once('=') {
+                    Some((name, value)) => (name, Some(value.to_string())),
+                    None => (long, None),
+                };
+                match name {
+                    "all" => options.all(),
+                    "file-header" => options.file_header = true,
+                    "program-headers" | "segments" => options.segments = true,
+                    "section-headers" | "sections" => options.sections = true,
+                    "headers" => options.headers(),
+                    "syms" | "symbols" => options.symbols = true,
+                    "dyn-syms" => options.dynamic_symbols = true,
+                    "relocs" => options.relocations = true,
+                    "dynamic" => options.dynamic = true,
+                    "notes" => options.notes = true,
+                    "version-info" => options.versions = true,
+                    "arch-specific" => options.arch_specific = true,
+                    "wide" => options.wide = true,
+                    "hex-dump" => {
+                        let section = value
+                            .or_else(|| args.next())
+                            .context("option '--hex-dump' requires an argument")?;
+                        options.hex_dumps.push(section);
+                    }
+                    _ => bail!("unrecognized option '{arg}'"),
+                }
+            } else if let Some(short) = arg.strip_prefix('-')
+                && !short.is_empty()
+            {
+                for (position, flag) in short.char_indices() {
+                    match flag {
+                        'a' => options.all(),
+                        'h' => options.file_header = true,
+                        'l' => options.segments = true,
+                        'S' => options.sections = true,
+                        'e' => options.headers(),
+                        's' => options.symbols = true,
+                        'r' => options.relocations = true,
+                        'd' => options.dynamic = true,
+                        'n' => options.notes = true,
+                        'V' => options.versions = true,
+                        'A' => options.arch_specific = true,
+                        'W' => options.wide = true,
+                        'x' => {
+                            let rest = &short[position + 1..];
+                            let section = if rest.is_empty() {
+                                args.next().context("option requires an argument -- 'x'")?
+                            } else {
+                                rest.to_string()
+                            };
+                            options.hex_dumps.push(section);
+                            break;
+                        }
+                        _ => bail!("invalid option -- '{flag}'"),
+                    }
+                }
+            } else {
+                options.files.push(arg);
+            }
+        }
+
+        Ok(options)
     }
 
-    println!("ELF Header:");
+    fn all(&mut self) {
+        self.headers();
+        self.symbols = true;
+        self.relocations = true;
+        self.dynamic = true;
+        self.notes = true;
+        self.versions = true;
+        self.arch_specific = true;
+    }
 
-    println!(
-        "  Class:                             {}",
-        header.class.as_human_string()
-    );
+    fn headers(&mut self) {
+        self.file_header = true;
+        self.segments = true;
+        self.sections = true;
+    }
 
-    println!(
-        "  Data:                              {}",
-        header.encoding.as_human_string()
-    );
+    fn has_display(&self) -> bool {
+        self.file_header
+            || self.segments
+            || self.sections
+            || self.symbols
+            || self.dynamic_symbols
+            || self.relocations
+            || self.dynamic
+            || self.notes
+            || self.versions
+            || self.arch_specific
+            || !self.hex_dumps.is_empty()
+    }
+}
 
-    println!(
-        "  Version:                           {}",
-        header.header_version.as_human_string()
-    );
+fn main() -> ExitCode {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("readelf: {err}");
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
 
-    println!(
-        "  OS/ABI:                            {}",
-        header.os_abi.as_human_string()
-    );
+    if !options.has_display() || options.files.is_empty() {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    }
 
-    println!(
-        "  ABI Version:                       {}",
-        header.abi_version
-    );
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let mut status = ExitCode::SUCCESS;
 
-    println!(
-        "  Type:                              {}",
-        header.object_type.as_human_string()
-    );
+    for path in &options.files {
+        if options.files.len() > 1 && writeln!(out, "\nFile: {path}").is_err() {
+            break;
+        }
 
-    println!(
-        "  Machine:                           {}",
-        header.machine.as_human_string()
-    );
+        let result = std::fs::read(path)
+            .with_context(|| format!("'{path}'"))
+            .and_then(|data| {
+                if data.starts_with(ARCHIVE_MAGIC) || data.starts_with(THIN_ARCHIVE_MAGIC) {
+                    process_archive(path, &data, &options, &mut out)
+                } else {
+                    let elf = ElfFile::parse(&data).with_context(|| format!("'{path}'"))?;
+                    ReadElf::new(elf, &options, &mut out).run()
+                }
+            });
 
-    println!(
-        "  Version:                           {}",
-        header.version.as_human_string()
-    );
-    println!(
-        "  Entry point address:               0x{:x}",
-        header.entry_point
-    );
-    println!(
-        "  Start of program headers:          {}",
-        header.program_header_offset
-    );
-    println!(
-        "  Start of section headers:          {}",
-        header.section_header_offset
-    );
-    println!("  Flags:                             0x{:x}", header.flags);
-    println!(
-        "  Size of this header:               {}",
-        header.header_size
-    );
-    println!(
-        "  Size of program headers:           {}",
-        header.program_header_entry_size
-    );
-    println!(
-        "  Number of program headers:         {}",
-        header.program_header_count
-    );
-    println!(
-        "  Size of section headers:           {}",
-        header.section_header_entry_size
-    );
-    println!(
-        "  Number of section headers:         {}",
-        header.section_header_count
-    );
-    println!(
-        "  Section header string table index: {}",
-        header.section_header_string_table_index
-    );
+        if let Err(err) = result {
+            let _ = out.flush();
+            eprintln!("readelf: Error: {err:#}");
+            status = ExitCode::FAILURE;
+        }
+    }
+
+    if out.flush().is_err() {
+        status = ExitCode::FAILURE;
+    }
+    status
+}
+
+/// Displays every member of an archive, each preceded by its name
+fn process_archive(path: &str, data: &[u8], options: &Options, out: &mut impl Write) -> Result<()> {
+    let archive = Archive::parse(data).with_context(|| format!("'{path}'"))?;
+
+    for member in archive.members() {
+        let member = member.with_context(|| format!("'{path}'"))?;
+        let name = show(member.name);
+        writeln!(out, "\nFile: {path}({name})")?;
+
+        let contents = member
+            .read(Path::new(path))
+            .with_context(|| format!("'{path}({name})'"))?;
+        let elf = ElfFile::parse(&contents).with_context(|| format!("'{path}({name})'"))?;
+        ReadElf::new(elf, options, &mut *out).run()?;
+    }
 
     Ok(())
 }
+
+/// A symbol version as shown after a symbol name
+enum SymbolVersion<'a> {
+    /// A version defined by the object, hidden unless it is the default one
+    Defined { name: &'a [u8], hidden: bool },
+    /// A version required from another object
+    Needed { name: &'a [u8], index: u16 },
+}
+
+struct ReadElf<'a, 'o, W> {
+    elf: ElfFile<'a>,
+    options: &'o Options,
+    out: W,
+    /// Version table of the dynamic symbols (`.gnu.version`)
+    versions: Option<VersionTable<'a>>,
+    /// Names of the versions defined by the object and their string table
+    /// offsets, by index
+    defined_versions: BTreeMap<u16, (u32, &'a [u8])>,
+    /// Names of the versions required from other objects, by index
+    needed_versions: BTreeMap<u16, &'a [u8]>,
+}
+
+impl<'a, 'o, W: Write> ReadElf<'a, 'o, W> {
+    fn new(elf: ElfFile<'a>, options: &'o Options, out: W) -> Self {
+        let mut readelf = Self {
+            elf,
+            options,
+            out,
+            versions: None,
+            defined_versions: BTreeMap::new(),
+            needed_versions: BTreeMap::new(),
+        };
+        readelf.load_versions();
+        readelf
+    }
+
+    fn load_versions(&mut self) {
+        for shdr in self.elf.section_headers() {
+            let Ok(data) = self.elf.section_data(&shdr) else {
+                continue;
+            };
+
+            match shdr.sh_type {
+                SectionType::GNU_VERSYM => self.versions = Some(VersionTable::new(data)),
+                SectionType::GNU_VERDEF => {
+                    let Ok(strings) = self.elf.linked_strings(&shdr) else {
+                        continue;
+                    };
+                    for definition in
+                        VersionDefinitionIter::new(data, shdr.sh_info as usize, strings)
+                    {
+                        if let Some(name) = definition.name
+                            && let Some((aux, _)) = definition.auxiliary.clone().next()
+                        {
+                            self.defined_versions
+                                .insert(definition.definition.vd_ndx, (aux.vda_name, name));
+                        }
+                    }
+                }
+                SectionType::GNU_VERNEED => {
+                    let Ok(strings) = self.elf.linked_strings(&shdr) else {
+                        continue;
+                    };
+                    for need in VersionNeedIter::new(data, shdr.sh_info as usize, strings) {
+                        for (aux, name) in need.auxiliary {
+                            if let Some(name) = name {
+                                self.needed_versions.insert(aux.vna_other, name);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn run(&mut self) -> Result<()> {
+        let options = self.options;
+
+        if options.file_header {
+            self.file_header()?;
+        }
+        if options.sections {
+            self.section_headers()?;
+        }
+        if options.segments {
+            self.program_headers()?;
+        }
+        if options.dynamic {
+            self.dynamic_section()?;
+        }
+        if options.relocations {
+            self.relocations()?;
+        }
+        if options.symbols || options.dynamic_symbols {
+            self.symbol_tables()?;
+        }
+        if options.versions {
+            self.version_sections()?;
+        }
+        if !options.hex_dumps.is_empty() {
+            self.hex_dumps()?;
+        }
+        if options.notes {
+            self.notes()?;
+        }
+        if options.arch_specific {
+            self.arch_specific()?;
+        }
+
+        Ok(())
+    }
+
+    fn machine(&self) -> &Machine {
+        &self.elf.header().machine
+    }
+
+    fn section_name(&self, shdr: &SectionHeader64) -> Cow<'a, str> {
+        show(self.elf.section_name(shdr).unwrap_or(b"<corrupt>"))
+    }
+
+    fn section_name_by_index(&self, index: u32) -> Cow<'a, str> {
+        match self.elf.section_header(index as usize) {
+            Some(shdr) => self.section_name(&shdr),
+            None => Cow::Borrowed("<corrupt>"),
+        }
+    }
+
+    fn is_pie(&self) -> bool {
+        self.elf.dynamic().is_ok_and(|dynamic| {
+            dynamic
+                .find(DynamicTag::FLAGS_1)
+                .is_some_and(|flags| flags & DYNAMIC_FLAG_1_PIE != 0)
+        })
+    }
+
+    fn file_type(&self) -> Cow<'static, str> {
+        let object_type = &self.elf.header().object_type;
+        match *object_type {
+            ObjectType::DYN if self.is_pie() => "DYN (Position-Independent Executable file)".into(),
+            ObjectType::NONE
+            | ObjectType::REL
+            | ObjectType::EXEC
+            | ObjectType::DYN
+            | ObjectType::CORE => object_type.as_human_string().into(),
+            _ => format!("<unknown>: {:x}", object_type.as_raw()).into(),
+        }
+    }
+
+    fn file_header(&mut self) -> Result<()> {
+        let header = self.elf.header().clone();
+        let file_type = self.file_type();
+        let out = &mut self.out;
+
+        writeln!(out, "ELF Header:")?;
+        write!(out, "  Magic:   ")?;
+        for byte in &self.elf.data()[..16] {
+            write!(out, "{byte:02x} ")?;
+        }
+        writeln!(out)?;
+
+        let field = |name: &str| format!("  {:<35}", format!("{name}:"));
+        let os_abi = os_abi_name(&header.os_abi);
+        let machine = match header.machine.as_human_string() {
+            "unknown" => format!("<unknown>: 0x{:x}", header.machine.as_raw()),
+            name => name.to_string(),
+        };
+
+        writeln!(out, "{}{}", field("Class"), header.class.as_human_string())?;
+        writeln!(
+            out,
+            "{}{}",
+            field("Data"),
+            header.encoding.as_human_string()
+        )?;
+        writeln!(
+            out,
+            "{}{}",
+            field("Version"),
+            header.header_version.as_human_string()
+        )?;
+        writeln!(out, "{}{os_abi}", field("OS/ABI"))?;
+        writeln!(out, "{}{}", field("ABI Version"), header.abi_version)?;
+        writeln!(out, "{}{file_type}", field("Type"))?;
+        writeln!(out, "{}{machine}", field("Machine"))?;
+        writeln!(out, "{}0x{:x}", field("Version"), header.version.as_raw())?;
+        writeln!(
+            out,
+            "{}0x{:x}",
+            field("Entry point address"),
+            header.entry_point
+        )?;
+        writeln!(
+            out,
+            "{}{} (bytes into file)",
+            field("Start of program headers"),
+            header.program_header_offset
+        )?;
+        writeln!(
+            out,
+            "{}{} (bytes into file)",
+            field("Start of section headers"),
+            header.section_header_offset
+        )?;
+        writeln!(
+            out,
+            "{}0x{:x}{}",
+            field("Flags"),
+            header.flags,
+            machine_flags(&header.machine, header.flags)
+        )?;
+        writeln!(
+            out,
+            "{}{} (bytes)",
+            field("Size of this header"),
+            header.header_size
+        )?;
+        writeln!(
+            out,
+            "{}{} (bytes)",
+            field("Size of program headers"),
+            header.program_header_entry_size
+        )?;
+        writeln!(
+            out,
+            "{}{}",
+            field("Number of program headers"),
+            header.program_header_count
+        )?;
+        writeln!(
+            out,
+            "{}{} (bytes)",
+            field("Size of section headers"),
+            header.section_header_entry_size
+        )?;
+        writeln!(
+            out,
+            "{}{}",
+            field("Number of section headers"),
+            header.section_header_count
+        )?;
+        writeln!(
+            out,
+            "{}{}",
+            field("Section header string table index"),
+            header.section_header_string_table_index
+        )?;
+
+        Ok(())
+    }
+
+    fn section_headers(&mut self) -> Result<()> {
+        let header = self.elf.header().clone();
+        let count = self.elf.section_count();
+        let wide = self.options.wide;
+
+        if count == 0 {
+            writeln!(self.out, "\nThere are no sections in this file.")?;
+            return Ok(());
+        }
+
+        if !self.options.file_header {
+            writeln!(
+                self.out,
+                "There {} {count} section header{}, starting at offset {}:",
+                if count == 1 { "is" } else { "are" },
+                if count == 1 { "" } else { "s" },
+                alt_hex(header.section_header_offset)
+            )?;
+        }
+
+        writeln!(
+            self.out,
+            "\nSection Header{}:",
+            if count == 1 { "" } else { "s" }
+        )?;
+        if wide {
+            writeln!(
+                self.out,
+                "  [Nr] Name              Type            Address          Off    Size   ES Flg Lk Inf Al"
+            )?;
+        } else {
+            writeln!(
+                self.out,
+                "  [Nr] Name              Type             Address           Offset"
+            )?;
+            writeln!(
+                self.out,
+                "       Size              EntSize          Flags  Link  Info  Align"
+            )?;
+        }
+
+        for (index, shdr) in self.elf.section_headers().enumerate() {
+            let name = print_symbol(-17, &self.section_name(&shdr), wide);
+            let section_type = self.section_type_name(&shdr.sh_type);
+            let flags = self.section_flags(shdr.sh_flags);
+
+            if wide {
+                writeln!(
+                    self.out,
+                    "  [{index:2}] {name} {section_type:<15} {:016x} {:06x} {:06x} {:02x} {flags:>3} {:2} {:3} {:2}",
+                    shdr.sh_addr,
+                    shdr.sh_offset,
+                    shdr.sh_size,
+                    shdr.sh_entsize,
+                    shdr.sh_link,
+                    shdr.sh_info,
+                    shdr.sh_addralign
+                )?;
+            } else {
+                writeln!(
+                    self.out,
+                    "  [{index:2}] {name} {section_type:<15.15}  {:016x}  {:08x}",
+                    shdr.sh_addr, shdr.sh_offset
+                )?;
+                writeln!(
+                    self.out,
+                    "       {:016x}  {:016x} {flags:>3}      {:2}   {:3}     {}",
+                    shdr.sh_size, shdr.sh_entsize, shdr.sh_link, shdr.sh_info, shdr.sh_addralign
+                )?;
+            }
+        }
+
+        writeln!(self.out, "Key to Flags:")?;
+        writeln!(
+            self.out,
+            "  W (write), A (alloc), X (execute), M (merge), S (strings), I (info),"
+        )?;
+        writeln!(
+            self.out,
+            "  L (link order), O (extra OS processing required), G (group), T (TLS),"
+        )?;
+        writeln!(
+            self.out,
+            "  C (compressed), x (unknown), o (OS specific), E (exclude),"
+        )?;
+        write!(self.out, "  ")?;
+        if matches!(header.os_abi, OsAbi::GNU | OsAbi::FREEBSD) {
+            write!(self.out, "R (retain), ")?;
+        }
+        if matches!(header.os_abi, OsAbi::NONE | OsAbi::GNU) {
+            write!(self.out, "D (mbind), ")?;
+        }
+        if header.machine == Machine::X86_64 {
+            write!(self.out, "l (large), ")?;
+        }
+        writeln!(self.out, "p (processor specific)")?;
+
+        Ok(())
+    }
+
+    fn section_type_name(&self, section_type: &SectionType) -> Cow<'static, str> {
+        let raw = section_type.as_raw();
+
+        match (self.machine(), raw) {
+            (&Machine::X86_64, 0x70000001) => return "X86_64_UNWIND".into(),
+            (&Machine::RISCV, SECTION_TYPE_RISCV_ATTRIBUTES) => return "RISCV_ATTRIBUTES".into(),
+            (_, 0x70000000..=0x7fffffff) => {
+                return format!("LOPROC+{}", alt_hex((raw - 0x70000000).into())).into();
+            }
+            _ => {}
+        }
+
+        match section_type.as_human_string() {
+            "unknown" | "LOOS" | "LOUSER" | "HIUSER" => match raw {
+                0x60000000..=0x6fffffff => {
+                    format!("LOOS+{}", alt_hex((raw - 0x60000000).into())).into()
+                }
+                0x80000000..=0xffffffff => {
+                    format!("LOUSER+{}", alt_hex((raw - 0x80000000).into())).into()
+                }
+                _ => format!("{raw:08x}: <unknown>").into(),
+            },
+            name => name.into(),
+        }
+    }
+
+    fn section_flags(&self, mut flags: u64) -> String {
+        let os_abi = &self.elf.header().os_abi;
+        let has_retain = matches!(*os_abi, OsAbi::GNU | OsAbi::FREEBSD);
+        let has_mbind = matches!(*os_abi, OsAbi::NONE | OsAbi::GNU);
+        let mut letters = String::new();
+
+        while flags != 0 {
+            let flag = flags & flags.wrapping_neg();
+            flags &= !flag;
+
+            let letter = match flag {
+                SECTION_FLAG_WRITE => 'W',
+                SECTION_FLAG_ALLOC => 'A',
+                SECTION_FLAG_EXECINSTR => 'X',
+                SECTION_FLAG_MERGE => 'M',
+                SECTION_FLAG_STRINGS => 'S',
+                SECTION_FLAG_INFO_LINK => 'I',
+                SECTION_FLAG_LINK_ORDER => 'L',
+                SECTION_FLAG_OS_NONCONFORMING => 'O',
+                SECTION_FLAG_GROUP => 'G',
+                SECTION_FLAG_TLS => 'T',
+                SECTION_FLAG_EXCLUDE => 'E',
+                SECTION_FLAG_COMPRESSED => 'C',
+                SECTION_FLAG_X86_64_LARGE if *self.machine() == Machine::X86_64 => 'l',
+                SECTION_FLAG_GNU_RETAIN if has_retain => 'R',
+                SECTION_FLAG_GNU_MBIND if has_mbind => 'D',
+                _ if flag & SECTION_FLAG_MASKOS != 0 => {
+                    flags &= !SECTION_FLAG_MASKOS;
+                    'o'
+                }
+                _ if flag & SECTION_FLAG_MASKPROC != 0 => {
+                    flags &= !SECTION_FLAG_MASKPROC;
+                    'p'
+                }
+                _ => 'x',
+            };
+            letters.push(letter);
+        }
+
+        letters
+    }
+
+    fn program_headers(&mut self) -> Result<()> {
+        let header = self.elf.header().clone();
+        let count = header.program_header_count;
+        let wide = self.options.wide;
+
+        if count == 0 {
+            writeln!(self.out, "\nThere are no program headers in this file.")?;
+            return Ok(());
+        }
+
+        if !self.options.file_header {
+            writeln!(self.out, "\nElf file type is {}", self.file_type())?;
+            writeln!(self.out, "Entry point 0x{:x}", header.entry_point)?;
+            writeln!(
+                self.out,
+                "There {} {count} program header{}, starting at offset {}",
+                if count == 1 { "is" } else { "are" },
+                if count == 1 { "" } else { "s" },
+                header.program_header_offset
+            )?;
+        }
+
+        writeln!(
+            self.out,
+            "\nProgram Header{}:",
+            if count == 1 { "" } else { "s" }
+        )?;
+        if wide {
+            writeln!(
+                self.out,
+                "  Type           Offset   VirtAddr           PhysAddr           FileSiz  MemSiz   Flg Align"
+            )?;
+        } else {
+            writeln!(
+                self.out,
+                "  Type           Offset             VirtAddr           PhysAddr"
+            )?;
+            writeln!(
+                self.out,
+                "                 FileSiz            MemSiz              Flags  Align"
+            )?;
+        }
+
+        let segments: Vec<ProgramHeader64> = self.elf.program_headers().collect();
+        for phdr in &segments {
+            let segment_type = segment_type_name(&phdr.p_type);
+            let flags = segment_flags(phdr.p_flags);
+
+            if wide {
+                writeln!(
+                    self.out,
+                    "  {segment_type:<14} 0x{:06x} 0x{:016x} 0x{:016x} 0x{:06x} 0x{:06x} {flags} 0x{:x}",
+                    phdr.p_offset,
+                    phdr.p_vaddr,
+                    phdr.p_paddr,
+                    phdr.p_filesz,
+                    phdr.p_memsz,
+                    phdr.p_align
+                )?;
+            } else {
+                writeln!(
+                    self.out,
+                    "  {segment_type:<14} 0x{:016x} 0x{:016x} 0x{:016x}",
+                    phdr.p_offset, phdr.p_vaddr, phdr.p_paddr
+                )?;
+                writeln!(
+                    self.out,
+                    "                 0x{:016x} 0x{:016x}  {flags}    0x{:x}",
+                    phdr.p_filesz, phdr.p_memsz, phdr.p_align
+                )?;
+            }
+
+            if phdr.p_type == ProgramType::INTERP
+                && let Ok(data) = self.elf.segment_data(phdr)
+            {
+                let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+                writeln!(
+                    self.out,
+                    "      [Requesting program interpreter: {}]",
+                    show(&data[..end])
+                )?;
+            }
+        }
+
+        if self.elf.section_count() == 0 {
+            return Ok(());
+        }
+
+        writeln!(self.out, "\n Section to Segment mapping:")?;
+        writeln!(self.out, "  Segment Sections...")?;
+
+        for (index, phdr) in segments.iter().enumerate() {
+            write!(self.out, "   {index:02}     ")?;
+            for shdr in self.elf.section_headers().skip(1) {
+                if !is_tbss_special(&shdr, phdr) && section_in_segment(&shdr, phdr) {
+                    write!(self.out, "{} ", self.section_name(&shdr))?;
+                }
+            }
+            writeln!(self.out)?;
+        }
+
+        Ok(())
+    }
+
+    fn dynamic_section(&mut self) -> Result<()> {
+        let (Some(segment), Ok(dynamic)) = (
+            self.elf.find_segment(ProgramType::DYNAMIC),
+            self.elf.dynamic(),
+        ) else {
+            writeln!(self.out, "\nThere is no dynamic section in this file.")?;
+            return Ok(());
+        };
+
+        let offset = self
+            .elf
+            .section_headers()
+            .find(|shdr| shdr.sh_type == SectionType::DYNAMIC)
+            .map_or(segment.p_offset, |shdr| shdr.sh_offset);
+
+        let mut entries: Vec<Dynamic64> = Vec::new();
+        for entry in dynamic.iter() {
+            let is_null = entry.d_tag == DynamicTag::NULL;
+            entries.push(entry);
+            if is_null {
+                break;
+            }
+        }
+
+        let strings = self.elf.dynamic_strings().ok();
+        let interpreter = self.elf.interpreter();
+
+        writeln!(
+            self.out,
+            "\nDynamic section at offset {} contains {} {}:",
+            alt_hex(offset),
+            entries.len(),
+            if entries.len() == 1 {
+                "entry"
+            } else {
+                "entries"
+            }
+        )?;
+        writeln!(
+            self.out,
+            "  Tag        Type                         Name/Value"
+        )?;
+
+        for entry in &entries {
+            let tag_name = dynamic_tag_name(&entry.d_tag);
+            let padding = 19usize.saturating_sub(tag_name.len()).max(1);
+            write!(
+                self.out,
+                " 0x{:016x} ({tag_name}){:padding$}",
+                entry.d_tag.as_raw(),
+                ""
+            )?;
+
+            let value = entry.d_val;
+            let string = strings.and_then(|strings| strings.get(u32::try_from(value).ok()?));
+
+            match entry.d_tag {
+                DynamicTag::NEEDED
+                | DynamicTag::SONAME
+                | DynamicTag::RPATH
+                | DynamicTag::RUNPATH
+                    if string.is_some() =>
+                {
+                    let string = string.unwrap_or_default();
+                    let label = match entry.d_tag {
+                        DynamicTag::NEEDED => "Shared library",
+                        DynamicTag::SONAME => "Library soname",
+                        DynamicTag::RPATH => "Library rpath",
+                        _ => "Library runpath",
+                    };
+                    write!(self.out, "{label}: [{}]", show(string))?;
+                    if entry.d_tag == DynamicTag::NEEDED && interpreter == Some(string) {
+                        write!(self.out, " program interpreter")?;
+                    }
+                    writeln!(self.out)?;
+                }
+                DynamicTag::PLTRELSZ
+                | DynamicTag::RELASZ
+                | DynamicTag::STRSZ
+                | DynamicTag::RELSZ
+                | DynamicTag::RELAENT
+                | DynamicTag::RELRENT
+                | DynamicTag::RELRSZ
+                | DynamicTag::SYMENT
+                | DynamicTag::RELENT
+                | DynamicTag::PREINIT_ARRAYSZ
+                | DynamicTag::INIT_ARRAYSZ
+                | DynamicTag::FINI_ARRAYSZ => writeln!(self.out, "{value} (bytes)")?,
+                DynamicTag::VERDEFNUM
+                | DynamicTag::RELACOUNT
+                | DynamicTag::RELCOUNT
+                | DynamicTag::VERNEEDNUM => writeln!(self.out, "{value}")?,
+                DynamicTag::PLTREL => {
+                    let tag = DynamicTag::from_raw(value as i64);
+                    writeln!(self.out, "{}", dynamic_tag_name(&tag))?;
+                }
+                DynamicTag::FLAGS => writeln!(self.out, "{}", dynamic_flags(value))?,
+                DynamicTag::FLAGS_1 => writeln!(self.out, "Flags:{}", dynamic_flags_1(value))?,
+                _ => writeln!(self.out, "0x{value:x}")?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn relocations(&mut self) -> Result<()> {
+        let wide = self.options.wide;
+        let mut found = false;
+
+        for shdr in self.elf.section_headers() {
+            let is_rela = match shdr.sh_type {
+                SectionType::RELA => true,
+                SectionType::REL => false,
+                SectionType::RELR => {
+                    found = true;
+                    self.relr_section(&shdr)?;
+                    continue;
+                }
+                _ => continue,
+            };
+            if shdr.sh_size == 0 {
+                continue;
+            }
+            found = true;
+
+            let data = self.elf.section_data(&shdr)?;
+            let entries: Vec<(u64, u64, i64)> = if is_rela {
+                RelaTable::with_entry_size(data, shdr.sh_entsize as usize)?
+                    .iter()
+                    .map(|rela| (rela.r_offset, rela.r_info, rela.r_addend))
+                    .collect()
+            } else {
+                RelTable::with_entry_size(data, shdr.sh_entsize as usize)?
+                    .iter()
+                    .map(|rel| (rel.r_offset, rel.r_info, 0))
+                    .collect()
+            };
+
+            writeln!(
+                self.out,
+                "\nRelocation section '{}' at offset {} contains {} {}:",
+                self.section_name(&shdr),
+                alt_hex(shdr.sh_offset),
+                entries.len(),
+                if entries.len() == 1 {
+                    "entry"
+                } else {
+                    "entries"
+                }
+            )?;
+            let columns = match (wide, is_rela) {
+                (true, _) => {
+                    "    Offset             Info             Type               Symbol's Value  Symbol's Name"
+                }
+                (false, _) => {
+                    "  Offset          Info           Type           Sym. Value    Sym. Name"
+                }
+            };
+            writeln!(
+                self.out,
+                "{columns}{}",
+                if is_rela { " + Addend" } else { "" }
+            )?;
+
+            let symbol_section = match shdr.sh_link {
+                0 => None,
+                link => self.elf.section_header(link as usize),
+            };
+            let symbols = symbol_section
+                .as_ref()
+                .and_then(|symtab| self.elf.symbols(symtab).ok());
+            let strings = symbol_section
+                .as_ref()
+                .and_then(|symtab| self.elf.linked_strings(symtab).ok());
+            let is_dynsym = symbol_section
+                .as_ref()
+                .is_some_and(|symtab| symtab.sh_type == SectionType::DYNSYM);
+
+            for (offset, info, addend) in entries {
+                let symbol_index = (info >> 32) as u32;
+                let r_type = info as u32;
+
+                if wide {
+                    write!(self.out, "{offset:016x}  {info:016x} ")?;
+                } else {
+                    write!(self.out, "{offset:012x}  {info:012x} ")?;
+                }
+
+                match relocation_type_name(self.machine(), r_type) {
+                    "unknown" => write!(self.out, "unrecognized: {r_type:<7x}")?,
+                    name if wide => write!(self.out, "{name:<22}")?,
+                    name => write!(self.out, "{name:<17.17}")?,
+                }
+
+                let symbol = match symbol_index {
+                    0 => None,
+                    index => symbols
+                        .as_ref()
+                        .and_then(|symbols| symbols.get(index as usize)),
+                };
+
+                match symbol {
+                    Some(symbol) => {
+                        write!(self.out, " {:016x} ", symbol.st_value)?;
+                        let name = self.symbol_name(&symbol, strings.as_ref());
+                        write!(self.out, "{}", print_symbol(22, &name, wide))?;
+                        if is_dynsym {
+                            match self.symbol_version(symbol_index as usize, &symbol) {
+                                Some(SymbolVersion::Defined {
+                                    name,
+                                    hidden: false,
+                                }) => write!(self.out, "@@{}", show(name))?,
+                                Some(
+                                    SymbolVersion::Defined { name, .. }
+                                    | SymbolVersion::Needed { name, .. },
+                                ) => write!(self.out, "@{}", show(name))?,
+                                None => {}
+                            }
+                        }
+                        if is_rela {
+                            write!(self.out, " {}", signed_hex(addend))?;
+                        }
+                    }
+                    None if is_rela => {
+                        let sign = if addend < 0 { "-" } else { "" };
+                        write!(self.out, "{:20}{sign}{:x}", "", addend.unsigned_abs())?;
+                    }
+                    None => {}
+                }
+                writeln!(self.out)?;
+            }
+        }
+
+        if !found {
+            writeln!(self.out, "\nThere are no relocations in this file.")?;
+        }
+
+        Ok(())
+    }
+
+    fn relr_section(&mut self, shdr: &SectionHeader64) -> Result<()> {
+        let entries: Table<u64> =
+            Table::with_entry_size(self.elf.section_data(shdr)?, shdr.sh_entsize as usize)?;
+
+        let mut offsets = Vec::new();
+        let mut next = 0;
+        for entry in &entries {
+            if entry & 1 == 0 {
+                offsets.push(entry);
+                next = entry + 8;
+            } else {
+                for bit in 1..64 {
+                    if entry >> bit & 1 != 0 {
+                        offsets.push(next + (bit - 1) * 8);
+                    }
+                }
+                next += 63 * 8;
+            }
+        }
+
+        writeln!(
+            self.out,
+            "\nRelocation section '{}' at offset {} contains {} {}:",
+            self.section_name(shdr),
+            alt_hex(shdr.sh_offset),
+            entries.len(),
+            if entries.len() == 1 {
+                "entry"
+            } else {
+                "entries"
+            }
+        )?;
+        writeln!(
+            self.out,
+            "  {} offset{}",
+            offsets.len(),
+            if offsets.len() == 1 { "" } else { "s" }
+        )?;
+        for offset in offsets {
+            writeln!(self.out, "{offset:016x}")?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the name shown for a symbol, which for section symbols is the
+    /// name of their section
+    fn symbol_name(&self, symbol: &Symbol64, strings: Option<&StringTable<'a>>) -> Cow<'a, str> {
+        if symbol.symbol_type() == SymbolType::SECTION
+            && symbol.st_name == 0
+            && let Some(shdr) = self.elf.section_header(symbol.st_shndx.as_raw() as usize)
+        {
+            return self.section_name(&shdr);
+        }
+
+        match strings.and_then(|strings| strings.get(symbol.st_name)) {
+            Some(name) => show(name),
+            None => Cow::Borrowed("<corrupt>"),
+        }
+    }
+
+    /// Looks up the version of a dynamic symbol, preferring the versions
+    /// defined by the object for defined symbols
+    fn symbol_version(&self, index: usize, symbol: &Symbol64) -> Option<SymbolVersion<'a>> {
+        let version = self.versions.as_ref()?.get(index)?;
+        if version.as_raw() == VersionIndex::LOCAL.as_raw() {
+            return None;
+        }
+
+        if !symbol.is_undefined()
+            && version.as_raw() != 0x8001
+            && let Some(&(offset, name)) = self.defined_versions.get(&version.index())
+        {
+            // The symbols naming the versions themselves are shown bare
+            if offset == symbol.st_name {
+                return None;
+            }
+            return Some(SymbolVersion::Defined {
+                name,
+                hidden: version.is_hidden(),
+            });
+        }
+
+        self.needed_versions
+            .get(&version.index())
+            .map(|name| SymbolVersion::Needed {
+                name,
+                index: version.index(),
+            })
+    }
+
+    fn symbol_tables(&mut self) -> Result<()> {
+        let wide = self.options.wide;
+
+        for shdr in self.elf.section_headers() {
+            let is_dynsym = match shdr.sh_type {
+                SectionType::DYNSYM => true,
+                SectionType::SYMTAB if self.options.symbols => false,
+                _ => continue,
+            };
+
+            let symbols = self.elf.symbols(&shdr)?;
+            let strings = self.elf.linked_strings(&shdr).ok();
+
+            writeln!(
+                self.out,
+                "\nSymbol table '{}' contains {} {}:",
+                self.section_name(&shdr),
+                symbols.len(),
+                if symbols.len() == 1 {
+                    "entry"
+                } else {
+                    "entries"
+                }
+            )?;
+            writeln!(
+                self.out,
+                "   Num:    Value          Size Type    Bind   Vis      Ndx Name"
+            )?;
+
+            for (index, symbol) in symbols.iter().enumerate() {
+                let size = if symbol.st_size <= 99999 {
+                    format!("{:5}", symbol.st_size)
+                } else {
+                    format!("0x{:x}", symbol.st_size)
+                };
+                write!(
+                    self.out,
+                    "{index:6}: {:016x} {size} {:<7} {:<6} {:<7}",
+                    symbol.st_value,
+                    symbol_type_name(&symbol.symbol_type()),
+                    symbol_binding_name(&symbol.binding()),
+                    symbol.visibility().as_human_string()
+                )?;
+                let other = symbol.st_other & !0x3;
+                if other != 0 {
+                    write!(self.out, " [{}]", self.symbol_other_name(other))?;
+                }
+                write!(
+                    self.out,
+                    " {:>4} ",
+                    self.symbol_index_name(&symbol.st_shndx)
+                )?;
+
+                let name = self.symbol_name(&symbol, strings.as_ref());
+                let version = if is_dynsym {
+                    self.symbol_version(index, &symbol)
+                } else {
+                    None
+                };
+                let suffix = match version {
+                    Some(SymbolVersion::Defined { name, hidden }) => {
+                        format!("{}{}", if hidden { "@" } else { "@@" }, show(name))
+                    }
+                    Some(SymbolVersion::Needed { name, index }) => {
+                        format!("@{} ({index})", show(name))
+                    }
+                    None => String::new(),
+                };
+
+                let width = if wide { 21 } else { 21 - suffix.len() as i32 };
+                writeln!(self.out, "{}{suffix}", print_symbol(width, &name, wide))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn symbol_other_name(&self, other: u8) -> String {
+        match (self.machine(), other) {
+            (&Machine::AARCH64, 0x80) => "VARIANT_PCS".into(),
+            (&Machine::RISCV, 0x80) => "VARIANT_CC".into(),
+            _ => format!("<other>: {other:x}"),
+        }
+    }
+
+    fn symbol_index_name(&self, index: &SectionIndex) -> String {
+        let raw = index.as_raw();
+        match *index {
+            SectionIndex::UNDEF | SectionIndex::ABS | SectionIndex::COMMON => {
+                index.as_human_string().into()
+            }
+            _ if *self.machine() == Machine::X86_64 && raw == 0xff02 => "LARGE_COM".into(),
+            _ if (0xff00..=0xff1f).contains(&raw) => format!("PRC[0x{raw:04x}]"),
+            _ if (0xff20..=0xff3f).contains(&raw) => format!("OS [0x{raw:04x}]"),
+            _ if raw >= 0xff00 => format!("RSV[0x{raw:04x}]"),
+            _ if raw as usize >= self.elf.section_count() && self.elf.section_count() != 0 => {
+                format!("bad section index[{raw:3}]")
+            }
+            _ => format!("{raw:3}"),
+        }
+    }
+
+    fn version_sections(&mut self) -> Result<()> {
+        let mut found = false;
+
+        for shdr in self.elf.section_headers() {
+            if !matches!(
+                shdr.sh_type,
+                SectionType::GNU_VERDEF | SectionType::GNU_VERNEED | SectionType::GNU_VERSYM
+            ) {
+                continue;
+            }
+            found = true;
+
+            let data = self.elf.section_data(&shdr)?;
+            let name = self.section_name(&shdr);
+            let link_name = self.section_name_by_index(shdr.sh_link);
+            let address_line = format!(
+                " Addr: 0x{:016x}  Offset: 0x{:08x}  Link: {} ({link_name})",
+                shdr.sh_addr, shdr.sh_offset, shdr.sh_link
+            );
+
+            match shdr.sh_type {
+                SectionType::GNU_VERDEF => {
+                    writeln!(
+                        self.out,
+                        "\nVersion definition section '{name}' contains {} {}:",
+                        shdr.sh_info,
+                        if shdr.sh_info == 1 {
+                            "entry"
+                        } else {
+                            "entries"
+                        }
+                    )?;
+                    writeln!(self.out, "{address_line}")?;
+
+                    let strings = self.elf.linked_strings(&shdr)?;
+                    let mut offset = 0usize;
+                    for definition in
+                        VersionDefinitionIter::new(data, shdr.sh_info as usize, strings)
+                    {
+                        let entry = &definition.definition;
+                        write!(
+                            self.out,
+                            "  {}: Rev: {}  Flags: {}  Index: {}  Cnt: {}  ",
+                            alt_hex_06(offset),
+                            entry.vd_version,
+                            version_flags(entry.vd_flags),
+                            entry.vd_ndx,
+                            entry.vd_cnt
+                        )?;
+                        if let Some(name) = definition.name {
+                            write!(self.out, "Name: {}", show(name))?;
+                        }
+                        writeln!(self.out)?;
+
+                        let mut aux_offset = offset + entry.vd_aux as usize;
+                        for (parent, (aux, name)) in definition.auxiliary.enumerate() {
+                            if parent > 0
+                                && let Some(name) = name
+                            {
+                                writeln!(
+                                    self.out,
+                                    "  {}: Parent {parent}: {}",
+                                    alt_hex_06(aux_offset),
+                                    show(name)
+                                )?;
+                            }
+                            aux_offset += aux.vda_next as usize;
+                        }
+                        offset += entry.vd_next as usize;
+                    }
+                }
+                SectionType::GNU_VERNEED => {
+                    writeln!(
+                        self.out,
+                        "\nVersion needs section '{name}' contains {} {}:",
+                        shdr.sh_info,
+                        if shdr.sh_info == 1 {
+                            "entry"
+                        } else {
+                            "entries"
+                        }
+                    )?;
+                    writeln!(self.out, "{address_line}")?;
+
+                    let strings = self.elf.linked_strings(&shdr)?;
+                    let mut offset = 0usize;
+                    for need in VersionNeedIter::new(data, shdr.sh_info as usize, strings) {
+                        let entry = &need.need;
+                        write!(
+                            self.out,
+                            "  {}: Version: {}",
+                            alt_hex_06(offset),
+                            entry.vn_version
+                        )?;
+                        match need.file {
+                            Some(file) => write!(self.out, "  File: {}", show(file))?,
+                            None => write!(self.out, "  File: {:x}", entry.vn_file)?,
+                        }
+                        writeln!(self.out, "  Cnt: {}", entry.vn_cnt)?;
+
+                        let mut aux_offset = offset + entry.vn_aux as usize;
+                        for (aux, name) in need.auxiliary {
+                            writeln!(
+                                self.out,
+                                "  {}:   Name: {}  Flags: {}  Version: {}",
+                                alt_hex_06(aux_offset),
+                                show(name.unwrap_or(b"<corrupt>")),
+                                version_flags(aux.vna_flags),
+                                aux.vna_other
+                            )?;
+                            aux_offset += aux.vna_next as usize;
+                        }
+                        offset += entry.vn_next as usize;
+                    }
+                }
+                _ => {
+                    let versions = VersionTable::new(data);
+                    writeln!(
+                        self.out,
+                        "\nVersion symbols section '{name}' contains {} {}:",
+                        versions.len(),
+                        if versions.len() == 1 {
+                            "entry"
+                        } else {
+                            "entries"
+                        }
+                    )?;
+                    writeln!(self.out, "{address_line}")?;
+
+                    let symbols = self
+                        .elf
+                        .section_header(shdr.sh_link as usize)
+                        .and_then(|symtab| self.elf.symbols(&symtab).ok());
+
+                    for (index, version) in versions.iter().enumerate() {
+                        if index % 4 == 0 {
+                            if index != 0 {
+                                writeln!(self.out)?;
+                            }
+                            write!(self.out, "  {index:03x}:")?;
+                        }
+
+                        let cell = match version.as_raw() {
+                            0 => "   0 (*local*)    ".to_string(),
+                            1 => "   1 (*global*)   ".to_string(),
+                            raw => {
+                                let mut cell = format!(
+                                    "{:4x}{}",
+                                    version.index(),
+                                    if version.is_hidden() { 'h' } else { ' ' }
+                                );
+                                let symbol =
+                                    symbols.as_ref().and_then(|symbols| symbols.get(index));
+                                if let Some(name) =
+                                    self.version_cell_name(raw, symbol.as_ref(), &version)
+                                {
+                                    let padding = (12 - name.len() as i32).unsigned_abs() as usize;
+                                    cell.push_str(&format!("({name}{:<padding$}", ")"));
+                                }
+                                format!("{cell:<18}")
+                            }
+                        };
+                        write!(self.out, "{cell}")?;
+                    }
+                    writeln!(self.out)?;
+                }
+            }
+        }
+
+        if !found {
+            writeln!(self.out, "\nNo version information found in this file.")?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the version name shown in the symbol version table, looked up
+    /// among the needed versions for undefined symbols and the defined ones
+    /// otherwise
+    fn version_cell_name(
+        &self,
+        raw: u16,
+        symbol: Option<&Symbol64>,
+        version: &VersionIndex,
+    ) -> Option<Cow<'a, str>> {
+        if raw == 0x8001 {
+            return None;
+        }
+
+        let nobits = symbol.is_some_and(|symbol| {
+            self.elf
+                .section_header(symbol.st_shndx.as_raw() as usize)
+                .is_some_and(|shdr| shdr.sh_type == SectionType::NOBITS)
+        });
+        let undefined = symbol.is_none_or(|symbol| symbol.is_undefined());
+        let check_need = nobits || undefined;
+        let check_def = nobits || !undefined;
+
+        if check_need && let Some(name) = self.needed_versions.get(&version.index()) {
+            return Some(show(name));
+        }
+        if check_def && let Some((_, name)) = self.defined_versions.get(&version.index()) {
+            return Some(show(name));
+        }
+        None
+    }
+
+    fn hex_dumps(&mut self) -> Result<()> {
+        let mut requested = vec![false; self.elf.section_count()];
+        let mut missing_numbers = Vec::new();
+
+        for request in &self.options.hex_dumps {
+            if let Ok(index) = request.parse::<usize>() {
+                match requested.get_mut(index) {
+                    Some(requested) => *requested = true,
+                    None => missing_numbers.push(index),
+                }
+                continue;
+            }
+
+            let mut matched = false;
+            for (index, shdr) in self.elf.section_headers().enumerate() {
+                if self.elf.section_name(&shdr) == Some(request.as_bytes()) {
+                    requested[index] = true;
+                    matched = true;
+                }
+            }
+            if !matched {
+                self.out.flush()?;
+                eprintln!(
+                    "readelf: Warning: Section '{request}' was not dumped because it does not exist"
+                );
+            }
+        }
+
+        for (index, shdr) in self.elf.section_headers().enumerate() {
+            if requested[index] {
+                self.hex_dump(index, &shdr)?;
+            }
+        }
+
+        for index in missing_numbers {
+            self.out.flush()?;
+            eprintln!(
+                "readelf: Warning: Section {index} was not dumped because it does not exist!"
+            );
+        }
+
+        Ok(())
+    }
+
+    fn hex_dump(&mut self, index: usize, shdr: &SectionHeader64) -> Result<()> {
+        let name = self.section_name(shdr);
+        let data = self.elf.section_data(shdr)?;
+
+        if data.is_empty() {
+            writeln!(self.out, "Section '{name}' has no data to dump.")?;
+            return Ok(());
+        }
+
+        writeln!(self.out, "\nHex dump of section '{name}':")?;
+
+        let has_relocations = self.elf.header().object_type == ObjectType::REL
+            && self.elf.section_headers().any(|other| {
+                matches!(other.sh_type, SectionType::REL | SectionType::RELA)
+                    && other.sh_info as usize == index
+            });
+        if has_relocations {
+            writeln!(
+                self.out,
+                " NOTE: This section has relocations against it, but these have NOT been applied to this dump."
+            )?;
+        }
+
+        let mut address = shdr.sh_addr;
+        for line in data.chunks(16) {
+            write!(self.out, "  0x{address:08x} ")?;
+            for column in 0..16 {
+                match line.get(column) {
+                    Some(byte) => write!(self.out, "{byte:02x}")?,
+                    None => write!(self.out, "  ")?,
+                }
+                if column % 4 == 3 {
+                    write!(self.out, " ")?;
+                }
+            }
+            for &byte in line {
+                let c = if (b' '..0x7f).contains(&byte) {
+                    byte as char
+                } else {
+                    '.'
+                };
+                write!(self.out, "{c}")?;
+            }
+            writeln!(self.out)?;
+            address += 16;
+        }
+        writeln!(self.out)?;
+
+        Ok(())
+    }
+
+    fn notes(&mut self) -> Result<()> {
+        let use_sections = self.elf.header().object_type != ObjectType::CORE
+            && self
+                .elf
+                .section_headers()
+                .any(|shdr| shdr.sh_type == SectionType::NOTE);
+
+        if use_sections {
+            for shdr in self.elf.section_headers() {
+                if shdr.sh_type != SectionType::NOTE {
+                    continue;
+                }
+                writeln!(
+                    self.out,
+                    "\nDisplaying notes found in: {}",
+                    self.section_name(&shdr)
+                )?;
+                let data = self.elf.section_data(&shdr)?;
+                self.note_list(NoteIter::new(data, shdr.sh_addralign))?;
+            }
+        } else {
+            let segments: Vec<ProgramHeader64> = self
+                .elf
+                .program_headers()
+                .filter(|phdr| phdr.p_type == ProgramType::NOTE)
+                .collect();
+            for phdr in segments {
+                writeln!(
+                    self.out,
+                    "\nDisplaying notes found at file offset 0x{:08x} with length 0x{:08x}:",
+                    phdr.p_offset, phdr.p_filesz
+                )?;
+                let data = self.elf.segment_data(&phdr)?;
+                self.note_list(NoteIter::new(data, phdr.p_align))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn note_list(&mut self, notes: NoteIter<'a>) -> Result<()> {
+        let wide = self.options.wide;
+        writeln!(
+            self.out,
+            "  {:<20} {:<10}\tDescription",
+            "Owner", "Data size"
+        )?;
+
+        for note in notes {
+            let description = self.note_type_name(&note);
+            write!(
+                self.out,
+                "  {} 0x{:08x}\t{description}{}",
+                print_symbol(-20, &show(note.name), wide),
+                note.desc.len(),
+                if wide { '\t' } else { '\n' }
+            )?;
+
+            let details = if note.name == Note::GNU {
+                self.gnu_note_details(&note)
+            } else if note.name == Note::CORE || note.name == Note::LINUX {
+                None
+            } else if !note.desc.is_empty() {
+                let bytes: String = note.desc.iter().map(|b| format!("{b:02x} ")).collect();
+                Some(format!("   description data: {bytes}"))
+            } else {
+                None
+            };
+
+            match details {
+                Some(details) => writeln!(self.out, "{details}")?,
+                None if wide => writeln!(self.out)?,
+                None => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn note_type_name(&self, note: &Note) -> String {
+        let unknown = || format!("Unknown note type: (0x{:08x})", note.n_type);
+        let known = |name: &'static str| match name {
+            "unknown" => unknown(),
+            name => name.to_string(),
+        };
+
+        if note.name == Note::GNU {
+            known(GnuNoteType::from_raw(note.n_type).as_human_string())
+        } else if self.elf.header().object_type == ObjectType::CORE {
+            known(CoreNoteType::from_raw(note.n_type).as_human_string())
+        } else {
+            match note.n_type {
+                1 => "NT_VERSION (version)".into(),
+                2 => "NT_ARCH (architecture)".into(),
+                _ => unknown(),
+            }
+        }
+    }
+
+    fn gnu_note_details(&self, note: &Note) -> Option<String> {
+        let desc = note.desc;
+        let word = |index: usize| {
+            desc.get(index * 4..index * 4 + 4)
+                .map(|bytes| u32::from_ne_bytes(bytes.try_into().unwrap()))
+        };
+
+        Some(match GnuNoteType::from_raw(note.n_type) {
+            GnuNoteType::BUILD_ID => {
+                let hex: String = desc.iter().map(|b| format!("{b:02x}")).collect();
+                format!("    Build ID: {hex}")
+            }
+            GnuNoteType::ABI_TAG => {
+                let (Some(os), Some(major), Some(minor), Some(subminor)) =
+                    (word(0), word(1), word(2), word(3))
+                else {
+                    return Some("    <corrupt GNU_ABI_TAG>".into());
+                };
+                let os = match os {
+                    0 => "Linux",
+                    1 => "Hurd",
+                    2 => "Solaris",
+                    3 => "FreeBSD",
+                    4 => "NetBSD",
+                    5 => "Syllable",
+                    6 => "NaCl",
+                    _ => "Unknown",
+                };
+                format!("    OS: {os}, ABI: {major}.{minor}.{subminor}")
+            }
+            GnuNoteType::GOLD_VERSION => format!("    Version: {}", show(desc)),
+            GnuNoteType::PROPERTY_TYPE_0 => self.gnu_properties(desc),
+            _ => {
+                let bytes: String = desc.iter().map(|b| format!("{b:02x} ")).collect();
+                format!("    Description data: {bytes}")
+            }
+        })
+    }
+
+    fn gnu_properties(&self, desc: &[u8]) -> String {
+        let mut text = String::from("      Properties: ");
+
+        if desc.len() < 8 || !desc.len().is_multiple_of(8) {
+            text.push_str(&format!(
+                "<corrupt GNU_PROPERTY_TYPE, size = {}>",
+                alt_hex(desc.len() as u64)
+            ));
+            return text;
+        }
+
+        let separator = if self.options.wide { ", " } else { "\n\t" };
+        let read_u32 = |bytes: &[u8]| u32::from_ne_bytes(bytes[..4].try_into().unwrap());
+        let mut rest = desc;
+
+        while rest.len() >= 8 {
+            let pr_type = read_u32(&rest[0..]);
+            let size = read_u32(&rest[4..]) as usize;
+            rest = &rest[8..];
+            if size > rest.len() {
+                text.push_str(&format!("<corrupt type ({pr_type:#x}) datasz: {size:#x}>"));
+                break;
+            }
+
+            let data = &rest[..size];
+            let value = (size == 4).then(|| read_u32(data));
+            text.push_str(&self.gnu_property(pr_type, data, value));
+
+            rest = rest.get(size.div_ceil(8) * 8..).unwrap_or_default();
+            if rest.is_empty() {
+                break;
+            }
+            text.push_str(separator);
+        }
+
+        text
+    }
+
+    fn gnu_property(&self, pr_type: u32, data: &[u8], value: Option<u32>) -> String {
+        let decode = |label: &str, names: &[&str], none: bool| match value {
+            Some(bitmask) => format!("{label}: {}", bit_names(bitmask, names, none)),
+            None => format!("{label}: <corrupt length: {:#x}> ", data.len()),
+        };
+
+        match (self.machine(), pr_type) {
+            (&Machine::X86_64, 0xc0010002) => {
+                return decode("x86 ISA used", X86_ISA, true);
+            }
+            (&Machine::X86_64, 0xc0008002) => {
+                return decode("x86 ISA needed", X86_ISA, true);
+            }
+            (&Machine::X86_64, 0xc0000002) => {
+                return decode("x86 feature", X86_FEATURE_1, true);
+            }
+            (&Machine::X86_64, 0xc0010001) => {
+                return decode("x86 feature used", X86_FEATURE_2, true);
+            }
+            (&Machine::X86_64, 0xc0008001) => {
+                return decode("x86 feature needed", X86_FEATURE_2, true);
+            }
+            (&Machine::AARCH64, 0xc0000000) => {
+                return decode("AArch64 feature", AARCH64_FEATURE_1, false);
+            }
+            (_, 1) if data.len() == 8 => {
+                let size = u64::from_ne_bytes(data.try_into().unwrap());
+                return format!("stack size: {}", alt_hex(size));
+            }
+            (_, 2) => {
+                let mut text = String::from("no copy on protected ");
+                if !data.is_empty() {
+                    text.push_str(&format!("<corrupt length: {:#x}> ", data.len()));
+                }
+                return text;
+            }
+            (_, 0xb0008000) => {
+                return decode("1_needed", &["indirect external access"], false);
+            }
+            (_, 0xb0000000..=0xb000ffff) => {
+                let kind = if pr_type <= 0xb0007fff {
+                    "UINT32_AND"
+                } else {
+                    "UINT32_OR"
+                };
+                return match value {
+                    Some(value) => format!("{kind} ({pr_type:#x}): {}", alt_hex(value.into())),
+                    None => format!(
+                        "{kind} ({pr_type:#x}): <corrupt length: {:#x}> ",
+                        data.len()
+                    ),
+                };
+            }
+            _ => {}
+        }
+
+        let kind = match pr_type {
+            ..0xc0000000 => "unknown type",
+            0xc0000000..0xe0000000 => "processor-specific type",
+            _ => "application-specific type",
+        };
+        let bytes: String = data.iter().map(|b| format!("{b:02x} ")).collect();
+        format!("<{kind} {pr_type:#x} data: {bytes}>")
+    }
+
+    fn arch_specific(&mut self) -> Result<()> {
+        if *self.machine() != Machine::RISCV {
+            return Ok(());
+        }
+
+        for shdr in self.elf.section_headers() {
+            if shdr.sh_type.as_raw() != SECTION_TYPE_RISCV_ATTRIBUTES {
+                continue;
+            }
+            let data = self.elf.section_data(&shdr)?;
+            self.riscv_attributes(data)?;
+        }
+
+        Ok(())
+    }
+
+    /// Prints a RISC-V attribute section, made of vendor subsections holding
+    /// tagged groups of attributes
+    fn riscv_attributes(&mut self, data: &[u8]) -> Result<()> {
+        let Some((&b'A', mut rest)) = data.split_first() else {
+            writeln!(self.out, "Unknown attributes version '{:?}'", data.first())?;
+            return Ok(());
+        };
+
+        while rest.len() >= 4 {
+            let length = u32::from_ne_bytes(rest[..4].try_into().unwrap()) as usize;
+            if length < 4 || length > rest.len() {
+                bail!("bad subsection length");
+            }
+            let (subsection, next) = rest.split_at(length);
+            rest = next;
+
+            let subsection = &subsection[4..];
+            let vendor_end = subsection
+                .iter()
+                .position(|&b| b == 0)
+                .context("corrupt vendor name")?;
+            writeln!(
+                self.out,
+                "Attribute Section: {}",
+                show(&subsection[..vendor_end])
+            )?;
+
+            let mut groups = &subsection[vendor_end + 1..];
+            while groups.len() >= 5 {
+                let tag = groups[0];
+                let size = u32::from_ne_bytes(groups[1..5].try_into().unwrap()) as usize;
+                if size < 5 || size > groups.len() {
+                    bail!("bad attribute group length");
+                }
+                let (group, next) = groups.split_at(size);
+                groups = next;
+
+                match tag {
+                    1 => writeln!(self.out, "File Attributes")?,
+                    2 => writeln!(self.out, "Section Attributes:")?,
+                    3 => writeln!(self.out, "Symbol Attributes:")?,
+                    tag => writeln!(self.out, "Unknown tag: {tag}")?,
+                }
+
+                let mut attributes = &group[5..];
+                while !attributes.is_empty() {
+                    attributes = self.riscv_attribute(attributes)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn riscv_attribute<'d>(&mut self, data: &'d [u8]) -> Result<&'d [u8]> {
+        let (tag, mut rest) = read_uleb128(data);
+        let name = match tag {
+            4 => "Tag_RISCV_stack_align",
+            5 => "Tag_RISCV_arch",
+            6 => "Tag_RISCV_unaligned_access",
+            8 => "Tag_RISCV_priv_spec",
+            10 => "Tag_RISCV_priv_spec_minor",
+            12 => "Tag_RISCV_priv_spec_revision",
+            _ => {
+                write!(self.out, "  Tag_unknown_{tag}: ")?;
+                return self.attribute_value(tag & 1 != 0, rest);
+            }
+        };
+        write!(self.out, "  {name}: ")?;
+
+        if tag == 5 {
+            return self.attribute_value(true, rest);
+        }
+
+        let value;
+        (value, rest) = read_uleb128(rest);
+        match tag {
+            4 => writeln!(self.out, "{value}-bytes")?,
+            6 => match value {
+                0 => writeln!(self.out, "No unaligned access")?,
+                1 => writeln!(self.out, "Unaligned access")?,
+                _ => {}
+            },
+            _ => writeln!(self.out, "{value}")?,
+        }
+
+        Ok(rest)
+    }
+
+    fn attribute_value<'d>(&mut self, is_string: bool, data: &'d [u8]) -> Result<&'d [u8]> {
+        if is_string {
+            let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+            writeln!(self.out, "\"{}\"", show(&data[..end]))?;
+            Ok(data.get(end + 1..).unwrap_or_default())
+        } else {
+            let (value, rest) = read_uleb128(data);
+            writeln!(self.out, "{value} (0x{value:x})")?;
+            Ok(rest)
+        }
+    }
+}
+
+const X86_ISA: &[&str] = &["x86-64-baseline", "x86-64-v2", "x86-64-v3", "x86-64-v4"];
+const X86_FEATURE_1: &[&str] = &["IBT", "SHSTK", "LAM_U48", "LAM_U57"];
+const X86_FEATURE_2: &[&str] = &[
+    "x86", "x87", "MMX", "XMM", "YMM", "ZMM", "FXSR", "XSAVE", "XSAVEOPT", "XSAVEC", "TMM", "MASK",
+];
+const AARCH64_FEATURE_1: &[&str] = &["BTI", "PAC"];
+
+/// Names the set bits of `bitmask`, lowest first, from a table indexed by bit
+/// number
+fn bit_names(mut bitmask: u32, names: &[&str], none: bool) -> String {
+    if bitmask == 0 && none {
+        return "<None>".into();
+    }
+
+    let mut text = String::new();
+    while bitmask != 0 {
+        let bit = bitmask & bitmask.wrapping_neg();
+        bitmask &= !bit;
+        match names.get(bit.trailing_zeros() as usize) {
+            Some(name) => text.push_str(name),
+            None => text.push_str(&format!("<unknown: {bit:x}>")),
+        }
+        if bitmask != 0 {
+            text.push_str(", ");
+        }
+    }
+    text
+}
+
+/// Returns the name readelf gives to an OS ABI, which differs from the one
+/// of [`OsAbi::as_human_string`]
+fn os_abi_name(os_abi: &OsAbi) -> Cow<'static, str> {
+    match os_abi.as_raw() {
+        0 => "UNIX - System V".into(),
+        1 => "UNIX - HP-UX".into(),
+        2 => "UNIX - NetBSD".into(),
+        3 => "UNIX - GNU".into(),
+        6 => "UNIX - Solaris".into(),
+        7 => "UNIX - AIX".into(),
+        8 => "UNIX - IRIX".into(),
+        9 => "UNIX - FreeBSD".into(),
+        10 => "UNIX - TRU64".into(),
+        11 => "Novell - Modesto".into(),
+        12 => "UNIX - OpenBSD".into(),
+        13 => "VMS - OpenVMS".into(),
+        14 => "HP - Non-Stop Kernel".into(),
+        15 => "AROS".into(),
+        16 => "FenixOS".into(),
+        17 => "Nuxi CloudABI".into(),
+        18 => "Stratus Technologies OpenVOS".into(),
+        raw => format!("<unknown: {raw:x}>").into(),
+    }
+}
+
+fn machine_flags(machine: &Machine, flags: u32) -> String {
+    let mut text = String::new();
+
+    if *machine == Machine::RISCV {
+        if flags & 0x1 != 0 {
+            text.push_str(", RVC");
+        }
+        if flags & 0x8 != 0 {
+            text.push_str(", RVE");
+        }
+        if flags & 0x10 != 0 {
+            text.push_str(", TSO");
+        }
+        text.push_str(match flags & 0x6 {
+            0x0 => ", soft-float ABI",
+            0x2 => ", single-float ABI",
+            0x4 => ", double-float ABI",
+            _ => ", quad-float ABI",
+        });
+    }
+
+    text
+}
+
+fn segment_type_name(segment_type: &ProgramType) -> Cow<'static, str> {
+    let raw = segment_type.as_raw();
+    match segment_type.as_human_string() {
+        "unknown" | "LOOS" | "HIOS" | "LOPROC" | "HIPROC" => match raw {
+            0x70000000..=0x7fffffff => format!("LOPROC+{:#x}", raw - 0x70000000).into(),
+            0x60000000..=0x6fffffff => format!("LOOS+{:#x}", raw - 0x60000000).into(),
+            _ => format!("<unknown>: {raw:x}").into(),
+        },
+        name => name.into(),
+    }
+}
+
+fn segment_flags(flags: u32) -> String {
+    [
+        (SEGMENT_FLAG_READ, 'R'),
+        (SEGMENT_FLAG_WRITE, 'W'),
+        (SEGMENT_FLAG_EXECUTE, 'E'),
+    ]
+    .iter()
+    .map(|&(flag, letter)| if flags & flag != 0 { letter } else { ' ' })
+    .collect()
+}
+
+/// Returns true for `.tbss` like sections in segments other than `PT_TLS`,
+/// which take no room outside of the TLS template
+fn is_tbss_special(shdr: &SectionHeader64, phdr: &ProgramHeader64) -> bool {
+    shdr.sh_flags & SECTION_FLAG_TLS != 0
+        && shdr.sh_type == SectionType::NOBITS
+        && phdr.p_type != ProgramType::TLS
+}
+
+/// Decides whether a section belongs to a segment, like the strict variant of
+/// `ELF_SECTION_IN_SEGMENT` in binutils
+fn section_in_segment(shdr: &SectionHeader64, phdr: &ProgramHeader64) -> bool {
+    let is_tls = shdr.sh_flags & SECTION_FLAG_TLS != 0;
+    let is_alloc = shdr.sh_flags & SECTION_FLAG_ALLOC != 0;
+    let is_nobits = shdr.sh_type == SectionType::NOBITS;
+    let p_type = &phdr.p_type;
+    let size = if is_tbss_special(shdr, phdr) {
+        0
+    } else {
+        shdr.sh_size
+    };
+
+    let type_matches = if is_tls {
+        matches!(
+            *p_type,
+            ProgramType::TLS | ProgramType::GNU_RELRO | ProgramType::LOAD
+        )
+    } else {
+        *p_type != ProgramType::TLS && *p_type != ProgramType::PHDR
+    };
+    if !type_matches {
+        return false;
+    }
+
+    let needs_alloc = matches!(
+        *p_type,
+        ProgramType::LOAD
+            | ProgramType::DYNAMIC
+            | ProgramType::GNU_EH_FRAME
+            | ProgramType::GNU_STACK
+            | ProgramType::GNU_RELRO
+    );
+    if !is_alloc && needs_alloc {
+        return false;
+    }
+
+    // A range of `size` bytes at `start` lies in the `length` bytes at
+    // `base`, an empty range at the very end of them excepted
+    let within = |start: u64, base: u64, length: u64| {
+        start >= base && start - base <= length.wrapping_sub(1) && start - base + size <= length
+    };
+    if !is_nobits && !within(shdr.sh_offset, phdr.p_offset, phdr.p_filesz) {
+        return false;
+    }
+    if is_alloc && !within(shdr.sh_addr, phdr.p_vaddr, phdr.p_memsz) {
+        return false;
+    }
+
+    if *p_type != ProgramType::DYNAMIC && *p_type != ProgramType::NOTE {
+        return true;
+    }
+
+    shdr.sh_size != 0
+        || phdr.p_memsz == 0
+        || ((is_nobits
+            || (shdr.sh_offset > phdr.p_offset && shdr.sh_offset - phdr.p_offset < phdr.p_filesz))
+            && (!is_alloc
+                || (shdr.sh_addr > phdr.p_vaddr && shdr.sh_addr - phdr.p_vaddr < phdr.p_memsz)))
+}
+
+fn dynamic_tag_name(tag: &DynamicTag) -> Cow<'static, str> {
+    let raw = tag.as_raw();
+    match tag.as_human_string() {
+        "unknown" | "LOOS" | "HIOS" | "LOPROC" | "HIPROC" => match raw {
+            0x70000000..=0x7fffffff => format!("Processor Specific: {raw:x}").into(),
+            0x6000000d..=0x6ffff000 => format!("Operating System specific: {raw:x}").into(),
+            _ => format!("<unknown>: {raw:x}").into(),
+        },
+        name => name.into(),
+    }
+}
+
+fn dynamic_flags(mut flags: u64) -> String {
+    let mut names = Vec::new();
+    while flags != 0 {
+        let flag = flags & flags.wrapping_neg();
+        flags &= !flag;
+        names.push(match flag {
+            DYNAMIC_FLAG_ORIGIN => "ORIGIN",
+            DYNAMIC_FLAG_SYMBOLIC => "SYMBOLIC",
+            DYNAMIC_FLAG_TEXTREL => "TEXTREL",
+            DYNAMIC_FLAG_BIND_NOW => "BIND_NOW",
+            DYNAMIC_FLAG_STATIC_TLS => "STATIC_TLS",
+            _ => "unknown",
+        });
+    }
+    names.join(" ")
+}
+
+/// Names of the `DT_FLAGS_1` bits, by bit number
+const DYNAMIC_FLAGS_1: &[&str] = &[
+    "NOW",
+    "GLOBAL",
+    "GROUP",
+    "NODELETE",
+    "LOADFLTR",
+    "INITFIRST",
+    "NOOPEN",
+    "ORIGIN",
+    "DIRECT",
+    "TRANS",
+    "INTERPOSE",
+    "NODEFLIB",
+    "NODUMP",
+    "CONFALT",
+    "ENDFILTEE",
+    "DISPRELDNE",
+    "DISPRELPND",
+    "NODIRECT",
+    "IGNMULDEF",
+    "NOKSYMS",
+    "NOHDR",
+    "EDITED",
+    "NORELOC",
+    "SYMINTPOSE",
+    "GLOBAUDIT",
+    "SINGLETON",
+    "STUB",
+    "PIE",
+    "KMOD",
+    "WEAKFILTER",
+    "NOCOMMON",
+];
+
+fn dynamic_flags_1(flags: u64) -> String {
+    if flags == 0 {
+        return " None".into();
+    }
+
+    let mut text = String::new();
+    let mut rest = flags;
+    for (bit, name) in DYNAMIC_FLAGS_1.iter().enumerate() {
+        if rest & 1 << bit != 0 {
+            text.push(' ');
+            text.push_str(name);
+            rest &= !(1 << bit);
+        }
+    }
+    if rest != 0 {
+        text.push_str(&format!(" {rest:x}"));
+    }
+    text
+}
+
+fn symbol_type_name(symbol_type: &SymbolType) -> Cow<'static, str> {
+    let raw = symbol_type.as_raw();
+    match symbol_type.as_human_string() {
+        "unknown" => match raw {
+            13..=15 => format!("<processor specific>: {raw}").into(),
+            10..=12 => format!("<OS specific>: {raw}").into(),
+            _ => format!("<unknown>: {raw}").into(),
+        },
+        name => name.into(),
+    }
+}
+
+fn symbol_binding_name(binding: &SymbolBinding) -> Cow<'static, str> {
+    let raw = binding.as_raw();
+    match binding.as_human_string() {
+        "unknown" => match raw {
+            13..=15 => format!("<processor specific>: {raw}").into(),
+            10..=12 => format!("<OS specific>: {raw}").into(),
+            _ => format!("<unknown>: {raw}").into(),
+        },
+        name => name.into(),
+    }
+}
+
+fn version_flags(flags: u16) -> String {
+    if flags == 0 {
+        return "none".into();
+    }
+
+    let mut names = Vec::new();
+    if flags & VERSION_FLAG_BASE != 0 {
+        names.push("BASE");
+    }
+    if flags & VERSION_FLAG_WEAK != 0 {
+        names.push("WEAK");
+    }
+    if flags & 0x4 != 0 {
+        names.push("INFO");
+    }
+    if flags & !0x7 != 0 {
+        names.push("<unknown>");
+    }
+    names.join(" | ")
+}
+
+/// Formats a symbol name in a column of `width` characters, cutting names
+/// that do not fit with `[...]` unless the output is wide
+///
+/// A negative width also pads the name to the width of the column.
+fn print_symbol(width: i32, name: &str, wide: bool) -> String {
+    let pad = width < 0;
+    let width = width.unsigned_abs() as usize;
+    if width == 0 {
+        return String::new();
+    }
+
+    let length = name.chars().count();
+    let mut text = if wide || length <= width {
+        name.to_string()
+    } else {
+        let mut text: String = name.chars().take(width.saturating_sub(5)).collect();
+        text.push_str("[...]");
+        text
+    };
+
+    if pad {
+        let printed = text.chars().count();
+        if printed < width {
+            text.push_str(&" ".repeat(width - printed));
+        }
+    }
+    text
+}
+
+fn show(bytes: &[u8]) -> Cow<'_, str> {
+    String::from_utf8_lossy(bytes)
+}
+
+/// Formats a value like printf's `%#x`, which prints zero without a prefix
+fn alt_hex(value: u64) -> String {
+    if value == 0 {
+        "0".into()
+    } else {
+        format!("{value:#x}")
+    }
+}
+
+/// Formats a value like printf's `%#06x`
+fn alt_hex_06(value: usize) -> String {
+    if value == 0 {
+        "000000".into()
+    } else {
+        format!("{value:#06x}")
+    }
+}
+
+fn signed_hex(value: i64) -> String {
+    if value < 0 {
+        format!("- {:x}", value.unsigned_abs())
+    } else {
+        format!("+ {value:x}")
+    }
+}
+
+fn read_uleb128(data: &[u8]) -> (u64, &[u8]) {
+    let mut value = 0u64;
+    let mut shift = 0;
+    for (index, &byte) in data.iter().enumerate() {
+        if shift < 64 {
+            value |= u64::from(byte & 0x7f) << shift;
+        }
+        shift += 7;
+        if byte & 0x80 == 0 {
+            return (value, &data[index + 1..]);
+        }
+    }
+    (value, &[])
+}
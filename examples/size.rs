@@ -0,0 +1,763 @@
+//! Reports the size of ELF files, like GNU size
+//!
+//! The Berkeley (`-B`, the default) and SysV (`-A`) formats follow the output
+//! of GNU size. `--report` goes further and attributes the bytes of a file to
+//! its sections, its segments and its largest symbols by `st_size`, and
+//! `--json` prints the same report as JSON, with stable ordering, so that the
+//! reports of two builds can be diffed. The share of a section is that of
+//! the file size, and the share of a symbol that of the loaded size (text,
+//! data and bss). Aliases of the same symbol are only counted once.
+
+use std::borrow::Cow;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::process::ExitCode;
+
+use anyhow::{Context, Result, bail};
+use elfling::archive::{ARCHIVE_MAGIC, Archive, THIN_ARCHIVE_MAGIC};
+use elfling::file::ElfFile;
+use elfling::program_header::{
+    ProgramHeader64, ProgramType, SEGMENT_FLAG_EXECUTE, SEGMENT_FLAG_READ, SEGMENT_FLAG_WRITE,
+};
+use elfling::section_header::{
+    SECTION_FLAG_ALLOC, SECTION_FLAG_EXECINSTR, SECTION_FLAG_TLS, SECTION_FLAG_WRITE,
+    SectionHeader64, SectionType,
+};
+use elfling::symbol::{SectionIndex, SymbolBinding, SymbolType};
+
+const USAGE: &str = "\
+Usage: size [option(s)] [file(s)]
+ Displays the sizes of sections inside binary files
+ If no input file(s) are specified, a.out is assumed
+ The options are:
+  -A|-B     --format={sysv|berkeley|report|json}
+                              Select output style (default is berkeley)
+            --report          Attribute sizes to sections, segments and symbols
+            --json            Print the report as JSON
+            --top=<number>    Number of symbols in the report (default 10)
+  -o|-d|-x  --radix={8|10|16} Display numbers in octal, decimal or hex
+  -t        --totals          Display the total sizes (Berkeley only)";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Format {
+    #[default]
+    Berkeley,
+    SysV,
+    Report,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Radix {
+    Octal,
+    Decimal,
+    Hex,
+}
+
+impl Radix {
+    /// Formats a number like GNU size, with a `0` or `0x` prefix outside of
+    /// decimal
+    fn format(self, value: u64) -> String {
+        match self {
+            Radix::Octal => format!("0{value:o}"),
+            Radix::Hex => format!("{value:#x}"),
+            Radix::Decimal => value.to_string(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Options {
+    format: Format,
+    radix: Radix,
+    totals: bool,
+    top: usize,
+    files: Vec<String>,
+}
+
+impl Options {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut options = Self {
+            format: Format::Berkeley,
+            radix: Radix::Decimal,
+            totals: false,
+            top: 10,
+            files: Vec::new(),
+        };
+
+        for arg in args {
+            if let Some(long) = arg.strip_prefix("--") {
+                let (name, value) = match long.split_once('=') {
+                    Some((name, value)) => (name, Some(value)),
+                    None => (long, None),
+                };
+                match (name, value) {
+                    ("format", Some(format)) => {
+                        options.format = match format {
+                            "berkeley" | "bsd" => Format::Berkeley,
+                            "sysv" => Format::SysV,
+                            "report" => Format::Report,
+                            "json" => Format::Json,
+                            _ => bail!("invalid argument to --format: {format}"),
+                        };
+                    }
+                    ("report", None) => options.format = Format::Report,
+                    ("json", None) => options.format = Format::Json,
+                    ("top", Some(top)) => {
+                        options.top = top
+                            .parse()
+                            .with_context(|| format!("invalid number of symbols: {top}"))?;
+                    }
+                    ("radix", Some(radix)) => {
+                        options.radix = match radix {
+                            "8" => Radix::Octal,
+                            "10" => Radix::Decimal,
+                            "16" => Radix::Hex,
+                            _ => bail!("invalid radix: {radix}"),
+                        };
+                    }
+                    ("totals", None) => options.totals = true,
+                    _ => bail!("unrecognized option '{arg}'"),
+                }
+            } else if let Some(short) = arg.strip_prefix('-')
+                && !short.is_empty()
+            {
+                for flag in short.chars() {
+                    match flag {
+                        'A' => options.format = Format::SysV,
+                        'B' => options.format = Format::Berkeley,
+                        'o' => options.radix = Radix::Octal,
+                        'd' => options.radix = Radix::Decimal,
+                        'x' => options.radix = Radix::Hex,
+                        't' => options.totals = true,
+                        _ => bail!("invalid option -- '{flag}'"),
+                    }
+                }
+            } else {
+                options.files.push(arg);
+            }
+        }
+
+        if options.files.is_empty() {
+            options.files.push("a.out".into());
+        }
+
+        Ok(options)
+    }
+}
+
+fn main() -> ExitCode {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("size: {err}");
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let mut status = ExitCode::SUCCESS;
+    let mut reports = Vec::new();
+
+    for path in &options.files {
+        let result = std::fs::read(path)
+            .with_context(|| format!("'{path}'"))
+            .and_then(|data| {
+                if data.starts_with(ARCHIVE_MAGIC) || data.starts_with(THIN_ARCHIVE_MAGIC) {
+                    let archive = Archive::parse(&data).with_context(|| path.clone())?;
+                    for member in archive.members() {
+                        let member = member.with_context(|| path.clone())?;
+                        let name = String::from_utf8_lossy(member.name);
+                        let contents = member
+                            .read(Path::new(path))
+                            .with_context(|| format!("{path}({name})"))?;
+                        let elf =
+                            ElfFile::parse(&contents).with_context(|| format!("{path}({name})"))?;
+                        let input = Input {
+                            name: &name,
+                            archive: Some(path),
+                        };
+                        reports.push(Report::new(&input, &elf, options.top));
+                    }
+                } else {
+                    let elf = ElfFile::parse(&data).with_context(|| path.clone())?;
+                    let input = Input {
+                        name: path,
+                        archive: None,
+                    };
+                    reports.push(Report::new(&input, &elf, options.top));
+                }
+                Ok(())
+            });
+
+        if let Err(err) = result {
+            eprintln!("size: {err:#}");
+            status = ExitCode::FAILURE;
+        }
+    }
+
+    let result = match options.format {
+        Format::Berkeley => print_berkeley(&reports, &options, &mut out),
+        Format::SysV => reports
+            .iter()
+            .try_for_each(|report| report.print_sysv(options.radix, &mut out)),
+        Format::Report => reports.iter().try_for_each(|report| report.print(&mut out)),
+        Format::Json => write_json(&reports, &mut out),
+    };
+
+    if result.and_then(|()| Ok(out.flush()?)).is_err() {
+        status = ExitCode::FAILURE;
+    }
+    status
+}
+
+/// A file being reported on, possibly a member of an archive
+struct Input<'a> {
+    name: &'a str,
+    archive: Option<&'a str>,
+}
+
+/// Sizes of the allocated sections by kind, as in the Berkeley format
+#[derive(Debug, Clone, Copy, Default)]
+struct Totals {
+    /// Code and read-only data
+    text: u64,
+    /// Writable data
+    data: u64,
+    /// Uninitialized data
+    bss: u64,
+}
+
+impl Totals {
+    fn total(&self) -> u64 {
+        self.text + self.data + self.bss
+    }
+}
+
+struct SectionUsage {
+    name: String,
+    section_type: String,
+    address: u64,
+    size: u64,
+    /// Bytes taken in the file, zero for `SHT_NOBITS` sections
+    file_size: u64,
+    flags: u64,
+    /// Whether GNU size shows the section
+    listed: bool,
+}
+
+struct SegmentUsage {
+    segment_type: String,
+    offset: u64,
+    address: u64,
+    file_size: u64,
+    memory_size: u64,
+    flags: String,
+    sections: Vec<String>,
+}
+
+struct SymbolUsage {
+    name: String,
+    symbol_type: String,
+    section: String,
+    address: u64,
+    size: u64,
+}
+
+struct Report {
+    /// Name of the file, followed by the archive for archive members
+    name: String,
+    archive: Option<String>,
+    file_size: u64,
+    totals: Totals,
+    sections: Vec<SectionUsage>,
+    segments: Vec<SegmentUsage>,
+    symbols: Vec<SymbolUsage>,
+}
+
+impl Report {
+    fn new(input: &Input<'_>, elf: &ElfFile<'_>, top: usize) -> Self {
+        let symtab_index = elf
+            .section_headers()
+            .position(|shdr| shdr.sh_type == SectionType::SYMTAB);
+
+        let mut totals = Totals::default();
+        let mut sections = Vec::new();
+        for shdr in elf.section_headers() {
+            let is_alloc = shdr.sh_flags & SECTION_FLAG_ALLOC != 0;
+            let has_contents = shdr.sh_type != SectionType::NOBITS;
+
+            if is_alloc {
+                if shdr.sh_flags & SECTION_FLAG_EXECINSTR != 0
+                    || shdr.sh_flags & SECTION_FLAG_WRITE == 0
+                {
+                    totals.text += shdr.sh_size;
+                } else if has_contents {
+                    totals.data += shdr.sh_size;
+                } else {
+                    totals.bss += shdr.sh_size;
+                }
+            }
+
+            sections.push(SectionUsage {
+                name: section_name(elf, &shdr).into_owned(),
+                section_type: section_type_name(&shdr.sh_type),
+                address: shdr.sh_addr,
+                size: shdr.sh_size,
+                file_size: if has_contents { shdr.sh_size } else { 0 },
+                flags: shdr.sh_flags,
+                listed: is_listed(&shdr, symtab_index),
+            });
+        }
+        // The null section is not worth reporting
+        if !sections.is_empty() {
+            sections.remove(0);
+        }
+
+        let segments = elf
+            .program_headers()
+            .map(|phdr| SegmentUsage {
+                segment_type: segment_type_name(&phdr.p_type),
+                offset: phdr.p_offset,
+                address: phdr.p_vaddr,
+                file_size: phdr.p_filesz,
+                memory_size: phdr.p_memsz,
+                flags: segment_flags(phdr.p_flags),
+                sections: elf
+                    .section_headers()
+                    .filter(|shdr| section_in_segment(shdr, &phdr))
+                    .map(|shdr| section_name(elf, &shdr).into_owned())
+                    .collect(),
+            })
+            .collect();
+
+        Self {
+            name: input.name.to_string(),
+            archive: input.archive.map(str::to_string),
+            file_size: elf.data().len() as u64,
+            totals,
+            sections,
+            segments,
+            symbols: largest_symbols(elf, top),
+        }
+    }
+
+    /// Returns the name of the file in the report, like `lib.a(member.o)`
+    /// for archive members
+    fn display_name(&self) -> String {
+        match &self.archive {
+            Some(archive) => format!("{archive}({})", self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    fn print_sysv(&self, radix: Radix, out: &mut impl Write) -> Result<()> {
+        let listed: Vec<&SectionUsage> = self
+            .sections
+            .iter()
+            .filter(|section| section.listed)
+            .collect();
+        let total: u64 = listed.iter().map(|section| section.size).sum();
+
+        let name_width = listed
+            .iter()
+            .map(|section| section.name.len())
+            .max()
+            .unwrap_or_default();
+        let size_width = radix.format(total).len().max("size".len());
+        let address_width = listed
+            .iter()
+            .map(|section| radix.format(section.address).len())
+            .max()
+            .unwrap_or_default()
+            .max("addr".len());
+
+        write!(out, "{}  ", self.name)?;
+        if let Some(archive) = &self.archive {
+            write!(out, " (ex {archive})")?;
+        }
+        writeln!(
+            out,
+            ":\n{:<name_width$}   {:>size_width$}   {:>address_width$}",
+            "section", "size", "addr"
+        )?;
+        for section in listed {
+            writeln!(
+                out,
+                "{:<name_width$}   {:>size_width$}   {:>address_width$}",
+                section.name,
+                radix.format(section.size),
+                radix.format(section.address)
+            )?;
+        }
+        writeln!(
+            out,
+            "{:<name_width$}   {:>size_width$}\n\n",
+            "Total",
+            radix.format(total)
+        )?;
+
+        Ok(())
+    }
+
+    fn print(&self, out: &mut impl Write) -> Result<()> {
+        let totals = &self.totals;
+        writeln!(out, "{}:", self.display_name())?;
+        writeln!(
+            out,
+            "  File size {}, text {}, data {}, bss {}, total {}",
+            self.file_size,
+            totals.text,
+            totals.data,
+            totals.bss,
+            totals.total()
+        )?;
+
+        writeln!(out, "\nSections:")?;
+        writeln!(
+            out,
+            "  {:<24} {:<14} {:<16} {:>10} {:>10} {:>6}",
+            "Name", "Type", "Address", "Size", "File size", "Share"
+        )?;
+        for section in &self.sections {
+            writeln!(
+                out,
+                "  {:<24} {:<14} {:016x} {:>10} {:>10} {:>6}",
+                section.name,
+                section.section_type,
+                section.address,
+                section.size,
+                section.file_size,
+                percent(section.file_size, self.file_size)
+            )?;
+        }
+
+        if !self.segments.is_empty() {
+            writeln!(out, "\nSegments:")?;
+            writeln!(
+                out,
+                "  {:<14} {:<16} {:>10} {:>10} {:<3}  Sections",
+                "Type", "Address", "File size", "Mem size", "Flg"
+            )?;
+            for segment in &self.segments {
+                writeln!(
+                    out,
+                    "  {:<14} {:016x} {:>10} {:>10} {}  {}",
+                    segment.segment_type,
+                    segment.address,
+                    segment.file_size,
+                    segment.memory_size,
+                    segment.flags,
+                    segment.sections.join(" ")
+                )?;
+            }
+        }
+
+        if !self.symbols.is_empty() {
+            writeln!(out, "\nTop {} symbols by size:", self.symbols.len())?;
+            writeln!(
+                out,
+                "  {:>10} {:>6} {:<7} {:<20} Name",
+                "Size", "Share", "Type", "Section"
+            )?;
+            for symbol in &self.symbols {
+                writeln!(
+                    out,
+                    "  {:>10} {:>6} {:<7} {:<20} {}",
+                    symbol.size,
+                    percent(symbol.size, totals.total()),
+                    symbol.symbol_type,
+                    symbol.section,
+                    symbol.name
+                )?;
+            }
+        }
+        writeln!(out)?;
+
+        Ok(())
+    }
+
+    fn write_json(&self, out: &mut impl Write) -> Result<()> {
+        let totals = &self.totals;
+        writeln!(out, "  {{")?;
+        writeln!(out, "    \"file\": {},", json_string(&self.display_name()))?;
+        writeln!(out, "    \"file_size\": {},", self.file_size)?;
+        writeln!(
+            out,
+            "    \"totals\": {{\"text\": {}, \"data\": {}, \"bss\": {}, \"total\": {}}},",
+            totals.text,
+            totals.data,
+            totals.bss,
+            totals.total()
+        )?;
+
+        writeln!(out, "    \"sections\": [")?;
+        for (index, section) in self.sections.iter().enumerate() {
+            writeln!(
+                out,
+                "      {{\"name\": {}, \"type\": {}, \"address\": {}, \"size\": {}, \"file_size\": {}, \"flags\": {}}}{}",
+                json_string(&section.name),
+                json_string(&section.section_type),
+                section.address,
+                section.size,
+                section.file_size,
+                section.flags,
+                separator(index, self.sections.len())
+            )?;
+        }
+        writeln!(out, "    ],")?;
+
+        writeln!(out, "    \"segments\": [")?;
+        for (index, segment) in self.segments.iter().enumerate() {
+            let sections: Vec<String> = segment
+                .sections
+                .iter()
+                .map(|name| json_string(name))
+                .collect();
+            writeln!(
+                out,
+                "      {{\"type\": {}, \"offset\": {}, \"address\": {}, \"file_size\": {}, \"memory_size\": {}, \"flags\": {}, \"sections\": [{}]}}{}",
+                json_string(&segment.segment_type),
+                segment.offset,
+                segment.address,
+                segment.file_size,
+                segment.memory_size,
+                json_string(segment.flags.trim_end()),
+                sections.join(", "),
+                separator(index, self.segments.len())
+            )?;
+        }
+        writeln!(out, "    ],")?;
+
+        writeln!(out, "    \"symbols\": [")?;
+        for (index, symbol) in self.symbols.iter().enumerate() {
+            writeln!(
+                out,
+                "      {{\"name\": {}, \"type\": {}, \"section\": {}, \"address\": {}, \"size\": {}}}{}",
+                json_string(&symbol.name),
+                json_string(&symbol.symbol_type),
+                json_string(&symbol.section),
+                symbol.address,
+                symbol.size,
+                separator(index, self.symbols.len())
+            )?;
+        }
+        writeln!(out, "    ]")?;
+        write!(out, "  }}")?;
+
+        Ok(())
+    }
+}
+
+fn print_berkeley(reports: &[Report], options: &Options, out: &mut impl Write) -> Result<()> {
+    let radix = options.radix;
+    writeln!(
+        out,
+        "   text\t   data\t    bss\t{:>7}\t    hex\tfilename",
+        if radix == Radix::Octal { "oct" } else { "dec" }
+    )?;
+
+    let mut sums = Totals::default();
+    let mut print = |totals: &Totals, name: &str| {
+        let total = totals.total();
+        let total = if radix == Radix::Octal {
+            format!("{total:o}")
+        } else {
+            total.to_string()
+        };
+        writeln!(
+            out,
+            "{:>7}\t{:>7}\t{:>7}\t{total:>7}\t{:>7x}\t{name}",
+            radix.format(totals.text),
+            radix.format(totals.data),
+            radix.format(totals.bss),
+            totals.total()
+        )
+    };
+
+    for report in reports {
+        let name = match &report.archive {
+            Some(archive) => format!("{} (ex {archive})", report.name),
+            None => report.name.clone(),
+        };
+        print(&report.totals, &name)?;
+
+        sums.text += report.totals.text;
+        sums.data += report.totals.data;
+        sums.bss += report.totals.bss;
+    }
+
+    if options.totals {
+        print(&sums, "(TOTALS)")?;
+    }
+
+    Ok(())
+}
+
+fn write_json(reports: &[Report], out: &mut impl Write) -> Result<()> {
+    writeln!(out, "[")?;
+    for (index, report) in reports.iter().enumerate() {
+        report.write_json(out)?;
+        writeln!(out, "{}", separator(index, reports.len()))?;
+    }
+    writeln!(out, "]")?;
+    Ok(())
+}
+
+/// Returns the defined symbols with the largest `st_size`, counting aliases
+/// of the same object once
+fn largest_symbols(elf: &ElfFile<'_>, top: usize) -> Vec<SymbolUsage> {
+    let symtab = elf
+        .section_headers()
+        .find(|shdr| shdr.sh_type == SectionType::SYMTAB)
+        .or_else(|| {
+            elf.section_headers()
+                .find(|shdr| shdr.sh_type == SectionType::DYNSYM)
+        });
+    let Some(symtab) = symtab else {
+        return Vec::new();
+    };
+    let (Ok(symbols), Ok(strings)) = (elf.symbols(&symtab), elf.linked_strings(&symtab)) else {
+        return Vec::new();
+    };
+
+    let mut candidates: Vec<_> = symbols
+        .iter()
+        .filter(|symbol| {
+            symbol.st_size != 0
+                && !symbol.is_undefined()
+                && !matches!(symbol.symbol_type(), SymbolType::SECTION | SymbolType::FILE)
+        })
+        .collect();
+
+    // Prefer global names for aliases, then sort the aliases together
+    candidates.sort_by(|a, b| {
+        (a.st_shndx.as_raw(), a.st_value, a.st_size)
+            .cmp(&(b.st_shndx.as_raw(), b.st_value, b.st_size))
+            .then(
+                (b.binding() == SymbolBinding::GLOBAL).cmp(&(a.binding() == SymbolBinding::GLOBAL)),
+            )
+            .then(strings.get(a.st_name).cmp(&strings.get(b.st_name)))
+    });
+    candidates.dedup_by(|a, b| {
+        (a.st_shndx.as_raw(), a.st_value, a.st_size) == (b.st_shndx.as_raw(), b.st_value, b.st_size)
+    });
+
+    candidates.sort_by(|a, b| {
+        b.st_size
+            .cmp(&a.st_size)
+            .then(strings.get(a.st_name).cmp(&strings.get(b.st_name)))
+    });
+    candidates.truncate(top);
+
+    candidates
+        .into_iter()
+        .map(|symbol| SymbolUsage {
+            name: String::from_utf8_lossy(strings.get(symbol.st_name).unwrap_or_default())
+                .into_owned(),
+            symbol_type: symbol.symbol_type().as_human_string().to_string(),
+            section: match symbol.st_shndx {
+                SectionIndex::ABS | SectionIndex::COMMON => {
+                    symbol.st_shndx.as_human_string().to_string()
+                }
+                ref index => elf
+                    .section_header(index.as_raw() as usize)
+                    .map(|shdr| section_name(elf, &shdr).into_owned())
+                    .unwrap_or_default(),
+            },
+            address: symbol.st_value,
+            size: symbol.st_size,
+        })
+        .collect()
+}
+
+/// Returns true for the sections GNU size lists, which leaves out the
+/// symbol and string tables and the relocations of relocatable files
+fn is_listed(shdr: &SectionHeader64, symtab_index: Option<usize>) -> bool {
+    let is_alloc = shdr.sh_flags & SECTION_FLAG_ALLOC != 0;
+    match shdr.sh_type {
+        SectionType::NULL | SectionType::SYMTAB | SectionType::SYMTAB_SHNDX => false,
+        SectionType::STRTAB => is_alloc,
+        SectionType::REL | SectionType::RELA => {
+            is_alloc || shdr.sh_info == 0 || Some(shdr.sh_link as usize) != symtab_index
+        }
+        _ => true,
+    }
+}
+
+/// Returns true if a section is mapped by a segment
+fn section_in_segment(shdr: &SectionHeader64, phdr: &ProgramHeader64) -> bool {
+    if shdr.sh_flags & SECTION_FLAG_ALLOC == 0 || shdr.sh_size == 0 {
+        return false;
+    }
+    // `.tbss` only takes room in the TLS template
+    if shdr.sh_flags & SECTION_FLAG_TLS != 0
+        && shdr.sh_type == SectionType::NOBITS
+        && phdr.p_type != ProgramType::TLS
+    {
+        return false;
+    }
+
+    shdr.sh_addr >= phdr.p_vaddr
+        && shdr.sh_addr - phdr.p_vaddr < phdr.p_memsz
+        && shdr.sh_addr - phdr.p_vaddr + shdr.sh_size <= phdr.p_memsz
+}
+
+fn section_name<'a>(elf: &ElfFile<'a>, shdr: &SectionHeader64) -> Cow<'a, str> {
+    String::from_utf8_lossy(elf.section_name(shdr).unwrap_or_default())
+}
+
+fn section_type_name(section_type: &SectionType) -> String {
+    match section_type.as_human_string() {
+        "unknown" => format!("{:#x}", section_type.as_raw()),
+        name => name.to_string(),
+    }
+}
+
+fn segment_type_name(segment_type: &ProgramType) -> String {
+    match segment_type.as_human_string() {
+        "unknown" => format!("{:#x}", segment_type.as_raw()),
+        name => name.to_string(),
+    }
+}
+
+fn segment_flags(flags: u32) -> String {
+    [
+        (SEGMENT_FLAG_READ, 'R'),
+        (SEGMENT_FLAG_WRITE, 'W'),
+        (SEGMENT_FLAG_EXECUTE, 'E'),
+    ]
+    .iter()
+    .map(|&(flag, letter)| if flags & flag != 0 { letter } else { ' ' })
+    .collect()
+}
+
+fn percent(part: u64, whole: u64) -> String {
+    if whole == 0 {
+        return "-".into();
+    }
+    format!("{:.1}%", part as f64 * 100.0 / whole as f64)
+}
+
+fn separator(index: usize, count: usize) -> &'static str {
+    if index + 1 < count { "," } else { "" }
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
@@ -0,0 +1,702 @@
+#[cfg(feature = "alloc")]
+use alloc::borrow::Cow;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+
+use zerocopy::{FromBytes, Immutable, IntoBytes};
+
+use crate::error::{Error, Result};
+use crate::file::ElfFile;
+#[cfg(feature = "alloc")]
+use crate::header::Magic;
+#[cfg(feature = "alloc")]
+use crate::section_header::SectionType;
+#[cfg(feature = "alloc")]
+use crate::symbol::{SectionIndex, SymbolBinding, SymbolType};
+
+/// Magic at the start of a regular archive
+pub const ARCHIVE_MAGIC: &[u8; 8] = b"!<arch>\n";
+/// Magic at the start of a thin archive, whose members are stored in
+/// separate files
+pub const THIN_ARCHIVE_MAGIC: &[u8; 8] = b"!<thin>\n";
+
+/// Archive member header (ar_hdr)
+///
+/// Every field is ASCII text padded with spaces.
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes, IntoBytes, Immutable)]
+#[doc(alias = "ar_hdr")]
+pub struct MemberHeader {
+    /// Member name, terminated by `/` in the GNU format
+    pub ar_name: [u8; 16],
+    /// Modification time in seconds since the epoch, in decimal
+    pub ar_date: [u8; 12],
+    /// Owner user ID, in decimal
+    pub ar_uid: [u8; 6],
+    /// Owner group ID, in decimal
+    pub ar_gid: [u8; 6],
+    /// File mode, in octal
+    pub ar_mode: [u8; 8],
+    /// Size of the member data, in decimal
+    pub ar_size: [u8; 10],
+    /// Header terminator, a backquote and a newline
+    pub ar_fmag: [u8; 2],
+}
+
+/// Terminator of every member header
+const HEADER_MAGIC: &[u8; 2] = b"`\n";
+/// Prefix of a BSD name stored at the start of the member data
+const BSD_NAME_PREFIX: &[u8] = b"#1/";
+
+/// Format of an archive symbol index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolIndexFormat {
+    /// GNU and System V `/` member with 32-bit big-endian offsets
+    Gnu,
+    /// GNU `/SYM64/` member with 64-bit big-endian offsets
+    Gnu64,
+    /// BSD `__.SYMDEF` member with `ranlib` entries in native byte order
+    Bsd,
+}
+
+/// The symbol index of an archive, mapping each defined symbol to the
+/// member that defines it
+#[derive(Debug, Clone)]
+pub struct SymbolIndex<'a> {
+    format: SymbolIndexFormat,
+    count: usize,
+    entries: &'a [u8],
+    names: &'a [u8],
+}
+
+impl<'a> SymbolIndex<'a> {
+    /// Parses the data of a symbol index member
+    pub fn parse(data: &'a [u8], format: SymbolIndexFormat) -> Result<Self> {
+        let truncated = Error::InvalidData("truncated archive symbol index");
+        let (count, entry_size, rest) = match format {
+            SymbolIndexFormat::Gnu => {
+                let (count, rest) = data.split_first_chunk::<4>().ok_or(truncated.clone())?;
+                (u32::from_be_bytes(*count) as usize, 4, rest)
+            }
+            SymbolIndexFormat::Gnu64 => {
+                let (count, rest) = data.split_first_chunk::<8>().ok_or(truncated.clone())?;
+                (u64::from_be_bytes(*count) as usize, 8, rest)
+            }
+            SymbolIndexFormat::Bsd => {
+                // The size in bytes of the ranlib entries, two words each
+                let (size, rest) = data.split_first_chunk::<4>().ok_or(truncated.clone())?;
+                (u32::from_ne_bytes(*size) as usize / 8, 8, rest)
+            }
+        };
+
+        let entries_size = count.checked_mul(entry_size).ok_or(truncated.clone())?;
+        if entries_size > rest.len() {
+            return Err(truncated);
+        }
+        let (entries, mut names) = rest.split_at(entries_size);
+        if format == SymbolIndexFormat::Bsd {
+            let (size, rest) = names.split_first_chunk::<4>().ok_or(truncated.clone())?;
+            names = rest
+                .get(..u32::from_ne_bytes(*size) as usize)
+                .ok_or(truncated)?;
+        }
+
+        Ok(Self {
+            format,
+            count,
+            entries,
+            names,
+        })
+    }
+
+    pub fn format(&self) -> SymbolIndexFormat {
+        self.format
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Iterates over the symbol names and the offsets of the member headers
+    /// that define them
+    pub fn iter(&self) -> impl Iterator<Item = (&'a [u8], u64)> + '_ {
+        let mut names = self.names;
+        (0..self.count).map_while(move |index| match self.format {
+            SymbolIndexFormat::Gnu | SymbolIndexFormat::Gnu64 => {
+                let end = names.iter().position(|&b| b == 0)?;
+                let name = &names[..end];
+                names = &names[end + 1..];
+                Some((name, self.offset(index)))
+            }
+            SymbolIndexFormat::Bsd => {
+                let entry = &self.entries[index * 8..];
+                let name_offset = u32::from_ne_bytes(entry[..4].try_into().unwrap()) as usize;
+                let name = names.get(name_offset..)?;
+                let end = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+                Some((&name[..end], self.offset(index)))
+            }
+        })
+    }
+
+    /// Returns the offset of the header of the member defining `name`
+    pub fn find(&self, name: &[u8]) -> Option<u64> {
+        self.iter()
+            .find(|(symbol, _)| *symbol == name)
+            .map(|(_, offset)| offset)
+    }
+
+    fn offset(&self, index: usize) -> u64 {
+        match self.format {
+            SymbolIndexFormat::Gnu => {
+                let entry = &self.entries[index * 4..index * 4 + 4];
+                u32::from_be_bytes(entry.try_into().unwrap()).into()
+            }
+            SymbolIndexFormat::Gnu64 => {
+                let entry = &self.entries[index * 8..index * 8 + 8];
+                u64::from_be_bytes(entry.try_into().unwrap())
+            }
+            SymbolIndexFormat::Bsd => {
+                let entry = &self.entries[index * 8 + 4..index * 8 + 8];
+                u32::from_ne_bytes(entry.try_into().unwrap()).into()
+            }
+        }
+    }
+}
+
+/// A member of an archive
+#[derive(Debug, Clone)]
+pub struct Member<'a> {
+    /// Offset of the member header in the archive
+    pub offset: u64,
+    /// Member name, resolved from the long name table or the BSD name
+    ///
+    /// The members of a thin archive are named by their path relative to the
+    /// archive.
+    pub name: &'a [u8],
+    /// Modification time in seconds since the epoch
+    pub date: u64,
+    pub uid: u32,
+    pub gid: u32,
+    pub mode: u32,
+    /// Size of the member contents
+    pub size: u64,
+    /// Member contents, or `None` for the members of a thin archive
+    pub data: Option<&'a [u8]>,
+}
+
+impl<'a> Member<'a> {
+    /// Returns true if the contents are stored outside the archive
+    pub fn is_thin(&self) -> bool {
+        self.data.is_none()
+    }
+
+    /// Parses the member contents as an ELF file
+    pub fn elf(&self) -> Result<ElfFile<'a>> {
+        let data = self
+            .data
+            .ok_or(Error::InvalidData("thin archive member has no contents"))?;
+        ElfFile::parse(data)
+    }
+
+    /// Returns the path of the contents of a thin archive member, relative to
+    /// the archive at `archive_path`
+    #[cfg(feature = "std")]
+    pub fn external_path(&self, archive_path: &Path) -> Option<PathBuf> {
+        let name = Path::new(core::str::from_utf8(self.name).ok()?);
+        Some(match archive_path.parent() {
+            Some(directory) if name.is_relative() => directory.join(name),
+            _ => name.to_path_buf(),
+        })
+    }
+
+    /// Reads the contents of the member, from the external file for the
+    /// members of a thin archive at `archive_path`
+    #[cfg(feature = "std")]
+    pub fn read(&self, archive_path: &Path) -> std::io::Result<Vec<u8>> {
+        if let Some(data) = self.data {
+            return Ok(data.to_vec());
+        }
+        let path = self.external_path(archive_path).ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidData, "invalid member path")
+        })?;
+        std::fs::read(path)
+    }
+}
+
+/// A view over an `ar` archive stored in memory, such as a static library
+///
+/// Both the GNU and System V format, with its `//` long name table, and the
+/// BSD format, with names stored before the member contents, are read.
+#[derive(Debug, Clone)]
+pub struct Archive<'a> {
+    data: &'a [u8],
+    thin: bool,
+    long_names: &'a [u8],
+    symbols: Option<SymbolIndex<'a>>,
+    first_member: usize,
+}
+
+impl<'a> Archive<'a> {
+    /// Parses the archive magic and the special members holding the symbol
+    /// index and the long name table
+    pub fn parse(data: &'a [u8]) -> Result<Self> {
+        let thin = if data.starts_with(ARCHIVE_MAGIC) {
+            false
+        } else if data.starts_with(THIN_ARCHIVE_MAGIC) {
+            true
+        } else {
+            return Err(Error::InvalidData("invalid archive magic"));
+        };
+
+        let mut archive = Self {
+            data,
+            thin,
+            long_names: &[],
+            symbols: None,
+            first_member: ARCHIVE_MAGIC.len(),
+        };
+
+        // The special members come first, the symbol index before the names
+        while archive.first_member < data.len() {
+            let entry = archive.entry(archive.first_member)?;
+            let name = trim(&entry.header.ar_name);
+            let format = match name {
+                b"/" => SymbolIndexFormat::Gnu,
+                b"/SYM64/" => SymbolIndexFormat::Gnu64,
+                b"//" => {
+                    archive.long_names = entry.contents;
+                    archive.first_member = entry.next;
+                    continue;
+                }
+                _ if name.starts_with(BSD_NAME_PREFIX) => {
+                    let (name, _) = bsd_name(name, entry.contents)?;
+                    if name != b"__.SYMDEF" && name != b"__.SYMDEF SORTED" {
+                        break;
+                    }
+                    SymbolIndexFormat::Bsd
+                }
+                _ => break,
+            };
+            let contents = match format {
+                SymbolIndexFormat::Bsd => bsd_name(name, entry.contents)?.1,
+                _ => entry.contents,
+            };
+            archive.symbols = Some(SymbolIndex::parse(contents, format)?);
+            archive.first_member = entry.next;
+        }
+
+        Ok(archive)
+    }
+
+    /// Returns true if this is a thin archive, whose members are stored in
+    /// separate files
+    pub fn is_thin(&self) -> bool {
+        self.thin
+    }
+
+    /// Returns the symbol index, if the archive has one
+    pub fn symbols(&self) -> Option<&SymbolIndex<'a>> {
+        self.symbols.as_ref()
+    }
+
+    /// Iterates over the members, excluding the symbol index and the long
+    /// name table
+    pub fn members(&self) -> MemberIter<'a> {
+        MemberIter {
+            archive: self.clone(),
+            offset: self.first_member,
+        }
+    }
+
+    /// Returns the member whose header starts at `offset`, as given by the
+    /// symbol index
+    pub fn member_at(&self, offset: u64) -> Result<Member<'a>> {
+        let offset = usize::try_from(offset).map_err(|_| Error::OutOfBounds { offset, size: 0 })?;
+        self.member(offset).map(|(member, _)| member)
+    }
+
+    /// Returns the member defining `symbol` according to the symbol index
+    pub fn find_symbol(&self, symbol: &[u8]) -> Option<Result<Member<'a>>> {
+        let offset = self.symbols.as_ref()?.find(symbol)?;
+        Some(self.member_at(offset))
+    }
+
+    /// Reads the header at `offset` and the member contents stored in the
+    /// archive
+    fn entry(&self, offset: usize) -> Result<Entry<'a>> {
+        let (header, rest) = self
+            .data
+            .get(offset..)
+            .and_then(|data| MemberHeader::read_from_prefix(data).ok())
+            .ok_or(Error::OutOfBounds {
+                offset: offset as u64,
+                size: size_of::<MemberHeader>() as u64,
+            })?;
+        if header.ar_fmag != *HEADER_MAGIC {
+            return Err(Error::InvalidData("invalid archive member header"));
+        }
+
+        // Only the special members have contents in a thin archive
+        let size = parse_number(&header.ar_size, 10)?;
+        let special = matches!(trim(&header.ar_name), b"/" | b"//" | b"/SYM64/");
+        let stored = if self.thin && !special { 0 } else { size };
+        let contents = rest.get(..stored as usize).ok_or(Error::OutOfBounds {
+            offset: (offset + size_of::<MemberHeader>()) as u64,
+            size: stored,
+        })?;
+
+        Ok(Entry {
+            header,
+            contents,
+            size,
+            next: (offset + size_of::<MemberHeader>() + contents.len()).next_multiple_of(2),
+        })
+    }
+
+    fn member(&self, offset: usize) -> Result<(Member<'a>, usize)> {
+        let Entry {
+            header,
+            mut contents,
+            mut size,
+            next,
+        } = self.entry(offset)?;
+
+        // Borrow the name from the archive rather than the copied header
+        let raw_name = trim(&self.data[offset..offset + size_of_val(&header.ar_name)]);
+        let name = if let Some(long) = raw_name.strip_prefix(b"/")
+            && !long.is_empty()
+            && long.iter().all(u8::is_ascii_digit)
+        {
+            let start = parse_number(long, 10)? as usize;
+            let names = self
+                .long_names
+                .get(start..)
+                .ok_or(Error::InvalidData("invalid archive long name offset"))?;
+            let end = names
+                .iter()
+                .position(|&b| b == b'\n')
+                .unwrap_or(names.len());
+            let name = &names[..end];
+            name.strip_suffix(b"/").unwrap_or(name)
+        } else if raw_name.starts_with(BSD_NAME_PREFIX) {
+            let (name, rest) = bsd_name(raw_name, contents)?;
+            size -= (contents.len() - rest.len()) as u64;
+            contents = rest;
+            name
+        } else {
+            raw_name.strip_suffix(b"/").unwrap_or(raw_name)
+        };
+
+        let member = Member {
+            offset: offset as u64,
+            name,
+            date: parse_number(&header.ar_date, 10)?,
+            uid: parse_number(&header.ar_uid, 10)? as u32,
+            gid: parse_number(&header.ar_gid, 10)? as u32,
+            mode: parse_number(&header.ar_mode, 8)? as u32,
+            size,
+            data: (!self.thin).then_some(contents),
+        };
+        Ok((member, next))
+    }
+}
+
+/// A member header with the contents stored after it
+struct Entry<'a> {
+    header: MemberHeader,
+    contents: &'a [u8],
+    /// Size from the header, which for thin archives is not stored
+    size: u64,
+    /// Offset of the following header
+    next: usize,
+}
+
+/// Iterator over the members of an [`Archive`]
+#[derive(Debug, Clone)]
+pub struct MemberIter<'a> {
+    archive: Archive<'a>,
+    offset: usize,
+}
+
+impl<'a> Iterator for MemberIter<'a> {
+    type Item = Result<Member<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.archive.data.len() {
+            return None;
+        }
+
+        match self.archive.member(self.offset) {
+            Ok((member, next)) => {
+                self.offset = next;
+                Some(Ok(member))
+            }
+            Err(error) => {
+                self.offset = self.archive.data.len();
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+/// Builder for GNU format archives, such as static libraries
+///
+/// The output is deterministic, like `ar rcD`: every member has a zero
+/// timestamp, owner and group and mode `644`. Names longer than 15 bytes go
+/// to the `//` long name table, and the symbol index lists the global
+/// symbols defined by each ELF member, switching to `/SYM64/` when a member
+/// starts past 4 GiB. Like `ar`, no index is written when no member defines
+/// a symbol.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Default)]
+pub struct ArchiveBuilder<'a> {
+    names: Vec<Cow<'a, [u8]>>,
+    contents: Vec<Cow<'a, [u8]>>,
+    symbol_index: bool,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> ArchiveBuilder<'a> {
+    pub fn new() -> Self {
+        Self {
+            names: Vec::new(),
+            contents: Vec::new(),
+            symbol_index: true,
+        }
+    }
+
+    /// Appends a member with the given file name and contents
+    pub fn add_member(
+        &mut self,
+        name: impl Into<Cow<'a, [u8]>>,
+        data: impl Into<Cow<'a, [u8]>>,
+    ) -> &mut Self {
+        self.names.push(name.into());
+        self.contents.push(data.into());
+        self
+    }
+
+    /// Sets whether a symbol index is written, which is the default
+    pub fn symbol_index(&mut self, symbol_index: bool) -> &mut Self {
+        self.symbol_index = symbol_index;
+        self
+    }
+
+    /// Produces the archive
+    pub fn write(&self) -> Result<Vec<u8>> {
+        let mut long_names = Vec::new();
+        let mut headers = Vec::with_capacity(self.names.len());
+        for (name, data) in self.names.iter().zip(&self.contents) {
+            if name.is_empty() || name.contains(&b'/') {
+                return Err(Error::InvalidData("invalid archive member name"));
+            }
+            let name_field = if name.len() < 16 {
+                [name.as_ref(), b"/"].concat()
+            } else {
+                let field = alloc::format!("/{}", long_names.len()).into_bytes();
+                long_names.extend_from_slice(name);
+                long_names.extend_from_slice(b"/\n");
+                field
+            };
+            headers.push(member_header(&name_field, b"0", b"644", data.len() as u64)?);
+        }
+        // The padding is part of the long name table
+        if !long_names.len().is_multiple_of(2) {
+            long_names.push(b'\n');
+        }
+
+        let mut symbols = Vec::new();
+        if self.symbol_index {
+            for (index, data) in self.contents.iter().enumerate() {
+                for name in defined_symbols(data)? {
+                    symbols.push((index, name));
+                }
+            }
+        }
+        let names_size = symbols
+            .iter()
+            .map(|(_, name)| name.len() + 1)
+            .sum::<usize>();
+
+        // Member offsets depend on the size of the index, which depends on
+        // the width of the offsets
+        let mut wide = false;
+        let (index_size, offsets) = loop {
+            let word = if wide { 8 } else { 4 };
+            let index_size = if !symbols.is_empty() {
+                (word * (symbols.len() + 1) + names_size).next_multiple_of(2)
+            } else {
+                0
+            };
+            let mut offset = ARCHIVE_MAGIC.len();
+            if index_size != 0 {
+                offset += size_of::<MemberHeader>() + index_size;
+            }
+            if !long_names.is_empty() {
+                offset += size_of::<MemberHeader>() + long_names.len();
+            }
+            let mut offsets = Vec::with_capacity(self.contents.len());
+            for data in &self.contents {
+                offsets.push(offset as u64);
+                offset += size_of::<MemberHeader>() + data.len().next_multiple_of(2);
+            }
+            if wide || offsets.last().is_none_or(|&last| last <= u32::MAX as u64) {
+                break (index_size, offsets);
+            }
+            wide = true;
+        };
+
+        let mut out = ARCHIVE_MAGIC.to_vec();
+        if index_size != 0 {
+            let name: &[u8] = if wide { b"/SYM64/" } else { b"/" };
+            let header = member_header(name, b"0", b"0", index_size as u64)?;
+            out.extend_from_slice(header.as_bytes());
+            let start = out.len();
+            if wide {
+                out.extend_from_slice(&(symbols.len() as u64).to_be_bytes());
+                for (member, _) in &symbols {
+                    out.extend_from_slice(&offsets[*member].to_be_bytes());
+                }
+            } else {
+                out.extend_from_slice(&(symbols.len() as u32).to_be_bytes());
+                for (member, _) in &symbols {
+                    out.extend_from_slice(&(offsets[*member] as u32).to_be_bytes());
+                }
+            }
+            for (_, name) in &symbols {
+                out.extend_from_slice(name);
+                out.push(0);
+            }
+            out.resize(start + index_size, 0);
+        }
+
+        if !long_names.is_empty() {
+            let header = member_header(b"//", b"", b"", long_names.len() as u64)?;
+            out.extend_from_slice(header.as_bytes());
+            out.extend_from_slice(&long_names);
+        }
+
+        for (header, data) in headers.iter().zip(&self.contents) {
+            out.extend_from_slice(header.as_bytes());
+            out.extend_from_slice(data);
+            pad(&mut out);
+        }
+
+        Ok(out)
+    }
+}
+
+/// Returns the names of the global symbols an ELF file defines, in symbol
+/// table order, or nothing for other files
+#[cfg(feature = "alloc")]
+fn defined_symbols(data: &[u8]) -> Result<Vec<&[u8]>> {
+    if !data.starts_with(Magic::ELF.as_bytes()) {
+        return Ok(Vec::new());
+    }
+
+    let file = ElfFile::parse(data)?;
+    let mut names = Vec::new();
+    for shdr in file.section_headers() {
+        if shdr.sh_type != SectionType::SYMTAB {
+            continue;
+        }
+        let strings = file.linked_strings(&shdr)?;
+        for symbol in file.symbols(&shdr)?.iter().skip(1) {
+            let binding = symbol.binding();
+            let global = binding == SymbolBinding::GLOBAL
+                || binding == SymbolBinding::WEAK
+                || binding == SymbolBinding::GNU_UNIQUE;
+            let symbol_type = symbol.symbol_type();
+            if !global
+                || symbol.st_shndx == SectionIndex::UNDEF
+                || symbol_type == SymbolType::SECTION
+                || symbol_type == SymbolType::FILE
+            {
+                continue;
+            }
+            if let Some(name) = strings.get(symbol.st_name)
+                && !name.is_empty()
+            {
+                names.push(name);
+            }
+        }
+    }
+    Ok(names)
+}
+
+/// Returns a deterministic member header, with `owner` as the timestamp,
+/// owner and group
+#[cfg(feature = "alloc")]
+fn member_header(name: &[u8], owner: &[u8], mode: &[u8], size: u64) -> Result<MemberHeader> {
+    fn field<const N: usize>(value: &[u8]) -> Result<[u8; N]> {
+        if value.len() > N {
+            return Err(Error::InvalidData("archive member header field too long"));
+        }
+        let mut field = [b' '; N];
+        field[..value.len()].copy_from_slice(value);
+        Ok(field)
+    }
+
+    Ok(MemberHeader {
+        ar_name: field(name)?,
+        ar_date: field(owner)?,
+        ar_uid: field(owner)?,
+        ar_gid: field(owner)?,
+        ar_mode: field(mode)?,
+        ar_size: field(alloc::format!("{size}").as_bytes())?,
+        ar_fmag: *HEADER_MAGIC,
+    })
+}
+
+/// Pads member contents to an even offset with a newline
+#[cfg(feature = "alloc")]
+fn pad(out: &mut Vec<u8>) {
+    if !out.len().is_multiple_of(2) {
+        out.push(b'\n');
+    }
+}
+
+/// Returns the size of the BSD name stored at the start of the contents
+fn bsd_name_size(name: &[u8]) -> u64 {
+    trim(name)
+        .strip_prefix(BSD_NAME_PREFIX)
+        .and_then(|size| parse_number(size, 10).ok())
+        .unwrap_or(0)
+}
+
+/// Splits the BSD name `#1/<size>` from the start of the member contents
+fn bsd_name<'a>(name: &[u8], contents: &'a [u8]) -> Result<(&'a [u8], &'a [u8])> {
+    let size = bsd_name_size(name) as usize;
+    if size > contents.len() {
+        return Err(Error::InvalidData("invalid BSD archive member name"));
+    }
+    let (name, contents) = contents.split_at(size);
+    let end = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+    Ok((&name[..end], contents))
+}
+
+fn trim(field: &[u8]) -> &[u8] {
+    let end = field
+        .iter()
+        .rposition(|&b| b != b' ')
+        .map_or(0, |end| end + 1);
+    &field[..end]
+}
+
+/// Parses a numeric header field, which is empty for the special members
+fn parse_number(field: &[u8], radix: u32) -> Result<u64> {
+    let field = trim(field);
+    if field.is_empty() {
+        return Ok(0);
+    }
+    core::str::from_utf8(field)
+        .ok()
+        .and_then(|field| u64::from_str_radix(field, radix).ok())
+        .ok_or(Error::InvalidData("invalid archive member header"))
+}
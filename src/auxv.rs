@@ -0,0 +1,140 @@
+use zerocopy::{FromBytes, Immutable, IntoBytes};
+
+use crate::elf_enum;
+use crate::endian::impl_swap_bytes;
+use crate::note::{CoreNoteType, Note};
+use crate::table::Table;
+
+/// 64-bit auxiliary vector entry (Elf64_auxv_t)
+///
+/// The kernel passes the auxiliary vector on the initial process stack, after
+/// the environment pointers, and core files carry a copy in an `NT_AUXV` note.
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes, IntoBytes, Immutable)]
+#[doc(alias = "Elf64_auxv_t")]
+pub struct AuxEntry64 {
+    /// Entry type
+    pub a_type: AuxType,
+    /// Integer value or address, depending on `a_type`
+    #[doc(alias = "a_un")]
+    pub a_val: u64,
+}
+
+impl_swap_bytes!(AuxEntry64 { a_type, a_val });
+
+/// A view over an auxiliary vector, stopping at the first `AT_NULL` entry
+pub type AuxVector<'a> = Table<'a, AuxEntry64>;
+
+impl<'a> AuxVector<'a> {
+    /// Creates a view over an auxiliary vector stored as native words, such as
+    /// the contents of `/proc/<pid>/auxv` read into a `u64` buffer
+    pub fn from_words(words: &'a [u64]) -> Self {
+        Self::new(words.as_bytes())
+    }
+
+    /// Creates a view over the auxiliary vector starting at `auxv`, up to and
+    /// including its terminating `AT_NULL` entry
+    ///
+    /// # Safety
+    ///
+    /// `auxv` must point to a valid auxiliary vector terminated by an
+    /// `AT_NULL` entry, such as the one found on the initial process stack,
+    /// and it must stay valid and unmodified for the lifetime `'a`.
+    pub unsafe fn from_ptr(auxv: *const AuxEntry64) -> Self {
+        let mut count = 0;
+        // SAFETY: the caller guarantees that every entry up to `AT_NULL` is valid
+        while unsafe { (*auxv.add(count)).a_type != AuxType::NULL } {
+            count += 1;
+        }
+
+        // SAFETY: the entries counted above, plus the terminator, are valid
+        let bytes = unsafe {
+            core::slice::from_raw_parts(auxv.cast::<u8>(), (count + 1) * size_of::<AuxEntry64>())
+        };
+        Self::new(bytes)
+    }
+
+    /// Creates a view over the auxiliary vector stored in an `NT_AUXV` note
+    pub fn from_note(note: &Note<'a>) -> Option<Self> {
+        note.is(Note::CORE, CoreNoteType::AUXV.as_raw())
+            .then(|| Self::new(note.desc))
+    }
+
+    /// Iterates over entries up to, but not including, the terminating `AT_NULL`
+    pub fn entries(&self) -> impl Iterator<Item = AuxEntry64> + '_ {
+        self.iter()
+            .take_while(|entry| entry.a_type != AuxType::NULL)
+    }
+
+    /// Returns the value of the first entry with the given type
+    pub fn find(&self, a_type: AuxType) -> Option<u64> {
+        self.entries()
+            .find(|entry| entry.a_type == a_type)
+            .map(|entry| entry.a_val)
+    }
+}
+
+elf_enum! {
+    pub struct AuxType(u64) {
+        /// Marks the end of the vector
+        NULL = 0, "AT_NULL",
+        /// Entry should be ignored
+        IGNORE = 1, "AT_IGNORE",
+        /// File descriptor of the program
+        EXECFD = 2, "AT_EXECFD",
+        /// Address of the program headers of the executable
+        PHDR = 3, "AT_PHDR",
+        /// Size of a program header entry
+        PHENT = 4, "AT_PHENT",
+        /// Number of program headers
+        PHNUM = 5, "AT_PHNUM",
+        /// System page size
+        PAGESZ = 6, "AT_PAGESZ",
+        /// Base address of the program interpreter
+        BASE = 7, "AT_BASE",
+        /// Flags
+        FLAGS = 8, "AT_FLAGS",
+        /// Entry point of the executable
+        ENTRY = 9, "AT_ENTRY",
+        /// The program is not ELF
+        NOTELF = 10, "AT_NOTELF",
+        /// Real user ID
+        UID = 11, "AT_UID",
+        /// Effective user ID
+        EUID = 12, "AT_EUID",
+        /// Real group ID
+        GID = 13, "AT_GID",
+        /// Effective group ID
+        EGID = 14, "AT_EGID",
+        /// Address of a string identifying the CPU, for path expansion
+        PLATFORM = 15, "AT_PLATFORM",
+        /// Machine-dependent hints about processor capabilities
+        HWCAP = 16, "AT_HWCAP",
+        /// Frequency of `times()`
+        CLKTCK = 17, "AT_CLKTCK",
+        /// The program runs in secure mode, such as setuid
+        SECURE = 23, "AT_SECURE",
+        /// Address of a string identifying the real platform
+        BASE_PLATFORM = 24, "AT_BASE_PLATFORM",
+        /// Address of 16 random bytes
+        RANDOM = 25, "AT_RANDOM",
+        /// Extension of `AT_HWCAP`
+        HWCAP2 = 26, "AT_HWCAP2",
+        /// Supported size of the rseq area
+        RSEQ_FEATURE_SIZE = 27, "AT_RSEQ_FEATURE_SIZE",
+        /// Required alignment of the rseq area
+        RSEQ_ALIGN = 28, "AT_RSEQ_ALIGN",
+        /// Extension of `AT_HWCAP`
+        HWCAP3 = 29, "AT_HWCAP3",
+        /// Extension of `AT_HWCAP`
+        HWCAP4 = 30, "AT_HWCAP4",
+        /// Address of the filename of the program
+        EXECFN = 31, "AT_EXECFN",
+        /// Entry point of the system call page
+        SYSINFO = 32, "AT_SYSINFO",
+        /// Address of the ELF header of the vDSO
+        SYSINFO_EHDR = 33, "AT_SYSINFO_EHDR",
+        /// Minimal stack size for signal delivery
+        MINSIGSTKSZ = 51, "AT_MINSIGSTKSZ",
+    }
+}
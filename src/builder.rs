@@ -0,0 +1,1384 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+use crate::dynamic::{Dynamic64, DynamicTag};
+use crate::endian;
+use crate::error::{Error, Result};
+use crate::hash::sysv_hash;
+use crate::header::{
+    Class, Encoding, Header64, HeaderVersion, Machine, Magic, ObjectType, OsAbi, Version,
+};
+use crate::program_header::{
+    ProgramHeader64, ProgramType, SEGMENT_FLAG_EXECUTE, SEGMENT_FLAG_READ, SEGMENT_FLAG_WRITE,
+};
+use crate::relocation::Rela64;
+use crate::section_header::{
+    SECTION_FLAG_ALLOC, SECTION_FLAG_EXECINSTR, SECTION_FLAG_INFO_LINK, SECTION_FLAG_WRITE,
+    SectionHeader64, SectionType,
+};
+use crate::string_table::StringTableBuilder;
+use crate::symbol::{SectionIndex, Symbol64, SymbolBinding, SymbolType, SymbolVisibility};
+
+/// Handle to a section added to an [`ElfBuilder`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SectionId(usize);
+
+/// Handle to a symbol added to an [`ElfBuilder`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SymbolId(usize);
+
+/// A section to be written by an [`ElfBuilder`]
+#[derive(Debug, Clone)]
+pub struct Section {
+    pub name: Vec<u8>,
+    pub sh_type: SectionType,
+    /// Combination of the `SECTION_FLAG_*` constants
+    pub sh_flags: u64,
+    pub sh_addralign: u64,
+    pub sh_entsize: u64,
+    /// Section referenced by `sh_link`
+    pub sh_link: Option<SectionLink>,
+    pub sh_info: u32,
+    /// Contents of the section, written in the file as is
+    pub data: Vec<u8>,
+    /// Size of a `SHT_NOBITS` section, which has no contents in the file
+    pub size: u64,
+}
+
+impl Section {
+    pub fn new(name: &[u8], sh_type: SectionType, sh_flags: u64, data: Vec<u8>) -> Self {
+        Self {
+            name: name.to_vec(),
+            sh_type,
+            sh_flags,
+            sh_addralign: 1,
+            sh_entsize: 0,
+            sh_link: None,
+            sh_info: 0,
+            data,
+            size: 0,
+        }
+    }
+
+    /// Creates a `SHT_NOBITS` section of `size` zero bytes, such as `.bss`
+    pub fn nobits(name: &[u8], sh_flags: u64, size: u64) -> Self {
+        Self {
+            size,
+            ..Self::new(name, SectionType::NOBITS, sh_flags, Vec::new())
+        }
+    }
+
+    /// Creates a `.text` section holding executable code
+    pub fn text(data: Vec<u8>) -> Self {
+        Self {
+            sh_addralign: 16,
+            ..Self::new(
+                b".text",
+                SectionType::PROGBITS,
+                SECTION_FLAG_ALLOC | SECTION_FLAG_EXECINSTR,
+                data,
+            )
+        }
+    }
+
+    /// Creates a `.data` section holding writable data
+    pub fn data(data: Vec<u8>) -> Self {
+        Self {
+            sh_addralign: 8,
+            ..Self::new(
+                b".data",
+                SectionType::PROGBITS,
+                SECTION_FLAG_ALLOC | SECTION_FLAG_WRITE,
+                data,
+            )
+        }
+    }
+
+    /// Creates a `.rodata` section holding read-only data
+    pub fn rodata(data: Vec<u8>) -> Self {
+        Self {
+            sh_addralign: 8,
+            ..Self::new(b".rodata", SectionType::PROGBITS, SECTION_FLAG_ALLOC, data)
+        }
+    }
+
+    /// Creates a `.bss` section of `size` zero-initialized bytes
+    pub fn bss(size: u64) -> Self {
+        Self {
+            sh_addralign: 8,
+            ..Self::nobits(b".bss", SECTION_FLAG_ALLOC | SECTION_FLAG_WRITE, size)
+        }
+    }
+
+    fn is_nobits(&self) -> bool {
+        self.sh_type == SectionType::NOBITS
+    }
+
+    fn memory_size(&self) -> u64 {
+        if self.is_nobits() {
+            self.size
+        } else {
+            self.data.len() as u64
+        }
+    }
+}
+
+/// Section referenced by the `sh_link` field of a [`Section`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SectionLink {
+    /// A section added to the builder
+    Section(SectionId),
+    /// The generated `.symtab`, such as for a `SHT_GROUP` section
+    SymTab,
+}
+
+/// Where a [`Symbol`] is defined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolSection {
+    /// Defined in another object
+    Undefined,
+    /// Not relative to any section
+    Absolute,
+    /// Common block to be allocated by the linker
+    Common,
+    /// Defined at an offset in a section
+    Section(SectionId),
+}
+
+/// A symbol to be written by an [`ElfBuilder`]
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub name: Vec<u8>,
+    pub section: SymbolSection,
+    /// Offset in the section, replaced by the address of the symbol in
+    /// executables and shared objects
+    pub value: u64,
+    pub size: u64,
+    pub binding: SymbolBinding,
+    pub symbol_type: SymbolType,
+    pub visibility: SymbolVisibility,
+}
+
+impl Symbol {
+    /// Creates a global symbol without a type
+    pub fn new(name: &[u8], section: SymbolSection, value: u64) -> Self {
+        Self {
+            name: name.to_vec(),
+            section,
+            value,
+            size: 0,
+            binding: SymbolBinding::GLOBAL,
+            symbol_type: SymbolType::NOTYPE,
+            visibility: SymbolVisibility::DEFAULT,
+        }
+    }
+
+    /// Creates a global function symbol
+    pub fn function(name: &[u8], section: SectionId, offset: u64, size: u64) -> Self {
+        Self {
+            size,
+            symbol_type: SymbolType::FUNC,
+            ..Self::new(name, SymbolSection::Section(section), offset)
+        }
+    }
+
+    /// Creates a global data object symbol
+    pub fn object(name: &[u8], section: SectionId, offset: u64, size: u64) -> Self {
+        Self {
+            size,
+            symbol_type: SymbolType::OBJECT,
+            ..Self::new(name, SymbolSection::Section(section), offset)
+        }
+    }
+
+    /// Creates a reference to a symbol defined in another object
+    pub fn undefined(name: &[u8]) -> Self {
+        Self::new(name, SymbolSection::Undefined, 0)
+    }
+
+    /// Creates the local `STT_SECTION` symbol used to relocate against the
+    /// start of a section
+    pub fn section(section: SectionId) -> Self {
+        Self {
+            binding: SymbolBinding::LOCAL,
+            symbol_type: SymbolType::SECTION,
+            ..Self::new(b"", SymbolSection::Section(section), 0)
+        }
+    }
+}
+
+/// A relocation to be written by an [`ElfBuilder`]
+///
+/// Relocations are written to a `.rela<section>` section in relocatable
+/// objects and to `.rela.dyn` otherwise, where the symbol must be dynamic.
+#[derive(Debug, Clone)]
+pub struct Relocation {
+    /// Section holding the location to relocate
+    pub section: SectionId,
+    /// Offset of the location in the section
+    pub offset: u64,
+    /// Machine specific relocation type
+    pub r_type: u32,
+    pub symbol: Option<SymbolId>,
+    pub addend: i64,
+}
+
+#[derive(Debug, Clone)]
+enum DynamicValue {
+    Value(u64),
+    String(Vec<u8>),
+}
+
+#[derive(Debug, Clone)]
+enum EntryPoint {
+    Address(u64),
+    Symbol(SymbolId),
+}
+
+#[derive(Debug, Clone)]
+struct BuilderSymbol {
+    symbol: Symbol,
+    dynamic: bool,
+}
+
+#[derive(Debug, Clone)]
+struct BuilderSegment {
+    p_type: ProgramType,
+    p_flags: u32,
+    sections: Vec<SectionId>,
+}
+
+/// Builder for ELF executables, shared objects and relocatable objects
+///
+/// Sections are added with their contents, and the builder generates the
+/// tables that describe them: symbol and string tables, relocation sections,
+/// and for executables and shared objects the dynamic linking sections and
+/// the program header table.
+///
+/// Allocated sections of executables and shared objects are grouped by
+/// permissions into read-only, executable and writable `PT_LOAD` segments,
+/// in that order, the first one also mapping the file headers. Within a
+/// segment, sections keep the order they were added in, except that
+/// `SHT_NOBITS` sections are moved to the end. Relocatable objects have no
+/// segments and keep all sections in order, each followed by the `.rela`
+/// section holding its relocations, with symbols ordered so that local
+/// symbols come first.
+///
+/// Only 64-bit objects can be written, in either byte order; there is no
+/// support for `ELFCLASS32`. Section contents are written as given, so they
+/// must already use the byte order of the target.
+#[derive(Debug, Clone)]
+pub struct ElfBuilder {
+    object_type: ObjectType,
+    machine: Machine,
+    encoding: Encoding,
+    os_abi: OsAbi,
+    flags: u32,
+    base_address: u64,
+    page_size: u64,
+    entry_point: Option<EntryPoint>,
+    interpreter: Option<Vec<u8>>,
+    executable_stack: bool,
+    sections: Vec<Section>,
+    symbols: Vec<BuilderSymbol>,
+    relocations: Vec<Relocation>,
+    dynamic: Vec<(DynamicTag, DynamicValue)>,
+    segments: Vec<BuilderSegment>,
+}
+
+impl ElfBuilder {
+    pub fn new(object_type: ObjectType, machine: Machine) -> Self {
+        Self {
+            object_type,
+            machine,
+            encoding: Encoding::native(),
+            os_abi: OsAbi::NONE,
+            flags: 0,
+            base_address: 0,
+            page_size: 0x1000,
+            entry_point: None,
+            interpreter: None,
+            executable_stack: false,
+            sections: Vec::new(),
+            symbols: Vec::new(),
+            relocations: Vec::new(),
+            dynamic: Vec::new(),
+            segments: Vec::new(),
+        }
+    }
+
+    /// Sets the byte order of the file, the host's by default
+    pub fn encoding(&mut self, encoding: Encoding) -> &mut Self {
+        self.encoding = encoding;
+        self
+    }
+
+    pub fn os_abi(&mut self, os_abi: OsAbi) -> &mut Self {
+        self.os_abi = os_abi;
+        self
+    }
+
+    /// Sets the processor specific flags (`e_flags`)
+    pub fn flags(&mut self, flags: u32) -> &mut Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Sets the address the file headers are loaded at, 0 by default
+    ///
+    /// The address must be a multiple of the page size.
+    pub fn base_address(&mut self, base_address: u64) -> &mut Self {
+        self.base_address = base_address;
+        self
+    }
+
+    /// Sets the alignment of `PT_LOAD` segments, 0x1000 by default
+    ///
+    /// The page size must be a power of two.
+    pub fn page_size(&mut self, page_size: u64) -> &mut Self {
+        self.page_size = page_size;
+        self
+    }
+
+    pub fn entry_point(&mut self, address: u64) -> &mut Self {
+        self.entry_point = Some(EntryPoint::Address(address));
+        self
+    }
+
+    /// Uses the address of a symbol as the entry point
+    pub fn entry_symbol(&mut self, symbol: SymbolId) -> &mut Self {
+        self.entry_point = Some(EntryPoint::Symbol(symbol));
+        self
+    }
+
+    /// Sets the program interpreter, adding an `.interp` section and a
+    /// `PT_INTERP` segment
+    pub fn interpreter(&mut self, path: &[u8]) -> &mut Self {
+        self.interpreter = Some(path.to_vec());
+        self
+    }
+
+    /// Requests an executable stack, which is not executable by default
+    ///
+    /// This sets the flags of the `PT_GNU_STACK` segment of executables and
+    /// shared objects, and of the `.note.GNU-stack` section that the builder
+    /// adds to relocatable objects unless one is already present.
+    pub fn executable_stack(&mut self, executable_stack: bool) -> &mut Self {
+        self.executable_stack = executable_stack;
+        self
+    }
+
+    /// Adds a `DT_NEEDED` entry for a shared object dependency
+    pub fn needed(&mut self, name: &[u8]) -> &mut Self {
+        self.dynamic_string(DynamicTag::NEEDED, name)
+    }
+
+    pub fn soname(&mut self, name: &[u8]) -> &mut Self {
+        self.dynamic_string(DynamicTag::SONAME, name)
+    }
+
+    pub fn runpath(&mut self, path: &[u8]) -> &mut Self {
+        self.dynamic_string(DynamicTag::RUNPATH, path)
+    }
+
+    /// Adds a dynamic entry whose value is an offset in `.dynstr`
+    pub fn dynamic_string(&mut self, tag: DynamicTag, string: &[u8]) -> &mut Self {
+        self.dynamic
+            .push((tag, DynamicValue::String(string.to_vec())));
+        self
+    }
+
+    /// Adds a dynamic entry
+    ///
+    /// Entries describing the tables generated by the builder, such as
+    /// `DT_SYMTAB` or `DT_RELA`, and the terminating `DT_NULL` are added
+    /// automatically.
+    pub fn dynamic(&mut self, tag: DynamicTag, value: u64) -> &mut Self {
+        self.dynamic.push((tag, DynamicValue::Value(value)));
+        self
+    }
+
+    pub fn section(&mut self, section: Section) -> SectionId {
+        self.sections.push(section);
+        SectionId(self.sections.len() - 1)
+    }
+
+    pub fn section_mut(&mut self, id: SectionId) -> &mut Section {
+        &mut self.sections[id.0]
+    }
+
+    /// Adds a symbol to `.symtab`
+    pub fn symbol(&mut self, symbol: Symbol) -> SymbolId {
+        self.symbols.push(BuilderSymbol {
+            symbol,
+            dynamic: false,
+        });
+        SymbolId(self.symbols.len() - 1)
+    }
+
+    /// Adds a symbol to both `.dynsym` and `.symtab`
+    pub fn dynamic_symbol(&mut self, symbol: Symbol) -> SymbolId {
+        self.symbols.push(BuilderSymbol {
+            symbol,
+            dynamic: true,
+        });
+        SymbolId(self.symbols.len() - 1)
+    }
+
+    pub fn relocation(&mut self, relocation: Relocation) -> &mut Self {
+        self.relocations.push(relocation);
+        self
+    }
+
+    /// Adds a segment covering the given sections, in addition to the
+    /// segments generated by the builder
+    pub fn segment(
+        &mut self,
+        p_type: ProgramType,
+        p_flags: u32,
+        sections: &[SectionId],
+    ) -> &mut Self {
+        self.segments.push(BuilderSegment {
+            p_type,
+            p_flags,
+            sections: sections.to_vec(),
+        });
+        self
+    }
+
+    /// Lays out and writes the file
+    pub fn build(&self) -> Result<Vec<u8>> {
+        if self.encoding != Encoding::ELFDATA2LSB && self.encoding != Encoding::ELFDATA2MSB {
+            return Err(Error::UnsupportedEncoding(self.encoding.clone()));
+        }
+
+        let relocatable = self.object_type == ObjectType::REL;
+        if !self.page_size.is_power_of_two() {
+            return Err(Error::InvalidData("page size is not a power of two"));
+        }
+        if !relocatable && !self.base_address.is_multiple_of(self.page_size) {
+            // Segments would not be congruent modulo their alignment
+            return Err(Error::InvalidData("base address is not page aligned"));
+        }
+        let has_dynamic_symbols = self.symbols.iter().any(|symbol| symbol.dynamic);
+        if relocatable
+            && (has_dynamic_symbols
+                || self.interpreter.is_some()
+                || !self.dynamic.is_empty()
+                || !self.segments.is_empty())
+        {
+            return Err(Error::InvalidData(
+                "relocatable objects cannot have segments or dynamic linking information",
+            ));
+        }
+
+        let dynamic = !relocatable
+            && (self.object_type == ObjectType::DYN
+                || has_dynamic_symbols
+                || self.interpreter.is_some()
+                || !self.dynamic.is_empty());
+
+        Layout::new(self, relocatable, dynamic)?.write()
+    }
+}
+
+/// Contents of a section in the output file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Contents {
+    Null,
+    GnuStack,
+    User(usize),
+    Interp,
+    Hash,
+    DynSym,
+    DynStr,
+    RelaDyn,
+    Dynamic,
+    /// Relocations against the given user section
+    Rela(usize),
+    SymTab,
+    StrTab,
+    ShStrTab,
+}
+
+#[derive(Debug, Clone)]
+struct OutputSection {
+    contents: Contents,
+    name: u32,
+    header: SectionHeader64,
+}
+
+/// Index of the first `PT_LOAD` segment class holding writable sections
+const WRITABLE: usize = 2;
+
+/// Assignment of file offsets, addresses and indices for an [`ElfBuilder`]
+struct Layout<'b> {
+    builder: &'b ElfBuilder,
+    relocatable: bool,
+    sections: Vec<OutputSection>,
+    /// Output index of each user section
+    section_indices: Vec<usize>,
+    /// `.symtab` index of each symbol
+    symtab_indices: Vec<u32>,
+    /// `.dynsym` index of each dynamic symbol
+    dynsym_indices: Vec<Option<u32>>,
+    symtab_order: Vec<usize>,
+    dynsym_order: Vec<usize>,
+    /// `.strtab` offset of the name of each symbol
+    symbol_names: Vec<u32>,
+    /// `.dynstr` offset of the name of each dynamic symbol
+    dynamic_symbol_names: Vec<u32>,
+    /// `.dynstr` offset of the value of each dynamic entry holding a string
+    dynamic_strings: Vec<u32>,
+    strtab: StringTableBuilder,
+    dynstr: StringTableBuilder,
+    shstrtab: StringTableBuilder,
+    program_headers: Vec<ProgramHeader64>,
+    section_header_offset: u64,
+}
+
+impl<'b> Layout<'b> {
+    fn new(builder: &'b ElfBuilder, relocatable: bool, dynamic: bool) -> Result<Self> {
+        let mut layout = Self {
+            builder,
+            relocatable,
+            sections: Vec::new(),
+            section_indices: vec![0; builder.sections.len()],
+            symtab_indices: vec![0; builder.symbols.len()],
+            dynsym_indices: vec![None; builder.symbols.len()],
+            symtab_order: Vec::new(),
+            dynsym_order: Vec::new(),
+            symbol_names: vec![0; builder.symbols.len()],
+            dynamic_symbol_names: vec![0; builder.symbols.len()],
+            dynamic_strings: vec![0; builder.dynamic.len()],
+            strtab: StringTableBuilder::new(),
+            dynstr: StringTableBuilder::new(),
+            shstrtab: StringTableBuilder::new(),
+            program_headers: Vec::new(),
+            section_header_offset: 0,
+        };
+
+        layout.order_symbols(dynamic);
+        layout.add_sections(dynamic)?;
+        if relocatable {
+            layout.assign_offsets();
+        } else {
+            layout.assign_addresses(dynamic);
+        }
+
+        Ok(layout)
+    }
+
+    /// Orders both symbol tables with local symbols first, as required by
+    /// their `sh_info` field, and fills the string tables
+    fn order_symbols(&mut self, dynamic: bool) {
+        let symbols = &self.builder.symbols;
+        let is_local = |&index: &usize| symbols[index].symbol.binding == SymbolBinding::LOCAL;
+
+        let (mut locals, globals): (Vec<usize>, Vec<usize>) =
+            (0..symbols.len()).partition(is_local);
+        locals.extend(globals);
+        self.symtab_order = locals;
+
+        for (position, &index) in self.symtab_order.iter().enumerate() {
+            self.symtab_indices[index] = position as u32 + 1;
+            self.symbol_names[index] = self.strtab.add(&symbols[index].symbol.name);
+        }
+
+        if !dynamic {
+            return;
+        }
+
+        self.dynsym_order = self
+            .symtab_order
+            .iter()
+            .copied()
+            .filter(|&index| symbols[index].dynamic)
+            .collect();
+
+        for (position, &index) in self.dynsym_order.iter().enumerate() {
+            self.dynsym_indices[index] = Some(position as u32 + 1);
+            self.dynamic_symbol_names[index] = self.dynstr.add(&symbols[index].symbol.name);
+        }
+
+        for (index, (_, value)) in self.builder.dynamic.iter().enumerate() {
+            if let DynamicValue::String(string) = value {
+                self.dynamic_strings[index] = self.dynstr.add(string);
+            }
+        }
+    }
+
+    fn push(&mut self, contents: Contents, name: &[u8], header: SectionHeader64) -> usize {
+        let name = self.shstrtab.add(name);
+        self.sections.push(OutputSection {
+            contents,
+            name,
+            header,
+        });
+        self.sections.len() - 1
+    }
+
+    fn push_user(&mut self, index: usize) {
+        let section = &self.builder.sections[index];
+        let header = SectionHeader64 {
+            sh_type: section.sh_type.clone(),
+            sh_flags: section.sh_flags,
+            sh_size: section.memory_size(),
+            sh_info: section.sh_info,
+            sh_addralign: section.sh_addralign.max(1),
+            sh_entsize: section.sh_entsize,
+            ..null_section_header()
+        };
+        self.section_indices[index] = self.push(Contents::User(index), &section.name, header);
+    }
+
+    /// Creates the output sections in their final order
+    fn add_sections(&mut self, dynamic: bool) -> Result<()> {
+        let builder = self.builder;
+        let symbol_count = builder.symbols.len() + 1;
+        self.push(Contents::Null, b"", null_section_header());
+
+        if self.relocatable {
+            for index in 0..builder.sections.len() {
+                self.push_user(index);
+
+                let count = builder
+                    .relocations
+                    .iter()
+                    .filter(|relocation| relocation.section.0 == index)
+                    .count();
+                if count > 0 {
+                    let mut name = b".rela".to_vec();
+                    name.extend_from_slice(&builder.sections[index].name);
+                    self.push(
+                        Contents::Rela(index),
+                        &name,
+                        generated_header(
+                            SectionType::RELA,
+                            SECTION_FLAG_INFO_LINK,
+                            count * size_of::<Rela64>(),
+                            size_of::<Rela64>(),
+                            8,
+                        ),
+                    );
+                }
+            }
+
+            // Without this note, linkers assume that the code of the object
+            // needs an executable stack
+            if !builder
+                .sections
+                .iter()
+                .any(|section| section.name == b".note.GNU-stack")
+            {
+                let flags = if builder.executable_stack {
+                    SECTION_FLAG_EXECINSTR
+                } else {
+                    0
+                };
+                self.push(
+                    Contents::GnuStack,
+                    b".note.GNU-stack",
+                    generated_header(SectionType::PROGBITS, flags, 0, 0, 1),
+                );
+            }
+        } else {
+            // Read-only sections, starting with the dynamic linking tables
+            if let Some(interpreter) = &builder.interpreter {
+                self.push(
+                    Contents::Interp,
+                    b".interp",
+                    generated_header(
+                        SectionType::PROGBITS,
+                        SECTION_FLAG_ALLOC,
+                        interpreter.len() + 1,
+                        0,
+                        1,
+                    ),
+                );
+            }
+
+            if dynamic {
+                let dynsym_count = self.dynsym_order.len() + 1;
+                self.push(
+                    Contents::Hash,
+                    b".hash",
+                    generated_header(
+                        SectionType::HASH,
+                        SECTION_FLAG_ALLOC,
+                        (2 + 2 * dynsym_count) * size_of::<u32>(),
+                        size_of::<u32>(),
+                        8,
+                    ),
+                );
+                self.push(
+                    Contents::DynSym,
+                    b".dynsym",
+                    generated_header(
+                        SectionType::DYNSYM,
+                        SECTION_FLAG_ALLOC,
+                        dynsym_count * size_of::<Symbol64>(),
+                        size_of::<Symbol64>(),
+                        8,
+                    ),
+                );
+                self.push(
+                    Contents::DynStr,
+                    b".dynstr",
+                    generated_header(
+                        SectionType::STRTAB,
+                        SECTION_FLAG_ALLOC,
+                        self.dynstr.len(),
+                        0,
+                        1,
+                    ),
+                );
+                if !builder.relocations.is_empty() {
+                    self.push(
+                        Contents::RelaDyn,
+                        b".rela.dyn",
+                        generated_header(
+                            SectionType::RELA,
+                            SECTION_FLAG_ALLOC,
+                            builder.relocations.len() * size_of::<Rela64>(),
+                            size_of::<Rela64>(),
+                            8,
+                        ),
+                    );
+                }
+            } else if !builder.relocations.is_empty() {
+                return Err(Error::InvalidData(
+                    "relocations require dynamic linking information",
+                ));
+            }
+
+            for class in 0..=WRITABLE {
+                if class == WRITABLE && dynamic {
+                    let count = builder.dynamic.len() + self.generated_dynamic_count() + 1;
+                    self.push(
+                        Contents::Dynamic,
+                        b".dynamic",
+                        generated_header(
+                            SectionType::DYNAMIC,
+                            SECTION_FLAG_ALLOC | SECTION_FLAG_WRITE,
+                            count * size_of::<Dynamic64>(),
+                            size_of::<Dynamic64>(),
+                            8,
+                        ),
+                    );
+                }
+
+                for nobits in [false, true] {
+                    for (index, section) in builder.sections.iter().enumerate() {
+                        if section.sh_flags & SECTION_FLAG_ALLOC != 0
+                            && segment_class(section.sh_flags) == class
+                            && section.is_nobits() == nobits
+                        {
+                            self.push_user(index);
+                        }
+                    }
+                }
+            }
+
+            for (index, section) in builder.sections.iter().enumerate() {
+                if section.sh_flags & SECTION_FLAG_ALLOC == 0 {
+                    self.push_user(index);
+                }
+            }
+        }
+
+        if self.relocatable || symbol_count > 1 {
+            self.push(
+                Contents::SymTab,
+                b".symtab",
+                generated_header(
+                    SectionType::SYMTAB,
+                    0,
+                    symbol_count * size_of::<Symbol64>(),
+                    size_of::<Symbol64>(),
+                    8,
+                ),
+            );
+            self.push(
+                Contents::StrTab,
+                b".strtab",
+                generated_header(SectionType::STRTAB, 0, self.strtab.len(), 0, 1),
+            );
+        }
+
+        self.shstrtab.add(b".shstrtab");
+        let size = self.shstrtab.len();
+        self.push(
+            Contents::ShStrTab,
+            b".shstrtab",
+            generated_header(SectionType::STRTAB, 0, size, 0, 1),
+        );
+
+        if self.sections.len() >= SectionIndex::LORESERVE.as_raw() as usize {
+            return Err(Error::InvalidData("too many sections"));
+        }
+
+        self.link_sections();
+        Ok(())
+    }
+
+    fn generated_dynamic_count(&self) -> usize {
+        // DT_HASH, DT_STRTAB, DT_SYMTAB, DT_STRSZ and DT_SYMENT, followed by
+        // DT_RELA, DT_RELASZ and DT_RELAENT when there are relocations
+        if self.builder.relocations.is_empty() {
+            5
+        } else {
+            8
+        }
+    }
+
+    fn find(&self, contents: Contents) -> u32 {
+        self.sections
+            .iter()
+            .position(|section| section.contents == contents)
+            .unwrap_or(0) as u32
+    }
+
+    /// Fills the `sh_link` and `sh_info` fields once every index is known
+    fn link_sections(&mut self) {
+        let symtab = self.find(Contents::SymTab);
+        let strtab = self.find(Contents::StrTab);
+        let dynsym = self.find(Contents::DynSym);
+        let dynstr = self.find(Contents::DynStr);
+        let first_global = |order: &[usize]| {
+            order
+                .iter()
+                .take_while(|&&index| {
+                    self.builder.symbols[index].symbol.binding == SymbolBinding::LOCAL
+                })
+                .count() as u32
+                + 1
+        };
+        let symtab_info = first_global(&self.symtab_order);
+        let dynsym_info = first_global(&self.dynsym_order);
+
+        for index in 0..self.sections.len() {
+            let (link, info) = match self.sections[index].contents {
+                Contents::User(user) => {
+                    let section = &self.builder.sections[user];
+                    let link = match section.sh_link {
+                        None => 0,
+                        Some(SectionLink::Section(id)) => self.section_indices[id.0] as u32,
+                        Some(SectionLink::SymTab) => symtab,
+                    };
+                    (link, section.sh_info)
+                }
+                Contents::Hash | Contents::RelaDyn => (dynsym, 0),
+                Contents::DynSym => (dynstr, dynsym_info),
+                Contents::Dynamic => (dynstr, 0),
+                Contents::Rela(user) => (symtab, self.section_indices[user] as u32),
+                Contents::SymTab => (strtab, symtab_info),
+                _ => continue,
+            };
+
+            let header = &mut self.sections[index].header;
+            header.sh_link = link;
+            header.sh_info = info;
+        }
+    }
+
+    fn headers_size(&self) -> u64 {
+        size_of::<Header64>() as u64
+            + self.program_headers.len() as u64 * size_of::<ProgramHeader64>() as u64
+    }
+
+    /// Places sections one after the other, for relocatable objects
+    fn assign_offsets(&mut self) {
+        let mut offset = self.headers_size();
+        for section in &mut self.sections[1..] {
+            offset = offset.next_multiple_of(section.header.sh_addralign);
+            section.header.sh_offset = offset;
+            offset += section.header.file_size();
+        }
+        self.section_header_offset = offset.next_multiple_of(8);
+    }
+
+    /// Places sections and creates the program header table, for executables
+    /// and shared objects
+    fn assign_addresses(&mut self, dynamic: bool) {
+        let builder = self.builder;
+        let page_size = builder.page_size;
+
+        let classes: Vec<usize> = (0..=WRITABLE)
+            .filter(|&class| {
+                class == 0
+                    || self.sections.iter().any(|section| {
+                        section.header.is_alloc() && segment_class(section.header.sh_flags) == class
+                    })
+            })
+            .collect();
+
+        // The program header table is sized before anything is placed
+        let count = 1
+            + classes.len()
+            + builder.interpreter.is_some() as usize
+            + dynamic as usize
+            + 1
+            + builder.segments.len();
+        self.program_headers = vec![null_program_header(); count];
+
+        let headers_size = self.headers_size();
+        let mut offset = headers_size;
+        let mut address = builder.base_address + headers_size;
+        let mut loads = Vec::new();
+
+        for &class in &classes {
+            if class != 0 {
+                offset = offset.next_multiple_of(page_size);
+                address = address.next_multiple_of(page_size);
+            }
+
+            let mut load = ProgramHeader64 {
+                p_type: ProgramType::LOAD,
+                p_flags: SEGMENT_FLAG_READ,
+                p_offset: offset,
+                p_vaddr: address,
+                p_paddr: address,
+                p_align: page_size,
+                ..null_program_header()
+            };
+            if class == 0 {
+                load.p_offset = 0;
+                load.p_vaddr = builder.base_address;
+                load.p_paddr = builder.base_address;
+            }
+
+            for section in &mut self.sections {
+                let header = &mut section.header;
+                if !header.is_alloc() || segment_class(header.sh_flags) != class {
+                    continue;
+                }
+
+                // Offsets follow addresses so that both stay congruent
+                // modulo the page size
+                let aligned = address.next_multiple_of(header.sh_addralign);
+                if header.sh_type != SectionType::NOBITS {
+                    offset += aligned - address;
+                }
+                address = aligned;
+
+                header.sh_addr = address;
+                header.sh_offset = offset;
+                address += header.sh_size;
+                offset += header.file_size();
+
+                if header.sh_flags & SECTION_FLAG_EXECINSTR != 0 {
+                    load.p_flags |= SEGMENT_FLAG_EXECUTE;
+                }
+                if header.sh_flags & SECTION_FLAG_WRITE != 0 {
+                    load.p_flags |= SEGMENT_FLAG_WRITE;
+                }
+            }
+
+            load.p_filesz = offset - load.p_offset;
+            load.p_memsz = address - load.p_vaddr;
+            loads.push(load);
+        }
+
+        for section in &mut self.sections[1..] {
+            if !section.header.is_alloc() {
+                offset = offset.next_multiple_of(section.header.sh_addralign);
+                section.header.sh_offset = offset;
+                offset += section.header.file_size();
+            }
+        }
+        self.section_header_offset = offset.next_multiple_of(8);
+
+        let phdr_size = (count * size_of::<ProgramHeader64>()) as u64;
+        let mut program_headers = vec![ProgramHeader64 {
+            p_type: ProgramType::PHDR,
+            p_flags: SEGMENT_FLAG_READ,
+            p_offset: size_of::<Header64>() as u64,
+            p_vaddr: builder.base_address + size_of::<Header64>() as u64,
+            p_paddr: builder.base_address + size_of::<Header64>() as u64,
+            p_filesz: phdr_size,
+            p_memsz: phdr_size,
+            p_align: 8,
+        }];
+        if builder.interpreter.is_some() {
+            program_headers.push(self.span(
+                ProgramType::INTERP,
+                SEGMENT_FLAG_READ,
+                &[Contents::Interp],
+            ));
+        }
+        program_headers.extend(loads);
+        if dynamic {
+            program_headers.push(self.span(
+                ProgramType::DYNAMIC,
+                SEGMENT_FLAG_READ | SEGMENT_FLAG_WRITE,
+                &[Contents::Dynamic],
+            ));
+        }
+        let mut stack_flags = SEGMENT_FLAG_READ | SEGMENT_FLAG_WRITE;
+        if builder.executable_stack {
+            stack_flags |= SEGMENT_FLAG_EXECUTE;
+        }
+        program_headers.push(ProgramHeader64 {
+            p_type: ProgramType::GNU_STACK,
+            p_flags: stack_flags,
+            p_align: 16,
+            ..null_program_header()
+        });
+        for segment in &builder.segments {
+            let contents: Vec<Contents> = segment
+                .sections
+                .iter()
+                .map(|id| Contents::User(id.0))
+                .collect();
+            program_headers.push(self.span(segment.p_type.clone(), segment.p_flags, &contents));
+        }
+
+        self.program_headers = program_headers;
+    }
+
+    /// Creates a segment covering the given sections
+    fn span(&self, p_type: ProgramType, p_flags: u32, contents: &[Contents]) -> ProgramHeader64 {
+        let headers = self
+            .sections
+            .iter()
+            .filter(|section| contents.contains(&section.contents))
+            .map(|section| &section.header);
+
+        let mut segment = ProgramHeader64 {
+            p_type,
+            p_flags,
+            p_offset: u64::MAX,
+            p_vaddr: u64::MAX,
+            p_paddr: 0,
+            p_filesz: 0,
+            p_memsz: 0,
+            p_align: 1,
+        };
+        let mut file_end = 0;
+        let mut memory_end = 0;
+        for header in headers {
+            segment.p_offset = segment.p_offset.min(header.sh_offset);
+            segment.p_vaddr = segment.p_vaddr.min(header.sh_addr);
+            segment.p_align = segment.p_align.max(header.sh_addralign);
+            file_end = file_end.max(header.sh_offset + header.file_size());
+            memory_end = memory_end.max(header.sh_addr + header.sh_size);
+        }
+
+        if segment.p_offset == u64::MAX {
+            return ProgramHeader64 {
+                p_type: segment.p_type,
+                p_flags,
+                ..null_program_header()
+            };
+        }
+
+        segment.p_paddr = segment.p_vaddr;
+        segment.p_filesz = file_end - segment.p_offset;
+        segment.p_memsz = memory_end - segment.p_vaddr;
+        segment
+    }
+
+    fn section_address(&self, id: SectionId) -> u64 {
+        self.sections[self.section_indices[id.0]].header.sh_addr
+    }
+
+    fn address_of(&self, contents: Contents) -> u64 {
+        self.sections[self.find(contents) as usize].header.sh_addr
+    }
+
+    fn symbol_entry(&self, symbol: &Symbol, st_name: u32) -> Symbol64 {
+        let (st_shndx, base) = match symbol.section {
+            SymbolSection::Undefined => (SectionIndex::UNDEF, 0),
+            SymbolSection::Absolute => (SectionIndex::ABS, 0),
+            SymbolSection::Common => (SectionIndex::COMMON, 0),
+            SymbolSection::Section(id) => (
+                SectionIndex::from_raw(self.section_indices[id.0] as u16),
+                self.section_address(id),
+            ),
+        };
+
+        Symbol64 {
+            st_name,
+            st_info: symbol.binding.as_raw() << 4 | symbol.symbol_type.as_raw() & 0xf,
+            st_other: symbol.visibility.as_raw() & 0x3,
+            st_shndx,
+            st_value: base + symbol.value,
+            st_size: symbol.size,
+        }
+    }
+
+    fn write_symbols(&self, order: &[usize], names: &[u32], out: &mut Vec<u8>) {
+        let encoding = &self.builder.encoding;
+        endian::write(out, &null_symbol(), encoding);
+        for &index in order {
+            let symbol = self.symbol_entry(&self.builder.symbols[index].symbol, names[index]);
+            endian::write(out, &symbol, encoding);
+        }
+    }
+
+    fn write_hash(&self, out: &mut Vec<u8>) {
+        let encoding = &self.builder.encoding;
+        let count = self.dynsym_order.len() + 1;
+        let mut buckets = vec![0u32; count];
+        let mut chains = vec![0u32; count];
+
+        // Symbols are prepended to their chain, so walking it backwards keeps
+        // the chains in symbol table order
+        for (position, &index) in self.dynsym_order.iter().enumerate().rev() {
+            let hash = sysv_hash(&self.builder.symbols[index].symbol.name) as usize % count;
+            chains[position + 1] = buckets[hash];
+            buckets[hash] = position as u32 + 1;
+        }
+
+        endian::write(out, &(count as u32), encoding);
+        endian::write(out, &(count as u32), encoding);
+        for word in buckets.iter().chain(&chains) {
+            endian::write(out, word, encoding);
+        }
+    }
+
+    fn write_relocations<'r>(
+        &self,
+        relocations: impl Iterator<Item = &'r Relocation>,
+        out: &mut Vec<u8>,
+    ) -> Result<()> {
+        for relocation in relocations {
+            let (r_offset, symbol) = if self.relocatable {
+                let symbol = relocation.symbol.map_or(0, |id| self.symtab_indices[id.0]);
+                (relocation.offset, symbol)
+            } else {
+                let symbol = match relocation.symbol {
+                    Some(id) => self.dynsym_indices[id.0].ok_or(Error::InvalidData(
+                        "dynamic relocation against a non-dynamic symbol",
+                    ))?,
+                    None => 0,
+                };
+                (
+                    self.section_address(relocation.section) + relocation.offset,
+                    symbol,
+                )
+            };
+
+            let rela = Rela64::new(r_offset, symbol, relocation.r_type, relocation.addend);
+            endian::write(out, &rela, &self.builder.encoding);
+        }
+        Ok(())
+    }
+
+    fn write_dynamic(&self, out: &mut Vec<u8>) {
+        let builder = self.builder;
+        let mut entries: Vec<(DynamicTag, u64)> = builder
+            .dynamic
+            .iter()
+            .zip(&self.dynamic_strings)
+            .map(|((tag, value), &string)| match value {
+                DynamicValue::Value(value) => (tag.clone(), *value),
+                DynamicValue::String(_) => (tag.clone(), string as u64),
+            })
+            .collect();
+
+        entries.extend([
+            (DynamicTag::HASH, self.address_of(Contents::Hash)),
+            (DynamicTag::STRTAB, self.address_of(Contents::DynStr)),
+            (DynamicTag::SYMTAB, self.address_of(Contents::DynSym)),
+            (DynamicTag::STRSZ, self.dynstr.len() as u64),
+            (DynamicTag::SYMENT, size_of::<Symbol64>() as u64),
+        ]);
+        if !builder.relocations.is_empty() {
+            entries.extend([
+                (DynamicTag::RELA, self.address_of(Contents::RelaDyn)),
+                (
+                    DynamicTag::RELASZ,
+                    (builder.relocations.len() * size_of::<Rela64>()) as u64,
+                ),
+                (DynamicTag::RELAENT, size_of::<Rela64>() as u64),
+            ]);
+        }
+        entries.push((DynamicTag::NULL, 0));
+
+        for (d_tag, d_val) in entries {
+            endian::write(out, &Dynamic64 { d_tag, d_val }, &builder.encoding);
+        }
+    }
+
+    fn contents(&self, section: &OutputSection) -> Result<Vec<u8>> {
+        let builder = self.builder;
+        let mut out = Vec::new();
+        match section.contents {
+            Contents::Null | Contents::GnuStack => {}
+            Contents::User(index) => {
+                let section = &builder.sections[index];
+                if !section.is_nobits() {
+                    out.extend_from_slice(&section.data);
+                }
+            }
+            Contents::Interp => {
+                out.extend_from_slice(builder.interpreter.as_deref().unwrap_or_default());
+                out.push(0);
+            }
+            Contents::Hash => self.write_hash(&mut out),
+            Contents::DynSym => {
+                self.write_symbols(&self.dynsym_order, &self.dynamic_symbol_names, &mut out)
+            }
+            Contents::DynStr => out.extend_from_slice(self.dynstr.as_bytes()),
+            Contents::RelaDyn => self.write_relocations(builder.relocations.iter(), &mut out)?,
+            Contents::Dynamic => self.write_dynamic(&mut out),
+            Contents::Rela(index) => self.write_relocations(
+                builder
+                    .relocations
+                    .iter()
+                    .filter(|relocation| relocation.section.0 == index),
+                &mut out,
+            )?,
+            Contents::SymTab => {
+                self.write_symbols(&self.symtab_order, &self.symbol_names, &mut out)
+            }
+            Contents::StrTab => out.extend_from_slice(self.strtab.as_bytes()),
+            Contents::ShStrTab => out.extend_from_slice(self.shstrtab.as_bytes()),
+        }
+        Ok(out)
+    }
+
+    fn entry_point(&self) -> u64 {
+        match &self.builder.entry_point {
+            Some(EntryPoint::Address(address)) => *address,
+            Some(EntryPoint::Symbol(id)) => {
+                let symbol = &self.builder.symbols[id.0].symbol;
+                match symbol.section {
+                    SymbolSection::Section(section) => self.section_address(section) + symbol.value,
+                    _ => symbol.value,
+                }
+            }
+            None => 0,
+        }
+    }
+
+    fn write(self) -> Result<Vec<u8>> {
+        let builder = self.builder;
+        let encoding = &builder.encoding;
+        let section_header_size = self.sections.len() * size_of::<SectionHeader64>();
+        let mut out = vec![0; self.section_header_offset as usize + section_header_size];
+
+        let header = Header64 {
+            magic: Magic::ELF,
+            class: Class::ELF64,
+            encoding: encoding.clone(),
+            header_version: HeaderVersion::CURRENT,
+            os_abi: builder.os_abi.clone(),
+            abi_version: 0,
+            pad: [0; 7],
+            object_type: builder.object_type.clone(),
+            machine: builder.machine.clone(),
+            version: Version::CURRENT,
+            entry_point: self.entry_point(),
+            program_header_offset: if self.program_headers.is_empty() {
+                0
+            } else {
+                size_of::<Header64>() as u64
+            },
+            section_header_offset: self.section_header_offset,
+            flags: builder.flags,
+            header_size: size_of::<Header64>() as u16,
+            program_header_entry_size: if self.program_headers.is_empty() {
+                0
+            } else {
+                size_of::<ProgramHeader64>() as u16
+            },
+            program_header_count: self.program_headers.len() as u16,
+            section_header_entry_size: size_of::<SectionHeader64>() as u16,
+            section_header_count: self.sections.len() as u16,
+            section_header_string_table_index: self.sections.len() as u16 - 1,
+        };
+        out[..size_of::<Header64>()].copy_from_slice(&header.to_bytes());
+
+        for (index, program_header) in self.program_headers.iter().enumerate() {
+            let offset = size_of::<Header64>() + index * size_of::<ProgramHeader64>();
+            endian::write_at(&mut out, offset, program_header, encoding)?;
+        }
+
+        for (index, section) in self.sections.iter().enumerate() {
+            let contents = self.contents(section)?;
+            let offset = section.header.sh_offset as usize;
+            out[offset..offset + contents.len()].copy_from_slice(&contents);
+
+            let header = SectionHeader64 {
+                sh_name: section.name,
+                ..section.header.clone()
+            };
+            let offset = self.section_header_offset as usize + index * size_of::<SectionHeader64>();
+            endian::write_at(&mut out, offset, &header, encoding)?;
+        }
+
+        Ok(out)
+    }
+}
+
+/// Returns the `PT_LOAD` class of an allocated section: read-only,
+/// executable or writable
+fn segment_class(sh_flags: u64) -> usize {
+    if sh_flags & SECTION_FLAG_WRITE != 0 {
+        WRITABLE
+    } else if sh_flags & SECTION_FLAG_EXECINSTR != 0 {
+        1
+    } else {
+        0
+    }
+}
+
+fn generated_header(
+    sh_type: SectionType,
+    sh_flags: u64,
+    size: usize,
+    entry_size: usize,
+    align: u64,
+) -> SectionHeader64 {
+    SectionHeader64 {
+        sh_type,
+        sh_flags,
+        sh_size: size as u64,
+        sh_addralign: align,
+        sh_entsize: entry_size as u64,
+        ..null_section_header()
+    }
+}
+
+fn null_section_header() -> SectionHeader64 {
+    SectionHeader64 {
+        sh_name: 0,
+        sh_type: SectionType::NULL,
+        sh_flags: 0,
+        sh_addr: 0,
+        sh_offset: 0,
+        sh_size: 0,
+        sh_link: 0,
+        sh_info: 0,
+        sh_addralign: 0,
+        sh_entsize: 0,
+    }
+}
+
+fn null_program_header() -> ProgramHeader64 {
+    ProgramHeader64 {
+        p_type: ProgramType::NULL,
+        p_flags: 0,
+        p_offset: 0,
+        p_vaddr: 0,
+        p_paddr: 0,
+        p_filesz: 0,
+        p_memsz: 0,
+        p_align: 0,
+    }
+}
+
+fn null_symbol() -> Symbol64 {
+    Symbol64 {
+        st_name: 0,
+        st_info: 0,
+        st_other: 0,
+        st_shndx: SectionIndex::UNDEF,
+        st_value: 0,
+        st_size: 0,
+    }
+}
@@ -0,0 +1,188 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use zerocopy::{FromBytes, Immutable, IntoBytes};
+
+use crate::elf_enum;
+use crate::endian::impl_swap_bytes;
+use crate::error::{Error, Result};
+use crate::header::Class;
+
+/// 32-bit compression header (Elf32_Chdr), at the start of a section with
+/// `SHF_COMPRESSED`
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes, IntoBytes, Immutable)]
+#[doc(alias = "Elf32_Chdr")]
+pub struct CompressionHeader32 {
+    /// Compression algorithm
+    pub ch_type: CompressionType,
+    /// Size of the uncompressed data
+    pub ch_size: u32,
+    /// Alignment of the uncompressed data
+    pub ch_addralign: u32,
+}
+
+impl_swap_bytes!(CompressionHeader32 {
+    ch_type,
+    ch_size,
+    ch_addralign,
+});
+
+/// 64-bit compression header (Elf64_Chdr), at the start of a section with
+/// `SHF_COMPRESSED`
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes, IntoBytes, Immutable)]
+#[doc(alias = "Elf64_Chdr")]
+pub struct CompressionHeader64 {
+    /// Compression algorithm
+    pub ch_type: CompressionType,
+    pub ch_reserved: u32,
+    /// Size of the uncompressed data
+    pub ch_size: u64,
+    /// Alignment of the uncompressed data
+    pub ch_addralign: u64,
+}
+
+impl_swap_bytes!(CompressionHeader64 {
+    ch_type,
+    ch_reserved,
+    ch_size,
+    ch_addralign,
+});
+
+elf_enum! {
+    pub struct CompressionType(u32) {
+        /// zlib stream, as produced by `deflate`
+        ZLIB = 1, "ZLIB",
+        /// Zstandard frames
+        ZSTD = 2, "ZSTD",
+        /// OS-specific range start
+        LOOS = 0x60000000, "LOOS",
+        /// OS-specific range end
+        HIOS = 0x6fffffff, "HIOS",
+        /// Processor-specific range start
+        LOPROC = 0x70000000, "LOPROC",
+        /// Processor-specific range end
+        HIPROC = 0x7fffffff, "HIPROC",
+    }
+}
+
+/// Magic at the start of a legacy `.zdebug_*` section
+const LEGACY_MAGIC: &[u8; 4] = b"ZLIB";
+
+/// The contents of a compressed section
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompressedData<'a> {
+    /// Compression algorithm
+    pub compression: CompressionType,
+    /// Size of the uncompressed data
+    pub uncompressed_size: u64,
+    /// Alignment of the uncompressed data
+    pub uncompressed_align: u64,
+    /// Compressed stream, following the header
+    pub data: &'a [u8],
+}
+
+impl<'a> CompressedData<'a> {
+    /// Parses the contents of a section with `SHF_COMPRESSED`, which start
+    /// with a compression header for the given class in native byte order
+    pub fn parse(data: &'a [u8], class: &Class) -> Result<Self> {
+        let truncated = Error::InvalidData("truncated compression header");
+        if *class == Class::ELF32 {
+            let (header, data) =
+                CompressionHeader32::read_from_prefix(data).map_err(|_| truncated)?;
+            Ok(Self {
+                compression: header.ch_type,
+                uncompressed_size: header.ch_size.into(),
+                uncompressed_align: header.ch_addralign.into(),
+                data,
+            })
+        } else {
+            let (header, data) =
+                CompressionHeader64::read_from_prefix(data).map_err(|_| truncated)?;
+            Ok(Self {
+                compression: header.ch_type,
+                uncompressed_size: header.ch_size,
+                uncompressed_align: header.ch_addralign,
+                data,
+            })
+        }
+    }
+
+    /// Parses the contents of a legacy `.zdebug_*` section
+    ///
+    /// These start with the magic `ZLIB` and the uncompressed size as a
+    /// 64-bit big-endian number, followed by a zlib stream.
+    pub fn parse_legacy(data: &'a [u8]) -> Result<Self> {
+        let data = data
+            .strip_prefix(LEGACY_MAGIC)
+            .ok_or(Error::InvalidData("missing ZLIB magic"))?;
+        let (size, data) = data
+            .split_first_chunk::<8>()
+            .ok_or(Error::InvalidData("truncated compression header"))?;
+
+        Ok(Self {
+            compression: CompressionType::ZLIB,
+            uncompressed_size: u64::from_be_bytes(*size),
+            uncompressed_align: 1,
+            data,
+        })
+    }
+
+    /// Returns true if `data` starts like a legacy `.zdebug_*` section
+    pub fn is_legacy(data: &[u8]) -> bool {
+        data.len() >= 12 && data.starts_with(LEGACY_MAGIC)
+    }
+
+    /// Decompresses the data
+    ///
+    /// zlib needs the `zlib` feature and Zstandard the `zstd` feature. The
+    /// result must have the size given in the header.
+    #[cfg(feature = "alloc")]
+    pub fn decompress(&self) -> Result<Vec<u8>> {
+        let size = usize::try_from(self.uncompressed_size)
+            .map_err(|_| Error::InvalidData("uncompressed section too large"))?;
+
+        let out = match self.compression {
+            CompressionType::ZLIB => inflate_zlib(self.data, size)?,
+            CompressionType::ZSTD => decode_zstd(self.data, size)?,
+            _ => return Err(Error::InvalidData("unsupported compression type")),
+        };
+
+        if out.len() != size {
+            return Err(Error::InvalidData("uncompressed size mismatch"));
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(feature = "zlib")]
+fn inflate_zlib(data: &[u8], size: usize) -> Result<Vec<u8>> {
+    miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(data, size)
+        .map_err(|_| Error::InvalidData("invalid zlib stream"))
+}
+
+#[cfg(all(feature = "alloc", not(feature = "zlib")))]
+fn inflate_zlib(_data: &[u8], _size: usize) -> Result<Vec<u8>> {
+    Err(Error::InvalidData("zlib support is not enabled"))
+}
+
+#[cfg(feature = "zstd")]
+fn decode_zstd(data: &[u8], size: usize) -> Result<Vec<u8>> {
+    use ruzstd::io::Read;
+
+    // The size comes from the file, so nothing is reserved up front and
+    // decoding stops one byte past it for the caller to report the mismatch
+    let mut out = Vec::new();
+    ruzstd::decoding::StreamingDecoder::new(data)
+        .map_err(|_| Error::InvalidData("invalid zstd stream"))?
+        .take(size as u64 + 1)
+        .read_to_end(&mut out)
+        .map_err(|_| Error::InvalidData("invalid zstd stream"))?;
+    Ok(out)
+}
+
+#[cfg(all(feature = "alloc", not(feature = "zstd")))]
+fn decode_zstd(_data: &[u8], _size: usize) -> Result<Vec<u8>> {
+    Err(Error::InvalidData("zstd support is not enabled"))
+}
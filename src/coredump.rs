@@ -0,0 +1,649 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use zerocopy::{FromBytes, Immutable, IntoBytes};
+
+#[cfg(feature = "alloc")]
+use crate::auxv::{AuxEntry64, AuxType};
+use crate::error::{Error, Result};
+use crate::header::Machine;
+#[cfg(feature = "alloc")]
+use crate::header::{Class, Encoding, Header64, HeaderVersion, Magic, ObjectType, OsAbi, Version};
+#[cfg(feature = "alloc")]
+use crate::note::write_note;
+use crate::note::{CoreNoteType, Note};
+#[cfg(feature = "alloc")]
+use crate::program_header::{ProgramHeader64, ProgramType};
+
+fn read<T: FromBytes>(data: &[u8], what: &'static str) -> Result<T> {
+    T::read_from_prefix(data)
+        .map(|(value, _)| value)
+        .map_err(|_| Error::InvalidData(what))
+}
+
+/// Signal information embedded in [`PrStatusHeader64`] (`struct elf_siginfo`)
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes, IntoBytes, Immutable)]
+#[doc(alias = "elf_siginfo")]
+pub struct ElfSigInfo {
+    /// Signal number
+    pub si_signo: i32,
+    /// Extra code
+    pub si_code: i32,
+    /// Errno value
+    pub si_errno: i32,
+}
+
+/// Time value used in process status notes (`struct timeval`)
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes, IntoBytes, Immutable)]
+#[doc(alias = "timeval")]
+pub struct TimeVal64 {
+    pub tv_sec: i64,
+    pub tv_usec: i64,
+}
+
+/// Architecture independent part of a 64-bit `NT_PRSTATUS` note
+///
+/// The descriptor continues with the general purpose registers of the
+/// thread, whose layout depends on the architecture, followed by
+/// `pr_fpvalid`.
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes, IntoBytes, Immutable)]
+#[doc(alias = "elf_prstatus")]
+pub struct PrStatusHeader64 {
+    /// Information about the signal that stopped the thread
+    pub pr_info: ElfSigInfo,
+    /// Current signal
+    pub pr_cursig: i16,
+    pub pad: [u8; 2],
+    /// Set of pending signals
+    pub pr_sigpend: u64,
+    /// Set of held signals
+    pub pr_sighold: u64,
+    /// Thread ID
+    pub pr_pid: i32,
+    /// Parent process ID
+    pub pr_ppid: i32,
+    /// Process group ID
+    pub pr_pgrp: i32,
+    /// Session ID
+    pub pr_sid: i32,
+    /// User time
+    pub pr_utime: TimeVal64,
+    /// System time
+    pub pr_stime: TimeVal64,
+    /// Cumulative user time
+    pub pr_cutime: TimeVal64,
+    /// Cumulative system time
+    pub pr_cstime: TimeVal64,
+}
+
+/// A decoded `NT_PRSTATUS` note, describing one thread
+#[derive(Debug, Clone)]
+pub struct PrStatus<'a> {
+    pub header: PrStatusHeader64,
+    /// Raw general purpose registers (`pr_reg`)
+    pub registers: &'a [u8],
+    /// True if the floating point registers are valid
+    pub fpvalid: bool,
+}
+
+impl<'a> PrStatus<'a> {
+    pub fn parse(desc: &'a [u8]) -> Result<Self> {
+        let header: PrStatusHeader64 = read(desc, "truncated NT_PRSTATUS note")?;
+
+        // `pr_fpvalid` is an int padded to the alignment of the structure
+        let registers = desc
+            .get(size_of::<PrStatusHeader64>()..desc.len().saturating_sub(8))
+            .ok_or(Error::InvalidData("truncated NT_PRSTATUS note"))?;
+        let fpvalid: i32 = read(&desc[desc.len() - 8..], "truncated NT_PRSTATUS note")?;
+
+        Ok(Self {
+            header,
+            registers,
+            fpvalid: fpvalid != 0,
+        })
+    }
+
+    /// Decodes the general purpose registers for the given architecture
+    pub fn registers(&self, machine: &Machine) -> Option<Registers> {
+        match *machine {
+            Machine::X86_64 => X86_64Registers::read_from_bytes(self.registers)
+                .ok()
+                .map(Registers::X86_64),
+            Machine::AARCH64 => Aarch64Registers::read_from_bytes(self.registers)
+                .ok()
+                .map(Registers::Aarch64),
+            _ => None,
+        }
+    }
+}
+
+/// General purpose registers of an x86_64 thread (`struct user_regs_struct`)
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes, IntoBytes, Immutable)]
+#[doc(alias = "user_regs_struct")]
+pub struct X86_64Registers {
+    pub r15: u64,
+    pub r14: u64,
+    pub r13: u64,
+    pub r12: u64,
+    pub rbp: u64,
+    pub rbx: u64,
+    pub r11: u64,
+    pub r10: u64,
+    pub r9: u64,
+    pub r8: u64,
+    pub rax: u64,
+    pub rcx: u64,
+    pub rdx: u64,
+    pub rsi: u64,
+    pub rdi: u64,
+    pub orig_rax: u64,
+    pub rip: u64,
+    pub cs: u64,
+    pub eflags: u64,
+    pub rsp: u64,
+    pub ss: u64,
+    pub fs_base: u64,
+    pub gs_base: u64,
+    pub ds: u64,
+    pub es: u64,
+    pub fs: u64,
+    pub gs: u64,
+}
+
+/// General purpose registers of an AArch64 thread (`struct user_pt_regs`)
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes, IntoBytes, Immutable)]
+#[doc(alias = "user_pt_regs")]
+pub struct Aarch64Registers {
+    /// General purpose registers `x0` to `x30`
+    pub regs: [u64; 31],
+    pub sp: u64,
+    pub pc: u64,
+    pub pstate: u64,
+}
+
+/// General purpose registers of a thread
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Registers {
+    X86_64(X86_64Registers),
+    Aarch64(Aarch64Registers),
+}
+
+impl Registers {
+    /// Returns the program counter
+    pub fn pc(&self) -> u64 {
+        match self {
+            Self::X86_64(registers) => registers.rip,
+            Self::Aarch64(registers) => registers.pc,
+        }
+    }
+
+    /// Returns the stack pointer
+    pub fn sp(&self) -> u64 {
+        match self {
+            Self::X86_64(registers) => registers.rsp,
+            Self::Aarch64(registers) => registers.sp,
+        }
+    }
+}
+
+/// A 64-bit `NT_PRPSINFO` note, describing the process
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes, IntoBytes, Immutable)]
+#[doc(alias = "elf_prpsinfo")]
+pub struct PrPsInfo64 {
+    /// Numeric process state
+    pub pr_state: u8,
+    /// Character for `pr_state`
+    pub pr_sname: u8,
+    /// Zombie
+    pub pr_zomb: u8,
+    /// Nice value
+    pub pr_nice: i8,
+    pub pad: [u8; 4],
+    /// Process flags
+    pub pr_flag: u64,
+    /// User ID
+    pub pr_uid: u32,
+    /// Group ID
+    pub pr_gid: u32,
+    /// Process ID
+    pub pr_pid: i32,
+    /// Parent process ID
+    pub pr_ppid: i32,
+    /// Process group ID
+    pub pr_pgrp: i32,
+    /// Session ID
+    pub pr_sid: i32,
+    /// Filename of the executable, possibly truncated
+    pub pr_fname: [u8; 16],
+    /// Initial part of the argument list
+    pub pr_psargs: [u8; 80],
+}
+
+impl PrPsInfo64 {
+    pub fn parse(desc: &[u8]) -> Result<Self> {
+        read(desc, "truncated NT_PRPSINFO note")
+    }
+
+    /// Returns the filename of the executable
+    pub fn fname(&self) -> &[u8] {
+        until_nul(&self.pr_fname)
+    }
+
+    /// Returns the initial part of the argument list, separated by spaces
+    pub fn psargs(&self) -> &[u8] {
+        until_nul(&self.pr_psargs)
+    }
+}
+
+fn until_nul(bytes: &[u8]) -> &[u8] {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    &bytes[..end]
+}
+
+/// A 64-bit `NT_SIGINFO` note (`siginfo_t`)
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes, IntoBytes, Immutable)]
+#[doc(alias = "siginfo_t")]
+pub struct SigInfo64 {
+    /// Signal number
+    pub si_signo: i32,
+    /// Errno value
+    pub si_errno: i32,
+    /// Signal code
+    pub si_code: i32,
+    pub pad: [u8; 4],
+    /// Signal specific fields, interpreted according to the signal and code
+    #[doc(alias = "_sifields")]
+    pub fields: [u8; 112],
+}
+
+impl SigInfo64 {
+    pub fn parse(desc: &[u8]) -> Result<Self> {
+        read(desc, "truncated NT_SIGINFO note")
+    }
+
+    /// Returns true if the signal was sent by a process rather than raised
+    /// by the kernel
+    pub fn is_user(&self) -> bool {
+        self.si_code <= 0
+    }
+
+    /// Returns the faulting address (`si_addr`) of a `SIGSEGV`, `SIGBUS`,
+    /// `SIGILL`, `SIGFPE` or `SIGTRAP` raised by the kernel
+    pub fn fault_address(&self) -> Option<u64> {
+        const FAULT_SIGNALS: [i32; 5] = [4, 5, 7, 8, 11];
+
+        (!self.is_user() && FAULT_SIGNALS.contains(&self.si_signo))
+            .then(|| u64::from_ne_bytes(self.fields[..8].try_into().unwrap()))
+    }
+
+    /// Returns the process and user ID (`si_pid`, `si_uid`) of the sender of
+    /// a signal sent by a process
+    pub fn sender(&self) -> Option<(i32, u32)> {
+        self.is_user().then(|| {
+            (
+                i32::from_ne_bytes(self.fields[..4].try_into().unwrap()),
+                u32::from_ne_bytes(self.fields[4..8].try_into().unwrap()),
+            )
+        })
+    }
+}
+
+/// A single mapping from an `NT_FILE` note
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MappedFile<'a> {
+    /// Start address of the mapping
+    pub start: u64,
+    /// End address of the mapping
+    pub end: u64,
+    /// File offset of the mapping, in bytes
+    pub file_offset: u64,
+    /// Path of the mapped file
+    pub path: &'a [u8],
+}
+
+/// A decoded 64-bit `NT_FILE` note, listing the file backed mappings of the
+/// process
+#[derive(Debug, Clone)]
+pub struct MappedFiles<'a> {
+    page_size: u64,
+    entries: &'a [u8],
+    paths: &'a [u8],
+}
+
+impl<'a> MappedFiles<'a> {
+    pub fn parse(desc: &'a [u8]) -> Result<Self> {
+        let truncated = || Error::InvalidData("truncated NT_FILE note");
+
+        let [count, page_size]: [u64; 2] = read(desc, "truncated NT_FILE note")?;
+        let entries_end = usize::try_from(count)
+            .ok()
+            .and_then(|count| count.checked_mul(24))
+            .and_then(|size| size.checked_add(16))
+            .ok_or_else(truncated)?;
+
+        Ok(Self {
+            page_size,
+            entries: desc.get(16..entries_end).ok_or_else(truncated)?,
+            paths: &desc[entries_end..],
+        })
+    }
+
+    /// Returns the page size used for file offsets
+    pub fn page_size(&self) -> u64 {
+        self.page_size
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len() / 24
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = MappedFile<'a>> + '_ {
+        self.entries
+            .chunks_exact(24)
+            .zip(self.paths.split(|&b| b == 0))
+            .filter_map(|(entry, path)| {
+                let [start, end, page_offset]: [u64; 3] = read(entry, "").ok()?;
+                Some(MappedFile {
+                    start,
+                    end,
+                    file_offset: page_offset.wrapping_mul(self.page_size),
+                    path,
+                })
+            })
+    }
+}
+
+/// A decoded core file note
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum CoreNote<'a> {
+    PrStatus(PrStatus<'a>),
+    PrPsInfo(PrPsInfo64),
+    SigInfo(SigInfo64),
+    MappedFiles(MappedFiles<'a>),
+}
+
+impl<'a> CoreNote<'a> {
+    /// Decodes a note owned by `CORE`, returning `None` for note types that
+    /// have no typed decoder
+    pub fn parse(note: &Note<'a>) -> Option<Result<Self>> {
+        if note.name != Note::CORE {
+            return None;
+        }
+
+        Some(match CoreNoteType::from_raw(note.n_type) {
+            CoreNoteType::PRSTATUS => PrStatus::parse(note.desc).map(Self::PrStatus),
+            CoreNoteType::PRPSINFO => PrPsInfo64::parse(note.desc).map(Self::PrPsInfo),
+            CoreNoteType::SIGINFO => SigInfo64::parse(note.desc).map(Self::SigInfo),
+            CoreNoteType::FILE => MappedFiles::parse(note.desc).map(Self::MappedFiles),
+            _ => return None,
+        })
+    }
+}
+
+impl Registers {
+    /// Returns the registers in the layout of `pr_reg`
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::X86_64(registers) => registers.as_bytes(),
+            Self::Aarch64(registers) => registers.as_bytes(),
+        }
+    }
+}
+
+/// A memory region to be written as a `PT_LOAD` segment of a core file
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+struct CoreSegment<'a> {
+    vaddr: u64,
+    memory_size: u64,
+    flags: u32,
+    data: &'a [u8],
+}
+
+/// Builder for minimal core files
+///
+/// The resulting file follows the layout produced by the Linux kernel: the
+/// ELF header is followed by the program header table, a single `PT_NOTE`
+/// segment holding every note in the order they were added, and one
+/// page-aligned `PT_LOAD` segment per memory region.
+///
+/// Debuggers expect the notes of each thread to start with its
+/// `NT_PRSTATUS`, and the process wide notes to follow the status of the
+/// first thread.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+pub struct CoreBuilder<'a> {
+    machine: Machine,
+    notes: Vec<u8>,
+    segments: Vec<CoreSegment<'a>>,
+    /// First error found while adding notes, returned by `build`
+    error: Option<Error>,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> CoreBuilder<'a> {
+    /// Alignment of the memory segments in the file
+    pub const PAGE_SIZE: u64 = 0x1000;
+
+    pub fn new(machine: Machine) -> Self {
+        Self {
+            machine,
+            notes: Vec::new(),
+            segments: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Adds an arbitrary note
+    pub fn note(&mut self, name: &[u8], n_type: u32, desc: &[u8]) -> &mut Self {
+        write_note(&mut self.notes, name, n_type, desc);
+        self
+    }
+
+    fn core_note(&mut self, n_type: CoreNoteType, desc: &[u8]) -> &mut Self {
+        self.note(Note::CORE, n_type.into_raw(), desc)
+    }
+
+    /// Adds an `NT_PRSTATUS` note describing a thread
+    pub fn prstatus(
+        &mut self,
+        status: &PrStatusHeader64,
+        registers: &Registers,
+        fpvalid: bool,
+    ) -> &mut Self {
+        let mut desc = Vec::new();
+        desc.extend_from_slice(status.as_bytes());
+        desc.extend_from_slice(registers.as_bytes());
+        desc.extend_from_slice((fpvalid as i32).as_bytes());
+        desc.resize(desc.len().next_multiple_of(8), 0);
+        self.core_note(CoreNoteType::PRSTATUS, &desc)
+    }
+
+    /// Adds an `NT_PRPSINFO` note describing the process
+    pub fn prpsinfo(&mut self, info: &PrPsInfo64) -> &mut Self {
+        self.core_note(CoreNoteType::PRPSINFO, info.as_bytes())
+    }
+
+    /// Adds an `NT_SIGINFO` note describing the signal that caused the dump
+    pub fn siginfo(&mut self, info: &SigInfo64) -> &mut Self {
+        self.core_note(CoreNoteType::SIGINFO, info.as_bytes())
+    }
+
+    /// Adds an `NT_AUXV` note holding the auxiliary vector of the process
+    ///
+    /// A terminating `AT_NULL` entry is appended if `entries` lacks one.
+    pub fn auxv(&mut self, entries: &[AuxEntry64]) -> &mut Self {
+        let mut desc = entries.as_bytes().to_vec();
+        if entries
+            .last()
+            .is_none_or(|entry| entry.a_type != AuxType::NULL)
+        {
+            let terminator = AuxEntry64 {
+                a_type: AuxType::NULL,
+                a_val: 0,
+            };
+            desc.extend_from_slice(terminator.as_bytes());
+        }
+        self.core_note(CoreNoteType::AUXV, &desc)
+    }
+
+    /// Adds an `NT_FILE` note listing the file backed mappings of the process
+    ///
+    /// File offsets are expressed in units of `page_size` in the note, so they
+    /// must be multiples of it. Otherwise, or if `page_size` is zero, no note
+    /// is added and [`build`](Self::build) fails.
+    pub fn mapped_files<'f>(
+        &mut self,
+        page_size: u64,
+        files: impl IntoIterator<Item = MappedFile<'f>>,
+    ) -> &mut Self {
+        if page_size == 0 {
+            self.error
+                .get_or_insert(Error::InvalidData("zero NT_FILE page size"));
+            return self;
+        }
+
+        let mut entries = Vec::new();
+        let mut paths = Vec::new();
+        let mut count: u64 = 0;
+
+        for file in files {
+            if !file.file_offset.is_multiple_of(page_size) {
+                self.error.get_or_insert(Error::InvalidData(
+                    "mapped file offset is not a multiple of the page size",
+                ));
+                return self;
+            }
+            entries.extend_from_slice(file.start.as_bytes());
+            entries.extend_from_slice(file.end.as_bytes());
+            entries.extend_from_slice((file.file_offset / page_size).as_bytes());
+            paths.extend_from_slice(file.path);
+            paths.push(0);
+            count += 1;
+        }
+
+        let mut desc = Vec::new();
+        desc.extend_from_slice(count.as_bytes());
+        desc.extend_from_slice(page_size.as_bytes());
+        desc.extend_from_slice(&entries);
+        desc.extend_from_slice(&paths);
+        self.core_note(CoreNoteType::FILE, &desc)
+    }
+
+    /// Adds a memory region whose contents are fully captured in `data`
+    pub fn memory(&mut self, vaddr: u64, flags: u32, data: &'a [u8]) -> &mut Self {
+        self.memory_with_size(vaddr, data.len() as u64, flags, data)
+    }
+
+    /// Adds a memory region of `memory_size` bytes of which only the leading
+    /// `data` is captured, such as a mapping whose contents were not dumped
+    pub fn memory_with_size(
+        &mut self,
+        vaddr: u64,
+        memory_size: u64,
+        flags: u32,
+        data: &'a [u8],
+    ) -> &mut Self {
+        self.segments.push(CoreSegment {
+            vaddr,
+            memory_size: memory_size.max(data.len() as u64),
+            flags,
+            data,
+        });
+        self
+    }
+
+    /// Writes the core file, or returns the first error found while adding
+    /// notes
+    pub fn build(&self) -> Result<Vec<u8>> {
+        if let Some(error) = &self.error {
+            return Err(error.clone());
+        }
+
+        let program_header_count = u16::try_from(self.segments.len() + 1)
+            .ok()
+            .filter(|&count| count < u16::MAX)
+            .ok_or(Error::InvalidData("too many core segments"))?;
+
+        let header_size = size_of::<Header64>() as u64;
+        let program_headers_size =
+            program_header_count as u64 * size_of::<ProgramHeader64>() as u64;
+        let notes_offset = header_size + program_headers_size;
+
+        let header = Header64 {
+            magic: Magic::ELF,
+            class: Class::ELF64,
+            encoding: Encoding::native(),
+            header_version: HeaderVersion::CURRENT,
+            os_abi: OsAbi::NONE,
+            abi_version: 0,
+            pad: [0; 7],
+            object_type: ObjectType::CORE,
+            machine: self.machine.clone(),
+            version: Version::CURRENT,
+            entry_point: 0,
+            program_header_offset: header_size,
+            section_header_offset: 0,
+            flags: 0,
+            header_size: header_size as u16,
+            program_header_entry_size: size_of::<ProgramHeader64>() as u16,
+            program_header_count,
+            section_header_entry_size: 0,
+            section_header_count: 0,
+            section_header_string_table_index: 0,
+        };
+
+        let mut program_headers = Vec::with_capacity(program_header_count as usize);
+        program_headers.push(ProgramHeader64 {
+            p_type: ProgramType::NOTE,
+            p_flags: 0,
+            p_offset: notes_offset,
+            p_vaddr: 0,
+            p_paddr: 0,
+            p_filesz: self.notes.len() as u64,
+            p_memsz: 0,
+            p_align: 4,
+        });
+
+        let mut offset = (notes_offset + self.notes.len() as u64).next_multiple_of(Self::PAGE_SIZE);
+        for segment in &self.segments {
+            program_headers.push(ProgramHeader64 {
+                p_type: ProgramType::LOAD,
+                p_flags: segment.flags,
+                p_offset: offset,
+                p_vaddr: segment.vaddr,
+                p_paddr: 0,
+                p_filesz: segment.data.len() as u64,
+                p_memsz: segment.memory_size,
+                p_align: Self::PAGE_SIZE,
+            });
+            offset = (offset + segment.data.len() as u64).next_multiple_of(Self::PAGE_SIZE);
+        }
+
+        let mut out = Vec::new();
+        out.extend_from_slice(header.as_bytes());
+        for program_header in &program_headers {
+            out.extend_from_slice(program_header.as_bytes());
+        }
+        out.extend_from_slice(&self.notes);
+
+        for (program_header, segment) in program_headers[1..].iter().zip(&self.segments) {
+            out.resize(program_header.p_offset as usize, 0);
+            out.extend_from_slice(segment.data);
+        }
+
+        Ok(out)
+    }
+}
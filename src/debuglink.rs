@@ -0,0 +1,294 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::fs;
+#[cfg(feature = "std")]
+use std::io::Read;
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+#[cfg(feature = "std")]
+use crate::file::ElfFile;
+
+/// Incremental CRC-32 as used by `.gnu_debuglink`
+///
+/// This is the reflected CRC-32 of zlib and IEEE 802.3, with polynomial
+/// `0xedb88320`.
+#[derive(Debug, Clone)]
+pub struct Crc32 {
+    state: u32,
+}
+
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut value = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            value = if value & 1 != 0 {
+                (value >> 1) ^ 0xedb88320
+            } else {
+                value >> 1
+            };
+            bit += 1;
+        }
+        table[index] = value;
+        index += 1;
+    }
+    table
+};
+
+impl Crc32 {
+    pub const fn new() -> Self {
+        Self { state: !0 }
+    }
+
+    /// Adds `data` to the checksum
+    pub fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.state =
+                CRC32_TABLE[((self.state ^ byte as u32) & 0xff) as usize] ^ (self.state >> 8);
+        }
+    }
+
+    /// Returns the checksum of the data added so far
+    pub const fn finish(&self) -> u32 {
+        !self.state
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the CRC-32 of `data`
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(data);
+    crc.finish()
+}
+
+/// Contents of a `.gnu_debuglink` section, naming the file that holds the
+/// debugging information of a stripped object
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugLink<'a> {
+    /// File name of the debug file, without its NUL terminator
+    pub name: &'a [u8],
+    /// CRC-32 of the whole debug file
+    pub crc: u32,
+}
+
+impl<'a> DebugLink<'a> {
+    /// Parses the contents of a `.gnu_debuglink` section
+    ///
+    /// The name is padded with NULs to a multiple of 4 bytes and followed by
+    /// the checksum in the byte order of the file, which must be native.
+    pub fn parse(data: &'a [u8]) -> Result<Self> {
+        let name = split_name(data)?;
+        let offset = (name.len() + 1).next_multiple_of(4);
+        let crc = data
+            .get(offset..offset + 4)
+            .ok_or(Error::InvalidData("truncated .gnu_debuglink section"))?;
+
+        Ok(Self {
+            name,
+            crc: u32::from_ne_bytes(crc.try_into().unwrap()),
+        })
+    }
+
+    /// Encodes the contents of a `.gnu_debuglink` section naming `name`, the
+    /// file name of the debug file `data`, like `objcopy --add-gnu-debuglink`
+    #[cfg(feature = "alloc")]
+    pub fn write(name: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut out = name.to_vec();
+        out.push(0);
+        out.resize(out.len().next_multiple_of(4), 0);
+        out.extend_from_slice(&crc32(data).to_ne_bytes());
+        out
+    }
+
+    /// Returns true if `data` is the debug file this link refers to
+    pub fn matches(&self, data: &[u8]) -> bool {
+        crc32(data) == self.crc
+    }
+}
+
+/// Contents of a `.gnu_debugaltlink` section, naming the supplementary debug
+/// file shared between several debug files, as produced by `dwz`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugAltLink<'a> {
+    /// Path of the supplementary file, without its NUL terminator
+    ///
+    /// A relative path is relative to the directory of the debug file.
+    pub name: &'a [u8],
+    /// Build ID of the supplementary file
+    pub build_id: &'a [u8],
+}
+
+impl<'a> DebugAltLink<'a> {
+    /// Parses the contents of a `.gnu_debugaltlink` section
+    ///
+    /// The NUL-terminated name is directly followed by the build ID.
+    pub fn parse(data: &'a [u8]) -> Result<Self> {
+        let name = split_name(data)?;
+        Ok(Self {
+            name,
+            build_id: &data[name.len() + 1..],
+        })
+    }
+}
+
+fn split_name(data: &[u8]) -> Result<&[u8]> {
+    let end = data
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or(Error::InvalidData("unterminated debug file name"))?;
+    Ok(&data[..end])
+}
+
+/// Locates separate debug files the way GDB does
+///
+/// A debug file is found through the build ID of the object, in
+/// `<debug directory>/.build-id/xx/yyyy.debug`, or through its
+/// `.gnu_debuglink` section, next to the object, in its `.debug`
+/// subdirectory, or below a debug directory at the path of the object. A
+/// candidate is only accepted if its build ID or CRC-32 matches.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct DebugFileResolver {
+    debug_directories: Vec<PathBuf>,
+}
+
+#[cfg(feature = "std")]
+impl DebugFileResolver {
+    /// Creates a resolver searching `/usr/lib/debug`
+    pub fn new() -> Self {
+        Self {
+            debug_directories: std::vec![PathBuf::from("/usr/lib/debug")],
+        }
+    }
+
+    /// Replaces the global debug directories to search, in order
+    pub fn debug_directories<I, P>(&mut self, directories: I) -> &mut Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.debug_directories = directories.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Finds the debug file of `file`, read from `path`
+    ///
+    /// The build ID is tried first, then the `.gnu_debuglink` section.
+    pub fn find(&self, path: &Path, file: &ElfFile) -> Option<PathBuf> {
+        if let Some(build_id) = file.build_id()
+            && let Some(found) = self.find_by_build_id(build_id)
+        {
+            return Some(found);
+        }
+
+        let link = file.debug_link()?.ok()?;
+        self.find_by_debug_link(path, &link)
+    }
+
+    /// Finds the debug file with the given build ID
+    pub fn find_by_build_id(&self, build_id: &[u8]) -> Option<PathBuf> {
+        if build_id.len() < 2 {
+            return None;
+        }
+
+        self.debug_directories
+            .iter()
+            .map(|directory| build_id_path(directory, build_id))
+            .find(|candidate| has_build_id(candidate, build_id))
+    }
+
+    /// Finds the debug file named by `link` for the object at `path`
+    pub fn find_by_debug_link(&self, path: &Path, link: &DebugLink) -> Option<PathBuf> {
+        let name = Path::new(std::str::from_utf8(link.name).ok()?);
+        // The link names a file, never a path
+        if name.components().count() != 1 {
+            return None;
+        }
+
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let directory = path.parent()?;
+        let relative = directory.strip_prefix("/").unwrap_or(directory);
+
+        let mut candidates = std::vec![directory.join(name), directory.join(".debug").join(name)];
+        for debug_directory in &self.debug_directories {
+            candidates.push(debug_directory.join(relative).join(name));
+        }
+
+        candidates
+            .into_iter()
+            .filter(|candidate| *candidate != path)
+            .find(|candidate| file_crc32(candidate).ok() == Some(link.crc))
+    }
+
+    /// Finds the supplementary file named by the `.gnu_debugaltlink` section
+    /// of the debug file `file`, read from `path`
+    pub fn find_alt(&self, path: &Path, file: &ElfFile) -> Option<PathBuf> {
+        let link = file.debug_alt_link()?.ok()?;
+        let name = Path::new(std::str::from_utf8(link.name).ok()?);
+
+        let direct = match path.parent() {
+            Some(directory) => directory.join(name),
+            None => name.to_path_buf(),
+        };
+        if has_build_id(&direct, link.build_id) {
+            return Some(direct);
+        }
+        self.find_by_build_id(link.build_id)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for DebugFileResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the path of the debug file with the given build ID below
+/// `debug_directory`, `.build-id/xx/yyyy.debug`
+#[cfg(feature = "std")]
+pub fn build_id_path(debug_directory: &Path, build_id: &[u8]) -> PathBuf {
+    use core::fmt::Write;
+
+    let mut name = std::string::String::from(".build-id/");
+    for (index, byte) in build_id.iter().enumerate() {
+        if index == 1 {
+            name.push('/');
+        }
+        let _ = write!(name, "{byte:02x}");
+    }
+    name.push_str(".debug");
+    debug_directory.join(name)
+}
+
+#[cfg(feature = "std")]
+fn has_build_id(path: &Path, build_id: &[u8]) -> bool {
+    let Ok(data) = fs::read(path) else {
+        return false;
+    };
+    ElfFile::parse(&data).is_ok_and(|file| file.build_id() == Some(build_id))
+}
+
+#[cfg(feature = "std")]
+fn file_crc32(path: &Path) -> std::io::Result<u32> {
+    let mut file = fs::File::open(path)?;
+    let mut crc = Crc32::new();
+    let mut buffer = std::vec![0; 64 * 1024];
+    loop {
+        match file.read(&mut buffer)? {
+            0 => return Ok(crc.finish()),
+            size => crc.update(&buffer[..size]),
+        }
+    }
+}
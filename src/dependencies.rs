@@ -0,0 +1,624 @@
+#[cfg(feature = "std")]
+use alloc::{borrow::ToOwned, string::String, string::ToString, vec::Vec};
+#[cfg(feature = "std")]
+use std::fs;
+#[cfg(feature = "std")]
+use std::io::{self, Read};
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+
+use zerocopy::{FromBytes, Immutable, IntoBytes};
+
+#[cfg(feature = "std")]
+use crate::dynamic::{DYNAMIC_FLAG_1_NODEFLIB, DynamicTag};
+use crate::error::{Error, Result};
+#[cfg(feature = "std")]
+use crate::file::ElfFile;
+#[cfg(feature = "std")]
+use crate::header::{Header64, Machine, ObjectType};
+use crate::string_table::StringTable;
+use crate::table::{Table, TableIter};
+
+/// Magic number of the `/etc/ld.so.cache` format written by glibc 2.32 and
+/// later, also appended to the legacy format by older versions
+pub const LD_SO_CACHE_MAGIC: &[u8] = b"glibc-ld.so.cache1.1";
+/// Magic number of the legacy `/etc/ld.so.cache` format
+pub const LD_SO_CACHE_LEGACY_MAGIC: &[u8] = b"ld.so-1.7.0";
+
+/// Header of the `/etc/ld.so.cache` format (cache_file_new)
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq, FromBytes, IntoBytes, Immutable)]
+#[doc(alias = "cache_file_new")]
+pub struct LdSoCacheHeader {
+    pub magic: [u8; 20],
+    /// Number of entries
+    pub nlibs: u32,
+    /// Size of the string table following the entries
+    pub len_strings: u32,
+    /// Byte order of the cache
+    pub flags: u8,
+    pub padding: [u8; 3],
+    /// Offset of the extension directory, or 0
+    pub extension_offset: u32,
+    pub unused: [u32; 3],
+}
+
+/// Entry of the `/etc/ld.so.cache` format (file_entry_new)
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq, FromBytes, IntoBytes, Immutable)]
+#[doc(alias = "file_entry_new")]
+pub struct LdSoCacheFileEntry {
+    /// Library type and architecture, such as `FLAG_X8664_LIB64`
+    pub flags: i32,
+    /// Offset of the file name the library is requested as
+    pub key: u32,
+    /// Offset of the path of the library
+    pub value: u32,
+    /// Minimum kernel version required by the library
+    pub os_version: u32,
+    /// Hardware capabilities required by the library
+    pub hwcap: u64,
+}
+
+/// An entry of the library cache together with its strings
+#[derive(Debug, Clone)]
+pub struct LdSoCacheEntry<'a> {
+    pub entry: LdSoCacheFileEntry,
+    /// The file name the library is requested as, usually its soname
+    pub name: Option<&'a [u8]>,
+    /// The absolute path of the library
+    pub path: Option<&'a [u8]>,
+}
+
+/// A view over the library cache written by `ldconfig`, `/etc/ld.so.cache`
+///
+/// The cache is stored in the byte order of the host that wrote it. Only the
+/// current format is supported, either alone or after a legacy one.
+#[derive(Debug, Clone)]
+pub struct LdSoCache<'a> {
+    entries: Table<'a, LdSoCacheFileEntry>,
+    strings: StringTable<'a>,
+}
+
+impl<'a> LdSoCache<'a> {
+    /// Parses the contents of a library cache
+    pub fn parse(data: &'a [u8]) -> Result<Self> {
+        let data = if data.starts_with(LD_SO_CACHE_LEGACY_MAGIC) {
+            // The legacy header is padded to 12 bytes and its 12-byte
+            // entries are followed by the current format, 8-byte aligned
+            let count = data
+                .get(12..16)
+                .ok_or(Error::InvalidData("truncated ld.so.cache header"))?;
+            let count = u32::from_ne_bytes(count.try_into().unwrap()) as usize;
+            let offset = count
+                .checked_mul(12)
+                .and_then(|size| size.checked_add(16))
+                .ok_or(Error::InvalidData("invalid ld.so.cache entry count"))?
+                .next_multiple_of(8);
+            data.get(offset..).ok_or(Error::InvalidData(
+                "legacy ld.so.cache without current format",
+            ))?
+        } else {
+            data
+        };
+
+        let (header, rest) = LdSoCacheHeader::read_from_prefix(data)
+            .map_err(|_| Error::InvalidData("truncated ld.so.cache header"))?;
+        if header.magic != LD_SO_CACHE_MAGIC {
+            return Err(Error::InvalidData("invalid ld.so.cache magic"));
+        }
+
+        let size = (header.nlibs as usize)
+            .checked_mul(size_of::<LdSoCacheFileEntry>())
+            .ok_or(Error::InvalidData("invalid ld.so.cache entry count"))?;
+        let entries = rest
+            .get(..size)
+            .ok_or(Error::InvalidData("truncated ld.so.cache entries"))?;
+
+        Ok(Self {
+            entries: Table::new(entries),
+            // String offsets are relative to the start of the header
+            strings: StringTable::new(data),
+        })
+    }
+
+    /// Iterates over the entries, in the order `ldconfig` sorted them
+    pub fn entries(&self) -> impl Iterator<Item = LdSoCacheEntry<'a>> + 'a {
+        let strings = self.strings;
+        let entries: TableIter<'a, LdSoCacheFileEntry> = self.entries.iter();
+        entries.map(move |entry| LdSoCacheEntry {
+            name: strings.get(entry.key),
+            path: strings.get(entry.value),
+            entry,
+        })
+    }
+
+    /// Iterates over the paths of the libraries requested as `name`, one per
+    /// architecture the cache knows the library for
+    pub fn find<'n>(&self, name: &'n [u8]) -> impl Iterator<Item = &'a [u8]> + 'n
+    where
+        'a: 'n,
+    {
+        self.entries()
+            .filter(move |entry| entry.name == Some(name))
+            .filter_map(|entry| entry.path)
+    }
+}
+
+/// Builds the tree of shared libraries a file depends on without running it,
+/// following the search rules of the glibc dynamic loader
+///
+/// For each `DT_NEEDED` entry, the loader searches, in order:
+///
+/// - the `DT_RPATH` of the object requesting the library and of the objects
+///   that loaded it in turn, unless the requesting object has a `DT_RUNPATH`
+/// - the library paths, like `LD_LIBRARY_PATH`
+/// - the `DT_RUNPATH` of the requesting object
+/// - `/etc/ld.so.cache` and the default paths, unless the requesting object
+///   is marked with `DF_1_NODEFLIB`
+///
+/// `$ORIGIN`, `$LIB` and `$PLATFORM` are expanded in search paths. Every
+/// path is looked up below the sysroot, except those relative to `$ORIGIN`,
+/// and candidates for another machine are skipped like the loader does.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct DependencyResolver {
+    sysroot: PathBuf,
+    library_paths: Vec<PathBuf>,
+    default_paths: Option<Vec<PathBuf>>,
+    use_cache: bool,
+    platform: Option<String>,
+    lib: Option<String>,
+}
+
+#[cfg(feature = "std")]
+impl DependencyResolver {
+    /// Creates a resolver for the running system, with `/` as sysroot
+    pub fn new() -> Self {
+        Self {
+            sysroot: PathBuf::from("/"),
+            library_paths: Vec::new(),
+            default_paths: None,
+            use_cache: true,
+            platform: None,
+            lib: None,
+        }
+    }
+
+    /// Sets the directory every absolute path is looked up in
+    pub fn sysroot(&mut self, sysroot: impl Into<PathBuf>) -> &mut Self {
+        self.sysroot = sysroot.into();
+        self
+    }
+
+    /// Sets the directories searched before `DT_RUNPATH`, like
+    /// `LD_LIBRARY_PATH`
+    pub fn library_paths<I, P>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.library_paths = paths.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Replaces the directories searched last
+    ///
+    /// By default these are the multiarch directories of the machine, if
+    /// known, then `/lib64`, `/usr/lib64`, `/lib` and `/usr/lib`.
+    pub fn default_paths<I, P>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.default_paths = Some(paths.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Sets whether `/etc/ld.so.cache` is searched, which it is by default
+    pub fn use_cache(&mut self, use_cache: bool) -> &mut Self {
+        self.use_cache = use_cache;
+        self
+    }
+
+    /// Sets the expansion of `$PLATFORM`, by default derived from the machine
+    /// of the file, like `x86_64`
+    pub fn platform(&mut self, platform: impl Into<String>) -> &mut Self {
+        self.platform = Some(platform.into());
+        self
+    }
+
+    /// Sets the expansion of `$LIB`, `lib64` by default
+    pub fn lib(&mut self, lib: impl Into<String>) -> &mut Self {
+        self.lib = Some(lib.into());
+        self
+    }
+
+    /// Builds the dependency tree of the file at `path`
+    ///
+    /// Libraries are loaded breadth first, like the dynamic loader does, and
+    /// each is only loaded once. Libraries that cannot be found are recorded
+    /// as such rather than failing.
+    pub fn resolve(&self, path: &Path) -> io::Result<DependencyTree> {
+        let data = fs::read(path)?;
+        let elf = ElfFile::parse(&data).map_err(invalid_data)?;
+        let machine = elf.header().machine.clone();
+
+        let search = Search {
+            resolver: self,
+            machine: machine.clone(),
+            platform: self
+                .platform
+                .clone()
+                .or_else(|| platform_name(&machine).map(ToOwned::to_owned)),
+            lib: self.lib.clone().unwrap_or_else(|| "lib64".into()),
+            cache: if self.use_cache {
+                fs::read(self.in_sysroot(Path::new("/etc/ld.so.cache"))).ok()
+            } else {
+                None
+            },
+        };
+
+        let mut objects = std::vec![LoadedObject::new(
+            path.display().to_string(),
+            path.to_path_buf(),
+            None,
+            &elf,
+        )];
+
+        // The interpreter is loaded before any library and satisfies the
+        // requests for its soname, like `ld-linux-x86-64.so.2` from libc
+        let interpreter = elf.interpreter().and_then(|interpreter| {
+            let name = String::from_utf8_lossy(interpreter).into_owned();
+            let path = self.in_sysroot(Path::new(&name));
+            let data = search.read_compatible(&path)?;
+            let elf = ElfFile::parse(&data).ok()?;
+            objects.push(LoadedObject::new(name, path, None, &elf));
+            Some(objects.len() - 1)
+        });
+
+        let mut index = 0;
+        while index < objects.len() {
+            let names: Vec<String> = objects[index]
+                .needed
+                .iter()
+                .map(|needed| needed.name.clone())
+                .collect();
+
+            for (position, name) in names.into_iter().enumerate() {
+                let loaded = objects
+                    .iter()
+                    .position(|object| object.is(&name))
+                    .or_else(|| {
+                        let (path, data) = search.find(&objects, index, &name)?;
+                        let canonical = fs::canonicalize(&path).ok();
+                        if let Some(found) = objects.iter().position(|object| {
+                            canonical.is_some() && fs::canonicalize(&object.path).ok() == canonical
+                        }) {
+                            return Some(found);
+                        }
+
+                        let elf = ElfFile::parse(&data).ok()?;
+                        objects.push(LoadedObject::new(name, path, Some(index), &elf));
+                        Some(objects.len() - 1)
+                    });
+                objects[index].needed[position].object = loaded;
+            }
+
+            index += 1;
+        }
+
+        Ok(DependencyTree {
+            objects,
+            interpreter,
+        })
+    }
+
+    fn in_sysroot(&self, path: &Path) -> PathBuf {
+        self.sysroot.join(path.strip_prefix("/").unwrap_or(path))
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for DependencyResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The state of a single resolution
+#[cfg(feature = "std")]
+struct Search<'r> {
+    resolver: &'r DependencyResolver,
+    machine: Machine,
+    platform: Option<String>,
+    lib: String,
+    cache: Option<Vec<u8>>,
+}
+
+#[cfg(feature = "std")]
+impl Search<'_> {
+    /// Finds the library `name` requested by `objects[requester]`, returning
+    /// its path and contents
+    fn find(
+        &self,
+        objects: &[LoadedObject],
+        requester: usize,
+        name: &str,
+    ) -> Option<(PathBuf, Vec<u8>)> {
+        let resolver = self.resolver;
+        let try_path = |path: PathBuf| Some((path.clone(), self.read_compatible(&path)?));
+        let try_directories = |paths: &str, origin: &Path| {
+            paths
+                .split(':')
+                .filter(|path| !path.is_empty())
+                .filter_map(|path| self.expand(path, origin))
+                .find_map(|directory| try_path(directory.join(name)))
+        };
+
+        if name.contains('/') {
+            return try_path(resolver.in_sysroot(Path::new(name)));
+        }
+
+        let object = &objects[requester];
+        if object.runpath.is_none() {
+            let mut current = Some(requester);
+            while let Some(index) = current {
+                let loader = &objects[index];
+                if let Some(rpath) = &loader.rpath
+                    && let Some(found) = try_directories(rpath, loader.origin())
+                {
+                    return Some(found);
+                }
+                current = loader.loader;
+            }
+        }
+
+        if let Some(found) = resolver
+            .library_paths
+            .iter()
+            .find_map(|directory| try_path(resolver.in_sysroot(directory).join(name)))
+        {
+            return Some(found);
+        }
+
+        if let Some(runpath) = &object.runpath
+            && let Some(found) = try_directories(runpath, object.origin())
+        {
+            return Some(found);
+        }
+
+        if object.nodeflib {
+            return None;
+        }
+
+        if let Some(cache) = &self.cache
+            && let Ok(cache) = LdSoCache::parse(cache)
+            && let Some(found) = cache.find(name.as_bytes()).find_map(|path| {
+                let path = std::str::from_utf8(path).ok()?;
+                try_path(resolver.in_sysroot(Path::new(path)))
+            })
+        {
+            return Some(found);
+        }
+
+        let default_paths = match &resolver.default_paths {
+            Some(paths) => paths.clone(),
+            None => default_paths(&self.machine),
+        };
+        default_paths
+            .iter()
+            .find_map(|directory| try_path(resolver.in_sysroot(directory).join(name)))
+    }
+
+    /// Expands the dynamic string tokens of a search path, returning `None`
+    /// if it uses an unknown token or one without a value
+    fn expand(&self, path: &str, origin: &Path) -> Option<PathBuf> {
+        let mut expanded = String::new();
+        let mut relative_to_origin = false;
+        let mut rest = path;
+
+        while let Some(start) = rest.find('$') {
+            expanded.push_str(&rest[..start]);
+            rest = &rest[start + 1..];
+
+            let (token, after) = match rest.strip_prefix('{') {
+                Some(braced) => {
+                    let end = braced.find('}')?;
+                    (&braced[..end], &braced[end + 1..])
+                }
+                None => {
+                    let end = rest
+                        .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                        .unwrap_or(rest.len());
+                    (&rest[..end], &rest[end..])
+                }
+            };
+
+            match token {
+                "ORIGIN" => {
+                    relative_to_origin = true;
+                    expanded.push_str(origin.to_str()?);
+                }
+                "LIB" => expanded.push_str(&self.lib),
+                "PLATFORM" => expanded.push_str(self.platform.as_deref()?),
+                _ => return None,
+            }
+            rest = after;
+        }
+        expanded.push_str(rest);
+
+        let path = PathBuf::from(expanded);
+        if relative_to_origin {
+            Some(path)
+        } else {
+            Some(self.resolver.in_sysroot(&path))
+        }
+    }
+
+    /// Reads the file at `path` if it is a shared object for the machine
+    /// being resolved for
+    fn read_compatible(&self, path: &Path) -> Option<Vec<u8>> {
+        let mut file = fs::File::open(path).ok()?;
+        let mut header = [0; size_of::<Header64>()];
+        file.read_exact(&mut header).ok()?;
+
+        // 32-bit objects fail to parse, which rejects them as well
+        let parsed = Header64::parse(&header).ok()?;
+        if parsed.machine != self.machine || parsed.object_type != ObjectType::DYN {
+            return None;
+        }
+
+        let mut data = header.to_vec();
+        file.read_to_end(&mut data).ok()?;
+        Some(data)
+    }
+}
+
+/// A dependency tree built by [`DependencyResolver::resolve`]
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct DependencyTree {
+    /// The objects in load order, starting with the resolved file
+    pub objects: Vec<LoadedObject>,
+    /// Index of the object loaded from `PT_INTERP`, if it was found
+    pub interpreter: Option<usize>,
+}
+
+#[cfg(feature = "std")]
+impl DependencyTree {
+    /// Returns the resolved file itself
+    pub fn root(&self) -> &LoadedObject {
+        &self.objects[0]
+    }
+
+    /// Iterates over the libraries that could not be found, with the object
+    /// requesting each
+    pub fn missing(&self) -> impl Iterator<Item = (&LoadedObject, &Needed)> {
+        self.objects.iter().flat_map(|object| {
+            object
+                .needed
+                .iter()
+                .filter(|needed| needed.object.is_none())
+                .map(move |needed| (object, needed))
+        })
+    }
+}
+
+/// An object of a [`DependencyTree`]
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct LoadedObject {
+    /// The name the object was requested as, or the path of the resolved
+    /// file for the root
+    pub name: String,
+    /// The path the object was read from, including the sysroot
+    pub path: PathBuf,
+    /// The `DT_SONAME` of the object
+    pub soname: Option<String>,
+    /// Index of the object whose request loaded this one
+    pub loader: Option<usize>,
+    /// The `DT_RPATH` of the object
+    pub rpath: Option<String>,
+    /// The `DT_RUNPATH` of the object
+    pub runpath: Option<String>,
+    /// The `DT_NEEDED` entries of the object, in order
+    pub needed: Vec<Needed>,
+    nodeflib: bool,
+}
+
+#[cfg(feature = "std")]
+impl LoadedObject {
+    fn new(name: String, path: PathBuf, loader: Option<usize>, elf: &ElfFile<'_>) -> Self {
+        let dynamic = elf.dynamic().ok();
+        let strings = elf.dynamic_strings().ok();
+        let string = |offset: u64| {
+            let string = strings?.get(u32::try_from(offset).ok()?)?;
+            Some(String::from_utf8_lossy(string).into_owned())
+        };
+        let entry = |tag| dynamic.as_ref()?.find(tag).and_then(string);
+
+        let needed = dynamic
+            .iter()
+            .flat_map(|dynamic| dynamic.entries())
+            .filter(|entry| entry.d_tag == DynamicTag::NEEDED)
+            .filter_map(|entry| string(entry.d_val))
+            .map(|name| Needed { name, object: None })
+            .collect();
+        let flags_1 = dynamic
+            .as_ref()
+            .and_then(|dynamic| dynamic.find(DynamicTag::FLAGS_1))
+            .unwrap_or(0);
+
+        Self {
+            name,
+            path,
+            soname: entry(DynamicTag::SONAME),
+            loader,
+            rpath: entry(DynamicTag::RPATH),
+            runpath: entry(DynamicTag::RUNPATH),
+            needed,
+            nodeflib: flags_1 & DYNAMIC_FLAG_1_NODEFLIB != 0,
+        }
+    }
+
+    /// Returns true if a request for `name` is satisfied by this object
+    fn is(&self, name: &str) -> bool {
+        self.name == name || self.soname.as_deref() == Some(name)
+    }
+
+    /// Returns the directory `$ORIGIN` expands to
+    fn origin(&self) -> &Path {
+        self.path.parent().unwrap_or(Path::new("."))
+    }
+}
+
+/// A `DT_NEEDED` entry of a [`LoadedObject`]
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Needed {
+    /// The name of the needed library
+    pub name: String,
+    /// Index of the object satisfying the request, or `None` if the library
+    /// could not be found
+    pub object: Option<usize>,
+}
+
+#[cfg(feature = "std")]
+fn invalid_data(err: Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+/// Returns the usual expansion of `$PLATFORM` for a machine
+#[cfg(feature = "std")]
+fn platform_name(machine: &Machine) -> Option<&'static str> {
+    Some(match *machine {
+        Machine::X86_64 => "x86_64",
+        Machine::AARCH64 => "aarch64",
+        Machine::RISCV => "riscv64",
+        Machine::PPC64 => "powerpc64",
+        Machine::S390 => "s390x",
+        _ => return None,
+    })
+}
+
+/// Returns the directories the dynamic loader searches last for a machine
+#[cfg(feature = "std")]
+fn default_paths(machine: &Machine) -> Vec<PathBuf> {
+    let multiarch = match *machine {
+        Machine::X86_64 => Some("x86_64-linux-gnu"),
+        Machine::AARCH64 => Some("aarch64-linux-gnu"),
+        Machine::RISCV => Some("riscv64-linux-gnu"),
+        Machine::PPC64 => Some("powerpc64le-linux-gnu"),
+        Machine::S390 => Some("s390x-linux-gnu"),
+        _ => None,
+    };
+
+    let mut paths = Vec::new();
+    if let Some(multiarch) = multiarch {
+        paths.push(Path::new("/lib").join(multiarch));
+        paths.push(Path::new("/usr/lib").join(multiarch));
+    }
+    paths.extend(["/lib64", "/usr/lib64", "/lib", "/usr/lib"].map(PathBuf::from));
+    paths
+}
@@ -0,0 +1,354 @@
+use zerocopy::{FromBytes, Immutable, IntoBytes};
+
+use crate::elf_enum;
+use crate::endian::impl_swap_bytes;
+use crate::error::{Error, Result};
+use crate::hash::{GnuHashTable, HashTable, SymbolHash};
+use crate::string_table::StringTable;
+use crate::symbol::{Symbol64, SymbolTable};
+use crate::table::Table;
+use crate::version::{VERSION_FLAG_BASE, VersionDefinitionIter, VersionNeedIter, VersionTable};
+
+/// 64-bit dynamic table entry (Elf64_Dyn)
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes, IntoBytes, Immutable)]
+#[doc(alias = "Elf64_Dyn")]
+pub struct Dynamic64 {
+    /// Entry type
+    pub d_tag: DynamicTag,
+    /// Integer value or address, depending on `d_tag`
+    #[doc(alias = "d_ptr")]
+    pub d_val: u64,
+}
+
+impl_swap_bytes!(Dynamic64 { d_tag, d_val });
+
+/// A view over a dynamic table, stopping at the first `DT_NULL` entry
+pub type DynamicTable<'a> = Table<'a, Dynamic64>;
+
+impl DynamicTable<'_> {
+    /// Iterates over entries up to, but not including, the terminating `DT_NULL`
+    pub fn entries(&self) -> impl Iterator<Item = Dynamic64> + '_ {
+        self.iter()
+            .take_while(|entry| entry.d_tag != DynamicTag::NULL)
+    }
+
+    /// Returns the value of the first entry with the given tag
+    pub fn find(&self, tag: DynamicTag) -> Option<u64> {
+        self.entries()
+            .find(|entry| entry.d_tag == tag)
+            .map(|entry| entry.d_val)
+    }
+}
+
+/// `DT_FLAGS` flag marking an object that may use `$ORIGIN`
+#[doc(alias = "DF_ORIGIN")]
+pub const DYNAMIC_FLAG_ORIGIN: u64 = 0x1;
+/// `DT_FLAGS` flag starting symbol resolution with the object itself
+#[doc(alias = "DF_SYMBOLIC")]
+pub const DYNAMIC_FLAG_SYMBOLIC: u64 = 0x2;
+/// `DT_FLAGS` flag marking relocations against non-writable segments
+#[doc(alias = "DF_TEXTREL")]
+pub const DYNAMIC_FLAG_TEXTREL: u64 = 0x4;
+/// `DT_FLAGS` flag requiring all relocations to be processed at load time
+#[doc(alias = "DF_BIND_NOW")]
+pub const DYNAMIC_FLAG_BIND_NOW: u64 = 0x8;
+/// `DT_FLAGS` flag marking an object that uses the static TLS model
+#[doc(alias = "DF_STATIC_TLS")]
+pub const DYNAMIC_FLAG_STATIC_TLS: u64 = 0x10;
+
+/// `DT_FLAGS_1` flag requiring all relocations to be processed at load time
+#[doc(alias = "DF_1_NOW")]
+pub const DYNAMIC_FLAG_1_NOW: u64 = 0x1;
+/// `DT_FLAGS_1` flag making the symbols of the object globally available
+#[doc(alias = "DF_1_GLOBAL")]
+pub const DYNAMIC_FLAG_1_GLOBAL: u64 = 0x2;
+/// `DT_FLAGS_1` flag marking a member of a group
+#[doc(alias = "DF_1_GROUP")]
+pub const DYNAMIC_FLAG_1_GROUP: u64 = 0x4;
+/// `DT_FLAGS_1` flag preventing the object from being unloaded
+#[doc(alias = "DF_1_NODELETE")]
+pub const DYNAMIC_FLAG_1_NODELETE: u64 = 0x8;
+/// `DT_FLAGS_1` flag running the initializers of the object first
+#[doc(alias = "DF_1_INITFIRST")]
+pub const DYNAMIC_FLAG_1_INITFIRST: u64 = 0x20;
+/// `DT_FLAGS_1` flag preventing the object from being opened with `dlopen`
+#[doc(alias = "DF_1_NOOPEN")]
+pub const DYNAMIC_FLAG_1_NOOPEN: u64 = 0x40;
+/// `DT_FLAGS_1` flag marking an object that may use `$ORIGIN`
+#[doc(alias = "DF_1_ORIGIN")]
+pub const DYNAMIC_FLAG_1_ORIGIN: u64 = 0x80;
+/// `DT_FLAGS_1` flag letting the symbols of the object interpose others
+#[doc(alias = "DF_1_INTERPOSE")]
+pub const DYNAMIC_FLAG_1_INTERPOSE: u64 = 0x400;
+/// `DT_FLAGS_1` flag ignoring the default library search path
+#[doc(alias = "DF_1_NODEFLIB")]
+pub const DYNAMIC_FLAG_1_NODEFLIB: u64 = 0x800;
+/// `DT_FLAGS_1` flag marking a position-independent executable
+#[doc(alias = "DF_1_PIE")]
+pub const DYNAMIC_FLAG_1_PIE: u64 = 0x8000000;
+
+elf_enum! {
+    pub struct DynamicTag(i64) {
+        /// Marks the end of the dynamic table
+        NULL = 0, "NULL",
+        /// String table offset of a needed library name
+        NEEDED = 1, "NEEDED",
+        /// Size in bytes of the PLT relocations
+        PLTRELSZ = 2, "PLTRELSZ",
+        /// Address of the PLT and/or GOT
+        PLTGOT = 3, "PLTGOT",
+        /// Address of the SysV symbol hash table
+        HASH = 4, "HASH",
+        /// Address of the dynamic string table
+        STRTAB = 5, "STRTAB",
+        /// Address of the dynamic symbol table
+        SYMTAB = 6, "SYMTAB",
+        /// Address of the Rela relocation table
+        RELA = 7, "RELA",
+        /// Size in bytes of the Rela relocation table
+        RELASZ = 8, "RELASZ",
+        /// Size in bytes of a Rela relocation entry
+        RELAENT = 9, "RELAENT",
+        /// Size in bytes of the dynamic string table
+        STRSZ = 10, "STRSZ",
+        /// Size in bytes of a symbol table entry
+        SYMENT = 11, "SYMENT",
+        /// Address of the initialization function
+        INIT = 12, "INIT",
+        /// Address of the termination function
+        FINI = 13, "FINI",
+        /// String table offset of this object's name
+        SONAME = 14, "SONAME",
+        /// String table offset of the library search path (deprecated)
+        RPATH = 15, "RPATH",
+        /// Start symbol search with this object
+        SYMBOLIC = 16, "SYMBOLIC",
+        /// Address of the Rel relocation table
+        REL = 17, "REL",
+        /// Size in bytes of the Rel relocation table
+        RELSZ = 18, "RELSZ",
+        /// Size in bytes of a Rel relocation entry
+        RELENT = 19, "RELENT",
+        /// Type of relocation used by the PLT
+        PLTREL = 20, "PLTREL",
+        /// Reserved for debugger use
+        DEBUG = 21, "DEBUG",
+        /// Relocations may modify a non-writable segment
+        TEXTREL = 22, "TEXTREL",
+        /// Address of the PLT relocations
+        JMPREL = 23, "JMPREL",
+        /// Process all relocations before transferring control
+        BIND_NOW = 24, "BIND_NOW",
+        /// Address of the array of initialization functions
+        INIT_ARRAY = 25, "INIT_ARRAY",
+        /// Address of the array of termination functions
+        FINI_ARRAY = 26, "FINI_ARRAY",
+        /// Size in bytes of the initialization function array
+        INIT_ARRAYSZ = 27, "INIT_ARRAYSZ",
+        /// Size in bytes of the termination function array
+        FINI_ARRAYSZ = 28, "FINI_ARRAYSZ",
+        /// String table offset of the library search path
+        RUNPATH = 29, "RUNPATH",
+        /// Flags for the object being loaded
+        FLAGS = 30, "FLAGS",
+        /// Address of the array of pre-initialization functions
+        PREINIT_ARRAY = 32, "PREINIT_ARRAY",
+        /// Size in bytes of the pre-initialization function array
+        PREINIT_ARRAYSZ = 33, "PREINIT_ARRAYSZ",
+        /// Address of the SHT_SYMTAB_SHNDX section
+        SYMTAB_SHNDX = 34, "SYMTAB_SHNDX",
+        /// Size in bytes of the Relr relocation table
+        RELRSZ = 35, "RELRSZ",
+        /// Address of the Relr relocation table
+        RELR = 36, "RELR",
+        /// Size in bytes of a Relr relocation entry
+        RELRENT = 37, "RELRENT",
+        /// OS-specific range start
+        LOOS = 0x6000000d, "LOOS",
+        /// OS-specific range end
+        HIOS = 0x6ffff000, "HIOS",
+        /// Address of the GNU symbol hash table
+        GNU_HASH = 0x6ffffef5, "GNU_HASH",
+        /// Address of the symbol version table
+        VERSYM = 0x6ffffff0, "VERSYM",
+        /// Number of relative Rela relocations
+        RELACOUNT = 0x6ffffff9, "RELACOUNT",
+        /// Number of relative Rel relocations
+        RELCOUNT = 0x6ffffffa, "RELCOUNT",
+        /// State flags
+        FLAGS_1 = 0x6ffffffb, "FLAGS_1",
+        /// Address of the version definition table
+        VERDEF = 0x6ffffffc, "VERDEF",
+        /// Number of version definitions
+        VERDEFNUM = 0x6ffffffd, "VERDEFNUM",
+        /// Address of the version dependency table
+        VERNEED = 0x6ffffffe, "VERNEED",
+        /// Number of version dependencies
+        VERNEEDNUM = 0x6fffffff, "VERNEEDNUM",
+        /// Processor-specific range start
+        LOPROC = 0x70000000, "LOPROC",
+        /// Processor-specific range end
+        HIPROC = 0x7fffffff, "HIPROC",
+    }
+}
+
+/// The dynamic symbol table of an object together with the tables needed to
+/// look symbols up by name and version
+///
+/// All of these are located through the dynamic table rather than through
+/// section headers, so they are available for images that are only mapped
+/// through their program headers.
+#[derive(Debug, Clone)]
+pub struct DynamicSymbols<'a> {
+    symbols: SymbolTable<'a>,
+    strings: StringTable<'a>,
+    hash: SymbolHash<'a>,
+    versions: Option<VersionTable<'a>>,
+    definitions: Option<(&'a [u8], usize)>,
+    needs: Option<(&'a [u8], usize)>,
+}
+
+impl<'a> DynamicSymbols<'a> {
+    /// Locates the dynamic symbol tables referenced by `dynamic`
+    ///
+    /// `read` maps a virtual address to the bytes stored there. It is given
+    /// the number of bytes needed, or `None` when the size is not known in
+    /// advance and everything up to the end of the containing segment should
+    /// be returned.
+    pub fn parse(
+        dynamic: &DynamicTable<'_>,
+        mut read: impl FnMut(u64, Option<u64>) -> Result<&'a [u8]>,
+    ) -> Result<Self> {
+        let require = |tag: DynamicTag| {
+            dynamic
+                .find(tag.clone())
+                .ok_or(Error::MissingDynamicEntry(tag))
+        };
+
+        let strings = StringTable::new(read(
+            require(DynamicTag::STRTAB)?,
+            Some(require(DynamicTag::STRSZ)?),
+        )?);
+
+        let hash = if let Some(address) = dynamic.find(DynamicTag::GNU_HASH) {
+            SymbolHash::Gnu(GnuHashTable::parse(read(address, None)?)?)
+        } else if let Some(address) = dynamic.find(DynamicTag::HASH) {
+            SymbolHash::Sysv(HashTable::parse(read(address, None)?)?)
+        } else {
+            return Err(Error::MissingDynamicEntry(DynamicTag::HASH));
+        };
+
+        let symbol_count = hash.symbol_count() as u64;
+        let symbol_size = dynamic
+            .find(DynamicTag::SYMENT)
+            .unwrap_or(size_of::<Symbol64>() as u64);
+        let symbols_size = symbol_count
+            .checked_mul(symbol_size)
+            .ok_or(Error::InvalidData("invalid dynamic symbol table size"))?;
+        let symbols = SymbolTable::with_entry_size(
+            read(require(DynamicTag::SYMTAB)?, Some(symbols_size))?,
+            symbol_size as usize,
+        )?;
+
+        let versions = dynamic
+            .find(DynamicTag::VERSYM)
+            .map(|address| read(address, Some(symbol_count * 2)).map(VersionTable::new))
+            .transpose()?;
+
+        let definitions = match (
+            dynamic.find(DynamicTag::VERDEF),
+            dynamic.find(DynamicTag::VERDEFNUM),
+        ) {
+            (Some(address), Some(count)) => Some((read(address, None)?, count as usize)),
+            _ => None,
+        };
+
+        let needs = match (
+            dynamic.find(DynamicTag::VERNEED),
+            dynamic.find(DynamicTag::VERNEEDNUM),
+        ) {
+            (Some(address), Some(count)) => Some((read(address, None)?, count as usize)),
+            _ => None,
+        };
+
+        Ok(Self {
+            symbols,
+            strings,
+            hash,
+            versions,
+            definitions,
+            needs,
+        })
+    }
+
+    pub fn symbols(&self) -> &SymbolTable<'a> {
+        &self.symbols
+    }
+
+    pub fn strings(&self) -> &StringTable<'a> {
+        &self.strings
+    }
+
+    pub fn hash(&self) -> &SymbolHash<'a> {
+        &self.hash
+    }
+
+    /// Returns the symbol version table, if the object has one
+    pub fn versions(&self) -> Option<&VersionTable<'a>> {
+        self.versions.as_ref()
+    }
+
+    /// Returns the version definitions, if the object has any
+    pub fn version_definitions(&self) -> Option<VersionDefinitionIter<'a>> {
+        self.definitions
+            .map(|(data, count)| VersionDefinitionIter::new(data, count, self.strings))
+    }
+
+    /// Returns the versions required from other objects, if the object
+    /// requires any
+    pub fn version_needs(&self) -> Option<VersionNeedIter<'a>> {
+        self.needs
+            .map(|(data, count)| VersionNeedIter::new(data, count, self.strings))
+    }
+
+    /// Returns the name of a symbol
+    pub fn symbol_name(&self, symbol: &Symbol64) -> Option<&'a [u8]> {
+        self.strings.get(symbol.st_name)
+    }
+
+    /// Looks up the index of the first symbol with the given name that is
+    /// accepted by `accept`
+    pub fn find_with(
+        &self,
+        name: &[u8],
+        accept: impl FnMut(usize, &Symbol64) -> bool,
+    ) -> Option<usize> {
+        self.hash
+            .find_with(name, &self.symbols, &self.strings, accept)
+    }
+
+    /// Returns the name of the version defined for the symbol at `index`
+    ///
+    /// Returns `None` for unversioned symbols and for symbols whose version
+    /// is required from another object rather than defined by this one.
+    pub fn version_name(&self, index: usize) -> Option<&'a [u8]> {
+        let version = self.versions.as_ref()?.get(index)?;
+
+        self.version_definitions()?
+            .filter(|definition| definition.definition.vd_flags & VERSION_FLAG_BASE == 0)
+            .find(|definition| definition.definition.vd_ndx & 0x7fff == version.index())
+            .and_then(|definition| definition.name)
+    }
+
+    /// Returns true if the symbol at `index` is defined with the given version
+    ///
+    /// Objects without version information match any version.
+    pub fn has_version(&self, index: usize, version: &[u8]) -> bool {
+        if self.versions.is_none() || self.definitions.is_none() {
+            return true;
+        }
+
+        self.version_name(index) == Some(version)
+    }
+}
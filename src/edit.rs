@@ -0,0 +1,863 @@
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+use crate::dynamic::{Dynamic64, DynamicTag};
+use crate::endian;
+use crate::error::{Error, Result};
+use crate::file::ElfFile;
+use crate::header::{Encoding, Header64};
+use crate::program_header::{ProgramHeader64, ProgramType, SEGMENT_FLAG_READ, SEGMENT_FLAG_WRITE};
+use crate::section_header::{SectionHeader64, SectionType};
+use crate::version::{VersionIndex, VersionNeed64, VersionNeedAux64};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum EntryValue {
+    Value(u64),
+    /// A string stored in the dynamic string table
+    String(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    tag: DynamicTag,
+    value: EntryValue,
+}
+
+/// Editor for the dynamic linking information of an executable or shared
+/// object, in the spirit of `patchelf`
+///
+/// Changes are recorded on the editor and applied by [`ElfEditor::write`],
+/// which produces a new file. Whatever fits is rewritten in place. When the
+/// interpreter path, the dynamic string table or the dynamic table outgrow
+/// their current space, they are moved to a new `PT_LOAD` segment appended to
+/// the end of the file, together with a copy of the program header table
+/// that describes it. The existing strings are kept at the start of the
+/// relocated string table, so offsets held by other tables stay valid.
+///
+/// The version requirements (`DT_VERNEED`) follow the `DT_NEEDED` edits: a
+/// requirement on a replaced dependency is pointed at the new name, and one
+/// on a removed dependency is unlinked from the chain, its symbols becoming
+/// unversioned, as the dynamic linker rejects requirements on objects that
+/// are not loaded.
+#[derive(Debug, Clone)]
+pub struct ElfEditor<'a> {
+    file: ElfFile<'a>,
+    interpreter: Option<Vec<u8>>,
+    entries: Vec<Entry>,
+    /// Replaced `DT_NEEDED` dependencies, in the order of the calls
+    replaced: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl<'a> ElfEditor<'a> {
+    pub fn new(file: &ElfFile<'a>) -> Result<Self> {
+        let mut entries = Vec::new();
+
+        if file.find_segment(ProgramType::DYNAMIC).is_some() {
+            let strings = file.dynamic_strings()?;
+            for entry in file.dynamic()?.entries() {
+                let value = if is_string_tag(&entry.d_tag) {
+                    let string = strings
+                        .get(entry.d_val as u32)
+                        .ok_or(Error::InvalidData("dynamic entry string out of bounds"))?;
+                    EntryValue::String(string.to_vec())
+                } else {
+                    EntryValue::Value(entry.d_val)
+                };
+                entries.push(Entry {
+                    tag: entry.d_tag,
+                    value,
+                });
+            }
+        }
+
+        Ok(Self {
+            file: file.clone(),
+            interpreter: None,
+            entries,
+            replaced: Vec::new(),
+        })
+    }
+
+    /// Returns the program interpreter path, including pending changes
+    pub fn interpreter(&self) -> Option<&[u8]> {
+        self.interpreter
+            .as_deref()
+            .or_else(|| self.file.interpreter())
+    }
+
+    /// Changes the program interpreter path stored in `PT_INTERP`
+    pub fn set_interpreter(&mut self, path: &[u8]) -> &mut Self {
+        self.interpreter = Some(path.to_vec());
+        self
+    }
+
+    fn strings(&self, tag: DynamicTag) -> impl Iterator<Item = &[u8]> + '_ {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.tag == tag)
+            .filter_map(|entry| match &entry.value {
+                EntryValue::String(string) => Some(string.as_slice()),
+                EntryValue::Value(_) => None,
+            })
+    }
+
+    /// Removes every entry with the given tag and inserts one holding
+    /// `string` where the first one was, or at the start of the table
+    fn set_string(&mut self, tag: DynamicTag, string: &[u8]) {
+        let position = self
+            .entries
+            .iter()
+            .position(|entry| entry.tag == tag)
+            .unwrap_or(0);
+        self.entries.retain(|entry| entry.tag != tag);
+        self.entries.insert(
+            position.min(self.entries.len()),
+            Entry {
+                tag,
+                value: EntryValue::String(string.to_vec()),
+            },
+        );
+    }
+
+    /// Iterates over the `DT_NEEDED` dependencies, in load order
+    pub fn needed(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        self.strings(DynamicTag::NEEDED)
+    }
+
+    /// Adds a `DT_NEEDED` dependency after the existing ones, unless it is
+    /// already present
+    pub fn add_needed(&mut self, name: &[u8]) -> &mut Self {
+        if self.needed().any(|needed| needed == name) {
+            return self;
+        }
+
+        let position = self
+            .entries
+            .iter()
+            .rposition(|entry| entry.tag == DynamicTag::NEEDED)
+            .map_or(0, |index| index + 1);
+        self.entries.insert(
+            position,
+            Entry {
+                tag: DynamicTag::NEEDED,
+                value: EntryValue::String(name.to_vec()),
+            },
+        );
+        self
+    }
+
+    /// Removes the `DT_NEEDED` dependency `name` and the versions required
+    /// from it
+    pub fn remove_needed(&mut self, name: &[u8]) -> &mut Self {
+        let removed = Entry {
+            tag: DynamicTag::NEEDED,
+            value: EntryValue::String(name.to_vec()),
+        };
+        self.entries.retain(|entry| *entry != removed);
+        self
+    }
+
+    /// Replaces the `DT_NEEDED` dependency `old` by `new`, keeping its
+    /// position in the load order and the versions required from it
+    pub fn replace_needed(&mut self, old: &[u8], new: &[u8]) -> &mut Self {
+        for entry in &mut self.entries {
+            if entry.tag == DynamicTag::NEEDED && entry.value == EntryValue::String(old.to_vec()) {
+                entry.value = EntryValue::String(new.to_vec());
+            }
+        }
+        self.replaced.push((old.to_vec(), new.to_vec()));
+        self
+    }
+
+    pub fn soname(&self) -> Option<&[u8]> {
+        self.strings(DynamicTag::SONAME).next()
+    }
+
+    pub fn set_soname(&mut self, name: &[u8]) -> &mut Self {
+        self.set_string(DynamicTag::SONAME, name);
+        self
+    }
+
+    pub fn remove_soname(&mut self) -> &mut Self {
+        self.entries.retain(|entry| entry.tag != DynamicTag::SONAME);
+        self
+    }
+
+    /// Returns the library search path from `DT_RUNPATH`, or from the
+    /// deprecated `DT_RPATH` when there is no `DT_RUNPATH`
+    pub fn runpath(&self) -> Option<&[u8]> {
+        self.strings(DynamicTag::RUNPATH)
+            .next()
+            .or_else(|| self.strings(DynamicTag::RPATH).next())
+    }
+
+    /// Sets `DT_RUNPATH`, removing any `DT_RPATH`
+    pub fn set_runpath(&mut self, path: &[u8]) -> &mut Self {
+        self.entries.retain(|entry| entry.tag != DynamicTag::RPATH);
+        self.set_string(DynamicTag::RUNPATH, path);
+        self
+    }
+
+    /// Sets the deprecated `DT_RPATH`, which unlike `DT_RUNPATH` also applies
+    /// to the dependencies of the object and takes precedence over
+    /// `LD_LIBRARY_PATH`, removing any `DT_RUNPATH`
+    pub fn set_rpath(&mut self, path: &[u8]) -> &mut Self {
+        self.entries
+            .retain(|entry| entry.tag != DynamicTag::RUNPATH);
+        self.set_string(DynamicTag::RPATH, path);
+        self
+    }
+
+    /// Removes both `DT_RUNPATH` and `DT_RPATH`
+    pub fn remove_runpath(&mut self) -> &mut Self {
+        self.entries
+            .retain(|entry| entry.tag != DynamicTag::RUNPATH && entry.tag != DynamicTag::RPATH);
+        self
+    }
+
+    /// Writes the edited file
+    pub fn write(&self) -> Result<Vec<u8>> {
+        let file = &self.file;
+        let encoding = Encoding::native();
+        let mut out = file.data().to_vec();
+        let mut header = file.header().clone();
+        let mut program_headers: Vec<ProgramHeader64> = file.program_headers().collect();
+        let mut section_headers: Vec<SectionHeader64> = file.section_headers().collect();
+
+        let dynamic_index = program_headers
+            .iter()
+            .position(|phdr| phdr.p_type == ProgramType::DYNAMIC);
+        let interp_index = program_headers
+            .iter()
+            .position(|phdr| phdr.p_type == ProgramType::INTERP);
+
+        if dynamic_index.is_none() && !self.entries.is_empty() {
+            return Err(Error::MissingSegment(ProgramType::DYNAMIC));
+        }
+        if interp_index.is_none() && self.interpreter.is_some() {
+            return Err(Error::MissingSegment(ProgramType::INTERP));
+        }
+
+        let mut entries = self.entries.clone();
+        let renamed_needs =
+            self.update_version_needs(&mut entries, &mut out, &mut section_headers)?;
+
+        // Strings already present in the table are reused, others appended
+        let old_strings = match dynamic_index {
+            Some(_) => file.dynamic_strings()?.as_bytes(),
+            None => &[],
+        };
+        let mut strings = old_strings.to_vec();
+        let mut values: Vec<u64> = Vec::with_capacity(entries.len());
+        for entry in &entries {
+            values.push(match &entry.value {
+                EntryValue::Value(value) => *value,
+                EntryValue::String(string) => {
+                    find_string(old_strings, string).unwrap_or_else(|| {
+                        let offset = strings.len() as u64;
+                        strings.extend_from_slice(string);
+                        strings.push(0);
+                        offset
+                    })
+                }
+            });
+        }
+
+        let dynamic_size = ((entries.len() + 1) * size_of::<Dynamic64>()) as u64;
+
+        // Requirements on replaced dependencies name them by their new string
+        for (offset, mut need, name) in renamed_needs {
+            let name = EntryValue::String(name);
+            let (_, &value) = entries
+                .iter()
+                .zip(&values)
+                .find(|(entry, _)| entry.tag == DynamicTag::NEEDED && entry.value == name)
+                .ok_or(Error::InvalidData(
+                    "version requirement on a missing dependency",
+                ))?;
+            need.vn_file = value as u32;
+            endian::write_at(&mut out, offset, &need, &encoding)?;
+        }
+        let strings_moved = strings.len() > old_strings.len();
+        let dynamic_moved =
+            dynamic_index.is_some_and(|index| dynamic_size > program_headers[index].p_filesz);
+        let interp_moved = match (&self.interpreter, interp_index) {
+            (Some(path), Some(index)) => path.len() as u64 + 1 > program_headers[index].p_filesz,
+            _ => false,
+        };
+
+        let old_strings_address = self.value(DynamicTag::STRTAB);
+        let old_dynamic = dynamic_index.map(|index| program_headers[index].clone());
+        let old_interp = interp_index.map(|index| program_headers[index].clone());
+
+        let mut strings_address = old_strings_address;
+        let mut dynamic_location = old_dynamic
+            .as_ref()
+            .map(|phdr| (phdr.p_offset, phdr.p_vaddr));
+        let mut interp_location = old_interp
+            .as_ref()
+            .map(|phdr| (phdr.p_offset, phdr.p_vaddr));
+        let mut strings_offset = None;
+
+        if strings_moved || dynamic_moved || interp_moved {
+            // Lay out the new segment, starting with the program header table
+            let count = program_headers.len() + 1;
+            let mut size: u64 = 0;
+            let mut place = |length: u64, align: u64| {
+                let at = size.next_multiple_of(align);
+                size = at + length;
+                at
+            };
+            let phdr_at = place((count * size_of::<ProgramHeader64>()) as u64, 8);
+            let interp_at = interp_moved.then(|| {
+                place(
+                    self.interpreter
+                        .as_ref()
+                        .map_or(0, |path| path.len() as u64 + 1),
+                    1,
+                )
+            });
+            let strings_at = strings_moved.then(|| place(strings.len() as u64, 1));
+            let dynamic_at = dynamic_moved.then(|| place(dynamic_size, 8));
+
+            let (offset, vaddr, align) = self.new_segment_location(&program_headers, out.len())?;
+            let end = offset
+                .checked_add(size)
+                .and_then(|end| usize::try_from(end).ok())
+                .ok_or(Error::InvalidData("new segment out of range"))?;
+            out.resize(end, 0);
+
+            header.program_header_offset = offset + phdr_at;
+            header.program_header_count = count as u16;
+            if let Some(at) = interp_at {
+                interp_location = Some((offset + at, vaddr + at));
+            }
+            if let Some(at) = strings_at {
+                strings_offset = Some(offset + at);
+                strings_address = Some(vaddr + at);
+            }
+            if let Some(at) = dynamic_at {
+                dynamic_location = Some((offset + at, vaddr + at));
+            }
+
+            let mut flags = SEGMENT_FLAG_READ;
+            if dynamic_moved {
+                // The dynamic linker writes to the dynamic table
+                flags |= SEGMENT_FLAG_WRITE;
+            }
+            let load = ProgramHeader64 {
+                p_type: ProgramType::LOAD,
+                p_flags: flags,
+                p_offset: offset,
+                p_vaddr: vaddr,
+                p_paddr: vaddr,
+                p_filesz: size,
+                p_memsz: size,
+                p_align: align,
+            };
+            let position = program_headers
+                .iter()
+                .rposition(|phdr| phdr.p_type == ProgramType::LOAD)
+                .map_or(program_headers.len(), |index| index + 1);
+            program_headers.insert(position, load);
+
+            for phdr in &mut program_headers {
+                if phdr.p_type == ProgramType::PHDR {
+                    phdr.p_offset = offset + phdr_at;
+                    phdr.p_vaddr = vaddr + phdr_at;
+                    phdr.p_paddr = vaddr + phdr_at;
+                    phdr.p_filesz = (count * size_of::<ProgramHeader64>()) as u64;
+                    phdr.p_memsz = phdr.p_filesz;
+                }
+            }
+        }
+
+        if let Some(offset) = strings_offset {
+            file_range(&mut out, offset, strings.len() as u64)?.copy_from_slice(&strings);
+        }
+
+        if let (Some(path), Some((offset, vaddr)), Some(old)) =
+            (&self.interpreter, interp_location, &old_interp)
+        {
+            if !interp_moved {
+                file_range(&mut out, old.p_offset, old.p_filesz)?.fill(0);
+            }
+            let size = path.len() as u64 + 1;
+            let location = file_range(&mut out, offset, size)?;
+            location[..path.len()].copy_from_slice(path);
+            location[path.len()] = 0;
+
+            for phdr in &mut program_headers {
+                if phdr.p_type == ProgramType::INTERP {
+                    set_location(phdr, offset, vaddr, size);
+                }
+            }
+            for shdr in &mut section_headers {
+                if shdr.sh_type == SectionType::PROGBITS && shdr.sh_offset == old.p_offset {
+                    shdr.sh_offset = offset;
+                    shdr.sh_addr = vaddr;
+                    shdr.sh_size = size;
+                }
+            }
+        }
+
+        if let (Some((offset, vaddr)), Some(old)) = (dynamic_location, &old_dynamic) {
+            let mut table = Vec::with_capacity(entries.len() + 1);
+            for (entry, &value) in entries.iter().zip(&values) {
+                let d_val = match entry.tag {
+                    DynamicTag::STRTAB => strings_address.unwrap_or(value),
+                    DynamicTag::STRSZ => strings.len() as u64,
+                    _ => value,
+                };
+                table.push(Dynamic64 {
+                    d_tag: entry.tag.clone(),
+                    d_val,
+                });
+            }
+            table.push(Dynamic64 {
+                d_tag: DynamicTag::NULL,
+                d_val: 0,
+            });
+
+            // Unused space in place is left as DT_NULL entries
+            let size = if dynamic_moved {
+                dynamic_size
+            } else {
+                old.p_filesz
+            };
+            file_range(&mut out, offset, size)?.fill(0);
+            for (index, entry) in table.iter().enumerate() {
+                let at = offset as usize + index * size_of::<Dynamic64>();
+                endian::write_at(&mut out, at, entry, &encoding)?;
+            }
+
+            if dynamic_moved {
+                for phdr in &mut program_headers {
+                    if phdr.p_type == ProgramType::DYNAMIC {
+                        set_location(phdr, offset, vaddr, dynamic_size);
+                    }
+                }
+                for shdr in &mut section_headers {
+                    if shdr.sh_type == SectionType::DYNAMIC {
+                        shdr.sh_offset = offset;
+                        shdr.sh_addr = vaddr;
+                        shdr.sh_size = dynamic_size;
+                    }
+                }
+            }
+        }
+
+        if let (Some(old_address), Some(offset), Some(address)) =
+            (old_strings_address, strings_offset, strings_address)
+        {
+            for shdr in &mut section_headers {
+                if shdr.sh_type == SectionType::STRTAB && shdr.sh_addr == old_address {
+                    shdr.sh_offset = offset;
+                    shdr.sh_addr = address;
+                    shdr.sh_size = strings.len() as u64;
+                }
+            }
+        }
+
+        out[..size_of::<Header64>()].copy_from_slice(&header.to_bytes());
+        for (index, phdr) in program_headers.iter().enumerate() {
+            let at = header.program_header_offset as usize + index * size_of::<ProgramHeader64>();
+            endian::write_at(&mut out, at, phdr, &encoding)?;
+        }
+        for (index, shdr) in section_headers.iter().enumerate() {
+            let at = header.section_header_offset as usize
+                + index * header.section_header_entry_size as usize;
+            endian::write_at(&mut out, at, shdr, &encoding)?;
+        }
+
+        Ok(out)
+    }
+
+    /// Returns the value of the first dynamic entry with the given tag
+    fn value(&self, tag: DynamicTag) -> Option<u64> {
+        self.entries
+            .iter()
+            .find(|entry| entry.tag == tag)
+            .and_then(|entry| match entry.value {
+                EntryValue::Value(value) => Some(value),
+                EntryValue::String(_) => None,
+            })
+    }
+
+    /// Updates the version requirements of dependencies removed or replaced
+    /// since the file was parsed
+    ///
+    /// Requirements on removed dependencies are unlinked from the chain in
+    /// `out`, and the symbols bound to their versions are made unversioned.
+    /// The dynamic entries describing the chain are updated in `entries`.
+    /// Returns the file offset, contents and new dependency name of each
+    /// requirement on a replaced dependency, whose `vn_file` can only be set
+    /// once the string offsets are known.
+    fn update_version_needs(
+        &self,
+        entries: &mut Vec<Entry>,
+        out: &mut [u8],
+        section_headers: &mut [SectionHeader64],
+    ) -> Result<Vec<(usize, VersionNeed64, Vec<u8>)>> {
+        let (Some(address), Some(count)) = (
+            self.value(DynamicTag::VERNEED),
+            self.value(DynamicTag::VERNEEDNUM),
+        ) else {
+            return Ok(Vec::new());
+        };
+        let file = &self.file;
+        let encoding = Encoding::native();
+        let strings = file.dynamic_strings()?;
+        let original: Vec<&[u8]> = file
+            .dynamic()?
+            .entries()
+            .filter(|entry| entry.d_tag == DynamicTag::NEEDED)
+            .filter_map(|entry| strings.get(entry.d_val as u32))
+            .collect();
+
+        let start = file
+            .address_to_offset(address)
+            .ok_or(Error::UnmappedAddress(address))? as usize;
+        let mut offset = start;
+        let mut kept = Vec::new();
+        let mut renamed = Vec::new();
+        let mut removed_versions = Vec::new();
+        for _ in 0..count {
+            let need: VersionNeed64 = endian::read_at(out, offset, &encoding)?;
+            let next = need.vn_next;
+            let name = strings.get(need.vn_file).unwrap_or_default();
+            let current = self.replaced.iter().fold(
+                name,
+                |name, (old, new)| if name == old { new } else { name },
+            );
+
+            if original.contains(&name) && !self.needed().any(|needed| needed == current) {
+                let mut aux_offset = offset.checked_add(need.vn_aux as usize);
+                for _ in 0..need.vn_cnt {
+                    let Some(at) = aux_offset else {
+                        break;
+                    };
+                    let aux: VersionNeedAux64 = endian::read_at(out, at, &encoding)?;
+                    removed_versions.push(aux.vna_other & 0x7fff);
+                    aux_offset = match aux.vna_next {
+                        0 => None,
+                        next => at.checked_add(next as usize),
+                    };
+                }
+            } else {
+                if current != name {
+                    renamed.push(kept.len());
+                }
+                kept.push((offset, need, current.to_vec()));
+            }
+
+            if next == 0 {
+                break;
+            }
+            offset = offset
+                .checked_add(next as usize)
+                .ok_or(Error::InvalidData("invalid version requirement offset"))?;
+        }
+
+        if !removed_versions.is_empty() {
+            // Link the remaining requirements, which keep their order
+            for index in 0..kept.len() {
+                let next = match kept.get(index + 1) {
+                    Some(&(next, ..)) => u32::try_from(next - kept[index].0)
+                        .map_err(|_| Error::InvalidData("invalid version requirement offset"))?,
+                    None => 0,
+                };
+                let (at, need, _) = &mut kept[index];
+                need.vn_next = next;
+                endian::write_at(out, *at, need, &encoding)?;
+            }
+
+            let skipped = kept
+                .first()
+                .map_or(0, |&(first, ..)| (first - start) as u64);
+            if kept.is_empty() {
+                // Without any version information left, the dynamic linker
+                // does not allocate the versions `DT_VERSYM` refers to
+                let versioned = self.value(DynamicTag::VERDEF).is_some();
+                entries.retain(|entry| {
+                    entry.tag != DynamicTag::VERNEED
+                        && entry.tag != DynamicTag::VERNEEDNUM
+                        && (versioned || entry.tag != DynamicTag::VERSYM)
+                });
+            } else {
+                set_value(entries, DynamicTag::VERNEED, address + skipped);
+                set_value(entries, DynamicTag::VERNEEDNUM, kept.len() as u64);
+            }
+            for shdr in section_headers.iter_mut() {
+                if shdr.sh_type == SectionType::GNU_VERNEED && shdr.sh_addr == address {
+                    shdr.sh_offset += skipped;
+                    shdr.sh_addr += skipped;
+                    shdr.sh_size = shdr.sh_size.saturating_sub(skipped);
+                    shdr.sh_info = kept.len() as u32;
+                }
+            }
+
+            if let Some(versions) = self.value(DynamicTag::VERSYM) {
+                let symbol_count = file.dynamic_symbols()?.symbols().len();
+                let versions =
+                    file.address_to_offset(versions)
+                        .ok_or(Error::UnmappedAddress(versions))? as usize;
+                for index in 0..symbol_count {
+                    let at = versions + index * size_of::<VersionIndex>();
+                    let version: VersionIndex = endian::read_at(out, at, &encoding)?;
+                    if removed_versions.contains(&version.index()) {
+                        endian::write_at(out, at, &VersionIndex::GLOBAL, &encoding)?;
+                    }
+                }
+            }
+        }
+
+        Ok(renamed
+            .into_iter()
+            .map(|index| kept[index].clone())
+            .collect())
+    }
+
+    /// Chooses the file offset, virtual address and alignment of a segment
+    /// appended to a file of `file_size` bytes
+    ///
+    /// The segment keeps the offset to address difference of the first
+    /// `PT_LOAD` segment, so that kernels deriving `AT_PHDR` from `e_phoff`
+    /// find the relocated program header table, and it starts past the end of
+    /// every other segment in memory.
+    fn new_segment_location(
+        &self,
+        program_headers: &[ProgramHeader64],
+        file_size: usize,
+    ) -> Result<(u64, u64, u64)> {
+        let loads = || {
+            program_headers
+                .iter()
+                .filter(|phdr| phdr.p_type == ProgramType::LOAD)
+        };
+        let first = loads()
+            .next()
+            .ok_or(Error::MissingSegment(ProgramType::LOAD))?;
+        let align = loads()
+            .map(|phdr| phdr.p_align)
+            .max()
+            .unwrap_or(0)
+            .max(0x1000);
+        let bias = first.p_vaddr.wrapping_sub(first.p_offset);
+        let mut memory_end = 0;
+        for phdr in loads() {
+            let end = phdr
+                .p_vaddr
+                .checked_add(phdr.p_memsz)
+                .and_then(|end| end.checked_next_multiple_of(align))
+                .ok_or(Error::InvalidData("segment end out of range"))?;
+            memory_end = memory_end.max(end);
+        }
+
+        let mut offset = (file_size as u64).next_multiple_of(align);
+        if bias.wrapping_add(offset) < memory_end {
+            offset = memory_end.wrapping_sub(bias);
+        }
+        Ok((offset, bias.wrapping_add(offset), align))
+    }
+}
+
+/// Returns true for dynamic tags whose value is an offset in the dynamic
+/// string table
+fn is_string_tag(tag: &DynamicTag) -> bool {
+    matches!(
+        *tag,
+        DynamicTag::NEEDED | DynamicTag::SONAME | DynamicTag::RPATH | DynamicTag::RUNPATH
+    )
+}
+
+/// Returns the offset of a NUL-terminated occurrence of `string`, which may
+/// be the tail of a longer string
+fn find_string(table: &[u8], string: &[u8]) -> Option<u64> {
+    (0..table.len())
+        .find(|&start| {
+            table[start..].starts_with(string) && table.get(start + string.len()) == Some(&0)
+        })
+        .map(|start| start as u64)
+}
+
+/// Returns the `size` bytes of `out` at `offset`
+fn file_range(out: &mut [u8], offset: u64, size: u64) -> Result<&mut [u8]> {
+    let out_of_bounds = Error::OutOfBounds { offset, size };
+    let start = usize::try_from(offset).map_err(|_| out_of_bounds.clone())?;
+    let end = offset
+        .checked_add(size)
+        .and_then(|end| usize::try_from(end).ok())
+        .ok_or(out_of_bounds.clone())?;
+    out.get_mut(start..end).ok_or(out_of_bounds)
+}
+
+/// Sets the value of the first entry with the given tag
+fn set_value(entries: &mut [Entry], tag: DynamicTag, value: u64) {
+    if let Some(entry) = entries.iter_mut().find(|entry| entry.tag == tag) {
+        entry.value = EntryValue::Value(value);
+    }
+}
+
+fn set_location(phdr: &mut ProgramHeader64, offset: u64, vaddr: u64, size: u64) {
+    phdr.p_offset = offset;
+    phdr.p_vaddr = vaddr;
+    phdr.p_paddr = vaddr;
+    phdr.p_filesz = size;
+    phdr.p_memsz = size;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    use crate::builder::{ElfBuilder, Section, Symbol};
+    use crate::header::{Machine, ObjectType};
+    use crate::section_header::SECTION_FLAG_ALLOC;
+    use crate::version::VersionNeedIter;
+
+    /// Builds a shared object needing `libfoo.so` and `libbaz.so`, with
+    /// `foo` bound to version 2 of the first and `baz` to version 3 of the
+    /// second
+    fn versioned_object() -> Vec<u8> {
+        let build = |versions: Vec<u8>, needs: Vec<u8>, addresses: (u64, u64)| {
+            let mut builder = ElfBuilder::new(ObjectType::DYN, Machine::X86_64);
+            builder.needed(b"libfoo.so").needed(b"libbaz.so");
+            builder.dynamic_symbol(Symbol::undefined(b"foo"));
+            builder.dynamic_symbol(Symbol::undefined(b"baz"));
+            builder.section(Section {
+                sh_addralign: 2,
+                sh_entsize: 2,
+                ..Section::new(
+                    b".gnu.version",
+                    SectionType::GNU_VERSYM,
+                    SECTION_FLAG_ALLOC,
+                    versions,
+                )
+            });
+            builder.section(Section {
+                sh_addralign: 8,
+                sh_info: 2,
+                ..Section::new(
+                    b".gnu.version_r",
+                    SectionType::GNU_VERNEED,
+                    SECTION_FLAG_ALLOC,
+                    needs,
+                )
+            });
+            builder
+                .dynamic(DynamicTag::VERSYM, addresses.0)
+                .dynamic(DynamicTag::VERNEED, addresses.1)
+                .dynamic(DynamicTag::VERNEEDNUM, 2);
+            builder.build().unwrap()
+        };
+
+        // The addresses and string offsets are only known once laid out,
+        // and stay the same with contents of the same size
+        let draft = build(vec![0; 6], vec![0; 64], (0, 0));
+        let elf = ElfFile::parse(&draft).unwrap();
+        let address = |name: &[u8]| elf.find_section(name).unwrap().1.sh_addr;
+        let addresses = (address(b".gnu.version"), address(b".gnu.version_r"));
+        let strings = elf.dynamic_strings().unwrap().as_bytes();
+        let string = |name: &[u8]| find_string(strings, name).unwrap() as u32;
+
+        let mut versions = Vec::new();
+        for index in [0u16, 2, 3] {
+            endian::write(&mut versions, &index, &Encoding::native());
+        }
+        let mut needs = Vec::new();
+        for (file, version, next) in [(b"libfoo.so", 2, 32), (b"libbaz.so", 3, 0)] {
+            let need = VersionNeed64 {
+                vn_version: 1,
+                vn_cnt: 1,
+                vn_file: string(file),
+                vn_aux: 16,
+                vn_next: next,
+            };
+            let aux = VersionNeedAux64 {
+                vna_hash: 0,
+                vna_flags: 0,
+                vna_other: version,
+                vna_name: 0,
+                vna_next: 0,
+            };
+            endian::write(&mut needs, &need, &Encoding::native());
+            endian::write(&mut needs, &aux, &Encoding::native());
+        }
+        build(versions, needs, addresses)
+    }
+
+    /// Returns the dependencies named by the version requirements and the
+    /// version indices of the dynamic symbols
+    fn versions(data: &[u8]) -> (Vec<Vec<u8>>, Vec<u16>) {
+        let elf = ElfFile::parse(data).unwrap();
+        let symbols = elf.dynamic_symbols().unwrap();
+        let files = symbols
+            .version_needs()
+            .map(|needs| needs.map(|need| need.file.unwrap().to_vec()).collect())
+            .unwrap_or_default();
+        let indices = symbols
+            .versions()
+            .map(|table| table.iter().map(|version| version.index()).collect())
+            .unwrap_or_default();
+        (files, indices)
+    }
+
+    #[test]
+    fn replace_needed_renames_version_requirement() {
+        let data = versioned_object();
+        let elf = ElfFile::parse(&data).unwrap();
+        let mut editor = ElfEditor::new(&elf).unwrap();
+        editor.replace_needed(b"libfoo.so", b"libbar.so");
+        let out = editor.write().unwrap();
+
+        let (files, indices) = versions(&out);
+        assert_eq!(files, [b"libbar.so".to_vec(), b"libbaz.so".to_vec()]);
+        assert_eq!(indices, [0, 2, 3]);
+    }
+
+    #[test]
+    fn remove_needed_drops_version_requirement() {
+        let data = versioned_object();
+        let elf = ElfFile::parse(&data).unwrap();
+
+        for (removed, kept, indices) in [
+            (b"libfoo.so", b"libbaz.so", [0, 1, 3]),
+            (b"libbaz.so", b"libfoo.so", [0, 2, 1]),
+        ] {
+            let mut editor = ElfEditor::new(&elf).unwrap();
+            editor.remove_needed(removed);
+            let out = editor.write().unwrap();
+
+            assert_eq!(versions(&out), (vec![kept.to_vec()], indices.to_vec()));
+            let elf = ElfFile::parse(&out).unwrap();
+            let dynamic = elf.dynamic().unwrap();
+            assert_eq!(dynamic.find(DynamicTag::VERNEEDNUM), Some(1));
+            let (_, shdr) = elf.find_section(b".gnu.version_r").unwrap();
+            assert_eq!(shdr.sh_addr, dynamic.find(DynamicTag::VERNEED).unwrap());
+            assert_eq!(shdr.sh_info, 1);
+            let needs = VersionNeedIter::new(
+                elf.section_data(&shdr).unwrap(),
+                usize::MAX,
+                elf.dynamic_strings().unwrap(),
+            );
+            assert_eq!(needs.count(), 1);
+        }
+
+        let mut editor = ElfEditor::new(&elf).unwrap();
+        editor
+            .remove_needed(b"libfoo.so")
+            .remove_needed(b"libbaz.so");
+        let out = editor.write().unwrap();
+        let elf = ElfFile::parse(&out).unwrap();
+        let dynamic = elf.dynamic().unwrap();
+        for tag in [
+            DynamicTag::VERNEED,
+            DynamicTag::VERNEEDNUM,
+            DynamicTag::VERSYM,
+        ] {
+            assert_eq!(dynamic.find(tag), None);
+        }
+    }
+}
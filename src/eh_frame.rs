@@ -0,0 +1,193 @@
+use crate::error::{Error, Result};
+
+const DW_EH_PE_OMIT: u8 = 0xff;
+const DW_EH_PE_ABSPTR: u8 = 0x00;
+const DW_EH_PE_ULEB128: u8 = 0x01;
+const DW_EH_PE_UDATA2: u8 = 0x02;
+const DW_EH_PE_UDATA4: u8 = 0x03;
+const DW_EH_PE_UDATA8: u8 = 0x04;
+const DW_EH_PE_SLEB128: u8 = 0x09;
+const DW_EH_PE_SDATA2: u8 = 0x0a;
+const DW_EH_PE_SDATA4: u8 = 0x0b;
+const DW_EH_PE_SDATA8: u8 = 0x0c;
+const DW_EH_PE_PCREL: u8 = 0x10;
+const DW_EH_PE_DATAREL: u8 = 0x30;
+const DW_EH_PE_INDIRECT: u8 = 0x80;
+
+/// A view over the `.eh_frame_hdr` section, located through `PT_GNU_EH_FRAME`
+///
+/// The header points at `.eh_frame` and usually carries a sorted table of
+/// FDE locations that unwinders use to find the frame description covering a
+/// given program counter.
+#[derive(Debug, Clone)]
+pub struct EhFrameHdr<'a> {
+    data: &'a [u8],
+    address: u64,
+    version: u8,
+    eh_frame_ptr: u64,
+    fde_count: u64,
+    table_encoding: u8,
+    table_offset: usize,
+}
+
+impl<'a> EhFrameHdr<'a> {
+    /// Parses the header stored in `data`, which is located at virtual address
+    /// `address`
+    pub fn parse(data: &'a [u8], address: u64) -> Result<Self> {
+        let invalid = || Error::InvalidData("truncated .eh_frame_hdr");
+
+        let [
+            version,
+            eh_frame_ptr_encoding,
+            fde_count_encoding,
+            table_encoding,
+        ] = *data.first_chunk::<4>().ok_or_else(invalid)?;
+
+        if version != 1 {
+            return Err(Error::InvalidData("unsupported .eh_frame_hdr version"));
+        }
+
+        let mut offset = 4;
+        let (eh_frame_ptr, size) =
+            read_encoded(data, offset, eh_frame_ptr_encoding, address).ok_or_else(invalid)?;
+        offset += size;
+
+        let fde_count = if fde_count_encoding == DW_EH_PE_OMIT || table_encoding == DW_EH_PE_OMIT {
+            0
+        } else {
+            let (count, size) =
+                read_encoded(data, offset, fde_count_encoding, address).ok_or_else(invalid)?;
+            offset += size;
+            count
+        };
+
+        Ok(Self {
+            data,
+            address,
+            version,
+            eh_frame_ptr,
+            fde_count,
+            table_encoding,
+            table_offset: offset,
+        })
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Returns the virtual address of the `.eh_frame` section
+    pub fn eh_frame_ptr(&self) -> u64 {
+        self.eh_frame_ptr
+    }
+
+    /// Returns the number of entries in the binary search table
+    pub fn fde_count(&self) -> u64 {
+        self.fde_count
+    }
+
+    fn entry_size(&self) -> Option<usize> {
+        match self.table_encoding & 0x0f {
+            DW_EH_PE_UDATA2 | DW_EH_PE_SDATA2 => Some(4),
+            DW_EH_PE_UDATA4 | DW_EH_PE_SDATA4 => Some(8),
+            DW_EH_PE_ABSPTR | DW_EH_PE_UDATA8 | DW_EH_PE_SDATA8 => Some(16),
+            _ => None,
+        }
+    }
+
+    /// Returns the initial location and FDE address of a table entry
+    pub fn entry(&self, index: u64) -> Option<(u64, u64)> {
+        if index >= self.fde_count {
+            return None;
+        }
+
+        let entry_size = self.entry_size()?;
+        let offset = self
+            .table_offset
+            .checked_add(usize::try_from(index).ok()?.checked_mul(entry_size)?)?;
+
+        let (location, size) = read_encoded(self.data, offset, self.table_encoding, self.address)?;
+        let (fde, _) = read_encoded(self.data, offset + size, self.table_encoding, self.address)?;
+
+        Some((location, fde))
+    }
+
+    /// Returns the address of the FDE whose range may contain `pc`
+    ///
+    /// This only consults the search table: the returned FDE starts at or
+    /// before `pc`, and its length must still be checked by the caller.
+    pub fn find_fde(&self, pc: u64) -> Option<u64> {
+        self.entry_size()?;
+
+        let (mut low, mut high) = (0, self.fde_count);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            let (location, _) = self.entry(middle)?;
+            if location <= pc {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+
+        low.checked_sub(1)
+            .and_then(|index| self.entry(index))
+            .map(|(_, fde)| fde)
+    }
+}
+
+fn read_leb128(data: &[u8], signed: bool) -> Option<(u64, usize)> {
+    let mut result: u64 = 0;
+    let mut shift = 0;
+
+    for (index, &byte) in data.iter().enumerate() {
+        if shift < 64 {
+            result |= ((byte & 0x7f) as u64) << shift;
+        }
+        shift += 7;
+
+        if byte & 0x80 == 0 {
+            if signed && shift < 64 && byte & 0x40 != 0 {
+                result |= !0 << shift;
+            }
+            return Some((result, index + 1));
+        }
+    }
+
+    None
+}
+
+/// Reads a DWARF exception header encoded pointer at `offset`, returning the
+/// decoded value and the number of bytes consumed
+///
+/// `base` is the virtual address of `data`, used for PC and data relative
+/// encodings.
+fn read_encoded(data: &[u8], offset: usize, encoding: u8, base: u64) -> Option<(u64, usize)> {
+    // Indirect pointers would require reading the target memory
+    if encoding & DW_EH_PE_INDIRECT != 0 {
+        return None;
+    }
+
+    let bytes = data.get(offset..)?;
+
+    let (value, size) = match encoding & 0x0f {
+        DW_EH_PE_ABSPTR | DW_EH_PE_UDATA8 => (u64::from_ne_bytes(*bytes.first_chunk()?), 8),
+        DW_EH_PE_SDATA8 => (i64::from_ne_bytes(*bytes.first_chunk()?) as u64, 8),
+        DW_EH_PE_UDATA4 => (u32::from_ne_bytes(*bytes.first_chunk()?) as u64, 4),
+        DW_EH_PE_SDATA4 => (i32::from_ne_bytes(*bytes.first_chunk()?) as u64, 4),
+        DW_EH_PE_UDATA2 => (u16::from_ne_bytes(*bytes.first_chunk()?) as u64, 2),
+        DW_EH_PE_SDATA2 => (i16::from_ne_bytes(*bytes.first_chunk()?) as u64, 2),
+        DW_EH_PE_ULEB128 => read_leb128(bytes, false)?,
+        DW_EH_PE_SLEB128 => read_leb128(bytes, true)?,
+        _ => return None,
+    };
+
+    let value = match encoding & 0x70 {
+        0 => value,
+        DW_EH_PE_PCREL => value.wrapping_add(base).wrapping_add(offset as u64),
+        DW_EH_PE_DATAREL => value.wrapping_add(base),
+        _ => return None,
+    };
+
+    Some((value, size))
+}
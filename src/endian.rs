@@ -0,0 +1,303 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use core::mem::size_of;
+
+use zerocopy::{FromBytes, Immutable, IntoBytes};
+
+use crate::error::{Error, Result};
+use crate::header::Encoding;
+
+/// ELF structures whose fields can be converted between byte orders
+///
+/// The structures of this crate are laid out in the byte order of the host.
+/// Data of the opposite encoding is converted on its way in and out by
+/// reversing the bytes of every multi-byte field.
+pub trait SwapBytes: Sized {
+    /// Reverses the byte order of every field
+    fn swap_bytes(self) -> Self;
+
+    /// Converts a value from the byte order of the host to `encoding`, or
+    /// back from `encoding` to the byte order of the host
+    fn convert(self, encoding: &Encoding) -> Self {
+        if encoding.is_native() {
+            self
+        } else {
+            self.swap_bytes()
+        }
+    }
+}
+
+macro_rules! impl_swap_bytes_for_int {
+    ($($int:ty),*) => {
+        $(
+            impl SwapBytes for $int {
+                fn swap_bytes(self) -> Self {
+                    <$int>::swap_bytes(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_swap_bytes_for_int!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+impl<T: SwapBytes, const N: usize> SwapBytes for [T; N] {
+    fn swap_bytes(self) -> Self {
+        self.map(SwapBytes::swap_bytes)
+    }
+}
+
+/// Implements [`SwapBytes`] for a structure by swapping each of its fields
+///
+/// Every field must be listed, which the struct expression checks.
+macro_rules! impl_swap_bytes {
+    ($name:ident { $($field:ident),* $(,)? }) => {
+        impl $crate::endian::SwapBytes for $name {
+            fn swap_bytes(self) -> Self {
+                Self {
+                    $($field: $crate::endian::SwapBytes::swap_bytes(self.$field),)*
+                }
+            }
+        }
+    };
+}
+
+pub(crate) use impl_swap_bytes;
+
+/// Reads a `T` stored in `encoding` at `offset`, returning it in the byte
+/// order of the host
+pub fn read_at<T: FromBytes + SwapBytes>(
+    data: &[u8],
+    offset: usize,
+    encoding: &Encoding,
+) -> Result<T> {
+    let bytes = data.get(offset..).ok_or(Error::OutOfBounds {
+        offset: offset as u64,
+        size: size_of::<T>() as u64,
+    })?;
+    let (value, _) = T::read_from_prefix(bytes).map_err(|_| Error::OutOfBounds {
+        offset: offset as u64,
+        size: size_of::<T>() as u64,
+    })?;
+    Ok(value.convert(encoding))
+}
+
+/// Writes `value` at `offset` in `encoding`
+pub fn write_at<T: IntoBytes + Immutable + SwapBytes + Clone>(
+    data: &mut [u8],
+    offset: usize,
+    value: &T,
+    encoding: &Encoding,
+) -> Result<()> {
+    let size = size_of::<T>();
+    let out = offset
+        .checked_add(size)
+        .and_then(|end| data.get_mut(offset..end))
+        .ok_or(Error::OutOfBounds {
+            offset: offset as u64,
+            size: size as u64,
+        })?;
+    out.copy_from_slice(value.clone().convert(encoding).as_bytes());
+    Ok(())
+}
+
+/// Appends `value` to `out` in `encoding`
+#[cfg(feature = "alloc")]
+pub fn write<T: IntoBytes + Immutable + SwapBytes + Clone>(
+    out: &mut Vec<u8>,
+    value: &T,
+    encoding: &Encoding,
+) {
+    out.extend_from_slice(value.clone().convert(encoding).as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::{Header64, Machine, ObjectType, OsAbi};
+    use crate::program_header::{ProgramHeader64, ProgramType};
+
+    /// Serializes integers in a fixed byte order, independently of
+    /// [`SwapBytes`]
+    struct Writer<const N: usize> {
+        bytes: [u8; N],
+        offset: usize,
+        big_endian: bool,
+    }
+
+    impl<const N: usize> Writer<N> {
+        fn new(big_endian: bool) -> Self {
+            Self {
+                bytes: [0; N],
+                offset: 0,
+                big_endian,
+            }
+        }
+
+        fn put(&mut self, value: u64, size: usize) -> &mut Self {
+            let (big, little) = (value.to_be_bytes(), value.to_le_bytes());
+            let bytes = if self.big_endian {
+                &big[8 - size..]
+            } else {
+                &little[..size]
+            };
+            self.bytes[self.offset..self.offset + size].copy_from_slice(bytes);
+            self.offset += size;
+            self
+        }
+    }
+
+    fn header_bytes(big_endian: bool) -> [u8; 64] {
+        let mut writer = Writer::<64>::new(big_endian);
+        writer.bytes[..16].copy_from_slice(&[
+            0x7f,
+            b'E',
+            b'L',
+            b'F',
+            2,
+            if big_endian { 2 } else { 1 },
+            1,
+            3,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+        ]);
+        writer.offset = 16;
+        writer
+            .put(3, 2)
+            .put(if big_endian { 21 } else { 62 }, 2)
+            .put(1, 4)
+            .put(0x0000_1234_5678_9abc, 8)
+            .put(64, 8)
+            .put(0x0001_0203_0405_0607, 8)
+            .put(0x8000_0001, 4)
+            .put(64, 2)
+            .put(56, 2)
+            .put(13, 2)
+            .put(64, 2)
+            .put(0x1f, 2)
+            .put(0x1e, 2);
+        writer.bytes
+    }
+
+    fn program_header_bytes(big_endian: bool) -> [u8; 56] {
+        let mut writer = Writer::<56>::new(big_endian);
+        writer
+            .put(0x6474e551, 4)
+            .put(0x6, 4)
+            .put(0x1122_3344_5566_7788, 8)
+            .put(0x0000_7fff_0000_1000, 8)
+            .put(0x0000_7fff_0000_2000, 8)
+            .put(0x0102, 8)
+            .put(0x0304_0506, 8)
+            .put(0x10, 8);
+        writer.bytes
+    }
+
+    #[test]
+    fn header_round_trip() {
+        for big_endian in [false, true] {
+            let bytes = header_bytes(big_endian);
+            let header = Header64::parse(&bytes).unwrap();
+
+            assert_eq!(header.os_abi, OsAbi::GNU);
+            assert_eq!(header.object_type, ObjectType::DYN);
+            let machine = if big_endian {
+                Machine::PPC64
+            } else {
+                Machine::X86_64
+            };
+            assert_eq!(header.machine, machine);
+            assert_eq!(header.entry_point, 0x0000_1234_5678_9abc);
+            assert_eq!(header.section_header_offset, 0x0001_0203_0405_0607);
+            assert_eq!(header.flags, 0x8000_0001);
+            assert_eq!(header.program_header_count, 13);
+            assert_eq!(header.section_header_string_table_index, 0x1e);
+
+            assert_eq!(header.to_bytes(), bytes);
+
+            let mut written = [0; 64];
+            write_at(&mut written, 0, &header, &header.encoding).unwrap();
+            assert_eq!(written, bytes);
+        }
+    }
+
+    #[test]
+    fn program_header_round_trip() {
+        for big_endian in [false, true] {
+            let bytes = program_header_bytes(big_endian);
+            let encoding = if big_endian {
+                Encoding::ELFDATA2MSB
+            } else {
+                Encoding::ELFDATA2LSB
+            };
+            let phdr: ProgramHeader64 = read_at(&bytes, 0, &encoding).unwrap();
+
+            assert_eq!(phdr.p_type, ProgramType::GNU_STACK);
+            assert_eq!(phdr.p_flags, 0x6);
+            assert_eq!(phdr.p_offset, 0x1122_3344_5566_7788);
+            assert_eq!(phdr.p_vaddr, 0x0000_7fff_0000_1000);
+            assert_eq!(phdr.p_paddr, 0x0000_7fff_0000_2000);
+            assert_eq!(phdr.p_filesz, 0x0102);
+            assert_eq!(phdr.p_memsz, 0x0304_0506);
+            assert_eq!(phdr.p_align, 0x10);
+
+            let mut written = [0; 56];
+            write_at(&mut written, 0, &phdr, &encoding).unwrap();
+            assert_eq!(written, bytes);
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn write_appends_in_encoding() {
+        use alloc::vec::Vec;
+
+        for big_endian in [false, true] {
+            let header_bytes = header_bytes(big_endian);
+            let phdr_bytes = program_header_bytes(big_endian);
+            let header = Header64::parse(&header_bytes).unwrap();
+            let phdr: ProgramHeader64 = read_at(&phdr_bytes, 0, &header.encoding).unwrap();
+
+            let mut out = Vec::new();
+            write(&mut out, &header, &header.encoding);
+            write(&mut out, &phdr, &header.encoding);
+
+            let expected: Vec<u8> = header_bytes.iter().chain(&phdr_bytes).copied().collect();
+            assert_eq!(out, expected);
+        }
+    }
+
+    #[test]
+    fn truncated_header() {
+        let bytes = header_bytes(false);
+        assert_eq!(
+            Header64::parse(&bytes[..40]),
+            Err(Error::OutOfBounds {
+                offset: 0,
+                size: 64
+            })
+        );
+        assert_eq!(
+            Header64::parse(&bytes[..2]),
+            Err(Error::OutOfBounds {
+                offset: 0,
+                size: 64
+            })
+        );
+        assert_eq!(Header64::parse(b"!<arch>\n"), Err(Error::InvalidMagic));
+        assert_eq!(
+            Header64::parse(b""),
+            Err(Error::OutOfBounds {
+                offset: 0,
+                size: 64
+            })
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use core::fmt;
+
+use crate::dynamic::DynamicTag;
+use crate::header::{Class, Encoding};
+use crate::program_header::ProgramType;
+
+/// Errors produced while parsing ELF data
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The data does not start with the ELF magic number
+    InvalidMagic,
+    /// The object file class is not supported by this parser
+    UnsupportedClass(Class),
+    /// The data encoding does not match the host and is not supported
+    UnsupportedEncoding(Encoding),
+    /// A table declares an entry size smaller than the structure it holds
+    InvalidEntrySize { expected: usize, found: usize },
+    /// A structure extends past the end of the available data
+    OutOfBounds { offset: u64, size: u64 },
+    /// A required segment is not present in the program header table
+    MissingSegment(ProgramType),
+    /// A required entry is not present in the dynamic table
+    MissingDynamicEntry(DynamicTag),
+    /// A virtual address is not covered by any loadable segment
+    UnmappedAddress(u64),
+    /// A structure is malformed
+    InvalidData(&'static str),
+}
+
+pub type Result<T, E = Error> = core::result::Result<T, E>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidMagic => write!(f, "invalid ELF magic"),
+            Self::UnsupportedClass(class) => {
+                write!(f, "unsupported ELF class: {}", class.as_human_string())
+            }
+            Self::UnsupportedEncoding(encoding) => {
+                write!(
+                    f,
+                    "unsupported data encoding: {}",
+                    encoding.as_human_string()
+                )
+            }
+            Self::InvalidEntrySize { expected, found } => {
+                write!(
+                    f,
+                    "invalid entry size {found}, expected at least {expected}"
+                )
+            }
+            Self::OutOfBounds { offset, size } => {
+                write!(f, "{size} bytes at offset 0x{offset:x} are out of bounds")
+            }
+            Self::MissingSegment(p_type) => write!(f, "missing {p_type:?} segment"),
+            Self::MissingDynamicEntry(tag) => write!(f, "missing {tag:?} dynamic entry"),
+            Self::UnmappedAddress(address) => write!(f, "address 0x{address:x} is not mapped"),
+            Self::InvalidData(reason) => write!(f, "invalid data: {reason}"),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
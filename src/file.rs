@@ -0,0 +1,581 @@
+#[cfg(feature = "alloc")]
+use alloc::borrow::Cow;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::auxv::AuxVector;
+use crate::compression::CompressedData;
+use crate::coredump::{CoreNote, MappedFiles, PrPsInfo64, PrStatus, SigInfo64};
+use crate::debuglink::{DebugAltLink, DebugLink};
+use crate::dynamic::{DynamicSymbols, DynamicTable, DynamicTag};
+use crate::error::{Error, Result};
+use crate::group::SectionGroup;
+use crate::header::Header64;
+use crate::note::{CoreNoteType, GnuNoteType, GnuPropertyIter, Note, NoteIter};
+use crate::program_header::{ProgramHeader64, ProgramType};
+use crate::section_header::{
+    SECTION_FLAG_COMPRESSED, SectionHeader64, SectionHeaderTable, SectionType,
+};
+use crate::string_table::StringTable;
+use crate::symbol::{SectionIndex, SymbolTable, SymbolType};
+use crate::table::{Table, TableIter};
+
+/// A view over an ELF file stored in memory, addressed by file offset
+#[derive(Debug, Clone)]
+pub struct ElfFile<'a> {
+    data: &'a [u8],
+    header: Header64,
+    program_headers: Table<'a, ProgramHeader64>,
+    section_headers: SectionHeaderTable<'a>,
+    section_names: StringTable<'a>,
+}
+
+impl<'a> ElfFile<'a> {
+    /// Parses the ELF header and program header table of `data`
+    pub fn parse(data: &'a [u8]) -> Result<Self> {
+        let header = Header64::parse(data)?;
+
+        if !header.encoding.is_native() {
+            return Err(Error::UnsupportedEncoding(header.encoding));
+        }
+
+        let program_headers = if header.program_header_count == 0 {
+            Table::new(&[])
+        } else {
+            let entry_size = header.program_header_entry_size as u64;
+            let size = entry_size * header.program_header_count as u64;
+            Table::with_entry_size(
+                slice(data, header.program_header_offset, size)?,
+                entry_size as usize,
+            )?
+        };
+
+        let (section_headers, section_names) = parse_section_headers(data, &header)?;
+
+        Ok(Self {
+            data,
+            header,
+            program_headers,
+            section_headers,
+            section_names,
+        })
+    }
+
+    pub fn header(&self) -> &Header64 {
+        &self.header
+    }
+
+    /// Returns the whole file
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Returns `size` bytes starting at file offset `offset`
+    pub fn read(&self, offset: u64, size: u64) -> Result<&'a [u8]> {
+        slice(self.data, offset, size)
+    }
+
+    pub fn program_headers(&self) -> TableIter<'a, ProgramHeader64> {
+        self.program_headers.iter()
+    }
+
+    pub fn section_headers(&self) -> TableIter<'a, SectionHeader64> {
+        self.section_headers.iter()
+    }
+
+    /// Returns the number of entries in the section header table
+    pub fn section_count(&self) -> usize {
+        self.section_headers.len()
+    }
+
+    pub fn section_header(&self, index: usize) -> Option<SectionHeader64> {
+        self.section_headers.get(index)
+    }
+
+    /// Returns the section header string table (`.shstrtab`)
+    pub fn section_names(&self) -> StringTable<'a> {
+        self.section_names
+    }
+
+    /// Returns the index of the section header string table, or 0 if the
+    /// file has none
+    pub fn section_names_index(&self) -> usize {
+        match self.header.section_header_string_table_index {
+            index if index == SectionIndex::XINDEX.as_raw() => self
+                .section_header(0)
+                .map_or(0, |shdr| shdr.sh_link as usize),
+            index => index as usize,
+        }
+    }
+
+    pub fn section_name(&self, shdr: &SectionHeader64) -> Option<&'a [u8]> {
+        self.section_names.get(shdr.sh_name)
+    }
+
+    /// Returns the first section with the given name and its index
+    pub fn find_section(&self, name: &[u8]) -> Option<(usize, SectionHeader64)> {
+        self.section_headers()
+            .enumerate()
+            .find(|(_, shdr)| self.section_name(shdr) == Some(name))
+    }
+
+    /// Returns the contents of a section as stored in the file, which is
+    /// empty for `SHT_NOBITS` sections
+    pub fn section_data(&self, shdr: &SectionHeader64) -> Result<&'a [u8]> {
+        slice(self.data, shdr.sh_offset, shdr.file_size())
+    }
+
+    /// Returns the compressed contents of a section with `SHF_COMPRESSED` or
+    /// of a legacy `.zdebug_*` section
+    pub fn section_compression(
+        &self,
+        shdr: &SectionHeader64,
+    ) -> Option<Result<CompressedData<'a>>> {
+        if shdr.sh_type == SectionType::NOBITS {
+            return None;
+        }
+        if shdr.sh_flags & SECTION_FLAG_COMPRESSED != 0 {
+            return Some(
+                self.section_data(shdr)
+                    .and_then(|data| CompressedData::parse(data, &self.header.class)),
+            );
+        }
+        if self.section_name(shdr)?.starts_with(b".zdebug") {
+            let data = self.section_data(shdr).ok()?;
+            return CompressedData::is_legacy(data).then(|| CompressedData::parse_legacy(data));
+        }
+        None
+    }
+
+    /// Returns the contents of a section, decompressed if it is compressed
+    #[cfg(feature = "alloc")]
+    pub fn decompressed_section_data(&self, shdr: &SectionHeader64) -> Result<Cow<'a, [u8]>> {
+        match self.section_compression(shdr) {
+            Some(compressed) => Ok(Cow::Owned(compressed?.decompress()?)),
+            None => self.section_data(shdr).map(Cow::Borrowed),
+        }
+    }
+
+    /// Returns the symbols of a `SHT_SYMTAB` or `SHT_DYNSYM` section
+    pub fn symbols(&self, shdr: &SectionHeader64) -> Result<SymbolTable<'a>> {
+        SymbolTable::with_entry_size(self.section_data(shdr)?, shdr.sh_entsize as usize)
+    }
+
+    /// Returns the string table named by the `sh_link` of a section, such as
+    /// the names of a symbol table
+    pub fn linked_strings(&self, shdr: &SectionHeader64) -> Result<StringTable<'a>> {
+        let strings = self
+            .section_header(shdr.sh_link as usize)
+            .ok_or(Error::InvalidData("invalid string table index"))?;
+        Ok(StringTable::new(self.section_data(&strings)?))
+    }
+
+    /// Iterates over the `SHT_GROUP` sections, with their signatures
+    pub fn section_groups(&self) -> impl Iterator<Item = Result<SectionGroup<'a>>> + '_ {
+        self.section_headers()
+            .enumerate()
+            .filter(|(_, shdr)| shdr.sh_type == SectionType::GROUP)
+            .map(|(index, shdr)| self.section_group(index, &shdr))
+    }
+
+    /// Returns the group the section with the given index belongs to
+    pub fn group_of(&self, section: usize) -> Option<SectionGroup<'a>> {
+        self.section_groups()
+            .filter_map(Result::ok)
+            .find(|group| group.contains(section))
+    }
+
+    /// Returns the index of the group section each section belongs to,
+    /// indexed by section
+    #[cfg(feature = "alloc")]
+    pub fn section_group_map(&self) -> Result<Vec<Option<usize>>> {
+        let mut map = alloc::vec![None; self.section_count()];
+        for group in self.section_groups() {
+            let group = group?;
+            for member in group.members() {
+                let entry = map
+                    .get_mut(member)
+                    .ok_or(Error::InvalidData("invalid section group member"))?;
+                *entry = Some(group.section);
+            }
+        }
+        Ok(map)
+    }
+
+    fn section_group(&self, index: usize, shdr: &SectionHeader64) -> Result<SectionGroup<'a>> {
+        let mut group = SectionGroup::parse(index, shdr.sh_info, self.section_data(shdr)?)?;
+        group.signature = self
+            .section_header(shdr.sh_link as usize)
+            .and_then(|symtab| {
+                let symbol = self.symbols(&symtab).ok()?.get(shdr.sh_info as usize)?;
+                // Assemblers may name a group after a section symbol
+                if symbol.symbol_type() == SymbolType::SECTION {
+                    let section = self.section_header(symbol.st_shndx.as_raw() as usize)?;
+                    self.section_name(&section)
+                } else {
+                    self.linked_strings(&symtab).ok()?.get(symbol.st_name)
+                }
+            });
+        Ok(group)
+    }
+
+    /// Returns the first program header of the given type
+    pub fn find_segment(&self, p_type: ProgramType) -> Option<ProgramHeader64> {
+        self.program_headers().find(|phdr| phdr.p_type == p_type)
+    }
+
+    /// Returns the contents of a segment as stored in the file, up to its
+    /// file size
+    pub fn segment_data(&self, phdr: &ProgramHeader64) -> Result<&'a [u8]> {
+        slice(self.data, phdr.p_offset, phdr.p_filesz)
+    }
+
+    /// Returns the `PT_LOAD` segment whose file contents cover `vaddr`
+    fn load_segment(&self, vaddr: u64) -> Option<ProgramHeader64> {
+        self.program_headers().find(|phdr| {
+            phdr.p_type == ProgramType::LOAD
+                && vaddr >= phdr.p_vaddr
+                && vaddr - phdr.p_vaddr < phdr.p_filesz
+        })
+    }
+
+    /// Translates a virtual address to the file offset holding its contents
+    pub fn address_to_offset(&self, vaddr: u64) -> Option<u64> {
+        self.load_segment(vaddr)
+            .and_then(|phdr| phdr.p_offset.checked_add(vaddr - phdr.p_vaddr))
+    }
+
+    /// Returns the bytes stored in the file from the virtual address `vaddr`
+    /// to the end of the file contents of the segment containing it
+    pub fn read_address_to_end(&self, vaddr: u64) -> Result<&'a [u8]> {
+        let segment = self
+            .load_segment(vaddr)
+            .ok_or(Error::UnmappedAddress(vaddr))?;
+        let start = vaddr - segment.p_vaddr;
+        let offset = segment
+            .p_offset
+            .checked_add(start)
+            .ok_or(Error::OutOfBounds {
+                offset: segment.p_offset,
+                size: segment.p_filesz,
+            })?;
+        slice(self.data, offset, segment.p_filesz - start)
+    }
+
+    /// Returns `size` bytes stored in the file at the virtual address `vaddr`
+    pub fn read_address(&self, vaddr: u64, size: u64) -> Result<&'a [u8]> {
+        let data = self.read_address_to_end(vaddr)?;
+        data.get(..size as usize).ok_or(Error::OutOfBounds {
+            offset: vaddr,
+            size,
+        })
+    }
+
+    /// Returns the dynamic table from the `PT_DYNAMIC` segment
+    pub fn dynamic(&self) -> Result<DynamicTable<'a>> {
+        let segment = self
+            .find_segment(ProgramType::DYNAMIC)
+            .ok_or(Error::MissingSegment(ProgramType::DYNAMIC))?;
+        Ok(DynamicTable::new(self.segment_data(&segment)?))
+    }
+
+    /// Returns the dynamic symbol table and the tables used to look it up
+    pub fn dynamic_symbols(&self) -> Result<DynamicSymbols<'a>> {
+        DynamicSymbols::parse(&self.dynamic()?, |vaddr, size| match size {
+            Some(size) => self.read_address(vaddr, size),
+            None => self.read_address_to_end(vaddr),
+        })
+    }
+
+    /// Returns the dynamic string table (`DT_STRTAB`)
+    pub fn dynamic_strings(&self) -> Result<StringTable<'a>> {
+        let dynamic = self.dynamic()?;
+        let address = dynamic
+            .find(DynamicTag::STRTAB)
+            .ok_or(Error::MissingDynamicEntry(DynamicTag::STRTAB))?;
+        let size = dynamic
+            .find(DynamicTag::STRSZ)
+            .ok_or(Error::MissingDynamicEntry(DynamicTag::STRSZ))?;
+        Ok(StringTable::new(self.read_address(address, size)?))
+    }
+
+    /// Returns the program interpreter path from `PT_INTERP`, without its
+    /// NUL terminator
+    pub fn interpreter(&self) -> Option<&'a [u8]> {
+        let segment = self.find_segment(ProgramType::INTERP)?;
+        let data = self.segment_data(&segment).ok()?;
+        let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+        Some(&data[..end])
+    }
+
+    /// Iterates over the notes of every `PT_NOTE` segment
+    pub fn notes(&self) -> impl Iterator<Item = Note<'a>> + '_ {
+        self.program_headers()
+            .filter(|phdr| phdr.p_type == ProgramType::NOTE)
+            .filter_map(|phdr| {
+                self.segment_data(&phdr)
+                    .ok()
+                    .map(|data| NoteIter::new(data, phdr.p_align))
+            })
+            .flatten()
+    }
+
+    /// Iterates over the notes of every `SHT_NOTE` section
+    pub fn section_notes(&self) -> impl Iterator<Item = Note<'a>> + '_ {
+        self.section_headers()
+            .filter(|shdr| shdr.sh_type == SectionType::NOTE)
+            .filter_map(|shdr| {
+                self.section_data(&shdr)
+                    .ok()
+                    .map(|data| NoteIter::new(data, shdr.sh_addralign))
+            })
+            .flatten()
+    }
+
+    /// Returns the descriptor of the `NT_GNU_BUILD_ID` note, from the
+    /// `PT_NOTE` segments or, failing that, the note sections
+    pub fn build_id(&self) -> Option<&'a [u8]> {
+        let is_build_id = |note: &Note| note.is(Note::GNU, GnuNoteType::BUILD_ID.as_raw());
+        self.notes()
+            .find(is_build_id)
+            .or_else(|| self.section_notes().find(is_build_id))
+            .map(|note| note.desc)
+    }
+
+    /// Iterates over the properties of the `NT_GNU_PROPERTY_TYPE_0` note,
+    /// from the `PT_NOTE` segments or, failing that, the note sections
+    pub fn gnu_properties(&self) -> GnuPropertyIter<'a> {
+        let is_property = |note: &Note| note.is(Note::GNU, GnuNoteType::PROPERTY_TYPE_0.as_raw());
+        let desc = self
+            .notes()
+            .find(is_property)
+            .or_else(|| self.section_notes().find(is_property))
+            .map(|note| note.desc)
+            .unwrap_or_default();
+        GnuPropertyIter::new(desc, &self.header.class)
+    }
+
+    /// Returns the `.gnu_debuglink` section naming the separate debug file
+    pub fn debug_link(&self) -> Option<Result<DebugLink<'a>>> {
+        let (_, shdr) = self.find_section(b".gnu_debuglink")?;
+        Some(self.section_data(&shdr).and_then(DebugLink::parse))
+    }
+
+    /// Returns the `.gnu_debugaltlink` section naming the supplementary debug
+    /// file
+    pub fn debug_alt_link(&self) -> Option<Result<DebugAltLink<'a>>> {
+        let (_, shdr) = self.find_section(b".gnu_debugaltlink")?;
+        Some(self.section_data(&shdr).and_then(DebugAltLink::parse))
+    }
+
+    /// Returns the auxiliary vector stored in the `NT_AUXV` note of a core file
+    pub fn auxv(&self) -> Option<AuxVector<'a>> {
+        self.notes().find_map(|note| AuxVector::from_note(&note))
+    }
+
+    /// Iterates over the decoded notes of a core file
+    pub fn core_notes(&self) -> impl Iterator<Item = Result<CoreNote<'a>>> + '_ {
+        self.notes().filter_map(|note| CoreNote::parse(&note))
+    }
+
+    /// Iterates over the `NT_PRSTATUS` notes of a core file, one per thread
+    ///
+    /// The first thread is the one that received the fatal signal.
+    pub fn threads(&self) -> impl Iterator<Item = Result<PrStatus<'a>>> + '_ {
+        self.notes()
+            .filter(|note| note.is(Note::CORE, CoreNoteType::PRSTATUS.as_raw()))
+            .map(|note| PrStatus::parse(note.desc))
+    }
+
+    /// Returns the `NT_PRPSINFO` note of a core file
+    pub fn process_info(&self) -> Option<Result<PrPsInfo64>> {
+        self.notes()
+            .find(|note| note.is(Note::CORE, CoreNoteType::PRPSINFO.as_raw()))
+            .map(|note| PrPsInfo64::parse(note.desc))
+    }
+
+    /// Returns the `NT_SIGINFO` note of a core file
+    pub fn signal_info(&self) -> Option<Result<SigInfo64>> {
+        self.notes()
+            .find(|note| note.is(Note::CORE, CoreNoteType::SIGINFO.as_raw()))
+            .map(|note| SigInfo64::parse(note.desc))
+    }
+
+    /// Returns the `NT_FILE` note of a core file
+    pub fn mapped_files(&self) -> Option<Result<MappedFiles<'a>>> {
+        self.notes()
+            .find(|note| note.is(Note::CORE, CoreNoteType::FILE.as_raw()))
+            .map(|note| MappedFiles::parse(note.desc))
+    }
+
+    /// Iterates over the memory described by the `PT_LOAD` segments
+    pub fn memory_regions(&self) -> impl Iterator<Item = MemoryRegion<'a>> + '_ {
+        self.program_headers()
+            .filter(|phdr| phdr.p_type == ProgramType::LOAD)
+            .map(|phdr| {
+                // Core files may be truncated, in which case only the part of
+                // the segment that is present is considered dumped
+                let data = self
+                    .data
+                    .get(phdr.p_offset as usize..)
+                    .map(|data| &data[..data.len().min(phdr.p_filesz as usize)])
+                    .unwrap_or_default();
+
+                MemoryRegion {
+                    start: phdr.p_vaddr,
+                    end: phdr.p_vaddr.saturating_add(phdr.p_memsz),
+                    flags: phdr.p_flags,
+                    data,
+                }
+            })
+    }
+
+    /// Returns the memory region containing `vaddr`
+    pub fn memory_region(&self, vaddr: u64) -> Option<MemoryRegion<'a>> {
+        self.memory_regions().find(|region| region.contains(vaddr))
+    }
+
+    /// Returns `size` bytes of memory at `vaddr` without copying
+    ///
+    /// The range must lie within the dumped part of a single region. Use
+    /// [`ElfFile::read_memory`] for ranges that span regions or include
+    /// memory that was not dumped.
+    pub fn memory_slice(&self, vaddr: u64, size: u64) -> Result<&'a [u8]> {
+        let region = self
+            .memory_region(vaddr)
+            .ok_or(Error::UnmappedAddress(vaddr))?;
+        let start = (vaddr - region.start) as usize;
+
+        start
+            .checked_add(size as usize)
+            .and_then(|end| region.data.get(start..end))
+            .ok_or(Error::OutOfBounds {
+                offset: vaddr,
+                size,
+            })
+    }
+
+    /// Reads process memory at `vaddr` into `buf`
+    ///
+    /// This is meant for core files, where each `PT_LOAD` segment holds a
+    /// mapping of the crashed process. The range may span several adjacent
+    /// segments. Bytes past the file size of a segment, which were either
+    /// zero-filled in the process or not dumped by the kernel, read as zero;
+    /// use [`MemoryRegion::is_dumped`] to tell them apart from real data.
+    ///
+    /// Returns [`Error::UnmappedAddress`] with the first address that is not
+    /// covered by any segment.
+    pub fn read_memory(&self, vaddr: u64, buf: &mut [u8]) -> Result<()> {
+        let mut address = vaddr;
+        let mut remaining = buf;
+
+        while !remaining.is_empty() {
+            let region = self
+                .memory_region(address)
+                .ok_or(Error::UnmappedAddress(address))?;
+
+            let available = (region.end - address).min(remaining.len() as u64) as usize;
+            let (chunk, rest) = remaining.split_at_mut(available);
+
+            let dumped = region
+                .data
+                .get((address - region.start) as usize..)
+                .unwrap_or_default();
+            let copied = dumped.len().min(chunk.len());
+            chunk[..copied].copy_from_slice(&dumped[..copied]);
+            chunk[copied..].fill(0);
+
+            remaining = rest;
+            address = address
+                .checked_add(available as u64)
+                .ok_or(Error::UnmappedAddress(u64::MAX))?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a native-endian 64-bit word of process memory at `vaddr`
+    pub fn read_memory_u64(&self, vaddr: u64) -> Result<u64> {
+        let mut bytes = [0; 8];
+        self.read_memory(vaddr, &mut bytes)?;
+        Ok(u64::from_ne_bytes(bytes))
+    }
+}
+
+/// A range of memory described by a `PT_LOAD` segment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryRegion<'a> {
+    /// First virtual address of the region
+    pub start: u64,
+    /// Virtual address one past the end of the region
+    pub end: u64,
+    /// Segment permissions (`p_flags`)
+    pub flags: u32,
+    /// The part of the region that is present in the file
+    pub data: &'a [u8],
+}
+
+impl MemoryRegion<'_> {
+    pub fn contains(&self, vaddr: u64) -> bool {
+        vaddr >= self.start && vaddr < self.end
+    }
+
+    /// Returns true if the contents at `vaddr` are stored in the file
+    pub fn is_dumped(&self, vaddr: u64) -> bool {
+        self.contains(vaddr) && vaddr - self.start < self.data.len() as u64
+    }
+}
+
+/// Locates the section header table and its string table
+///
+/// When a file has `SHN_LORESERVE` sections or more, `e_shnum` is 0 and the
+/// count is stored in the `sh_size` of the first entry, and an `e_shstrndx`
+/// of `SHN_XINDEX` means that the index is stored in its `sh_link`.
+fn parse_section_headers<'a>(
+    data: &'a [u8],
+    header: &Header64,
+) -> Result<(SectionHeaderTable<'a>, StringTable<'a>)> {
+    if header.section_header_offset == 0 {
+        return Ok((Table::new(&[]), StringTable::default()));
+    }
+
+    let entry_size = header.section_header_entry_size as u64;
+    let first = Table::<SectionHeader64>::with_entry_size(
+        slice(data, header.section_header_offset, entry_size)?,
+        entry_size as usize,
+    )?
+    .get(0)
+    .ok_or(Error::InvalidData("empty section header table"))?;
+
+    let count = match header.section_header_count {
+        0 => first.sh_size,
+        count => count as u64,
+    };
+    let size = count
+        .checked_mul(entry_size)
+        .ok_or(Error::InvalidData("section header table too large"))?;
+    let section_headers: SectionHeaderTable = Table::with_entry_size(
+        slice(data, header.section_header_offset, size)?,
+        entry_size as usize,
+    )?;
+
+    let names_index = match header.section_header_string_table_index {
+        index if index == SectionIndex::XINDEX.as_raw() => first.sh_link as usize,
+        index => index as usize,
+    };
+    let section_names = match section_headers.get(names_index) {
+        Some(shdr) if names_index != 0 => {
+            StringTable::new(slice(data, shdr.sh_offset, shdr.file_size())?)
+        }
+        _ => StringTable::default(),
+    };
+
+    Ok((section_headers, section_names))
+}
+
+fn slice(data: &[u8], offset: u64, size: u64) -> Result<&[u8]> {
+    offset
+        .checked_add(size)
+        .and_then(|end| data.get(offset as usize..end as usize))
+        .ok_or(Error::OutOfBounds { offset, size })
+}
@@ -0,0 +1,65 @@
+use crate::error::{Error, Result};
+use crate::table::Table;
+
+/// Group flag marking a COMDAT group, of which the linker keeps a single copy
+/// among all groups with the same signature
+#[doc(alias = "GRP_COMDAT")]
+pub const GROUP_FLAG_COMDAT: u32 = 0x1;
+/// Mask of the OS-specific group flags
+#[doc(alias = "GRP_MASKOS")]
+pub const GROUP_FLAG_MASKOS: u32 = 0x0ff00000;
+/// Mask of the processor-specific group flags
+#[doc(alias = "GRP_MASKPROC")]
+pub const GROUP_FLAG_MASKPROC: u32 = 0xf0000000;
+
+/// A section group (`SHT_GROUP`), a set of sections that the linker keeps or
+/// discards together
+///
+/// The section holds a flags word followed by the indices of the member
+/// sections. The group is identified by its signature, the name of the
+/// symbol given by `sh_info` in the symbol table given by `sh_link`.
+#[derive(Debug, Clone)]
+pub struct SectionGroup<'a> {
+    /// Index of the `SHT_GROUP` section
+    pub section: usize,
+    /// Group flags, a combination of the `GROUP_FLAG_*` constants
+    pub flags: u32,
+    /// Index of the signature symbol
+    pub signature_symbol: u32,
+    /// Name of the signature symbol, if it can be resolved
+    pub signature: Option<&'a [u8]>,
+    members: Table<'a, u32>,
+}
+
+impl<'a> SectionGroup<'a> {
+    /// Parses the contents of a `SHT_GROUP` section, in native byte order
+    ///
+    /// The signature is left unresolved.
+    pub fn parse(section: usize, signature_symbol: u32, data: &'a [u8]) -> Result<Self> {
+        let (flags, members) = data
+            .split_first_chunk::<4>()
+            .ok_or(Error::InvalidData("empty section group"))?;
+
+        Ok(Self {
+            section,
+            flags: u32::from_ne_bytes(*flags),
+            signature_symbol,
+            signature: None,
+            members: Table::new(members),
+        })
+    }
+
+    pub const fn is_comdat(&self) -> bool {
+        self.flags & GROUP_FLAG_COMDAT != 0
+    }
+
+    /// Iterates over the indices of the member sections
+    pub fn members(&self) -> impl Iterator<Item = usize> + '_ {
+        self.members.iter().map(|index| index as usize)
+    }
+
+    /// Returns true if the section with the given index belongs to the group
+    pub fn contains(&self, section: usize) -> bool {
+        self.members().any(|member| member == section)
+    }
+}
@@ -0,0 +1,280 @@
+use crate::error::{Error, Result};
+use crate::string_table::StringTable;
+use crate::symbol::{Symbol64, SymbolTable};
+
+/// Computes the SysV ELF hash of a symbol name
+#[doc(alias = "elf_hash")]
+pub fn sysv_hash(name: &[u8]) -> u32 {
+    let mut hash: u32 = 0;
+    for &byte in name {
+        hash = (hash << 4).wrapping_add(byte as u32);
+        let high = hash & 0xf0000000;
+        if high != 0 {
+            hash ^= high >> 24;
+        }
+        hash &= !high;
+    }
+    hash
+}
+
+/// Computes the GNU hash of a symbol name
+#[doc(alias = "dl_new_hash")]
+pub fn gnu_hash(name: &[u8]) -> u32 {
+    name.iter().fold(5381u32, |hash, &byte| {
+        hash.wrapping_mul(33).wrapping_add(byte as u32)
+    })
+}
+
+fn read_u32(data: &[u8], index: usize) -> Option<u32> {
+    let start = index.checked_mul(4)?;
+    let bytes = data.get(start..start.checked_add(4)?)?;
+    Some(u32::from_ne_bytes(bytes.try_into().ok()?))
+}
+
+fn read_u64(data: &[u8], index: usize) -> Option<u64> {
+    let start = index.checked_mul(8)?;
+    let bytes = data.get(start..start.checked_add(8)?)?;
+    Some(u64::from_ne_bytes(bytes.try_into().ok()?))
+}
+
+fn truncated(data: &[u8]) -> Error {
+    Error::OutOfBounds {
+        offset: 0,
+        size: data.len() as u64,
+    }
+}
+
+fn symbol_matches(symbol: &Symbol64, strings: &StringTable<'_>, name: &[u8]) -> bool {
+    strings.get(symbol.st_name) == Some(name)
+}
+
+/// A view over a SysV symbol hash table (`DT_HASH`, `SHT_HASH`)
+#[derive(Debug, Clone, Copy)]
+pub struct HashTable<'a> {
+    buckets: &'a [u8],
+    chains: &'a [u8],
+}
+
+impl<'a> HashTable<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Self> {
+        let bucket_count = read_u32(data, 0).ok_or_else(|| truncated(data))? as usize;
+        let chain_count = read_u32(data, 1).ok_or_else(|| truncated(data))? as usize;
+
+        let buckets_end = 8 + bucket_count * 4;
+        let chains_end = buckets_end + chain_count * 4;
+        if data.len() < chains_end {
+            return Err(truncated(data));
+        }
+
+        Ok(Self {
+            buckets: &data[8..buckets_end],
+            chains: &data[buckets_end..chains_end],
+        })
+    }
+
+    /// Returns the number of symbols covered by the table
+    pub fn symbol_count(&self) -> usize {
+        self.chains.len() / 4
+    }
+
+    /// Looks up the index of the symbol with the given name
+    pub fn find(
+        &self,
+        name: &[u8],
+        symbols: &SymbolTable<'_>,
+        strings: &StringTable<'_>,
+    ) -> Option<usize> {
+        self.find_with(name, symbols, strings, |_, _| true)
+    }
+
+    /// Looks up the index of the first symbol with the given name that is
+    /// accepted by `accept`
+    pub fn find_with(
+        &self,
+        name: &[u8],
+        symbols: &SymbolTable<'_>,
+        strings: &StringTable<'_>,
+        mut accept: impl FnMut(usize, &Symbol64) -> bool,
+    ) -> Option<usize> {
+        let bucket_count = self.buckets.len() / 4;
+        if bucket_count == 0 {
+            return None;
+        }
+
+        let hash = sysv_hash(name) as usize;
+        let mut index = read_u32(self.buckets, hash % bucket_count)? as usize;
+
+        // Guard against malformed chains that loop forever
+        for _ in 0..self.symbol_count() {
+            if index == 0 {
+                return None;
+            }
+
+            let symbol = symbols.get(index)?;
+            if symbol_matches(&symbol, strings, name) && accept(index, &symbol) {
+                return Some(index);
+            }
+
+            index = read_u32(self.chains, index)? as usize;
+        }
+
+        None
+    }
+}
+
+/// A view over a 64-bit GNU symbol hash table (`DT_GNU_HASH`, `SHT_GNU_HASH`)
+#[derive(Debug, Clone, Copy)]
+pub struct GnuHashTable<'a> {
+    symbol_offset: u32,
+    bloom_shift: u32,
+    bloom: &'a [u8],
+    buckets: &'a [u8],
+    chains: &'a [u8],
+}
+
+impl<'a> GnuHashTable<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Self> {
+        let header = |index| read_u32(data, index).ok_or_else(|| truncated(data));
+
+        let bucket_count = header(0)? as usize;
+        let symbol_offset = header(1)?;
+        let bloom_size = header(2)? as usize;
+        let bloom_shift = header(3)?;
+        if bloom_shift >= 32 {
+            return Err(Error::InvalidData("invalid GNU hash bloom shift"));
+        }
+
+        let bloom_end = 16 + bloom_size * 8;
+        let buckets_end = bloom_end + bucket_count * 4;
+        if data.len() < buckets_end {
+            return Err(truncated(data));
+        }
+
+        Ok(Self {
+            symbol_offset,
+            bloom_shift,
+            bloom: &data[16..bloom_end],
+            buckets: &data[bloom_end..buckets_end],
+            chains: &data[buckets_end..],
+        })
+    }
+
+    /// Returns the number of symbols covered by the table
+    ///
+    /// The GNU hash table does not store this directly, so it is computed by
+    /// walking the chain of the highest non-empty bucket to its end.
+    pub fn symbol_count(&self) -> usize {
+        let last_bucket = (0..self.buckets.len() / 4)
+            .filter_map(|index| read_u32(self.buckets, index))
+            .max()
+            .unwrap_or(0);
+
+        if last_bucket < self.symbol_offset {
+            return self.symbol_offset as usize;
+        }
+
+        let mut index = last_bucket;
+        while let Some(hash) = read_u32(self.chains, (index - self.symbol_offset) as usize) {
+            index += 1;
+            if hash & 1 != 0 {
+                break;
+            }
+        }
+
+        index as usize
+    }
+
+    /// Looks up the index of the symbol with the given name
+    pub fn find(
+        &self,
+        name: &[u8],
+        symbols: &SymbolTable<'_>,
+        strings: &StringTable<'_>,
+    ) -> Option<usize> {
+        self.find_with(name, symbols, strings, |_, _| true)
+    }
+
+    /// Looks up the index of the first symbol with the given name that is
+    /// accepted by `accept`
+    pub fn find_with(
+        &self,
+        name: &[u8],
+        symbols: &SymbolTable<'_>,
+        strings: &StringTable<'_>,
+        mut accept: impl FnMut(usize, &Symbol64) -> bool,
+    ) -> Option<usize> {
+        let bloom_words = self.bloom.len() / 8;
+        let bucket_count = self.buckets.len() / 4;
+        if bloom_words == 0 || bucket_count == 0 {
+            return None;
+        }
+
+        let hash = gnu_hash(name);
+
+        let word = read_u64(self.bloom, (hash as usize / 64) % bloom_words)?;
+        let mask = (1u64 << (hash % 64)) | (1u64 << ((hash >> self.bloom_shift) % 64));
+        if word & mask != mask {
+            return None;
+        }
+
+        let mut index = read_u32(self.buckets, hash as usize % bucket_count)?;
+        if index < self.symbol_offset {
+            return None;
+        }
+
+        loop {
+            let chain_hash = read_u32(self.chains, (index - self.symbol_offset) as usize)?;
+
+            if chain_hash | 1 == hash | 1 {
+                let symbol = symbols.get(index as usize)?;
+                if symbol_matches(&symbol, strings, name) && accept(index as usize, &symbol) {
+                    return Some(index as usize);
+                }
+            }
+
+            if chain_hash & 1 != 0 {
+                return None;
+            }
+
+            index += 1;
+        }
+    }
+}
+
+/// Either kind of symbol hash table
+#[derive(Debug, Clone, Copy)]
+pub enum SymbolHash<'a> {
+    Sysv(HashTable<'a>),
+    Gnu(GnuHashTable<'a>),
+}
+
+impl SymbolHash<'_> {
+    pub fn symbol_count(&self) -> usize {
+        match self {
+            Self::Sysv(table) => table.symbol_count(),
+            Self::Gnu(table) => table.symbol_count(),
+        }
+    }
+
+    pub fn find(
+        &self,
+        name: &[u8],
+        symbols: &SymbolTable<'_>,
+        strings: &StringTable<'_>,
+    ) -> Option<usize> {
+        self.find_with(name, symbols, strings, |_, _| true)
+    }
+
+    pub fn find_with(
+        &self,
+        name: &[u8],
+        symbols: &SymbolTable<'_>,
+        strings: &StringTable<'_>,
+        accept: impl FnMut(usize, &Symbol64) -> bool,
+    ) -> Option<usize> {
+        match self {
+            Self::Sysv(table) => table.find_with(name, symbols, strings, accept),
+            Self::Gnu(table) => table.find_with(name, symbols, strings, accept),
+        }
+    }
+}
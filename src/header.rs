@@ -1,6 +1,10 @@
-use zerocopy::FromBytes;
+use core::mem::size_of;
+
+use zerocopy::{FromBytes, Immutable, IntoBytes};
 
 use crate::elf_enum;
+use crate::endian::{SwapBytes, impl_swap_bytes};
+use crate::error::{Error, Result};
 
 /// 64-bit ELF header structure (Elf64_Ehdr)
 ///
@@ -8,7 +12,7 @@ use crate::elf_enum;
 /// It provides essential information about the file format, target architecture,
 /// and layout of the ELF file.
 #[repr(C)]
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes, IntoBytes, Immutable)]
 #[doc(alias = "Elf64_Ehdr")]
 pub struct Header64 {
     /// ELF identification magic number [0x7f, 'E', 'L', 'F']
@@ -76,18 +80,87 @@ pub struct Header64 {
     pub section_header_string_table_index: u16,
 }
 
+impl_swap_bytes!(Header64 {
+    magic,
+    class,
+    encoding,
+    header_version,
+    os_abi,
+    abi_version,
+    pad,
+    object_type,
+    machine,
+    version,
+    entry_point,
+    program_header_offset,
+    section_header_offset,
+    flags,
+    header_size,
+    program_header_entry_size,
+    program_header_count,
+    section_header_entry_size,
+    section_header_count,
+    section_header_string_table_index,
+});
+
+impl Header64 {
+    /// Reads an ELF header, converting it from the byte order given by its
+    /// `EI_DATA` byte to the byte order of the host
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        // Data too short for a header is only truncated if what is there
+        // matches the magic number
+        let magic_size = data.len().min(Magic::ELF.inner.len());
+        if data[..magic_size] != Magic::ELF.inner[..magic_size] {
+            return Err(Error::InvalidMagic);
+        }
+
+        let (header, _) = Self::read_from_prefix(data).map_err(|_| Error::OutOfBounds {
+            offset: 0,
+            size: size_of::<Self>() as u64,
+        })?;
+
+        if header.class != Class::ELF64 {
+            return Err(Error::UnsupportedClass(header.class));
+        }
+
+        let encoding = header.encoding.clone();
+        if encoding != Encoding::ELFDATA2LSB && encoding != Encoding::ELFDATA2MSB {
+            return Err(Error::UnsupportedEncoding(encoding));
+        }
+
+        Ok(header.convert(&encoding))
+    }
+
+    /// Serializes the header in the byte order given by its `encoding` field
+    ///
+    /// Writing back a header returned by [`Header64::parse`] reproduces the
+    /// original bytes.
+    pub fn to_bytes(&self) -> [u8; size_of::<Header64>()] {
+        let mut bytes = [0; size_of::<Header64>()];
+        bytes.copy_from_slice(self.clone().convert(&self.encoding).as_bytes());
+        bytes
+    }
+}
+
 #[repr(transparent)]
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes, IntoBytes, Immutable)]
 pub struct Magic {
     inner: [u8; 4],
 }
 
 impl Magic {
+    /// The ELF magic number
+    pub const ELF: Self = Self {
+        inner: [0x7f, b'E', b'L', b'F'],
+    };
+
     pub fn is_valid(&self) -> bool {
         self.inner == [0x7f, b'E', b'L', b'F']
     }
 }
 
+impl_swap_bytes!(Magic { inner });
+
 elf_enum! {
     pub struct Class(u8) {
         NONE = 0, "Invalid",
@@ -104,6 +177,22 @@ elf_enum! {
     }
 }
 
+impl Encoding {
+    /// Returns the encoding matching the byte order of the host
+    pub const fn native() -> Self {
+        if cfg!(target_endian = "little") {
+            Self::ELFDATA2LSB
+        } else {
+            Self::ELFDATA2MSB
+        }
+    }
+
+    /// Returns true if the encoding matches the byte order of the host
+    pub fn is_native(&self) -> bool {
+        *self == Self::native()
+    }
+}
+
 elf_enum! {
     pub struct HeaderVersion(u8) {
         /// Invalid version
@@ -0,0 +1,252 @@
+use core::mem::size_of;
+
+use zerocopy::FromBytes;
+
+use crate::dynamic::{DynamicSymbols, DynamicTable};
+use crate::eh_frame::EhFrameHdr;
+use crate::error::{Error, Result};
+use crate::header::{Class, Header64};
+use crate::note::{GnuNoteType, Note, NoteIter};
+use crate::program_header::{ProgramHeader64, ProgramType};
+use crate::table::{Table, TableIter};
+
+/// A view over an ELF image that is already loaded in memory
+///
+/// Loaded images, such as the running executable, shared objects reported by
+/// `dl_iterate_phdr` or the vDSO, are addressed by virtual address rather
+/// than by file offset, and their section headers are usually not mapped.
+/// Everything is therefore located through the program header table: a
+/// virtual address `vaddr` is found in memory at `vaddr + bias` as long as
+/// it is covered by a `PT_LOAD` segment.
+#[derive(Debug, Clone)]
+pub struct ElfImage<'a> {
+    program_headers: Table<'a, ProgramHeader64>,
+    bias: u64,
+}
+
+impl<'a> ElfImage<'a> {
+    /// Builds a view over an image whose ELF header is mapped at `base`
+    ///
+    /// This is the case for the vDSO, whose address is given by the
+    /// `AT_SYSINFO_EHDR` auxiliary vector entry.
+    ///
+    /// # Safety
+    ///
+    /// `base` must point to an ELF header followed by its program header
+    /// table, and every `PT_LOAD` segment must be mapped at its virtual
+    /// address plus the load bias. The mapping must stay valid and unmodified
+    /// for the lifetime `'a`, except for writable segments which must not be
+    /// written to while borrowed from this view.
+    pub unsafe fn from_base(base: *const u8) -> Result<Self> {
+        // SAFETY: the caller guarantees that a full ELF header is mapped at `base`
+        let header_bytes = unsafe { core::slice::from_raw_parts(base, size_of::<Header64>()) };
+        let header = Header64::read_from_bytes(header_bytes).map_err(|_| Error::InvalidMagic)?;
+
+        if !header.magic.is_valid() {
+            return Err(Error::InvalidMagic);
+        }
+
+        if header.class != Class::ELF64 {
+            return Err(Error::UnsupportedClass(header.class));
+        }
+
+        let entry_size = header.program_header_entry_size as usize;
+        let count = header.program_header_count as usize;
+        // SAFETY: the caller guarantees that the program header table is mapped
+        let program_header_bytes = unsafe {
+            core::slice::from_raw_parts(
+                base.add(header.program_header_offset as usize),
+                entry_size * count,
+            )
+        };
+        let program_headers = Table::with_entry_size(program_header_bytes, entry_size)?;
+
+        // The segment containing the ELF header maps file offset 0 to `base`
+        let first_load = program_headers
+            .iter()
+            .find(|phdr: &ProgramHeader64| phdr.p_type == ProgramType::LOAD)
+            .ok_or(Error::MissingSegment(ProgramType::LOAD))?;
+        let bias = (base as u64)
+            .wrapping_add(first_load.p_offset)
+            .wrapping_sub(first_load.p_vaddr);
+
+        Ok(Self {
+            program_headers,
+            bias,
+        })
+    }
+
+    /// Builds a view from a mapped program header table and a known load bias
+    ///
+    /// This matches the `dlpi_phdr`, `dlpi_phnum` and `dlpi_addr` fields
+    /// reported by `dl_iterate_phdr`.
+    ///
+    /// # Safety
+    ///
+    /// `program_headers` must point to `count` program headers, and every
+    /// `PT_LOAD` segment must be mapped at its virtual address plus `bias`.
+    /// The mapping must stay valid and unmodified for the lifetime `'a`,
+    /// except for writable segments which must not be written to while
+    /// borrowed from this view.
+    pub unsafe fn from_program_headers(
+        program_headers: *const ProgramHeader64,
+        count: usize,
+        bias: u64,
+    ) -> Self {
+        // SAFETY: the caller guarantees that `count` program headers are mapped
+        let bytes = unsafe {
+            core::slice::from_raw_parts(
+                program_headers.cast::<u8>(),
+                count * size_of::<ProgramHeader64>(),
+            )
+        };
+
+        Self {
+            program_headers: Table::new(bytes),
+            bias,
+        }
+    }
+
+    /// Builds a view from a mapped program header table that contains a
+    /// `PT_PHDR` entry, deriving the load bias from the table's own address
+    ///
+    /// This matches the `AT_PHDR` and `AT_PHNUM` auxiliary vector entries of
+    /// the running executable.
+    ///
+    /// # Safety
+    ///
+    /// Same requirements as [`ElfImage::from_program_headers`].
+    pub unsafe fn from_phdr(program_headers: *const ProgramHeader64, count: usize) -> Result<Self> {
+        // SAFETY: forwarded to the caller, the bias is only a placeholder
+        let mut image = unsafe { Self::from_program_headers(program_headers, count, 0) };
+
+        let phdr = image
+            .program_headers
+            .iter()
+            .find(|phdr| phdr.p_type == ProgramType::PHDR)
+            .ok_or(Error::MissingSegment(ProgramType::PHDR))?;
+        image.bias = (program_headers as u64).wrapping_sub(phdr.p_vaddr);
+
+        Ok(image)
+    }
+
+    /// Returns the difference between runtime and link-time addresses
+    pub fn bias(&self) -> u64 {
+        self.bias
+    }
+
+    pub fn program_headers(&self) -> TableIter<'a, ProgramHeader64> {
+        self.program_headers.iter()
+    }
+
+    /// Returns the first program header of the given type
+    pub fn find_segment(&self, p_type: ProgramType) -> Option<ProgramHeader64> {
+        self.program_headers().find(|phdr| phdr.p_type == p_type)
+    }
+
+    /// Returns the loaded segment covering `vaddr`
+    fn load_segment(&self, vaddr: u64) -> Option<ProgramHeader64> {
+        self.program_headers().find(|phdr| {
+            phdr.p_type == ProgramType::LOAD
+                && vaddr >= phdr.p_vaddr
+                && vaddr - phdr.p_vaddr < phdr.p_memsz
+        })
+    }
+
+    /// Translates a link-time virtual address to a pointer into memory
+    pub fn translate(&self, vaddr: u64) -> Option<*const u8> {
+        self.load_segment(vaddr)?;
+        Some(vaddr.wrapping_add(self.bias) as usize as *const u8)
+    }
+
+    /// Returns `size` bytes starting at the link-time virtual address `vaddr`
+    pub fn read(&self, vaddr: u64, size: u64) -> Result<&'a [u8]> {
+        let data = self.read_to_end(vaddr)?;
+        data.get(..size as usize).ok_or(Error::OutOfBounds {
+            offset: vaddr,
+            size,
+        })
+    }
+
+    /// Returns the bytes from `vaddr` to the end of the segment containing it
+    pub fn read_to_end(&self, vaddr: u64) -> Result<&'a [u8]> {
+        let segment = self
+            .load_segment(vaddr)
+            .ok_or(Error::UnmappedAddress(vaddr))?;
+        let size = segment.p_memsz - (vaddr - segment.p_vaddr);
+
+        // SAFETY: the constructors require every `PT_LOAD` segment to be
+        // mapped at its virtual address plus the bias for the lifetime `'a`
+        Ok(unsafe {
+            core::slice::from_raw_parts(
+                vaddr.wrapping_add(self.bias) as usize as *const u8,
+                size as usize,
+            )
+        })
+    }
+
+    /// Returns the in-memory contents of a segment, up to its memory size
+    pub fn segment_data(&self, phdr: &ProgramHeader64) -> Result<&'a [u8]> {
+        self.read(phdr.p_vaddr, phdr.p_memsz)
+    }
+
+    /// Converts an address stored in the dynamic table to a link-time address
+    ///
+    /// The dynamic linker relocates some entries of the dynamic table in
+    /// place on most architectures, so the stored value may already include
+    /// the load bias.
+    pub fn dynamic_address(&self, value: u64) -> u64 {
+        let unbiased = value.wrapping_sub(self.bias);
+        if self.bias != 0 && self.load_segment(unbiased).is_some() {
+            unbiased
+        } else {
+            value
+        }
+    }
+
+    /// Returns the dynamic table from the `PT_DYNAMIC` segment
+    pub fn dynamic(&self) -> Result<DynamicTable<'a>> {
+        let segment = self
+            .find_segment(ProgramType::DYNAMIC)
+            .ok_or(Error::MissingSegment(ProgramType::DYNAMIC))?;
+        Ok(DynamicTable::new(self.segment_data(&segment)?))
+    }
+
+    /// Returns the dynamic symbol table and the tables used to look it up
+    pub fn dynamic_symbols(&self) -> Result<DynamicSymbols<'a>> {
+        DynamicSymbols::parse(&self.dynamic()?, |value, size| {
+            let vaddr = self.dynamic_address(value);
+            match size {
+                Some(size) => self.read(vaddr, size),
+                None => self.read_to_end(vaddr),
+            }
+        })
+    }
+
+    /// Iterates over the notes of every `PT_NOTE` segment
+    pub fn notes(&self) -> impl Iterator<Item = Note<'a>> + '_ {
+        self.program_headers()
+            .filter(|phdr| phdr.p_type == ProgramType::NOTE)
+            .filter_map(|phdr| {
+                self.segment_data(&phdr)
+                    .ok()
+                    .map(|data| NoteIter::new(data, phdr.p_align))
+            })
+            .flatten()
+    }
+
+    /// Returns the descriptor of the `NT_GNU_BUILD_ID` note
+    pub fn build_id(&self) -> Option<&'a [u8]> {
+        self.notes()
+            .find(|note| note.is(Note::GNU, GnuNoteType::BUILD_ID.as_raw()))
+            .map(|note| note.desc)
+    }
+
+    /// Returns the `.eh_frame_hdr` section from the `PT_GNU_EH_FRAME` segment
+    pub fn eh_frame_hdr(&self) -> Result<EhFrameHdr<'a>> {
+        let segment = self
+            .find_segment(ProgramType::GNU_EH_FRAME)
+            .ok_or(Error::MissingSegment(ProgramType::GNU_EH_FRAME))?;
+        EhFrameHdr::parse(self.segment_data(&segment)?, segment.p_vaddr)
+    }
+}
@@ -0,0 +1,192 @@
+use alloc::vec::Vec;
+
+use crate::error::{Error, Result};
+
+/// Description of the memory layout of a linked image, in the spirit of a
+/// linker script
+///
+/// Memory regions (`MEMORY`) are address ranges that output sections are
+/// allocated in, one after the other in the order of the placement rules.
+/// Each output section collects the input sections matching its patterns
+/// and has a run address (VMA) in one region and, optionally, a load address
+/// (LMA) in another, like `.data` stored in flash and copied to RAM at
+/// startup. Boundary symbols let the startup code find these addresses.
+#[derive(Debug, Clone, Default)]
+pub struct Layout {
+    pub(crate) regions: Vec<MemoryRegion>,
+    pub(crate) sections: Vec<SectionPlacement>,
+    pub(crate) discarded: Vec<Vec<u8>>,
+    pub(crate) symbols: Vec<(Vec<u8>, LayoutSymbol)>,
+}
+
+impl Layout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a memory region
+    pub fn region(&mut self, name: &[u8], origin: u64, length: u64) -> &mut Self {
+        self.regions.push(MemoryRegion {
+            name: name.to_vec(),
+            origin,
+            length,
+        });
+        self
+    }
+
+    /// Adds an output section, placed after the ones added before it in the
+    /// same region
+    pub fn section(&mut self, placement: &SectionPlacement) -> &mut Self {
+        self.sections.push(placement.clone());
+        self
+    }
+
+    /// Drops the input sections matching `pattern`, like `/DISCARD/`
+    pub fn discard(&mut self, pattern: &[u8]) -> &mut Self {
+        self.discarded.push(pattern.to_vec());
+        self
+    }
+
+    /// Defines a symbol, unless the inputs define it, like `PROVIDE`
+    pub fn symbol(&mut self, name: &[u8], value: LayoutSymbol) -> &mut Self {
+        self.symbols.push((name.to_vec(), value));
+        self
+    }
+
+    pub fn regions(&self) -> &[MemoryRegion] {
+        &self.regions
+    }
+
+    pub fn sections(&self) -> &[SectionPlacement] {
+        &self.sections
+    }
+
+    pub(crate) fn find_region(&self, name: &[u8]) -> Option<usize> {
+        self.regions.iter().position(|region| region.name == name)
+    }
+
+    /// Returns the index of the output section an input section goes to
+    pub(crate) fn find_section(&self, input: &[u8]) -> Option<usize> {
+        self.sections
+            .iter()
+            .position(|section| section.matches(input))
+    }
+
+    pub(crate) fn is_discarded(&self, input: &[u8]) -> bool {
+        self.discarded
+            .iter()
+            .any(|pattern| glob_match(pattern, input))
+    }
+}
+
+/// A named range of memory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryRegion {
+    pub name: Vec<u8>,
+    /// Start address
+    pub origin: u64,
+    /// Size in bytes
+    pub length: u64,
+}
+
+impl MemoryRegion {
+    /// Returns the address following the region
+    ///
+    /// Fails for a region reaching the end of the address space, whose end
+    /// cannot be represented.
+    pub fn end(&self) -> Result<u64> {
+        self.origin
+            .checked_add(self.length)
+            .ok_or(Error::InvalidData("memory region end out of range"))
+    }
+}
+
+/// Rule creating an output section from the input sections matching its
+/// patterns
+///
+/// Patterns match input section names and may contain `*` wildcards, like
+/// `.text.*`. The pattern `COMMON` matches the common symbols and `.got`
+/// the GOT created by the linker. An input section goes to the first rule
+/// it matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionPlacement {
+    /// Name of the output section
+    pub name: Vec<u8>,
+    /// Patterns of the input sections
+    pub inputs: Vec<Vec<u8>>,
+    /// Region holding the run address (VMA)
+    pub region: Vec<u8>,
+    /// Region holding the load address (LMA), the run region if `None`
+    pub load_region: Option<Vec<u8>>,
+    /// Minimum alignment of the output section
+    pub align: u64,
+}
+
+impl SectionPlacement {
+    /// Creates a rule for the output section `name` in `region`, collecting
+    /// the input sections with the same name
+    pub fn new(name: &[u8], region: &[u8]) -> Self {
+        Self {
+            name: name.to_vec(),
+            inputs: Vec::new(),
+            region: region.to_vec(),
+            load_region: None,
+            align: 1,
+        }
+    }
+
+    /// Adds a pattern of input sections
+    pub fn input(&mut self, pattern: &[u8]) -> &mut Self {
+        self.inputs.push(pattern.to_vec());
+        self
+    }
+
+    /// Loads the section in `region` rather than its run region, like
+    /// `AT>` in a linker script
+    pub fn load_region(&mut self, region: &[u8]) -> &mut Self {
+        self.load_region = Some(region.to_vec());
+        self
+    }
+
+    pub fn align(&mut self, align: u64) -> &mut Self {
+        self.align = align.max(1);
+        self
+    }
+
+    fn matches(&self, input: &[u8]) -> bool {
+        if self.inputs.is_empty() {
+            return self.name == input;
+        }
+        self.inputs.iter().any(|pattern| glob_match(pattern, input))
+    }
+}
+
+/// Value of a symbol defined by a layout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutSymbol {
+    /// Run address of an output section, like `ADDR`
+    SectionStart(Vec<u8>),
+    /// Run address following an output section, like `ADDR + SIZEOF`
+    SectionEnd(Vec<u8>),
+    /// Load address of an output section, like `LOADADDR`
+    SectionLoadStart(Vec<u8>),
+    /// Start of a memory region, like `ORIGIN`
+    RegionStart(Vec<u8>),
+    /// Address following a memory region, like `ORIGIN + LENGTH`, often the
+    /// initial stack pointer
+    RegionEnd(Vec<u8>),
+    /// Fixed address
+    Absolute(u64),
+}
+
+/// Matches `name` against a pattern in which `*` stands for any sequence of
+/// bytes
+fn glob_match(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|skip| glob_match(rest, &name[skip..])),
+        Some((&byte, rest)) => name
+            .split_first()
+            .is_some_and(|(&first, name)| first == byte && glob_match(rest, name)),
+    }
+}
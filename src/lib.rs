@@ -1,7 +1,49 @@
 #![no_std]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+pub mod archive;
+pub mod auxv;
+#[cfg(feature = "alloc")]
+pub mod builder;
+pub mod compression;
+pub mod coredump;
+pub mod debuglink;
+pub mod dependencies;
+pub mod dynamic;
+#[cfg(feature = "alloc")]
+pub mod edit;
+pub mod eh_frame;
+pub mod endian;
+pub mod error;
+pub mod file;
+pub mod group;
+pub mod hash;
 pub mod header;
+pub mod image;
+#[cfg(feature = "alloc")]
+pub mod layout;
+#[cfg(feature = "alloc")]
+pub mod link;
+pub mod note;
+#[cfg(feature = "alloc")]
+pub mod objcopy;
 pub mod program_header;
+pub mod relocation;
+#[cfg(feature = "alloc")]
+mod rewrite;
+pub mod section_header;
+pub mod security;
+pub mod string_table;
+#[cfg(feature = "alloc")]
+pub mod strip;
+pub mod symbol;
+pub mod table;
+pub mod vdso;
+pub mod version;
 
 macro_rules! elf_enum {
     (
@@ -14,7 +56,16 @@ macro_rules! elf_enum {
         }
     ) => {
         #[repr(transparent)]
-        #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes)]
+        #[derive(
+            Clone,
+            PartialEq,
+            Eq,
+            PartialOrd,
+            Ord,
+            zerocopy::FromBytes,
+            zerocopy::IntoBytes,
+            zerocopy::Immutable,
+        )]
         $(#[$attr])*
         $vis struct $name {
             inner: $inner_type,
@@ -65,6 +116,12 @@ macro_rules! elf_enum {
             }
         }
 
+        impl $crate::endian::SwapBytes for $name {
+            fn swap_bytes(self) -> Self {
+                Self::from_raw(self.inner.swap_bytes())
+            }
+        }
+
         impl From<$name> for $inner_type {
             fn from(e: $name) -> $inner_type {
                 e.into_raw()
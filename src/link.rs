@@ -0,0 +1,2214 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+use crate::archive::Archive;
+use crate::endian;
+use crate::error::{Error, Result};
+use crate::file::ElfFile;
+use crate::header::{
+    Class, Encoding, Header64, HeaderVersion, Machine, Magic, ObjectType, Version,
+};
+use crate::layout::{Layout, LayoutSymbol};
+use crate::program_header::{
+    ProgramHeader64, ProgramType, SEGMENT_FLAG_EXECUTE, SEGMENT_FLAG_READ, SEGMENT_FLAG_WRITE,
+};
+use crate::relocation::{
+    Aarch64RelocationType, RelaTable, RiscvRelocationType, X86_64RelocationType,
+};
+use crate::section_header::{
+    SECTION_FLAG_ALLOC, SECTION_FLAG_EXCLUDE, SECTION_FLAG_EXECINSTR, SECTION_FLAG_TLS,
+    SECTION_FLAG_WRITE, SectionHeader64, SectionType,
+};
+use crate::string_table::{StringTable, StringTableBuilder};
+use crate::symbol::{SectionIndex, Symbol64, SymbolBinding, SymbolTable, SymbolType};
+
+/// Static linker combining relocatable objects into an executable
+///
+/// The allocated sections of the inputs are merged by name, so that
+/// `.text.*` sections end up in `.text`, `.rodata.*` in `.rodata`, `.data.*`
+/// in `.data` and `.bss.*` in `.bss`, and only the first copy of each COMDAT
+/// group is kept. Global symbols are resolved across the inputs, pulling
+/// members out of archives as needed, and common symbols are allocated in
+/// `.bss`. Relocations are applied for `EM_X86_64`, `EM_AARCH64` and
+/// `EM_RISCV`, with a `.got` section for the ones that need a GOT entry.
+///
+/// The output is a static `ET_EXEC` file with read-only, executable and
+/// writable `PT_LOAD` segments, in that order, the first one also mapping
+/// the file headers at the base address. A [`Layout`] replaces this with
+/// sections placed in memory regions, with separate load addresses. Debugging
+/// information, notes and thread-local storage are not supported, and
+/// RISC-V objects must be built without linker relaxation (`-mno-relax`).
+#[derive(Debug, Clone)]
+pub struct Linker<'a> {
+    objects: Vec<ElfFile<'a>>,
+    archives: Vec<Archive<'a>>,
+    base_address: u64,
+    page_size: u64,
+    entry: Vec<u8>,
+    layout: Option<Layout>,
+}
+
+impl<'a> Linker<'a> {
+    pub fn new() -> Self {
+        Self {
+            objects: Vec::new(),
+            archives: Vec::new(),
+            base_address: 0x400000,
+            page_size: 0x1000,
+            entry: b"_start".to_vec(),
+            layout: None,
+        }
+    }
+
+    /// Adds a relocatable object, which is always linked in
+    pub fn add_object(&mut self, object: ElfFile<'a>) -> &mut Self {
+        self.objects.push(object);
+        self
+    }
+
+    /// Adds an archive, whose members are linked in when they define a
+    /// symbol that is otherwise undefined
+    ///
+    /// Members are found through the symbol index of the archive.
+    pub fn add_archive(&mut self, archive: Archive<'a>) -> &mut Self {
+        self.archives.push(archive);
+        self
+    }
+
+    /// Sets the address the file headers are loaded at, 0x400000 by default
+    pub fn base_address(&mut self, base_address: u64) -> &mut Self {
+        self.base_address = base_address;
+        self
+    }
+
+    /// Sets the alignment of `PT_LOAD` segments, 0x1000 by default
+    ///
+    /// It must be a power of two.
+    pub fn page_size(&mut self, page_size: u64) -> &mut Self {
+        self.page_size = page_size;
+        self
+    }
+
+    /// Sets the symbol whose address is the entry point, `_start` by default
+    pub fn entry_symbol(&mut self, name: &[u8]) -> &mut Self {
+        self.entry = name.to_vec();
+        self
+    }
+
+    /// Places the output sections as described by `layout`
+    ///
+    /// Every allocated input section must then match a placement rule or be
+    /// discarded. The file headers are not loaded, so the base address is
+    /// unused, and each `PT_LOAD` segment gets the load address of its first
+    /// section as `p_paddr`.
+    pub fn layout(&mut self, layout: Layout) -> &mut Self {
+        self.layout = Some(layout);
+        self
+    }
+
+    /// Links the inputs and writes the executable
+    pub fn link(&self) -> Result<Vec<u8>> {
+        let objects = self.extract_members()?;
+        let first = objects
+            .first()
+            .ok_or(Error::InvalidData("no objects to link"))?;
+        let machine = first.header().machine.clone();
+        for object in &objects {
+            let header = object.header();
+            if header.object_type != ObjectType::REL {
+                return Err(Error::InvalidData(
+                    "linker input is not a relocatable object",
+                ));
+            }
+            if header.machine != machine {
+                return Err(Error::InvalidData("linker inputs have different machines"));
+            }
+        }
+        if machine != Machine::X86_64 && machine != Machine::AARCH64 && machine != Machine::RISCV {
+            return Err(Error::InvalidData("unsupported machine"));
+        }
+        if !self.page_size.is_power_of_two() {
+            return Err(Error::InvalidData("page size is not a power of two"));
+        }
+
+        let mut link = Link::new(self, objects, machine)?;
+        link.scan_got()?;
+        link.place_sections()?;
+        link.resolve_symbols()?;
+        link.allocate_commons()?;
+        link.assign_addresses()?;
+        link.fill()?;
+        link.write()
+    }
+
+    /// Returns the objects followed by the archive members they need
+    fn extract_members(&self) -> Result<Vec<ElfFile<'a>>> {
+        let mut objects = self.objects.clone();
+        let mut extracted = BTreeSet::new();
+        loop {
+            let undefined = undefined_symbols(&objects)?;
+            let mut added = false;
+            for (index, archive) in self.archives.iter().enumerate() {
+                let Some(symbols) = archive.symbols() else {
+                    continue;
+                };
+                for (name, offset) in symbols.iter() {
+                    if undefined.contains(name) && extracted.insert((index, offset)) {
+                        objects.push(archive.member_at(offset)?.elf()?);
+                        added = true;
+                    }
+                }
+            }
+            if !added {
+                return Ok(objects);
+            }
+        }
+    }
+}
+
+impl Default for Linker<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the names of the global symbols referenced but not defined by
+/// `objects`
+fn undefined_symbols<'a>(objects: &[ElfFile<'a>]) -> Result<BTreeSet<&'a [u8]>> {
+    let mut defined = BTreeSet::new();
+    let mut referenced = BTreeSet::new();
+    for object in objects {
+        let Some((symbols, strings)) = symbol_table(object)? else {
+            continue;
+        };
+        for symbol in symbols.iter().skip(1) {
+            if symbol.binding() == SymbolBinding::LOCAL {
+                continue;
+            }
+            let Some(name) = strings.get(symbol.st_name) else {
+                continue;
+            };
+            if symbol.is_undefined() {
+                if symbol.binding() != SymbolBinding::WEAK {
+                    referenced.insert(name);
+                }
+            } else {
+                defined.insert(name);
+            }
+        }
+    }
+    Ok(referenced.difference(&defined).copied().collect())
+}
+
+fn symbol_table<'a>(object: &ElfFile<'a>) -> Result<Option<(SymbolTable<'a>, StringTable<'a>)>> {
+    let Some(shdr) = object
+        .section_headers()
+        .find(|shdr| shdr.sh_type == SectionType::SYMTAB)
+    else {
+        return Ok(None);
+    };
+    Ok(Some((
+        object.symbols(&shdr)?,
+        object.linked_strings(&shdr)?,
+    )))
+}
+
+/// Where an input section is placed in the output
+#[derive(Debug, Clone, Copy)]
+struct Placement {
+    output: usize,
+    offset: u64,
+}
+
+#[derive(Debug, Clone)]
+struct OutputSection {
+    name: Vec<u8>,
+    sh_type: SectionType,
+    sh_flags: u64,
+    align: u64,
+    size: u64,
+    data: Vec<u8>,
+    address: u64,
+    load_address: u64,
+    offset: u64,
+}
+
+impl OutputSection {
+    fn new(name: &[u8]) -> Self {
+        Self {
+            name: name.to_vec(),
+            sh_type: SectionType::NOBITS,
+            sh_flags: SECTION_FLAG_ALLOC,
+            align: 1,
+            size: 0,
+            data: Vec::new(),
+            address: 0,
+            load_address: 0,
+            offset: 0,
+        }
+    }
+
+    fn is_nobits(&self) -> bool {
+        self.sh_type == SectionType::NOBITS
+    }
+
+    fn file_size(&self) -> u64 {
+        if self.is_nobits() { 0 } else { self.size }
+    }
+
+    /// Reserves `size` bytes aligned to `align` for an input section and
+    /// returns their offset
+    fn add(&mut self, sh_type: &SectionType, sh_flags: u64, size: u64, align: u64) -> Result<u64> {
+        let align = align.max(1);
+        let offset = self.size.checked_next_multiple_of(align).ok_or(TOO_LARGE)?;
+        self.size = offset.checked_add(size).ok_or(TOO_LARGE)?;
+        self.sh_flags |= sh_flags & (SECTION_FLAG_WRITE | SECTION_FLAG_EXECINSTR);
+        if *sh_type != SectionType::NOBITS {
+            self.sh_type = SectionType::PROGBITS;
+        }
+        self.align = self.align.max(align);
+        Ok(offset)
+    }
+}
+
+/// How strongly a global symbol is defined, in increasing precedence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Strength {
+    Weak,
+    Common,
+    Strong,
+}
+
+#[derive(Debug, Clone)]
+struct Global<'a> {
+    name: &'a [u8],
+    strength: Strength,
+    object: usize,
+    symbol: Symbol64,
+    /// Offset of a common symbol in its output section
+    common_offset: u64,
+}
+
+/// Identifies a symbol for GOT entries: globals by name, locals by object
+/// and index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum SymbolKey<'a> {
+    Global(&'a [u8]),
+    Local(usize, usize),
+}
+
+/// Size of a GOT entry
+const GOT_ENTRY_SIZE: u64 = 8;
+
+const TOO_LARGE: Error = Error::InvalidData("output too large");
+
+/// State of a link in progress
+struct Link<'l, 'a> {
+    linker: &'l Linker<'a>,
+    objects: Vec<ElfFile<'a>>,
+    machine: Machine,
+    symbol_tables: Vec<Option<(SymbolTable<'a>, StringTable<'a>)>>,
+    /// Sections of each object dropped as duplicate COMDAT group members
+    discarded: Vec<Vec<bool>>,
+    placements: Vec<Vec<Option<Placement>>>,
+    outputs: Vec<OutputSection>,
+    /// Output sections in file order
+    order: Vec<usize>,
+    globals: Vec<Global<'a>>,
+    global_index: BTreeMap<&'a [u8], usize>,
+    got: BTreeMap<SymbolKey<'a>, u64>,
+    got_placement: Option<Placement>,
+    common_output: Option<usize>,
+    program_headers: Vec<ProgramHeader64>,
+    /// File offset following the contents of the allocated sections
+    end_offset: u64,
+}
+
+impl<'l, 'a> Link<'l, 'a> {
+    fn new(linker: &'l Linker<'a>, objects: Vec<ElfFile<'a>>, machine: Machine) -> Result<Self> {
+        let mut symbol_tables = Vec::with_capacity(objects.len());
+        let mut discarded = Vec::with_capacity(objects.len());
+        let mut signatures = BTreeSet::new();
+        for object in &objects {
+            symbol_tables.push(symbol_table(object)?);
+
+            // Only the first group with a given signature is kept
+            let mut dropped = vec![false; object.section_count()];
+            for group in object.section_groups() {
+                let group = group?;
+                let Some(signature) = group.signature else {
+                    continue;
+                };
+                if group.is_comdat() && !signatures.insert(signature) {
+                    for member in group.members() {
+                        if let Some(dropped) = dropped.get_mut(member) {
+                            *dropped = true;
+                        }
+                    }
+                }
+            }
+            discarded.push(dropped);
+        }
+
+        // With a layout, the output sections are those of its rules
+        let outputs = match &linker.layout {
+            Some(layout) => layout
+                .sections()
+                .iter()
+                .map(|section| OutputSection::new(&section.name))
+                .collect(),
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            linker,
+            placements: objects
+                .iter()
+                .map(|object| vec![None; object.section_count()])
+                .collect(),
+            objects,
+            machine,
+            symbol_tables,
+            discarded,
+            outputs,
+            order: Vec::new(),
+            globals: Vec::new(),
+            global_index: BTreeMap::new(),
+            got: BTreeMap::new(),
+            got_placement: None,
+            common_output: None,
+            program_headers: Vec::new(),
+            end_offset: 0,
+        })
+    }
+
+    fn symbol(&self, object: usize, index: usize) -> Result<(Symbol64, &'a [u8])> {
+        let (symbols, strings) = self.symbol_tables[object]
+            .as_ref()
+            .ok_or(Error::InvalidData("relocation without a symbol table"))?;
+        let symbol = symbols
+            .get(index)
+            .ok_or(Error::InvalidData("invalid symbol index"))?;
+        let name = strings.get(symbol.st_name).unwrap_or_default();
+        Ok((symbol, name))
+    }
+
+    fn symbol_key(&self, object: usize, index: usize) -> Result<SymbolKey<'a>> {
+        let (symbol, name) = self.symbol(object, index)?;
+        Ok(if symbol.binding() == SymbolBinding::LOCAL {
+            SymbolKey::Local(object, index)
+        } else {
+            SymbolKey::Global(name)
+        })
+    }
+
+    /// Iterates over the relocation sections applying to kept sections
+    fn relocation_sections(&self) -> impl Iterator<Item = (usize, SectionHeader64)> + '_ {
+        self.objects
+            .iter()
+            .enumerate()
+            .flat_map(move |(object, file)| {
+                file.section_headers()
+                    .filter(move |shdr| {
+                        (shdr.sh_type == SectionType::RELA || shdr.sh_type == SectionType::REL)
+                            && self.is_kept(object, shdr.sh_info as usize)
+                    })
+                    .map(move |shdr| (object, shdr))
+            })
+    }
+
+    fn is_kept(&self, object: usize, section: usize) -> bool {
+        let file = &self.objects[object];
+        let Some(shdr) = file.section_header(section) else {
+            return false;
+        };
+        let name = file.section_name(&shdr);
+        !self.discarded[object].get(section).copied().unwrap_or(true)
+            && is_linked(&shdr, name)
+            && !self
+                .linker
+                .layout
+                .as_ref()
+                .is_some_and(|layout| layout.is_discarded(name.unwrap_or_default()))
+    }
+
+    /// Returns the output section of the input sections named `name`
+    ///
+    /// The GOT is named `.got` and the common symbols `COMMON`.
+    fn output_for(&mut self, name: &[u8]) -> Result<usize> {
+        if let Some(layout) = &self.linker.layout {
+            return layout
+                .find_section(name)
+                .ok_or(Error::InvalidData("input section not placed by the layout"));
+        }
+
+        let name = if name == b"COMMON" {
+            b".bss"
+        } else {
+            output_name(name)
+        };
+        if let Some(output) = self.outputs.iter().position(|output| output.name == name) {
+            return Ok(output);
+        }
+        self.outputs.push(OutputSection::new(name));
+        Ok(self.outputs.len() - 1)
+    }
+
+    /// Assigns a GOT entry to every symbol referenced through the GOT
+    fn scan_got(&mut self) -> Result<()> {
+        let mut keys = Vec::new();
+        for (object, shdr) in self.relocation_sections() {
+            if shdr.sh_type == SectionType::REL {
+                return Err(Error::InvalidData("REL relocations are not supported"));
+            }
+            let file = &self.objects[object];
+            let data = file.section_data(&shdr)?;
+            for relocation in RelaTable::with_entry_size(data, shdr.sh_entsize as usize)?.iter() {
+                if uses_got(&self.machine, relocation.r_type()) {
+                    keys.push(self.symbol_key(object, relocation.symbol() as usize)?);
+                }
+            }
+        }
+
+        for key in keys {
+            let next = self.got.len() as u64;
+            self.got.entry(key).or_insert(next);
+        }
+        if !self.got.is_empty() {
+            let output = self.output_for(b".got")?;
+            let offset = self.outputs[output].add(
+                &SectionType::PROGBITS,
+                SECTION_FLAG_WRITE,
+                self.got.len() as u64 * GOT_ENTRY_SIZE,
+                GOT_ENTRY_SIZE,
+            )?;
+            self.got_placement = Some(Placement { output, offset });
+        }
+        Ok(())
+    }
+
+    /// Merges the input sections into output sections
+    fn place_sections(&mut self) -> Result<()> {
+        for object in 0..self.objects.len() {
+            let file = self.objects[object].clone();
+            for (index, shdr) in file.section_headers().enumerate() {
+                if !self.is_kept(object, index) {
+                    continue;
+                }
+                if shdr.sh_flags & SECTION_FLAG_TLS != 0 {
+                    return Err(Error::InvalidData("thread-local storage is not supported"));
+                }
+
+                // The contents are copied later, so their size must be
+                // backed by the file
+                if shdr.sh_type != SectionType::NOBITS {
+                    file.section_data(&shdr)?;
+                }
+
+                let output = self.output_for(file.section_name(&shdr).unwrap_or_default())?;
+                let offset = self.outputs[output].add(
+                    &shdr.sh_type,
+                    shdr.sh_flags,
+                    shdr.sh_size,
+                    shdr.sh_addralign,
+                )?;
+                self.placements[object][index] = Some(Placement { output, offset });
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds the global symbol table from the definitions of every object
+    fn resolve_symbols(&mut self) -> Result<()> {
+        for object in 0..self.objects.len() {
+            let Some((symbols, strings)) = self.symbol_tables[object].clone() else {
+                continue;
+            };
+            for symbol in symbols.iter().skip(1) {
+                let binding = symbol.binding();
+                if binding == SymbolBinding::LOCAL || symbol.is_undefined() {
+                    continue;
+                }
+                let Some(name) = strings.get(symbol.st_name) else {
+                    continue;
+                };
+                let strength = if symbol.st_shndx == SectionIndex::COMMON {
+                    Strength::Common
+                } else if binding == SymbolBinding::WEAK {
+                    Strength::Weak
+                } else {
+                    Strength::Strong
+                };
+                let section = symbol.st_shndx.as_raw();
+                if section == SectionIndex::XINDEX.as_raw() {
+                    return Err(Error::InvalidData(
+                        "extended section indices are not supported",
+                    ));
+                }
+                // The definitions in a dropped group come from the kept copy
+                if section < SectionIndex::LORESERVE.as_raw()
+                    && self.placements[object]
+                        .get(section as usize)
+                        .ok_or(Error::InvalidData("invalid symbol section index"))?
+                        .is_none()
+                {
+                    continue;
+                }
+
+                let global = Global {
+                    name,
+                    strength,
+                    object,
+                    symbol: symbol.clone(),
+                    common_offset: 0,
+                };
+                match self.global_index.get(name) {
+                    None => {
+                        self.global_index.insert(name, self.globals.len());
+                        self.globals.push(global);
+                    }
+                    Some(&index) => {
+                        let existing = &mut self.globals[index];
+                        if strength == Strength::Strong && existing.strength == Strength::Strong {
+                            return Err(Error::InvalidData("duplicate symbol definition"));
+                        }
+                        if strength == Strength::Common && existing.strength == Strength::Common {
+                            // The largest common block wins, with the
+                            // strictest alignment
+                            let align = existing.symbol.st_value.max(symbol.st_value);
+                            if symbol.st_size > existing.symbol.st_size {
+                                *existing = global;
+                            }
+                            existing.symbol.st_value = align;
+                        } else if strength > existing.strength {
+                            *existing = global;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Allocates the common symbols at the end of `.bss`
+    fn allocate_commons(&mut self) -> Result<()> {
+        for index in 0..self.globals.len() {
+            if self.globals[index].strength != Strength::Common {
+                continue;
+            }
+            let output = match self.common_output {
+                Some(output) => output,
+                None => self.output_for(b"COMMON")?,
+            };
+            self.common_output = Some(output);
+            let symbol = &self.globals[index].symbol;
+            let offset = self.outputs[output].add(
+                &SectionType::NOBITS,
+                SECTION_FLAG_WRITE,
+                symbol.st_size,
+                symbol.st_value,
+            )?;
+            self.globals[index].common_offset = offset;
+        }
+        Ok(())
+    }
+
+    /// Assigns addresses and file offsets and creates the program headers
+    fn assign_addresses(&mut self) -> Result<()> {
+        let linker = self.linker;
+        match &linker.layout {
+            Some(layout) => self.assign_regions(layout)?,
+            None => self.assign_default()?,
+        }
+
+        self.program_headers.push(ProgramHeader64 {
+            p_type: ProgramType::GNU_STACK,
+            p_flags: SEGMENT_FLAG_READ | SEGMENT_FLAG_WRITE,
+            p_offset: 0,
+            p_vaddr: 0,
+            p_paddr: 0,
+            p_filesz: 0,
+            p_memsz: 0,
+            p_align: 16,
+        });
+        Ok(())
+    }
+
+    /// Places the sections in read-only, executable and writable segments,
+    /// the first one also mapping the file headers at the base address
+    fn assign_default(&mut self) -> Result<()> {
+        let linker = self.linker;
+        let page_size = linker.page_size;
+
+        self.order = (0..self.outputs.len()).collect();
+        self.order.sort_by_key(|&index| {
+            let output = &self.outputs[index];
+            (segment_class(output.sh_flags), output.is_nobits())
+        });
+
+        let classes: Vec<usize> = (0..=WRITABLE)
+            .filter(|&class| {
+                class == 0
+                    || self
+                        .outputs
+                        .iter()
+                        .any(|output| segment_class(output.sh_flags) == class)
+            })
+            .collect();
+
+        // One PT_LOAD per class and PT_GNU_STACK
+        let count = classes.len() + 1;
+        let headers_size = (size_of::<Header64>() + count * size_of::<ProgramHeader64>()) as u64;
+        let mut offset = headers_size;
+        let mut address = linker
+            .base_address
+            .checked_add(headers_size)
+            .ok_or(TOO_LARGE)?;
+
+        for &class in &classes {
+            if class != 0 {
+                offset = offset.next_multiple_of(page_size);
+                address = address
+                    .checked_next_multiple_of(page_size)
+                    .ok_or(TOO_LARGE)?;
+            }
+
+            let mut load = ProgramHeader64 {
+                p_type: ProgramType::LOAD,
+                p_flags: SEGMENT_FLAG_READ,
+                p_offset: offset,
+                p_vaddr: address,
+                p_paddr: address,
+                p_filesz: 0,
+                p_memsz: 0,
+                p_align: page_size,
+            };
+            if class == 0 {
+                load.p_offset = 0;
+                load.p_vaddr = linker.base_address;
+                load.p_paddr = linker.base_address;
+            }
+
+            for &index in &self.order {
+                let output = &mut self.outputs[index];
+                if segment_class(output.sh_flags) != class {
+                    continue;
+                }
+
+                let aligned = address
+                    .checked_next_multiple_of(output.align)
+                    .ok_or(TOO_LARGE)?;
+                if !output.is_nobits() {
+                    offset += aligned - address;
+                }
+                address = aligned;
+                output.address = address;
+                output.load_address = address;
+                output.offset = offset;
+                address = address.checked_add(output.size).ok_or(TOO_LARGE)?;
+                offset += output.file_size();
+
+                if output.sh_flags & SECTION_FLAG_EXECINSTR != 0 {
+                    load.p_flags |= SEGMENT_FLAG_EXECUTE;
+                }
+                if output.sh_flags & SECTION_FLAG_WRITE != 0 {
+                    load.p_flags |= SEGMENT_FLAG_WRITE;
+                }
+            }
+
+            load.p_filesz = offset - load.p_offset;
+            load.p_memsz = address - load.p_vaddr;
+            self.program_headers.push(load);
+        }
+        self.end_offset = offset;
+        Ok(())
+    }
+
+    /// Places the sections in the memory regions of `layout`, in the order
+    /// of its rules
+    ///
+    /// Consecutive sections sharing their run and load regions go to the
+    /// same `PT_LOAD` segment, as long as the distance between the run and
+    /// load addresses stays the same and no `SHT_PROGBITS` section follows a
+    /// `SHT_NOBITS` one.
+    ///
+    /// Load addresses are aligned like run addresses, as with
+    /// `ALIGN_WITH_INPUT`, so the distance stays the same within a segment.
+    fn assign_regions(&mut self, layout: &Layout) -> Result<()> {
+        let region_index = |name: &[u8]| {
+            layout
+                .find_region(name)
+                .ok_or(Error::InvalidData("unknown memory region"))
+        };
+
+        let mut cursors: Vec<u64> = layout
+            .regions()
+            .iter()
+            .map(|region| region.origin)
+            .collect();
+        let mut segments: Vec<(usize, usize, Vec<usize>)> = Vec::new();
+        for (index, rule) in layout.sections().iter().enumerate() {
+            let region = region_index(&rule.region)?;
+            let load_region = match &rule.load_region {
+                Some(name) => region_index(name)?,
+                None => region,
+            };
+
+            let output = &mut self.outputs[index];
+            output.align = output.align.max(rule.align);
+            let place = |cursor: u64| {
+                let address = cursor
+                    .checked_next_multiple_of(output.align)
+                    .ok_or(TOO_LARGE)?;
+                let end = address.checked_add(output.size).ok_or(TOO_LARGE)?;
+                Ok::<_, Error>((address, end))
+            };
+            (output.address, cursors[region]) = place(cursors[region])?;
+            output.load_address = output.address;
+            if load_region != region && !output.is_nobits() {
+                (output.load_address, cursors[load_region]) = place(cursors[load_region])?;
+            }
+            for region in [region, load_region] {
+                if cursors[region] > layout.regions()[region].end()? {
+                    return Err(Error::InvalidData("memory region overflow"));
+                }
+            }
+            if output.size == 0 {
+                continue;
+            }
+
+            let outputs = &self.outputs;
+            let joins = segments.last().is_some_and(|(run, load, members)| {
+                let last = &outputs[*members.last().unwrap()];
+                let output = &outputs[index];
+                *run == region
+                    && *load == load_region
+                    && last.address.wrapping_sub(last.load_address)
+                        == output.address.wrapping_sub(output.load_address)
+                    && (output.is_nobits() || !last.is_nobits())
+            });
+            match segments.last_mut() {
+                Some((_, _, members)) if joins => members.push(index),
+                _ => segments.push((region, load_region, vec![index])),
+            }
+        }
+
+        // One PT_LOAD per segment and PT_GNU_STACK
+        let count = segments.len() + 1;
+        let page_size = self.linker.page_size;
+        let mut offset = (size_of::<Header64>() + count * size_of::<ProgramHeader64>()) as u64;
+        for (_, _, members) in &segments {
+            let first = &self.outputs[members[0]];
+            let (vaddr, paddr) = (first.address, first.load_address);
+            // Keep the offset congruent to the address modulo the alignment
+            offset += vaddr.wrapping_sub(offset) % page_size;
+
+            let mut load = ProgramHeader64 {
+                p_type: ProgramType::LOAD,
+                p_flags: SEGMENT_FLAG_READ,
+                p_offset: offset,
+                p_vaddr: vaddr,
+                p_paddr: paddr,
+                p_filesz: 0,
+                p_memsz: 0,
+                p_align: page_size,
+            };
+            for &index in members {
+                let output = &mut self.outputs[index];
+                output.offset = load.p_offset + (output.address - vaddr);
+                if !output.is_nobits() {
+                    offset = output.offset + output.size;
+                }
+                load.p_memsz = output.address + output.size - vaddr;
+                if output.sh_flags & SECTION_FLAG_EXECINSTR != 0 {
+                    load.p_flags |= SEGMENT_FLAG_EXECUTE;
+                }
+                if output.sh_flags & SECTION_FLAG_WRITE != 0 {
+                    load.p_flags |= SEGMENT_FLAG_WRITE;
+                }
+            }
+            load.p_filesz = offset - load.p_offset;
+            self.program_headers.push(load);
+        }
+
+        self.order = (0..self.outputs.len()).collect();
+        self.end_offset = offset;
+        Ok(())
+    }
+
+    /// Returns the address of a defined symbol, or `None` if it lives in a
+    /// discarded section
+    fn defined_address(&self, object: usize, symbol: &Symbol64) -> Result<Option<u64>> {
+        let section = symbol.st_shndx.as_raw();
+        if section == SectionIndex::ABS.as_raw() {
+            return Ok(Some(symbol.st_value));
+        }
+        if section == SectionIndex::XINDEX.as_raw() || section >= SectionIndex::LORESERVE.as_raw() {
+            return Err(Error::InvalidData("unsupported symbol section index"));
+        }
+        Ok(self.placements[object]
+            .get(section as usize)
+            .copied()
+            .flatten()
+            .map(|placement| {
+                self.outputs[placement.output].address + placement.offset + symbol.st_value
+            }))
+    }
+
+    fn global_address(&self, global: &Global) -> Result<u64> {
+        if global.strength == Strength::Common {
+            let output = self.common_output.unwrap_or_default();
+            return Ok(self.outputs[output].address + global.common_offset);
+        }
+        Ok(self
+            .defined_address(global.object, &global.symbol)?
+            .unwrap_or_default())
+    }
+
+    /// Returns the address and size of the symbol a relocation refers to
+    fn resolve(&self, object: usize, index: usize) -> Result<(u64, u64)> {
+        let (symbol, name) = self.symbol(object, index)?;
+        if symbol.binding() == SymbolBinding::LOCAL {
+            // References to sections of dropped groups resolve to 0, like
+            // the unwinding information of their functions
+            let address = self.defined_address(object, &symbol)?.unwrap_or_default();
+            return Ok((address, symbol.st_size));
+        }
+
+        match self.global_symbol(name)? {
+            Some(resolved) => Ok(resolved),
+            None if symbol.binding() == SymbolBinding::WEAK => Ok((0, 0)),
+            None => Err(Error::InvalidData("undefined symbol")),
+        }
+    }
+
+    /// Returns the address and size of a global symbol defined by the
+    /// inputs, the layout or the linker, or `None` if it is undefined
+    fn global_symbol(&self, name: &[u8]) -> Result<Option<(u64, u64)>> {
+        if let Some(&global) = self.global_index.get(name) {
+            let global = &self.globals[global];
+            return Ok(Some((self.global_address(global)?, global.symbol.st_size)));
+        }
+        if let Some((address, _)) = self.layout_symbol(name)? {
+            return Ok(Some((address, 0)));
+        }
+        if name == b"_GLOBAL_OFFSET_TABLE_" {
+            return Ok(Some((self.got_address(), 0)));
+        }
+        Ok(None)
+    }
+
+    /// Returns the address of a symbol defined by the layout and the output
+    /// section it is relative to
+    fn layout_symbol(&self, name: &[u8]) -> Result<Option<(u64, Option<usize>)>> {
+        let Some(layout) = &self.linker.layout else {
+            return Ok(None);
+        };
+        let Some((_, value)) = layout.symbols.iter().find(|(symbol, _)| symbol == name) else {
+            return Ok(None);
+        };
+
+        let section = |name: &[u8]| {
+            layout
+                .sections()
+                .iter()
+                .position(|section| section.name == name)
+                .ok_or(Error::InvalidData("unknown output section"))
+        };
+        let region = |name: &[u8]| {
+            layout
+                .find_region(name)
+                .map(|index| &layout.regions()[index])
+                .ok_or(Error::InvalidData("unknown memory region"))
+        };
+        let value = match value {
+            LayoutSymbol::SectionStart(name) => {
+                let output = section(name)?;
+                (self.outputs[output].address, Some(output))
+            }
+            LayoutSymbol::SectionEnd(name) => {
+                let output = section(name)?;
+                let output_section = &self.outputs[output];
+                (output_section.address + output_section.size, Some(output))
+            }
+            LayoutSymbol::SectionLoadStart(name) => {
+                let output = section(name)?;
+                (self.outputs[output].load_address, Some(output))
+            }
+            LayoutSymbol::RegionStart(name) => (region(name)?.origin, None),
+            LayoutSymbol::RegionEnd(name) => (region(name)?.end()?, None),
+            LayoutSymbol::Absolute(address) => (*address, None),
+        };
+        Ok(Some(value))
+    }
+
+    fn got_address(&self) -> u64 {
+        self.got_placement.map_or(0, |placement| {
+            self.outputs[placement.output].address + placement.offset
+        })
+    }
+
+    /// Copies the input sections and applies their relocations
+    fn fill(&mut self) -> Result<()> {
+        for output in &mut self.outputs {
+            if !output.is_nobits() {
+                output.data = vec![0; usize::try_from(output.size).map_err(|_| TOO_LARGE)?];
+            }
+        }
+
+        for object in 0..self.objects.len() {
+            let file = &self.objects[object];
+            for (index, shdr) in file.section_headers().enumerate() {
+                let Some(placement) = self.placements[object][index] else {
+                    continue;
+                };
+                if shdr.sh_type == SectionType::NOBITS {
+                    continue;
+                }
+                let data = file.section_data(&shdr)?;
+                let start = placement.offset as usize;
+                self.outputs[placement.output].data[start..start + data.len()]
+                    .copy_from_slice(data);
+            }
+        }
+
+        let mut entries = Vec::with_capacity(self.got.len());
+        for (&key, &slot) in &self.got {
+            let address = match key {
+                // Undefined weak symbols have a null entry
+                SymbolKey::Global(name) => {
+                    self.global_symbol(name)?.map_or(0, |(address, _)| address)
+                }
+                SymbolKey::Local(object, index) => self.resolve(object, index)?.0,
+            };
+            entries.push((slot, address));
+        }
+        if let Some(placement) = self.got_placement {
+            for (slot, address) in entries {
+                let offset = (placement.offset + slot * GOT_ENTRY_SIZE) as usize;
+                self.outputs[placement.output].data[offset..offset + 8]
+                    .copy_from_slice(&address.to_ne_bytes());
+            }
+        }
+
+        let sections: Vec<(usize, SectionHeader64)> = self.relocation_sections().collect();
+        for (object, shdr) in sections {
+            self.relocate_section(object, &shdr)?;
+        }
+        Ok(())
+    }
+
+    fn relocate_section(&mut self, object: usize, shdr: &SectionHeader64) -> Result<()> {
+        let file = &self.objects[object];
+        let target = shdr.sh_info as usize;
+        let Some(placement) = self.placements[object][target] else {
+            return Ok(());
+        };
+        let Some(target_header) = file.section_header(target) else {
+            return Ok(());
+        };
+        if target_header.sh_type == SectionType::NOBITS {
+            return Ok(());
+        }
+
+        let data = file.section_data(shdr)?;
+        let relocations = RelaTable::with_entry_size(data, shdr.sh_entsize as usize)?;
+        let base = self.outputs[placement.output].address + placement.offset;
+
+        // RISC-V %pcrel_lo relocations refer to the instruction holding the
+        // matching %pcrel_hi relocation
+        let mut high_parts = BTreeMap::new();
+        let mut resolved = Vec::with_capacity(relocations.len());
+        for relocation in relocations.iter() {
+            if relocation.r_offset > target_header.sh_size {
+                return Err(Error::OutOfBounds {
+                    offset: relocation.r_offset,
+                    size: target_header.sh_size,
+                });
+            }
+            let (symbol, size) = self.resolve(object, relocation.symbol() as usize)?;
+            let got = if uses_got(&self.machine, relocation.r_type()) {
+                let key = self.symbol_key(object, relocation.symbol() as usize)?;
+                self.got_address() + self.got[&key] * GOT_ENTRY_SIZE
+            } else {
+                0
+            };
+            let target = Target {
+                place: base + relocation.r_offset,
+                symbol,
+                addend: relocation.r_addend,
+                got,
+                size,
+                got_base: self.got_address(),
+            };
+            if self.machine == Machine::RISCV {
+                let r_type = RiscvRelocationType::from_raw(relocation.r_type());
+                if r_type == RiscvRelocationType::PCREL_HI20 {
+                    high_parts.insert(target.place, target.pc_relative());
+                } else if r_type == RiscvRelocationType::GOT_HI20 {
+                    high_parts.insert(target.place, target.got_relative());
+                }
+            }
+            resolved.push((relocation.r_offset, relocation.r_type(), target));
+        }
+
+        // Relocations cannot write past their section into the next one
+        let output = &mut self.outputs[placement.output];
+        let start = placement.offset as usize;
+        let section = &mut output.data[start..start + target_header.sh_size as usize];
+        for (r_offset, r_type, target) in resolved {
+            let location = &mut section[r_offset as usize..];
+            match self.machine {
+                Machine::X86_64 => relocate_x86_64(r_type, location, &target)?,
+                Machine::AARCH64 => relocate_aarch64(r_type, location, &target)?,
+                _ => relocate_riscv(r_type, location, &target, &high_parts)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the executable
+    fn write(self) -> Result<Vec<u8>> {
+        let encoding = Encoding::native();
+        let entry_name = self.linker.entry.as_slice();
+        let entry = match self.global_index.get(entry_name) {
+            Some(&global) => self.global_address(&self.globals[global])?,
+            None => match self.layout_symbol(entry_name)? {
+                Some((address, _)) => address,
+                None => return Err(Error::InvalidData("undefined entry symbol")),
+            },
+        };
+
+        // Output section indices follow the file order
+        let mut section_indices = vec![0; self.outputs.len()];
+        for (position, &index) in self.order.iter().enumerate() {
+            section_indices[index] = position as u16 + 1;
+        }
+        let symtab_index = self.order.len() + 1;
+
+        let mut strtab = StringTableBuilder::new();
+        let mut symbols = vec![Symbol64 {
+            st_name: 0,
+            st_info: 0,
+            st_other: 0,
+            st_shndx: SectionIndex::UNDEF,
+            st_value: 0,
+            st_size: 0,
+        }];
+        let output_index = |object: usize, symbol: &Symbol64| {
+            let section = symbol.st_shndx.as_raw();
+            if section == SectionIndex::ABS.as_raw() {
+                Some(SectionIndex::ABS)
+            } else {
+                self.placements[object]
+                    .get(section as usize)
+                    .copied()
+                    .flatten()
+                    .map(|placement| SectionIndex::from_raw(section_indices[placement.output]))
+            }
+        };
+
+        for object in 0..self.objects.len() {
+            let Some((table, strings)) = &self.symbol_tables[object] else {
+                continue;
+            };
+            for symbol in table.iter().skip(1) {
+                let symbol_type = symbol.symbol_type();
+                if symbol.binding() != SymbolBinding::LOCAL
+                    || symbol_type == SymbolType::SECTION
+                    || symbol_type == SymbolType::FILE
+                {
+                    continue;
+                }
+                let (Some(name), Some(st_shndx)) =
+                    (strings.get(symbol.st_name), output_index(object, &symbol))
+                else {
+                    continue;
+                };
+                if name.is_empty() {
+                    continue;
+                }
+                let st_value = self.defined_address(object, &symbol)?.unwrap_or_default();
+                symbols.push(Symbol64 {
+                    st_name: strtab.add(name),
+                    st_shndx,
+                    st_value,
+                    ..symbol
+                });
+            }
+        }
+        let first_global = symbols.len() as u32;
+        for global in &self.globals {
+            let st_shndx = if global.strength == Strength::Common {
+                SectionIndex::from_raw(section_indices[self.common_output.unwrap_or_default()])
+            } else {
+                output_index(global.object, &global.symbol).unwrap_or(SectionIndex::ABS)
+            };
+            let mut st_info = global.symbol.st_info;
+            if global.symbol.symbol_type() == SymbolType::COMMON {
+                st_info = st_info & 0xf0 | SymbolType::OBJECT.as_raw();
+            }
+            symbols.push(Symbol64 {
+                st_name: strtab.add(global.name),
+                st_info,
+                st_shndx,
+                st_value: self.global_address(global)?,
+                ..global.symbol.clone()
+            });
+        }
+        if let Some(layout) = &self.linker.layout {
+            for (name, _) in &layout.symbols {
+                if self.global_index.contains_key(name.as_slice()) {
+                    continue;
+                }
+                let Some((st_value, output)) = self.layout_symbol(name)? else {
+                    continue;
+                };
+                symbols.push(Symbol64 {
+                    st_name: strtab.add(name),
+                    st_info: SymbolBinding::GLOBAL.as_raw() << 4 | SymbolType::NOTYPE.as_raw(),
+                    st_other: 0,
+                    st_shndx: output.map_or(SectionIndex::ABS, |output| {
+                        SectionIndex::from_raw(section_indices[output])
+                    }),
+                    st_value,
+                    st_size: 0,
+                });
+            }
+        }
+
+        let mut shstrtab = StringTableBuilder::new();
+        let mut headers = vec![SectionHeader64 {
+            sh_name: 0,
+            sh_type: SectionType::NULL,
+            sh_flags: 0,
+            sh_addr: 0,
+            sh_offset: 0,
+            sh_size: 0,
+            sh_link: 0,
+            sh_info: 0,
+            sh_addralign: 0,
+            sh_entsize: 0,
+        }];
+        for &index in &self.order {
+            let output = &self.outputs[index];
+            headers.push(SectionHeader64 {
+                sh_name: shstrtab.add(&output.name),
+                sh_type: output.sh_type.clone(),
+                sh_flags: output.sh_flags,
+                sh_addr: output.address,
+                sh_offset: output.offset,
+                sh_size: output.size,
+                sh_addralign: output.align,
+                ..headers[0].clone()
+            });
+        }
+
+        let mut symbol_data = Vec::with_capacity(symbols.len() * size_of::<Symbol64>());
+        for symbol in &symbols {
+            endian::write(&mut symbol_data, symbol, &encoding);
+        }
+        let symtab_offset = self.end_offset.next_multiple_of(8);
+        headers.push(SectionHeader64 {
+            sh_name: shstrtab.add(b".symtab"),
+            sh_type: SectionType::SYMTAB,
+            sh_offset: symtab_offset,
+            sh_size: symbol_data.len() as u64,
+            sh_link: symtab_index as u32 + 1,
+            sh_info: first_global,
+            sh_addralign: 8,
+            sh_entsize: size_of::<Symbol64>() as u64,
+            ..headers[0].clone()
+        });
+        let strtab_offset = symtab_offset + symbol_data.len() as u64;
+        headers.push(SectionHeader64 {
+            sh_name: shstrtab.add(b".strtab"),
+            sh_type: SectionType::STRTAB,
+            sh_offset: strtab_offset,
+            sh_size: strtab.len() as u64,
+            sh_addralign: 1,
+            ..headers[0].clone()
+        });
+        let shstrtab_name = shstrtab.add(b".shstrtab");
+        let shstrtab_offset = strtab_offset + strtab.len() as u64;
+        headers.push(SectionHeader64 {
+            sh_name: shstrtab_name,
+            sh_type: SectionType::STRTAB,
+            sh_offset: shstrtab_offset,
+            sh_size: shstrtab.len() as u64,
+            sh_addralign: 1,
+            ..headers[0].clone()
+        });
+        let section_header_offset = (shstrtab_offset + shstrtab.len() as u64).next_multiple_of(8);
+
+        let first = self.objects[0].header();
+        let header = Header64 {
+            magic: Magic::ELF,
+            class: Class::ELF64,
+            encoding: encoding.clone(),
+            header_version: HeaderVersion::CURRENT,
+            os_abi: first.os_abi.clone(),
+            abi_version: first.abi_version,
+            pad: [0; 7],
+            object_type: ObjectType::EXEC,
+            machine: self.machine.clone(),
+            version: Version::CURRENT,
+            entry_point: entry,
+            program_header_offset: size_of::<Header64>() as u64,
+            section_header_offset,
+            flags: first.flags,
+            header_size: size_of::<Header64>() as u16,
+            program_header_entry_size: size_of::<ProgramHeader64>() as u16,
+            program_header_count: self.program_headers.len() as u16,
+            section_header_entry_size: size_of::<SectionHeader64>() as u16,
+            section_header_count: headers.len() as u16,
+            section_header_string_table_index: headers.len() as u16 - 1,
+        };
+
+        let size = section_header_offset as usize + headers.len() * size_of::<SectionHeader64>();
+        let mut out = vec![0; size];
+        out[..size_of::<Header64>()].copy_from_slice(&header.to_bytes());
+        for (index, program_header) in self.program_headers.iter().enumerate() {
+            let offset = size_of::<Header64>() + index * size_of::<ProgramHeader64>();
+            endian::write_at(&mut out, offset, program_header, &encoding)?;
+        }
+        for output in &self.outputs {
+            let offset = output.offset as usize;
+            out[offset..offset + output.data.len()].copy_from_slice(&output.data);
+        }
+        for (offset, data) in [
+            (symtab_offset, symbol_data.as_slice()),
+            (strtab_offset, strtab.as_bytes()),
+            (shstrtab_offset, shstrtab.as_bytes()),
+        ] {
+            out[offset as usize..offset as usize + data.len()].copy_from_slice(data);
+        }
+        for (index, header) in headers.iter().enumerate() {
+            let offset = section_header_offset as usize + index * size_of::<SectionHeader64>();
+            endian::write_at(&mut out, offset, header, &encoding)?;
+        }
+
+        Ok(out)
+    }
+}
+
+/// Index of the `PT_LOAD` segment class holding writable sections
+const WRITABLE: usize = 2;
+
+/// Returns the `PT_LOAD` class of an allocated section: read-only,
+/// executable or writable
+fn segment_class(sh_flags: u64) -> usize {
+    if sh_flags & SECTION_FLAG_WRITE != 0 {
+        WRITABLE
+    } else if sh_flags & SECTION_FLAG_EXECINSTR != 0 {
+        1
+    } else {
+        0
+    }
+}
+
+/// Returns true if an input section goes to the output
+fn is_linked(shdr: &SectionHeader64, name: Option<&[u8]>) -> bool {
+    shdr.is_alloc()
+        && shdr.sh_flags & SECTION_FLAG_EXCLUDE == 0
+        && shdr.sh_type != SectionType::NOTE
+        && shdr.sh_type != SectionType::GROUP
+        && name.is_some()
+}
+
+/// Returns the name of the output section an input section is merged into
+fn output_name(name: &[u8]) -> &[u8] {
+    const PREFIXES: [&[u8]; 12] = [
+        b".text",
+        b".rodata",
+        b".data.rel.ro",
+        b".data",
+        b".bss",
+        b".sdata",
+        b".sbss",
+        b".srodata",
+        b".init_array",
+        b".fini_array",
+        b".preinit_array",
+        b".gcc_except_table",
+    ];
+    PREFIXES
+        .into_iter()
+        .find(|prefix| {
+            name.strip_prefix(*prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(b"."))
+        })
+        .unwrap_or(name)
+}
+
+/// Returns true if a relocation needs a GOT entry for its symbol
+fn uses_got(machine: &Machine, r_type: u32) -> bool {
+    match *machine {
+        Machine::X86_64 => {
+            let r_type = X86_64RelocationType::from_raw(r_type);
+            r_type == X86_64RelocationType::GOTPCREL
+                || r_type == X86_64RelocationType::GOTPCRELX
+                || r_type == X86_64RelocationType::REX_GOTPCRELX
+        }
+        Machine::AARCH64 => {
+            let r_type = Aarch64RelocationType::from_raw(r_type);
+            r_type == Aarch64RelocationType::ADR_GOT_PAGE
+                || r_type == Aarch64RelocationType::LD64_GOT_LO12_NC
+        }
+        Machine::RISCV => RiscvRelocationType::from_raw(r_type) == RiscvRelocationType::GOT_HI20,
+        _ => false,
+    }
+}
+
+/// Values a relocation is computed from
+#[derive(Debug, Clone)]
+struct Target {
+    /// Address of the location being relocated (P)
+    place: u64,
+    /// Address of the symbol (S)
+    symbol: u64,
+    /// Addend (A)
+    addend: i64,
+    /// Address of the GOT entry of the symbol (G)
+    got: u64,
+    /// Size of the symbol (Z)
+    size: u64,
+    /// Address of the GOT
+    got_base: u64,
+}
+
+impl Target {
+    /// S + A
+    fn absolute(&self) -> u64 {
+        self.symbol.wrapping_add(self.addend as u64)
+    }
+
+    /// S + A - P
+    fn pc_relative(&self) -> i64 {
+        self.absolute().wrapping_sub(self.place) as i64
+    }
+
+    /// G + A - P
+    fn got_relative(&self) -> i64 {
+        self.got
+            .wrapping_add(self.addend as u64)
+            .wrapping_sub(self.place) as i64
+    }
+}
+
+const OUT_OF_RANGE: Error = Error::InvalidData("relocation out of range");
+const UNSUPPORTED: Error = Error::InvalidData("unsupported relocation type");
+
+fn write_bytes(location: &mut [u8], bytes: &[u8]) -> Result<()> {
+    location
+        .get_mut(..bytes.len())
+        .ok_or(Error::InvalidData("relocation past the end of its section"))?
+        .copy_from_slice(bytes);
+    Ok(())
+}
+
+fn read_u32(location: &[u8]) -> Result<u32> {
+    let bytes = location
+        .get(..4)
+        .ok_or(Error::InvalidData("relocation past the end of its section"))?;
+    Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// Reads an instruction word, which is little-endian on every supported
+/// machine, and writes it back after `update`
+fn patch_u32(location: &mut [u8], update: impl FnOnce(u32) -> u32) -> Result<()> {
+    let instruction = update(read_u32(location)?);
+    write_bytes(location, &instruction.to_le_bytes())
+}
+
+fn patch_u16(location: &mut [u8], update: impl FnOnce(u16) -> u16) -> Result<()> {
+    let bytes = location
+        .get(..2)
+        .ok_or(Error::InvalidData("relocation past the end of its section"))?;
+    let instruction = update(u16::from_le_bytes(bytes.try_into().unwrap()));
+    write_bytes(location, &instruction.to_le_bytes())
+}
+
+fn fits_signed(value: i64, bits: u32) -> bool {
+    let limit = 1i64 << (bits - 1);
+    (-limit..limit).contains(&value)
+}
+
+fn signed(value: i64, bits: u32) -> Result<i64> {
+    if fits_signed(value, bits) {
+        Ok(value)
+    } else {
+        Err(OUT_OF_RANGE)
+    }
+}
+
+fn write_u32(location: &mut [u8], value: u64) -> Result<()> {
+    let value = u32::try_from(value).map_err(|_| OUT_OF_RANGE)?;
+    write_bytes(location, &value.to_ne_bytes())
+}
+
+fn write_i32(location: &mut [u8], value: i64) -> Result<()> {
+    write_bytes(location, &(signed(value, 32)? as i32).to_ne_bytes())
+}
+
+fn relocate_x86_64(r_type: u32, location: &mut [u8], target: &Target) -> Result<()> {
+    match X86_64RelocationType::from_raw(r_type) {
+        X86_64RelocationType::NONE => Ok(()),
+        X86_64RelocationType::ABS64 => write_bytes(location, &target.absolute().to_ne_bytes()),
+        X86_64RelocationType::PC64 => write_bytes(location, &target.pc_relative().to_ne_bytes()),
+        X86_64RelocationType::ABS32 => write_u32(location, target.absolute()),
+        X86_64RelocationType::ABS32S => write_i32(location, target.absolute() as i64),
+        X86_64RelocationType::PC32 | X86_64RelocationType::PLT32 => {
+            write_i32(location, target.pc_relative())
+        }
+        X86_64RelocationType::GOTPCREL
+        | X86_64RelocationType::GOTPCRELX
+        | X86_64RelocationType::REX_GOTPCRELX => write_i32(location, target.got_relative()),
+        X86_64RelocationType::GOTPC32 => write_i32(
+            location,
+            target
+                .got_base
+                .wrapping_add(target.addend as u64)
+                .wrapping_sub(target.place) as i64,
+        ),
+        X86_64RelocationType::GOTOFF64 => write_bytes(
+            location,
+            &target
+                .absolute()
+                .wrapping_sub(target.got_base)
+                .to_ne_bytes(),
+        ),
+        X86_64RelocationType::ABS16 => {
+            let value = u16::try_from(target.absolute()).map_err(|_| OUT_OF_RANGE)?;
+            write_bytes(location, &value.to_ne_bytes())
+        }
+        X86_64RelocationType::PC16 => write_bytes(
+            location,
+            &(signed(target.pc_relative(), 16)? as i16).to_ne_bytes(),
+        ),
+        X86_64RelocationType::ABS8 => {
+            let value = u8::try_from(target.absolute()).map_err(|_| OUT_OF_RANGE)?;
+            write_bytes(location, &[value])
+        }
+        X86_64RelocationType::PC8 => {
+            write_bytes(location, &[signed(target.pc_relative(), 8)? as u8])
+        }
+        X86_64RelocationType::SIZE32 => {
+            write_u32(location, target.size.wrapping_add(target.addend as u64))
+        }
+        X86_64RelocationType::SIZE64 => write_bytes(
+            location,
+            &target.size.wrapping_add(target.addend as u64).to_ne_bytes(),
+        ),
+        _ => Err(UNSUPPORTED),
+    }
+}
+
+/// Returns the 4 KiB page of an address, as used by `adrp`
+fn page(address: u64) -> u64 {
+    address & !0xfff
+}
+
+/// Encodes the 21-bit immediate of `adr` and `adrp`
+fn aarch64_adr(instruction: u32, imm: i64) -> u32 {
+    let imm = imm as u32;
+    let low = (imm & 0x3) << 29;
+    let high = ((imm >> 2) & 0x7ffff) << 5;
+    (instruction & !(0x3 << 29 | 0x7ffff << 5)) | low | high
+}
+
+fn relocate_aarch64(r_type: u32, location: &mut [u8], target: &Target) -> Result<()> {
+    type R = Aarch64RelocationType;
+    let r_type = R::from_raw(r_type);
+
+    // Unsigned 12-bit offset of the load and store instructions, scaled by
+    // the access size
+    let scale = match r_type {
+        R::ADD_ABS_LO12_NC | R::LDST8_ABS_LO12_NC => Some(0),
+        R::LDST16_ABS_LO12_NC => Some(1),
+        R::LDST32_ABS_LO12_NC => Some(2),
+        R::LDST64_ABS_LO12_NC => Some(3),
+        R::LDST128_ABS_LO12_NC => Some(4),
+        _ => None,
+    };
+    if let Some(scale) = scale {
+        let imm = ((target.absolute() & 0xfff) >> scale) as u32;
+        return patch_u32(location, |insn| (insn & !(0xfff << 10)) | imm << 10);
+    }
+
+    // Moves of a 16-bit part of the absolute value
+    let part = match r_type {
+        R::MOVW_UABS_G0 | R::MOVW_UABS_G0_NC => Some((0, r_type == R::MOVW_UABS_G0)),
+        R::MOVW_UABS_G1 | R::MOVW_UABS_G1_NC => Some((1, r_type == R::MOVW_UABS_G1)),
+        R::MOVW_UABS_G2 | R::MOVW_UABS_G2_NC => Some((2, r_type == R::MOVW_UABS_G2)),
+        R::MOVW_UABS_G3 => Some((3, true)),
+        _ => None,
+    };
+    if let Some((part, check)) = part {
+        let value = target.absolute();
+        if check && part < 3 && value >> (16 * (part + 1)) != 0 {
+            return Err(OUT_OF_RANGE);
+        }
+        let imm = ((value >> (16 * part)) & 0xffff) as u32;
+        return patch_u32(location, |insn| (insn & !(0xffff << 5)) | imm << 5);
+    }
+
+    match r_type {
+        R::NONE => Ok(()),
+        R::ABS64 => write_bytes(location, &target.absolute().to_ne_bytes()),
+        R::ABS32 => {
+            let value = target.absolute() as i64;
+            if !(i32::MIN as i64..=u32::MAX as i64).contains(&value) {
+                return Err(OUT_OF_RANGE);
+            }
+            write_bytes(location, &(value as u32).to_ne_bytes())
+        }
+        R::ABS16 => {
+            let value = target.absolute() as i64;
+            if !(i16::MIN as i64..=u16::MAX as i64).contains(&value) {
+                return Err(OUT_OF_RANGE);
+            }
+            write_bytes(location, &(value as u16).to_ne_bytes())
+        }
+        R::PREL64 => write_bytes(location, &target.pc_relative().to_ne_bytes()),
+        R::PREL32 => write_i32(location, target.pc_relative()),
+        R::PREL16 => write_bytes(
+            location,
+            &(signed(target.pc_relative(), 16)? as i16).to_ne_bytes(),
+        ),
+        R::CALL26 | R::JUMP26 => {
+            let offset = signed(target.pc_relative(), 28)?;
+            let imm = ((offset >> 2) & 0x3ffffff) as u32;
+            patch_u32(location, |insn| (insn & 0xfc000000) | imm)
+        }
+        R::CONDBR19 | R::LD_PREL_LO19 => {
+            let offset = signed(target.pc_relative(), 21)?;
+            let imm = ((offset >> 2) & 0x7ffff) as u32;
+            patch_u32(location, |insn| (insn & !(0x7ffff << 5)) | imm << 5)
+        }
+        R::TSTBR14 => {
+            let offset = signed(target.pc_relative(), 16)?;
+            let imm = ((offset >> 2) & 0x3fff) as u32;
+            patch_u32(location, |insn| (insn & !(0x3fff << 5)) | imm << 5)
+        }
+        R::ADR_PREL_LO21 => {
+            let offset = signed(target.pc_relative(), 21)?;
+            patch_u32(location, |insn| aarch64_adr(insn, offset))
+        }
+        R::ADR_PREL_PG_HI21 | R::ADR_PREL_PG_HI21_NC => {
+            let offset = page(target.absolute()).wrapping_sub(page(target.place)) as i64;
+            if r_type == R::ADR_PREL_PG_HI21 && !fits_signed(offset, 33) {
+                return Err(OUT_OF_RANGE);
+            }
+            patch_u32(location, |insn| aarch64_adr(insn, offset >> 12))
+        }
+        R::ADR_GOT_PAGE => {
+            let offset = page(target.got.wrapping_add(target.addend as u64))
+                .wrapping_sub(page(target.place)) as i64;
+            let offset = signed(offset, 33)?;
+            patch_u32(location, |insn| aarch64_adr(insn, offset >> 12))
+        }
+        R::LD64_GOT_LO12_NC => {
+            let imm = ((target.got.wrapping_add(target.addend as u64) & 0xfff) >> 3) as u32;
+            patch_u32(location, |insn| (insn & !(0xfff << 10)) | imm << 10)
+        }
+        _ => Err(UNSUPPORTED),
+    }
+}
+
+/// Splits a PC-relative offset into the parts added by `auipc` and the
+/// following 12-bit immediate
+fn riscv_hi_lo(value: i64) -> Result<(u32, u32)> {
+    let value = signed(value, 32)?;
+    let high = (value.wrapping_add(0x800) as u32) & 0xfffff000;
+    let low = (value as u32) & 0xfff;
+    Ok((high, low))
+}
+
+fn riscv_i_type(instruction: u32, low: u32) -> u32 {
+    (instruction & 0x000fffff) | low << 20
+}
+
+fn riscv_s_type(instruction: u32, low: u32) -> u32 {
+    (instruction & 0x01fff07f) | (low >> 5) << 25 | (low & 0x1f) << 7
+}
+
+fn relocate_riscv(
+    r_type: u32,
+    location: &mut [u8],
+    target: &Target,
+    high_parts: &BTreeMap<u64, i64>,
+) -> Result<()> {
+    type R = RiscvRelocationType;
+    let r_type = R::from_raw(r_type);
+
+    match r_type {
+        R::NONE | R::RELAX => Ok(()),
+        R::ALIGN => Err(Error::InvalidData(
+            "R_RISCV_ALIGN requires linker relaxation; recompile with -mno-relax",
+        )),
+        R::ABS64 => write_bytes(location, &target.absolute().to_ne_bytes()),
+        R::ABS32 => write_u32(location, target.absolute() & 0xffffffff),
+        R::PCREL32 => write_bytes(location, &(target.pc_relative() as u32).to_ne_bytes()),
+        R::BRANCH => {
+            let offset = signed(target.pc_relative(), 13)? as u32;
+            patch_u32(location, |insn| {
+                (insn & 0x01fff07f)
+                    | ((offset >> 12) & 0x1) << 31
+                    | ((offset >> 5) & 0x3f) << 25
+                    | ((offset >> 1) & 0xf) << 8
+                    | ((offset >> 11) & 0x1) << 7
+            })
+        }
+        R::JAL => {
+            let offset = signed(target.pc_relative(), 21)? as u32;
+            patch_u32(location, |insn| {
+                (insn & 0xfff)
+                    | ((offset >> 20) & 0x1) << 31
+                    | ((offset >> 1) & 0x3ff) << 21
+                    | ((offset >> 11) & 0x1) << 20
+                    | ((offset >> 12) & 0xff) << 12
+            })
+        }
+        R::CALL | R::CALL_PLT => {
+            let (high, low) = riscv_hi_lo(target.pc_relative())?;
+            patch_u32(location, |insn| (insn & 0xfff) | high)?;
+            patch_u32(location.get_mut(4..).ok_or(OUT_OF_RANGE)?, |insn| {
+                riscv_i_type(insn, low)
+            })
+        }
+        R::PCREL_HI20 | R::GOT_HI20 => {
+            let value = if r_type == R::GOT_HI20 {
+                target.got_relative()
+            } else {
+                target.pc_relative()
+            };
+            let (high, _) = riscv_hi_lo(value)?;
+            patch_u32(location, |insn| (insn & 0xfff) | high)
+        }
+        R::PCREL_LO12_I | R::PCREL_LO12_S => {
+            // The symbol is the label of the auipc instruction
+            let value = high_parts
+                .get(&target.symbol)
+                .copied()
+                .ok_or(Error::InvalidData("missing R_RISCV_PCREL_HI20 relocation"))?;
+            let (_, low) = riscv_hi_lo(value)?;
+            if r_type == R::PCREL_LO12_I {
+                patch_u32(location, |insn| riscv_i_type(insn, low))
+            } else {
+                patch_u32(location, |insn| riscv_s_type(insn, low))
+            }
+        }
+        R::HI20 => {
+            let (high, _) = riscv_hi_lo(target.absolute() as i64)?;
+            patch_u32(location, |insn| (insn & 0xfff) | high)
+        }
+        R::LO12_I => {
+            let low = (target.absolute() & 0xfff) as u32;
+            patch_u32(location, |insn| riscv_i_type(insn, low))
+        }
+        R::LO12_S => {
+            let low = (target.absolute() & 0xfff) as u32;
+            patch_u32(location, |insn| riscv_s_type(insn, low))
+        }
+        R::RVC_BRANCH => {
+            let offset = signed(target.pc_relative(), 9)? as u16;
+            patch_u16(location, |insn| {
+                (insn & 0xe383)
+                    | ((offset >> 8) & 0x1) << 12
+                    | ((offset >> 3) & 0x3) << 10
+                    | ((offset >> 6) & 0x3) << 5
+                    | ((offset >> 1) & 0x3) << 3
+                    | ((offset >> 5) & 0x1) << 2
+            })
+        }
+        R::RVC_JUMP => {
+            let offset = signed(target.pc_relative(), 12)? as u16;
+            patch_u16(location, |insn| {
+                (insn & 0xe003)
+                    | ((offset >> 11) & 0x1) << 12
+                    | ((offset >> 4) & 0x1) << 11
+                    | ((offset >> 8) & 0x3) << 9
+                    | ((offset >> 10) & 0x1) << 8
+                    | ((offset >> 6) & 0x1) << 7
+                    | ((offset >> 7) & 0x1) << 6
+                    | ((offset >> 1) & 0x7) << 3
+                    | ((offset >> 5) & 0x1) << 2
+            })
+        }
+        R::ADD8 | R::ADD16 | R::ADD32 | R::ADD64 | R::SUB8 | R::SUB16 | R::SUB32 | R::SUB64 => {
+            let size = match r_type {
+                R::ADD8 | R::SUB8 => 1,
+                R::ADD16 | R::SUB16 => 2,
+                R::ADD32 | R::SUB32 => 4,
+                _ => 8,
+            };
+            let bytes = location.get_mut(..size).ok_or(OUT_OF_RANGE)?;
+            let mut buffer = [0; 8];
+            buffer[..size].copy_from_slice(bytes);
+            let value = u64::from_le_bytes(buffer);
+            let value = match r_type {
+                R::ADD8 | R::ADD16 | R::ADD32 | R::ADD64 => value.wrapping_add(target.absolute()),
+                _ => value.wrapping_sub(target.absolute()),
+            };
+            bytes.copy_from_slice(&value.to_le_bytes()[..size]);
+            Ok(())
+        }
+        R::SET6 | R::SUB6 => {
+            let byte = location.first_mut().ok_or(OUT_OF_RANGE)?;
+            let value = if r_type == R::SET6 {
+                target.absolute() as u8
+            } else {
+                (*byte).wrapping_sub(target.absolute() as u8)
+            };
+            *byte = (*byte & 0xc0) | (value & 0x3f);
+            Ok(())
+        }
+        R::SET8 => write_bytes(location, &[target.absolute() as u8]),
+        R::SET16 => write_bytes(location, &(target.absolute() as u16).to_le_bytes()),
+        R::SET32 => write_bytes(location, &(target.absolute() as u32).to_le_bytes()),
+        R::SET_ULEB128 | R::SUB_ULEB128 => {
+            let (value, size) = read_uleb128(location)?;
+            let value = if r_type == R::SET_ULEB128 {
+                target.absolute()
+            } else {
+                value.wrapping_sub(target.absolute())
+            };
+            write_uleb128(&mut location[..size], value)
+        }
+        _ => Err(UNSUPPORTED),
+    }
+}
+
+fn read_uleb128(data: &[u8]) -> Result<(u64, usize)> {
+    let mut value = 0u64;
+    for (index, byte) in data.iter().enumerate().take(10) {
+        value |= u64::from(byte & 0x7f) << (7 * index);
+        if byte & 0x80 == 0 {
+            return Ok((value, index + 1));
+        }
+    }
+    Err(Error::InvalidData("invalid ULEB128 value"))
+}
+
+/// Overwrites a ULEB128 value, keeping its encoded size
+fn write_uleb128(location: &mut [u8], mut value: u64) -> Result<()> {
+    let size = location.len();
+    for (index, byte) in location.iter_mut().enumerate() {
+        *byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if index + 1 < size {
+            *byte |= 0x80;
+        }
+    }
+    if value != 0 {
+        return Err(OUT_OF_RANGE);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{
+        ElfBuilder, Relocation, Section, SectionId, SectionLink, Symbol, SymbolId, SymbolSection,
+    };
+    use crate::group::GROUP_FLAG_COMDAT;
+    use crate::layout::SectionPlacement;
+    use crate::section_header::SECTION_FLAG_GROUP;
+
+    fn relocation(
+        section: SectionId,
+        offset: u64,
+        r_type: u32,
+        symbol: SymbolId,
+        addend: i64,
+    ) -> Relocation {
+        Relocation {
+            section,
+            offset,
+            r_type,
+            symbol: Some(symbol),
+            addend,
+        }
+    }
+
+    /// Builds an object whose `.text` holds `code` and defines `f`, and whose
+    /// `.data` holds `size` bytes with `value` at `offset`
+    fn library(machine: Machine, code: &[u8], size: usize, offset: u64) -> Vec<u8> {
+        let mut builder = ElfBuilder::new(ObjectType::REL, machine);
+        let text = builder.section(Section::text(code.to_vec()));
+        let data = builder.section(Section::data(vec![0; size]));
+        builder.symbol(Symbol::function(b"f", text, 0, code.len() as u64));
+        builder.symbol(Symbol::object(b"value", data, offset, 8));
+        builder.build().unwrap()
+    }
+
+    fn link(objects: &[&[u8]]) -> Result<Vec<u8>> {
+        let mut linker = Linker::new();
+        for object in objects {
+            linker.add_object(ElfFile::parse(object)?);
+        }
+        linker.link()
+    }
+
+    /// Returns the address of a symbol of a linked executable
+    fn address(elf: &ElfFile<'_>, name: &[u8]) -> u64 {
+        let (_, symtab) = elf.find_section(b".symtab").unwrap();
+        let strings = elf.linked_strings(&symtab).unwrap();
+        elf.symbols(&symtab)
+            .unwrap()
+            .iter()
+            .find(|symbol| strings.get(symbol.st_name) == Some(name))
+            .unwrap()
+            .st_value
+    }
+
+    fn word(elf: &ElfFile<'_>, address: u64) -> u32 {
+        let bytes = elf.read_address(address, 4).unwrap();
+        u32::from_le_bytes(bytes.try_into().unwrap())
+    }
+
+    fn sign_extend(value: u32, bits: u32) -> i64 {
+        i64::from((value << (32 - bits)) as i32 >> (32 - bits))
+    }
+
+    #[test]
+    fn x86_64_relocations() {
+        type R = X86_64RelocationType;
+        let mut builder = ElfBuilder::new(ObjectType::REL, Machine::X86_64);
+        // call f; mov value(%rip), %rax; ret
+        let text = builder.section(Section::text(vec![
+            0xe8, 0, 0, 0, 0, 0x48, 0x8b, 0x05, 0, 0, 0, 0, 0xc3,
+        ]));
+        let data = builder.section(Section::data(vec![0; 8]));
+        builder.symbol(Symbol::function(b"_start", text, 0, 13));
+        builder.symbol(Symbol::object(b"pointer", data, 0, 8));
+        let f = builder.symbol(Symbol::undefined(b"f"));
+        let value = builder.symbol(Symbol::undefined(b"value"));
+        builder
+            .relocation(relocation(text, 1, R::PLT32.as_raw(), f, -4))
+            .relocation(relocation(text, 8, R::PC32.as_raw(), value, -4))
+            .relocation(relocation(data, 0, R::ABS64.as_raw(), f, 0));
+        let main = builder.build().unwrap();
+        let library = library(Machine::X86_64, &[0xc3], 16, 8);
+
+        let out = link(&[&main, &library]).unwrap();
+        let elf = ElfFile::parse(&out).unwrap();
+        let start = address(&elf, b"_start");
+        let f = address(&elf, b"f");
+        let pointer = address(&elf, b"pointer");
+        let value = address(&elf, b"value");
+
+        // The sections of the second object follow those of the first,
+        // aligned
+        assert_eq!(elf.header().entry_point, start);
+        assert_eq!(f, start + 16);
+        assert_eq!(value, pointer + 16);
+
+        let call = word(&elf, start + 1) as i32;
+        assert_eq!(i64::from(call), f as i64 - (start as i64 + 5));
+        let load = word(&elf, start + 8) as i32;
+        assert_eq!(i64::from(load), value as i64 - (start as i64 + 12));
+        let bytes = elf.read_address(pointer, 8).unwrap();
+        assert_eq!(u64::from_le_bytes(bytes.try_into().unwrap()), f);
+    }
+
+    #[test]
+    fn aarch64_relocations() {
+        type R = Aarch64RelocationType;
+        let mut code = Vec::new();
+        // bl f; adrp x0, value; add x0, x0, :lo12:value;
+        // ldr x1, [x0, :lo12:value]; ret
+        for instruction in [
+            0x94000000u32,
+            0x90000000,
+            0x91000000,
+            0xf9400001,
+            0xd65f03c0,
+        ] {
+            code.extend_from_slice(&instruction.to_le_bytes());
+        }
+        let mut builder = ElfBuilder::new(ObjectType::REL, Machine::AARCH64);
+        let text = builder.section(Section::text(code));
+        builder.symbol(Symbol::function(b"_start", text, 0, 20));
+        let f = builder.symbol(Symbol::undefined(b"f"));
+        let value = builder.symbol(Symbol::undefined(b"value"));
+        builder
+            .relocation(relocation(text, 0, R::CALL26.as_raw(), f, 0))
+            .relocation(relocation(text, 4, R::ADR_PREL_PG_HI21.as_raw(), value, 0))
+            .relocation(relocation(text, 8, R::ADD_ABS_LO12_NC.as_raw(), value, 0))
+            .relocation(relocation(
+                text,
+                12,
+                R::LDST64_ABS_LO12_NC.as_raw(),
+                value,
+                0,
+            ));
+        let main = builder.build().unwrap();
+        let library = library(Machine::AARCH64, &0xd65f03c0u32.to_le_bytes(), 0x40, 0x38);
+
+        let out = link(&[&main, &library]).unwrap();
+        let elf = ElfFile::parse(&out).unwrap();
+        let start = address(&elf, b"_start");
+        let f = address(&elf, b"f");
+        let value = address(&elf, b"value");
+        assert_eq!(f, start + 32);
+        assert_eq!(value % 0x1000, 0x38);
+
+        assert_eq!(word(&elf, start), 0x94000000 | ((f - start) >> 2) as u32);
+
+        let adrp = word(&elf, start + 4);
+        assert_eq!(adrp & 0x9f00001f, 0x90000000);
+        let pages = ((adrp >> 29) & 0x3) | ((adrp >> 5) & 0x7ffff) << 2;
+        assert_eq!(
+            sign_extend(pages, 21) << 12,
+            (value & !0xfff) as i64 - ((start + 4) & !0xfff) as i64
+        );
+
+        assert_eq!(word(&elf, start + 8), 0x91000000 | 0x38 << 10);
+        assert_eq!(word(&elf, start + 12), 0xf9400001 | (0x38 >> 3) << 10);
+    }
+
+    #[test]
+    fn riscv_relocations() {
+        type R = RiscvRelocationType;
+        let mut code = Vec::new();
+        // 1: auipc a0, %pcrel_hi(value); addi a0, a0, %pcrel_lo(1b);
+        // sd a1, %pcrel_lo(1b)(a0); beqz a0, 2f; jal f; 2: ret
+        for instruction in [
+            0x00000517u32,
+            0x00050513,
+            0x00b53023,
+            0x00050063,
+            0x000000ef,
+            0x00008067,
+        ] {
+            code.extend_from_slice(&instruction.to_le_bytes());
+        }
+        let mut builder = ElfBuilder::new(ObjectType::REL, Machine::RISCV);
+        let text = builder.section(Section::text(code));
+        builder.symbol(Symbol::function(b"_start", text, 0, 24));
+        let high = builder.symbol(Symbol {
+            binding: SymbolBinding::LOCAL,
+            ..Symbol::new(b".L1", SymbolSection::Section(text), 0)
+        });
+        let end = builder.symbol(Symbol {
+            binding: SymbolBinding::LOCAL,
+            ..Symbol::new(b".L2", SymbolSection::Section(text), 20)
+        });
+        let f = builder.symbol(Symbol::undefined(b"f"));
+        let value = builder.symbol(Symbol::undefined(b"value"));
+        builder
+            .relocation(relocation(text, 0, R::PCREL_HI20.as_raw(), value, 0))
+            .relocation(relocation(text, 4, R::PCREL_LO12_I.as_raw(), high, 0))
+            .relocation(relocation(text, 8, R::PCREL_LO12_S.as_raw(), high, 0))
+            .relocation(relocation(text, 12, R::BRANCH.as_raw(), end, 0))
+            .relocation(relocation(text, 16, R::JAL.as_raw(), f, 0));
+        let main = builder.build().unwrap();
+        // The low part is negative, so the high part is rounded up
+        let library = library(Machine::RISCV, &0x00008067u32.to_le_bytes(), 0x908, 0x900);
+
+        let out = link(&[&main, &library]).unwrap();
+        let elf = ElfFile::parse(&out).unwrap();
+        let start = address(&elf, b"_start");
+        let f = address(&elf, b"f");
+        let value = address(&elf, b"value");
+        assert_eq!(f, start + 32);
+        let offset = value as i64 - start as i64;
+
+        let auipc = word(&elf, start);
+        assert_eq!(auipc & 0xfff, 0x517);
+        let high = i64::from((auipc & 0xfffff000) as i32);
+        let addi = word(&elf, start + 4);
+        assert_eq!(addi & 0xfffff, 0x50513);
+        assert_eq!(high + sign_extend(addi >> 20, 12), offset);
+        let sd = word(&elf, start + 8);
+        assert_eq!(sd & 0x1fff07f, 0xb53023 & 0x1fff07f);
+        let low = (sd >> 25) << 5 | (sd >> 7) & 0x1f;
+        assert_eq!(high + sign_extend(low, 12), offset);
+
+        let beqz = word(&elf, start + 12);
+        let branch = (beqz >> 31) << 12
+            | ((beqz >> 7) & 0x1) << 11
+            | ((beqz >> 25) & 0x3f) << 5
+            | ((beqz >> 8) & 0xf) << 1;
+        assert_eq!(sign_extend(branch, 13), 8);
+
+        let jal = word(&elf, start + 16);
+        assert_eq!(jal & 0xfff, 0xef);
+        let jump = (jal >> 31) << 20
+            | ((jal >> 12) & 0xff) << 12
+            | ((jal >> 20) & 0x1) << 11
+            | ((jal >> 21) & 0x3ff) << 1;
+        assert_eq!(sign_extend(jump, 21), (f - (start + 16)) as i64);
+    }
+
+    #[test]
+    fn riscv_relaxation_is_rejected() {
+        let mut builder = ElfBuilder::new(ObjectType::REL, Machine::RISCV);
+        let text = builder.section(Section::text(vec![0x13, 0, 0, 0, 0x67, 0x80, 0, 0]));
+        let start = builder.symbol(Symbol::function(b"_start", text, 0, 8));
+        builder.relocation(relocation(
+            text,
+            0,
+            RiscvRelocationType::ALIGN.as_raw(),
+            start,
+            4,
+        ));
+        let object = builder.build().unwrap();
+
+        assert_eq!(
+            link(&[&object]),
+            Err(Error::InvalidData(
+                "R_RISCV_ALIGN requires linker relaxation; recompile with -mno-relax"
+            ))
+        );
+    }
+
+    #[test]
+    fn relocation_past_section_end() {
+        let mut builder = ElfBuilder::new(ObjectType::REL, Machine::X86_64);
+        let text = builder.section(Section::text(vec![0xc3]));
+        let start = builder.symbol(Symbol::function(b"_start", text, 0, 1));
+        builder.relocation(relocation(
+            text,
+            2,
+            X86_64RelocationType::ABS32.as_raw(),
+            start,
+            0,
+        ));
+        let object = builder.build().unwrap();
+
+        assert_eq!(
+            link(&[&object]),
+            Err(Error::OutOfBounds { offset: 2, size: 1 })
+        );
+    }
+
+    #[test]
+    fn duplicate_symbol() {
+        let mut builder = ElfBuilder::new(ObjectType::REL, Machine::X86_64);
+        let text = builder.section(Section::text(vec![0xc3]));
+        builder.symbol(Symbol::function(b"_start", text, 0, 1));
+        builder.symbol(Symbol::function(b"f", text, 0, 1));
+        let main = builder.build().unwrap();
+        let library = library(Machine::X86_64, &[0xc3], 8, 0);
+
+        assert_eq!(
+            link(&[&main, &library]),
+            Err(Error::InvalidData("duplicate symbol definition"))
+        );
+    }
+
+    #[test]
+    fn undefined_symbol() {
+        let mut builder = ElfBuilder::new(ObjectType::REL, Machine::X86_64);
+        let text = builder.section(Section::text(vec![0xe8, 0, 0, 0, 0]));
+        builder.symbol(Symbol::function(b"_start", text, 0, 5));
+        let missing = builder.symbol(Symbol::undefined(b"missing"));
+        builder.relocation(relocation(
+            text,
+            1,
+            X86_64RelocationType::PLT32.as_raw(),
+            missing,
+            -4,
+        ));
+        let object = builder.build().unwrap();
+
+        assert_eq!(
+            link(&[&object]),
+            Err(Error::InvalidData("undefined symbol"))
+        );
+    }
+
+    /// Builds an object defining `inline` in a COMDAT group of the same name
+    fn comdat(code: &[u8], start: bool) -> Vec<u8> {
+        let mut builder = ElfBuilder::new(ObjectType::REL, Machine::X86_64);
+        // The group is section 1 and `.text.inline` section 2
+        let mut members = Vec::new();
+        for word in [GROUP_FLAG_COMDAT, 2] {
+            members.extend_from_slice(&word.to_ne_bytes());
+        }
+        builder.section(Section {
+            sh_addralign: 4,
+            sh_entsize: 4,
+            sh_link: Some(SectionLink::SymTab),
+            sh_info: 1,
+            ..Section::new(b".group", SectionType::GROUP, 0, members)
+        });
+        let inline = builder.section(Section {
+            sh_flags: SECTION_FLAG_ALLOC | SECTION_FLAG_EXECINSTR | SECTION_FLAG_GROUP,
+            ..Section::text(code.to_vec())
+        });
+        builder.section_mut(inline).name = b".text.inline".to_vec();
+        // The signature is the first symbol
+        builder.symbol(Symbol::function(b"inline", inline, 0, code.len() as u64));
+        if start {
+            let text = builder.section(Section::text(vec![0xc3]));
+            builder.symbol(Symbol::function(b"_start", text, 0, 1));
+        }
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn comdat_deduplication() {
+        let first = comdat(&[0x90, 0xc3], true);
+        let second = comdat(&[0xcc, 0xcc, 0xc3], false);
+
+        let out = link(&[&first, &second]).unwrap();
+        let elf = ElfFile::parse(&out).unwrap();
+        let inline = address(&elf, b"inline");
+        assert_eq!(elf.read_address(inline, 2).unwrap(), [0x90, 0xc3]);
+
+        // Only the first copy is linked
+        let (_, text) = elf.find_section(b".text").unwrap();
+        assert_eq!(text.sh_size, 16 + 1);
+    }
+
+    #[test]
+    fn layout_symbol_through_got() {
+        type R = X86_64RelocationType;
+        let mut builder = ElfBuilder::new(ObjectType::REL, Machine::X86_64);
+        // mov __bss_start@GOTPCREL(%rip), %rax; lea __bss_start(%rip), %rcx;
+        // ret
+        let text = builder.section(Section::text(vec![
+            0x48, 0x8b, 0x05, 0, 0, 0, 0, 0x48, 0x8d, 0x0d, 0, 0, 0, 0, 0xc3,
+        ]));
+        builder.section(Section::bss(16));
+        builder.symbol(Symbol::function(b"_start", text, 0, 15));
+        let bss_start = builder.symbol(Symbol::undefined(b"__bss_start"));
+        builder
+            .relocation(relocation(
+                text,
+                3,
+                R::REX_GOTPCRELX.as_raw(),
+                bss_start,
+                -4,
+            ))
+            .relocation(relocation(text, 10, R::PC32.as_raw(), bss_start, -4));
+        let object = builder.build().unwrap();
+
+        let mut layout = Layout::new();
+        layout.region(b"ram", 0x2000_0000, 0x10000);
+        for name in [&b".text"[..], b".got", b".bss"] {
+            layout.section(&SectionPlacement::new(name, b"ram"));
+        }
+        layout.symbol(b"__bss_start", LayoutSymbol::SectionStart(b".bss".to_vec()));
+        let elf = ElfFile::parse(&object).unwrap();
+        let out = Linker::new().add_object(elf).layout(layout).link().unwrap();
+
+        let elf = ElfFile::parse(&out).unwrap();
+        let start = address(&elf, b"_start");
+        let bss_start = address(&elf, b"__bss_start");
+        assert_eq!(bss_start, elf.find_section(b".bss").unwrap().1.sh_addr);
+
+        let slot = (start + 7).wrapping_add_signed(i64::from(word(&elf, start + 3) as i32));
+        let entry = elf.read_address(slot, 8).unwrap();
+        assert_eq!(u64::from_le_bytes(entry.try_into().unwrap()), bss_start);
+        let direct = (start + 14).wrapping_add_signed(i64::from(word(&elf, start + 10) as i32));
+        assert_eq!(direct, bss_start);
+    }
+}
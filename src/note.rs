@@ -0,0 +1,268 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use zerocopy::{FromBytes, Immutable, IntoBytes};
+
+use crate::elf_enum;
+use crate::endian::impl_swap_bytes;
+use crate::header::Class;
+
+/// Note header (Elf64_Nhdr)
+///
+/// The layout is identical for 32-bit and 64-bit objects.
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes, IntoBytes, Immutable)]
+#[doc(alias = "Elf64_Nhdr")]
+#[doc(alias = "Elf32_Nhdr")]
+pub struct NoteHeader {
+    /// Size of the owner name, including its NUL terminator
+    pub n_namesz: u32,
+    /// Size of the descriptor
+    pub n_descsz: u32,
+    /// Note type, interpreted according to the owner name
+    pub n_type: u32,
+}
+
+impl_swap_bytes!(NoteHeader {
+    n_namesz,
+    n_descsz,
+    n_type,
+});
+
+/// A single note entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Note<'a> {
+    /// Owner name, without its NUL terminator
+    pub name: &'a [u8],
+    /// Note type, interpreted according to the owner name
+    pub n_type: u32,
+    /// Note descriptor
+    pub desc: &'a [u8],
+}
+
+impl Note<'_> {
+    /// Owner name of notes produced by the GNU toolchain
+    pub const GNU: &'static [u8] = b"GNU";
+    /// Owner name of notes describing a process in a core file
+    pub const CORE: &'static [u8] = b"CORE";
+    /// Owner name of Linux specific notes in a core file
+    pub const LINUX: &'static [u8] = b"LINUX";
+
+    /// Returns true if the note has the given owner and type
+    pub fn is(&self, name: &[u8], n_type: u32) -> bool {
+        self.name == name && self.n_type == n_type
+    }
+}
+
+/// Appends a note to `out`, padding the name and descriptor to 4 bytes
+///
+/// Padding is computed relative to the start of `out`, which should hold the
+/// contents of a single note segment or section.
+#[cfg(feature = "alloc")]
+pub fn write_note(out: &mut Vec<u8>, name: &[u8], n_type: u32, desc: &[u8]) {
+    let header = NoteHeader {
+        n_namesz: name.len() as u32 + 1,
+        n_descsz: desc.len() as u32,
+        n_type,
+    };
+
+    out.extend_from_slice(header.as_bytes());
+    out.extend_from_slice(name);
+    out.push(0);
+    out.resize(out.len().next_multiple_of(4), 0);
+    out.extend_from_slice(desc);
+    out.resize(out.len().next_multiple_of(4), 0);
+}
+
+/// Iterator over the notes of a `PT_NOTE` segment or `SHT_NOTE` section
+#[derive(Debug, Clone)]
+pub struct NoteIter<'a> {
+    data: &'a [u8],
+    align: usize,
+}
+
+impl<'a> NoteIter<'a> {
+    /// Creates an iterator over the notes in `data`
+    ///
+    /// `align` is the alignment of the containing segment or section. Notes
+    /// are padded to 4 bytes unless the alignment is 8.
+    pub fn new(data: &'a [u8], align: u64) -> Self {
+        Self {
+            data,
+            align: if align == 8 { 8 } else { 4 },
+        }
+    }
+
+    fn align_up(&self, value: usize) -> Option<usize> {
+        value
+            .checked_add(self.align - 1)
+            .map(|value| value & !(self.align - 1))
+    }
+}
+
+impl<'a> Iterator for NoteIter<'a> {
+    type Item = Note<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (header, _) = NoteHeader::read_from_prefix(self.data).ok()?;
+        let name_size = header.n_namesz as usize;
+        let desc_size = header.n_descsz as usize;
+
+        let name_start = size_of::<NoteHeader>();
+        let desc_start = self.align_up(name_start + name_size)?;
+        let desc_end = desc_start.checked_add(desc_size)?;
+
+        let (Some(name), Some(desc)) = (
+            self.data.get(name_start..name_start + name_size),
+            self.data.get(desc_start..desc_end),
+        ) else {
+            // A truncated note ends the iteration
+            self.data = &[];
+            return None;
+        };
+
+        let next = self.align_up(desc_end)?.min(self.data.len());
+        self.data = &self.data[next..];
+
+        let name = name.strip_suffix(&[0]).unwrap_or(name);
+        Some(Note {
+            name,
+            n_type: header.n_type,
+            desc,
+        })
+    }
+}
+
+elf_enum! {
+    /// Note types for notes owned by `GNU`
+    pub struct GnuNoteType(u32) {
+        /// Minimum kernel ABI the object was built for
+        ABI_TAG = 1, "NT_GNU_ABI_TAG (ABI version tag)",
+        /// Hardware capabilities of the object
+        HWCAP = 2, "NT_GNU_HWCAP (DSO-supplied software HWCAP info)",
+        /// Unique build identifier
+        BUILD_ID = 3, "NT_GNU_BUILD_ID (unique build ID bitstring)",
+        /// Version of gold that linked the object
+        GOLD_VERSION = 4, "NT_GNU_GOLD_VERSION (gold version)",
+        /// Program properties
+        PROPERTY_TYPE_0 = 5, "NT_GNU_PROPERTY_TYPE_0",
+    }
+}
+
+elf_enum! {
+    /// Note types for notes owned by `CORE` and `LINUX` in core files
+    pub struct CoreNoteType(u32) {
+        /// Thread status and general purpose registers
+        PRSTATUS = 1, "NT_PRSTATUS (prstatus structure)",
+        /// Floating point registers
+        FPREGSET = 2, "NT_FPREGSET (floating point registers)",
+        /// Process information
+        PRPSINFO = 3, "NT_PRPSINFO (prpsinfo structure)",
+        /// Task structure
+        TASKSTRUCT = 4, "NT_TASKSTRUCT (task structure)",
+        /// Auxiliary vector
+        AUXV = 6, "NT_AUXV (auxiliary vector)",
+        /// x86 extended state
+        X86_XSTATE = 0x202, "NT_X86_XSTATE (x86 XSAVE extended state)",
+        /// AArch64 TLS register
+        ARM_TLS = 0x401, "NT_ARM_TLS (AArch TLS registers)",
+        /// AArch64 hardware breakpoint registers
+        ARM_HW_BREAK = 0x402, "NT_ARM_HW_BREAK (AArch hardware breakpoint registers)",
+        /// AArch64 hardware watchpoint registers
+        ARM_HW_WATCH = 0x403, "NT_ARM_HW_WATCH (AArch hardware watchpoint registers)",
+        /// AArch64 pointer authentication code masks
+        ARM_PAC_MASK = 0x406, "NT_ARM_PAC_MASK (AArch pointer authentication code masks)",
+        /// Mapped files
+        FILE = 0x46494c45, "NT_FILE (mapped files)",
+        /// Signal information
+        SIGINFO = 0x53494749, "NT_SIGINFO (siginfo_t data)",
+        /// x86 extended floating point registers
+        PRXFPREG = 0x46e62b7f, "NT_PRXFPREG (user_xfpregs structure)",
+    }
+}
+
+/// `NT_GNU_PROPERTY_TYPE_0` property holding the x86 features every input
+/// object supports
+#[doc(alias = "GNU_PROPERTY_X86_FEATURE_1_AND")]
+pub const PROPERTY_X86_FEATURE_1_AND: u32 = 0xc0000002;
+/// x86 feature bit for indirect branch tracking (CET IBT)
+#[doc(alias = "GNU_PROPERTY_X86_FEATURE_1_IBT")]
+pub const PROPERTY_X86_FEATURE_1_IBT: u32 = 0x1;
+/// x86 feature bit for shadow stacks (CET SHSTK)
+#[doc(alias = "GNU_PROPERTY_X86_FEATURE_1_SHSTK")]
+pub const PROPERTY_X86_FEATURE_1_SHSTK: u32 = 0x2;
+/// `NT_GNU_PROPERTY_TYPE_0` property holding the AArch64 features every
+/// input object supports
+#[doc(alias = "GNU_PROPERTY_AARCH64_FEATURE_1_AND")]
+pub const PROPERTY_AARCH64_FEATURE_1_AND: u32 = 0xc0000000;
+/// AArch64 feature bit for branch target identification
+#[doc(alias = "GNU_PROPERTY_AARCH64_FEATURE_1_BTI")]
+pub const PROPERTY_AARCH64_FEATURE_1_BTI: u32 = 0x1;
+/// AArch64 feature bit for pointer authentication of return addresses
+#[doc(alias = "GNU_PROPERTY_AARCH64_FEATURE_1_PAC")]
+pub const PROPERTY_AARCH64_FEATURE_1_PAC: u32 = 0x2;
+
+/// A single property of an `NT_GNU_PROPERTY_TYPE_0` note
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GnuProperty<'a> {
+    /// Property type, processor specific from `0xc0000000`
+    pub pr_type: u32,
+    /// Property data
+    pub data: &'a [u8],
+}
+
+impl GnuProperty<'_> {
+    /// Returns the data of a 4-byte property, such as a feature bitmask
+    pub fn value(&self) -> Option<u32> {
+        Some(u32::from_ne_bytes(self.data.try_into().ok()?))
+    }
+}
+
+/// Iterator over the properties of an `NT_GNU_PROPERTY_TYPE_0` note
+#[derive(Debug, Clone)]
+pub struct GnuPropertyIter<'a> {
+    data: &'a [u8],
+    align: usize,
+}
+
+impl<'a> GnuPropertyIter<'a> {
+    /// Creates an iterator over the properties in the descriptor `desc`
+    ///
+    /// Properties are padded to 8 bytes in 64-bit objects and to 4 bytes in
+    /// 32-bit ones.
+    pub fn new(desc: &'a [u8], class: &Class) -> Self {
+        Self {
+            data: desc,
+            align: if *class == Class::ELF32 { 4 } else { 8 },
+        }
+    }
+}
+
+impl<'a> Iterator for GnuPropertyIter<'a> {
+    type Item = GnuProperty<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let read_u32 = |offset: usize| {
+            Some(u32::from_ne_bytes(
+                self.data.get(offset..offset + 4)?.try_into().ok()?,
+            ))
+        };
+        let (Some(pr_type), Some(size)) = (read_u32(0), read_u32(4)) else {
+            self.data = &[];
+            return None;
+        };
+
+        let Some(data) = self.data.get(8..8 + size as usize) else {
+            // A truncated property ends the iteration
+            self.data = &[];
+            return None;
+        };
+
+        let next = (8 + data.len())
+            .next_multiple_of(self.align)
+            .min(self.data.len());
+        self.data = &self.data[next..];
+
+        Some(GnuProperty { pr_type, data })
+    }
+}
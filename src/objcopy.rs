@@ -0,0 +1,365 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use core::ops::Range;
+
+use zerocopy::FromZeros;
+
+use crate::error::{Error, Result};
+use crate::file::ElfFile;
+use crate::program_header::ProgramType;
+use crate::rewrite::SectionRewriter;
+use crate::section_header::{SectionHeader64, SectionType};
+
+/// Editor for the sections of an ELF file, in the spirit of `objcopy`
+///
+/// Changes are recorded on the editor and applied by
+/// [`SectionEditor::write`], which produces a new file. The program headers
+/// and every byte covered by a segment stay at their original offsets, so
+/// sections can be added, removed and renamed without changing how the file
+/// loads. Removing a section also removes the sections that link to it, such
+/// as its relocations, and the symbols defined in it.
+#[derive(Debug, Clone)]
+pub struct SectionEditor<'a> {
+    sections: SectionRewriter<'a>,
+}
+
+impl<'a> SectionEditor<'a> {
+    pub fn new(file: &ElfFile<'a>) -> Result<Self> {
+        Ok(Self {
+            sections: SectionRewriter::new(file)?,
+        })
+    }
+
+    /// Returns true if the file has a section with the given name, including
+    /// pending changes
+    pub fn has_section(&self, name: &[u8]) -> bool {
+        self.sections.find(name).is_some()
+    }
+
+    /// Adds a non-allocated `SHT_PROGBITS` section holding `data` after the
+    /// existing sections, like `objcopy --add-section`
+    pub fn add_section(&mut self, name: &[u8], data: &[u8]) -> &mut Self {
+        let header = SectionHeader64 {
+            sh_type: SectionType::PROGBITS,
+            sh_addralign: 1,
+            ..SectionHeader64::new_zeroed()
+        };
+        self.sections.add(name, header, data.to_vec());
+        self
+    }
+
+    /// Removes every section with the given name, like `objcopy
+    /// --remove-section`
+    ///
+    /// The section header string table cannot be removed. The contents of a
+    /// removed section that is covered by a segment are left in place.
+    pub fn remove_section(&mut self, name: &[u8]) -> &mut Self {
+        while let Some(index) = self
+            .sections
+            .find(name)
+            .filter(|&index| index != self.sections.names_index())
+        {
+            self.sections.removed[index] = true;
+        }
+        self
+    }
+
+    /// Renames every section called `from` to `to`, like `objcopy
+    /// --rename-section`
+    pub fn rename_section(&mut self, from: &[u8], to: &[u8]) -> &mut Self {
+        if from == to {
+            return self;
+        }
+        while let Some(index) = self.sections.find(from) {
+            self.sections.names[index] = to.to_vec().into();
+        }
+        self
+    }
+
+    /// Replaces the flags of every section with the given name by `flags`, a
+    /// combination of the `SECTION_FLAG_*` constants
+    pub fn set_section_flags(&mut self, name: &[u8], flags: u64) -> &mut Self {
+        for index in 1..self.sections.headers.len() {
+            if !self.sections.removed[index] && *self.sections.names[index] == *name {
+                self.sections.headers[index].sh_flags = flags;
+            }
+        }
+        self
+    }
+
+    /// Produces the edited file
+    pub fn write(&self) -> Result<Vec<u8>> {
+        let mut sections = self.sections.clone();
+        sections.remove_dependents()?;
+        sections.write()
+    }
+}
+
+/// Bytes to be stored at a physical address, as written to flat binary,
+/// Intel HEX and S-record files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block<'a> {
+    pub address: u64,
+    pub data: &'a [u8],
+}
+
+impl Block<'_> {
+    /// Returns the addresses covered by the block
+    pub fn range(&self) -> Range<u64> {
+        self.address..self.address + self.data.len() as u64
+    }
+}
+
+/// Returns the memory image of a file at its physical load addresses,
+/// sorted by address
+///
+/// This is the image `objcopy -O binary` writes: one block for each
+/// allocated section with contents, placed at the physical address
+/// (`p_paddr`) of the `PT_LOAD` segment that holds it. Files without section
+/// headers give one block for the file contents of each `PT_LOAD` segment.
+pub fn load_blocks<'a>(file: &ElfFile<'a>) -> Result<Vec<Block<'a>>> {
+    let mut blocks = Vec::new();
+    if file.section_count() == 0 {
+        for phdr in file.program_headers() {
+            if phdr.p_type == ProgramType::LOAD && phdr.p_filesz != 0 {
+                blocks.push(Block {
+                    address: phdr.p_paddr,
+                    data: file.segment_data(&phdr)?,
+                });
+            }
+        }
+    } else {
+        for shdr in file.section_headers() {
+            if shdr.is_alloc() && shdr.file_size() != 0 {
+                blocks.push(Block {
+                    address: load_address(file, &shdr),
+                    data: file.section_data(&shdr)?,
+                });
+            }
+        }
+    }
+    blocks.sort_by_key(|block| block.address);
+    Ok(blocks)
+}
+
+/// Returns the contents of the first section with the given name at its
+/// physical load address
+pub fn section_block<'a>(file: &ElfFile<'a>, name: &[u8]) -> Result<Option<Block<'a>>> {
+    let Some((_, shdr)) = file.find_section(name) else {
+        return Ok(None);
+    };
+
+    Ok(Some(Block {
+        address: load_address(file, &shdr),
+        data: file.section_data(&shdr)?,
+    }))
+}
+
+/// Returns the physical address a section is loaded at
+///
+/// A section inside a `PT_LOAD` segment follows the segment's physical
+/// address; other sections are placed at `sh_addr`.
+fn load_address(file: &ElfFile, shdr: &SectionHeader64) -> u64 {
+    file.program_headers()
+        .find(|phdr| {
+            phdr.p_type == ProgramType::LOAD
+                && shdr.sh_type != SectionType::NOBITS
+                && shdr.sh_offset >= phdr.p_offset
+                && shdr.sh_offset - phdr.p_offset < phdr.p_filesz
+        })
+        .map_or(shdr.sh_addr, |phdr| {
+            phdr.p_paddr + (shdr.sh_offset - phdr.p_offset)
+        })
+}
+
+/// Returns the parts of `blocks` that fall within `range`
+pub fn clip_blocks<'a>(blocks: &[Block<'a>], range: Range<u64>) -> Vec<Block<'a>> {
+    blocks
+        .iter()
+        .filter_map(|block| {
+            let start = block.address.max(range.start);
+            let end = block.range().end.min(range.end);
+            (start < end).then(|| Block {
+                address: start,
+                data: &block.data[(start - block.address) as usize..(end - block.address) as usize],
+            })
+        })
+        .collect()
+}
+
+/// Writes `blocks` as a flat binary starting at the lowest address, with
+/// gaps filled with zeros, like `objcopy -O binary`
+pub fn to_binary(blocks: &[Block]) -> Vec<u8> {
+    let Some(start) = blocks.iter().map(|block| block.address).min() else {
+        return Vec::new();
+    };
+    let end = blocks
+        .iter()
+        .map(|block| block.range().end)
+        .max()
+        .unwrap_or(start);
+
+    let mut out = alloc::vec![0; (end - start) as usize];
+    for block in blocks {
+        let offset = (block.address - start) as usize;
+        out[offset..offset + block.data.len()].copy_from_slice(block.data);
+    }
+    out
+}
+
+/// Number of data bytes per Intel HEX or S-record line
+const RECORD_LENGTH: usize = 16;
+
+/// Writes `blocks` in the Intel HEX format, like `objcopy -O ihex`
+///
+/// Addresses up to 20 bits are reached with extended segment address
+/// records and higher ones with extended linear address records, and no
+/// record crosses a 64 KiB boundary. `start` is written as a start segment
+/// or start linear address record. Lines end with CR LF.
+pub fn to_intel_hex(blocks: &[Block], start: Option<u64>) -> Result<String> {
+    let mut out = String::new();
+    let mut segment_base = 0;
+    let mut linear_base = 0;
+
+    for block in blocks {
+        if block.range().end > 1 << 32 {
+            return Err(Error::InvalidData("address does not fit in Intel HEX"));
+        }
+
+        let mut address = block.address;
+        let mut data = block.data;
+        while !data.is_empty() {
+            let base = segment_base + linear_base;
+            if address < base || address > base + 0xffff {
+                if linear_base == 0 && address <= 0xfffff {
+                    segment_base = address & 0xf0000;
+                    hex_record(
+                        &mut out,
+                        0,
+                        0x02,
+                        &((segment_base >> 4) as u16).to_be_bytes(),
+                    );
+                } else {
+                    // Readers may add both bases, so clear the segment base
+                    if segment_base != 0 {
+                        segment_base = 0;
+                        hex_record(&mut out, 0, 0x02, &[0, 0]);
+                    }
+                    linear_base = address & 0xffff_0000;
+                    hex_record(
+                        &mut out,
+                        0,
+                        0x04,
+                        &((linear_base >> 16) as u16).to_be_bytes(),
+                    );
+                }
+            }
+
+            let offset = address - segment_base - linear_base;
+            let length = data
+                .len()
+                .min(RECORD_LENGTH)
+                .min((0x10000 - offset) as usize);
+            let (line, rest) = data.split_at(length);
+            hex_record(&mut out, offset as u16, 0x00, line);
+            address += length as u64;
+            data = rest;
+        }
+    }
+
+    match start {
+        Some(start @ 0..=0xfffff) => {
+            let segment = ((start & 0xf0000) >> 4) as u16;
+            let [segment_high, segment_low] = segment.to_be_bytes();
+            let [offset_high, offset_low] = (start as u16).to_be_bytes();
+            let record = [segment_high, segment_low, offset_high, offset_low];
+            hex_record(&mut out, 0, 0x03, &record);
+        }
+        Some(start) => {
+            let start = u32::try_from(start)
+                .map_err(|_| Error::InvalidData("address does not fit in Intel HEX"))?;
+            hex_record(&mut out, 0, 0x05, &start.to_be_bytes());
+        }
+        None => {}
+    }
+    hex_record(&mut out, 0, 0x01, &[]);
+
+    Ok(out)
+}
+
+fn hex_record(out: &mut String, address: u16, record_type: u8, data: &[u8]) {
+    let [high, low] = address.to_be_bytes();
+    let mut sum = (data.len() as u8)
+        .wrapping_add(high)
+        .wrapping_add(low)
+        .wrapping_add(record_type);
+    let _ = write!(out, ":{:02X}{address:04X}{record_type:02X}", data.len());
+    for byte in data {
+        sum = sum.wrapping_add(*byte);
+        let _ = write!(out, "{byte:02X}");
+    }
+    let _ = write!(out, "{:02X}\r\n", sum.wrapping_neg());
+}
+
+/// Maximum number of header bytes in an S0 record
+const SRECORD_HEADER_LENGTH: usize = 40;
+
+/// Writes `blocks` as Motorola S-records, like `objcopy -O srec`
+///
+/// `header` is stored in the S0 record, truncated to the 40 bytes objcopy
+/// allows. The narrowest of the S1, S2 and S3
+/// data records that holds every address is used, and `start` is written in
+/// the matching S9, S8 or S7 termination record. Empty blocks are skipped.
+/// Lines end with CR LF.
+pub fn to_srecord(blocks: &[Block], header: &[u8], start: Option<u64>) -> Result<String> {
+    let blocks = blocks.iter().filter(|block| !block.data.is_empty());
+    let end = blocks
+        .clone()
+        .map(|block| block.range().end - 1)
+        .chain(start)
+        .max()
+        .unwrap_or(0);
+    let (data_type, address_size) = match end {
+        0..=0xffff => (1, 2),
+        0x1_0000..=0xff_ffff => (2, 3),
+        0x100_0000..=0xffff_ffff => (3, 4),
+        _ => return Err(Error::InvalidData("address does not fit in an S-record")),
+    };
+
+    let mut out = String::new();
+    srecord(
+        &mut out,
+        0,
+        0,
+        2,
+        &header[..header.len().min(SRECORD_HEADER_LENGTH)],
+    );
+    for block in blocks {
+        for (index, line) in block.data.chunks(RECORD_LENGTH).enumerate() {
+            let address = block.address + (index * RECORD_LENGTH) as u64;
+            srecord(&mut out, data_type, address, address_size, line);
+        }
+    }
+    srecord(
+        &mut out,
+        10 - data_type,
+        start.unwrap_or(0),
+        address_size,
+        &[],
+    );
+
+    Ok(out)
+}
+
+fn srecord(out: &mut String, record_type: u8, address: u64, address_size: usize, data: &[u8]) {
+    let count = (address_size + data.len() + 1) as u8;
+    let address = &address.to_be_bytes()[8 - address_size..];
+    let _ = write!(out, "S{record_type}{count:02X}");
+    let mut sum = count;
+    for byte in address.iter().chain(data) {
+        sum = sum.wrapping_add(*byte);
+        let _ = write!(out, "{byte:02X}");
+    }
+    let _ = write!(out, "{:02X}\r\n", !sum);
+}
@@ -1,9 +1,10 @@
-use zerocopy::FromBytes;
+use zerocopy::{FromBytes, Immutable, IntoBytes};
 
 use crate::elf_enum;
+use crate::endian::impl_swap_bytes;
 
 #[repr(C)]
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes, IntoBytes, Immutable)]
 #[doc(alias = "Elf64_Phdr")]
 pub struct ProgramHeader64 {
     pub p_type: ProgramType,
@@ -16,31 +17,60 @@ pub struct ProgramHeader64 {
     pub p_align: u64,
 }
 
+impl_swap_bytes!(ProgramHeader64 {
+    p_type,
+    p_flags,
+    p_offset,
+    p_vaddr,
+    p_paddr,
+    p_filesz,
+    p_memsz,
+    p_align,
+});
+
+/// Segment flag marking executable memory
+#[doc(alias = "PF_X")]
+pub const SEGMENT_FLAG_EXECUTE: u32 = 0x1;
+/// Segment flag marking writable memory
+#[doc(alias = "PF_W")]
+pub const SEGMENT_FLAG_WRITE: u32 = 0x2;
+/// Segment flag marking readable memory
+#[doc(alias = "PF_R")]
+pub const SEGMENT_FLAG_READ: u32 = 0x4;
+
 elf_enum! {
     pub struct ProgramType(u32) {
-        ///
-        NULL = 0, "",
-        ///
-        LOAD = 1, "",
-        ///
-        DYNAMIC = 2, "",
-        ///
-        INTERP = 3, "",
-        ///
-        NOTE = 4, "",
-        ///
-        SHLIB = 5, "",
-        ///
-        PHDR = 6, "",
-        ///
-        TLS = 7, "",
-        ///
-        LOOS = 0x60000000, "",
-        ///
-        HIOS = 0x6fffffff, "",
-        ///
-        LOPROC = 0x70000000, "",
-        ///
-        HIPROC = 0x7fffffff, "",
+        /// Unused entry
+        NULL = 0, "NULL",
+        /// Loadable segment
+        LOAD = 1, "LOAD",
+        /// Dynamic linking information
+        DYNAMIC = 2, "DYNAMIC",
+        /// Program interpreter path
+        INTERP = 3, "INTERP",
+        /// Auxiliary information
+        NOTE = 4, "NOTE",
+        /// Reserved
+        SHLIB = 5, "SHLIB",
+        /// Program header table itself
+        PHDR = 6, "PHDR",
+        /// Thread-local storage template
+        TLS = 7, "TLS",
+        /// OS-specific range start
+        LOOS = 0x60000000, "LOOS",
+        /// Location of the `.eh_frame_hdr` section
+        GNU_EH_FRAME = 0x6474e550, "GNU_EH_FRAME",
+        /// Stack permissions
+        GNU_STACK = 0x6474e551, "GNU_STACK",
+        /// Read-only after relocation
+        GNU_RELRO = 0x6474e552, "GNU_RELRO",
+        /// GNU property notes
+        GNU_PROPERTY = 0x6474e553, "GNU_PROPERTY",
+        /// OS-specific range end
+        HIOS = 0x6fffffff, "HIOS",
+        /// Processor-specific range start
+        LOPROC = 0x70000000, "LOPROC",
+        /// Processor-specific range end
+        HIPROC = 0x7fffffff, "HIPROC",
     }
 }
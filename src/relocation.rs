@@ -0,0 +1,352 @@
+use zerocopy::{FromBytes, Immutable, IntoBytes};
+
+use crate::elf_enum;
+use crate::endian::impl_swap_bytes;
+use crate::header::Machine;
+use crate::table::Table;
+
+/// 64-bit relocation entry without an addend (Elf64_Rel)
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes, IntoBytes, Immutable)]
+#[doc(alias = "Elf64_Rel")]
+pub struct Rel64 {
+    /// Location to relocate, as a section offset or a virtual address
+    pub r_offset: u64,
+    /// Symbol index and relocation type
+    pub r_info: u64,
+}
+
+impl_swap_bytes!(Rel64 { r_offset, r_info });
+
+impl Rel64 {
+    #[doc(alias = "ELF64_R_SYM")]
+    pub const fn symbol(&self) -> u32 {
+        (self.r_info >> 32) as u32
+    }
+
+    #[doc(alias = "ELF64_R_TYPE")]
+    pub const fn r_type(&self) -> u32 {
+        self.r_info as u32
+    }
+}
+
+/// 64-bit relocation entry with an explicit addend (Elf64_Rela)
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes, IntoBytes, Immutable)]
+#[doc(alias = "Elf64_Rela")]
+pub struct Rela64 {
+    /// Location to relocate, as a section offset or a virtual address
+    pub r_offset: u64,
+    /// Symbol index and relocation type
+    pub r_info: u64,
+    /// Constant added to the value computed for the relocation
+    pub r_addend: i64,
+}
+
+impl_swap_bytes!(Rela64 {
+    r_offset,
+    r_info,
+    r_addend,
+});
+
+impl Rela64 {
+    pub const fn new(r_offset: u64, symbol: u32, r_type: u32, r_addend: i64) -> Self {
+        Self {
+            r_offset,
+            r_info: (symbol as u64) << 32 | r_type as u64,
+            r_addend,
+        }
+    }
+
+    #[doc(alias = "ELF64_R_SYM")]
+    pub const fn symbol(&self) -> u32 {
+        (self.r_info >> 32) as u32
+    }
+
+    #[doc(alias = "ELF64_R_TYPE")]
+    pub const fn r_type(&self) -> u32 {
+        self.r_info as u32
+    }
+}
+
+/// A view over a `SHT_REL` section
+pub type RelTable<'a> = Table<'a, Rel64>;
+
+/// A view over a `SHT_RELA` section
+pub type RelaTable<'a> = Table<'a, Rela64>;
+
+/// Returns the name of a relocation type for the given machine
+pub fn relocation_type_name(machine: &Machine, r_type: u32) -> &'static str {
+    match *machine {
+        Machine::X86_64 => X86_64RelocationType::from_raw(r_type).as_human_string(),
+        Machine::AARCH64 => Aarch64RelocationType::from_raw(r_type).as_human_string(),
+        Machine::RISCV => RiscvRelocationType::from_raw(r_type).as_human_string(),
+        _ => "unknown",
+    }
+}
+
+elf_enum! {
+    /// Relocation types for `EM_X86_64`
+    pub struct X86_64RelocationType(u32) {
+        /// No relocation
+        NONE = 0, "R_X86_64_NONE",
+        /// Direct 64-bit, S + A
+        ABS64 = 1, "R_X86_64_64",
+        /// PC-relative 32-bit, S + A - P
+        PC32 = 2, "R_X86_64_PC32",
+        /// 32-bit GOT entry offset, G + A
+        GOT32 = 3, "R_X86_64_GOT32",
+        /// 32-bit PLT address, L + A - P
+        PLT32 = 4, "R_X86_64_PLT32",
+        /// Copy the symbol at run time
+        COPY = 5, "R_X86_64_COPY",
+        /// Create a GOT entry, S
+        GLOB_DAT = 6, "R_X86_64_GLOB_DAT",
+        /// Create a PLT entry, S
+        JUMP_SLOT = 7, "R_X86_64_JUMP_SLOT",
+        /// Adjust by the load bias, B + A
+        RELATIVE = 8, "R_X86_64_RELATIVE",
+        /// 32-bit PC-relative offset to the GOT entry, G + GOT + A - P
+        GOTPCREL = 9, "R_X86_64_GOTPCREL",
+        /// Direct 32-bit zero-extended, S + A
+        ABS32 = 10, "R_X86_64_32",
+        /// Direct 32-bit sign-extended, S + A
+        ABS32S = 11, "R_X86_64_32S",
+        /// Direct 16-bit zero-extended, S + A
+        ABS16 = 12, "R_X86_64_16",
+        /// PC-relative 16-bit sign-extended, S + A - P
+        PC16 = 13, "R_X86_64_PC16",
+        /// Direct 8-bit sign-extended, S + A
+        ABS8 = 14, "R_X86_64_8",
+        /// PC-relative 8-bit sign-extended, S + A - P
+        PC8 = 15, "R_X86_64_PC8",
+        /// Module ID of the symbol
+        DTPMOD64 = 16, "R_X86_64_DTPMOD64",
+        /// Offset in the TLS block of the module
+        DTPOFF64 = 17, "R_X86_64_DTPOFF64",
+        /// Offset in the initial TLS block
+        TPOFF64 = 18, "R_X86_64_TPOFF64",
+        /// PC-relative offset to the GD GOT entry
+        TLSGD = 19, "R_X86_64_TLSGD",
+        /// PC-relative offset to the LD GOT entry
+        TLSLD = 20, "R_X86_64_TLSLD",
+        /// 32-bit offset in the TLS block of the module
+        DTPOFF32 = 21, "R_X86_64_DTPOFF32",
+        /// PC-relative offset to the IE GOT entry
+        GOTTPOFF = 22, "R_X86_64_GOTTPOFF",
+        /// 32-bit offset in the initial TLS block
+        TPOFF32 = 23, "R_X86_64_TPOFF32",
+        /// PC-relative 64-bit, S + A - P
+        PC64 = 24, "R_X86_64_PC64",
+        /// 64-bit offset to the GOT, S + A - GOT
+        GOTOFF64 = 25, "R_X86_64_GOTOFF64",
+        /// 32-bit PC-relative offset to the GOT, GOT + A - P
+        GOTPC32 = 26, "R_X86_64_GOTPC32",
+        /// Symbol size, Z + A
+        SIZE32 = 32, "R_X86_64_SIZE32",
+        /// Symbol size, Z + A
+        SIZE64 = 33, "R_X86_64_SIZE64",
+        /// PC-relative offset to the TLS descriptor GOT entry
+        GOTPC32_TLSDESC = 34, "R_X86_64_GOTPC32_TLSDESC",
+        /// Marker for the TLS descriptor call
+        TLSDESC_CALL = 35, "R_X86_64_TLSDESC_CALL",
+        /// TLS descriptor
+        TLSDESC = 36, "R_X86_64_TLSDESC",
+        /// Adjust indirectly by the load bias
+        IRELATIVE = 37, "R_X86_64_IRELATIVE",
+        /// Relaxable PC-relative offset to the GOT entry
+        GOTPCRELX = 41, "R_X86_64_GOTPCRELX",
+        /// Relaxable PC-relative offset to the GOT entry, with a REX prefix
+        REX_GOTPCRELX = 42, "R_X86_64_REX_GOTPCRELX",
+    }
+}
+
+elf_enum! {
+    /// Relocation types for `EM_AARCH64`
+    pub struct Aarch64RelocationType(u32) {
+        /// No relocation
+        NONE = 0, "R_AARCH64_NONE",
+        /// Direct 64-bit, S + A
+        ABS64 = 257, "R_AARCH64_ABS64",
+        /// Direct 32-bit, S + A
+        ABS32 = 258, "R_AARCH64_ABS32",
+        /// Direct 16-bit, S + A
+        ABS16 = 259, "R_AARCH64_ABS16",
+        /// PC-relative 64-bit, S + A - P
+        PREL64 = 260, "R_AARCH64_PREL64",
+        /// PC-relative 32-bit, S + A - P
+        PREL32 = 261, "R_AARCH64_PREL32",
+        /// PC-relative 16-bit, S + A - P
+        PREL16 = 262, "R_AARCH64_PREL16",
+        /// Bits 0-15 of S + A for `MOVZ`
+        MOVW_UABS_G0 = 263, "R_AARCH64_MOVW_UABS_G0",
+        /// Bits 0-15 of S + A for `MOVK`
+        MOVW_UABS_G0_NC = 264, "R_AARCH64_MOVW_UABS_G0_NC",
+        /// Bits 16-31 of S + A for `MOVZ`
+        MOVW_UABS_G1 = 265, "R_AARCH64_MOVW_UABS_G1",
+        /// Bits 16-31 of S + A for `MOVK`
+        MOVW_UABS_G1_NC = 266, "R_AARCH64_MOVW_UABS_G1_NC",
+        /// Bits 32-47 of S + A for `MOVZ`
+        MOVW_UABS_G2 = 267, "R_AARCH64_MOVW_UABS_G2",
+        /// Bits 32-47 of S + A for `MOVK`
+        MOVW_UABS_G2_NC = 268, "R_AARCH64_MOVW_UABS_G2_NC",
+        /// Bits 48-63 of S + A for `MOVZ` or `MOVK`
+        MOVW_UABS_G3 = 269, "R_AARCH64_MOVW_UABS_G3",
+        /// PC-relative 19-bit word offset for `LDR` literal
+        LD_PREL_LO19 = 273, "R_AARCH64_LD_PREL_LO19",
+        /// PC-relative 21-bit offset for `ADR`
+        ADR_PREL_LO21 = 274, "R_AARCH64_ADR_PREL_LO21",
+        /// PC-relative page offset for `ADRP`, Page(S + A) - Page(P)
+        ADR_PREL_PG_HI21 = 275, "R_AARCH64_ADR_PREL_PG_HI21",
+        /// Like `ADR_PREL_PG_HI21`, without overflow check
+        ADR_PREL_PG_HI21_NC = 276, "R_AARCH64_ADR_PREL_PG_HI21_NC",
+        /// Low 12 bits of S + A for `ADD`
+        ADD_ABS_LO12_NC = 277, "R_AARCH64_ADD_ABS_LO12_NC",
+        /// Low 12 bits of S + A for 8-bit loads and stores
+        LDST8_ABS_LO12_NC = 278, "R_AARCH64_LDST8_ABS_LO12_NC",
+        /// PC-relative 14-bit word offset for `TBZ` and `TBNZ`
+        TSTBR14 = 279, "R_AARCH64_TSTBR14",
+        /// PC-relative 19-bit word offset for conditional branches
+        CONDBR19 = 280, "R_AARCH64_CONDBR19",
+        /// PC-relative 26-bit word offset for `B`
+        JUMP26 = 282, "R_AARCH64_JUMP26",
+        /// PC-relative 26-bit word offset for `BL`
+        CALL26 = 283, "R_AARCH64_CALL26",
+        /// Low 12 bits of S + A for 16-bit loads and stores
+        LDST16_ABS_LO12_NC = 284, "R_AARCH64_LDST16_ABS_LO12_NC",
+        /// Low 12 bits of S + A for 32-bit loads and stores
+        LDST32_ABS_LO12_NC = 285, "R_AARCH64_LDST32_ABS_LO12_NC",
+        /// Low 12 bits of S + A for 64-bit loads and stores
+        LDST64_ABS_LO12_NC = 286, "R_AARCH64_LDST64_ABS_LO12_NC",
+        /// Low 12 bits of S + A for 128-bit loads and stores
+        LDST128_ABS_LO12_NC = 299, "R_AARCH64_LDST128_ABS_LO12_NC",
+        /// Page offset of the GOT entry for `ADRP`
+        ADR_GOT_PAGE = 311, "R_AARCH64_ADR_GOT_PAGE",
+        /// Low 12 bits of the GOT entry address for `LDR`
+        LD64_GOT_LO12_NC = 312, "R_AARCH64_LD64_GOT_LO12_NC",
+        /// Copy the symbol at run time
+        COPY = 1024, "R_AARCH64_COPY",
+        /// Create a GOT entry, S + A
+        GLOB_DAT = 1025, "R_AARCH64_GLOB_DAT",
+        /// Create a PLT entry, S + A
+        JUMP_SLOT = 1026, "R_AARCH64_JUMP_SLOT",
+        /// Adjust by the load bias, Delta(S) + A
+        RELATIVE = 1027, "R_AARCH64_RELATIVE",
+        /// Module ID of the symbol
+        TLS_DTPMOD = 1028, "R_AARCH64_TLS_DTPMOD",
+        /// Offset in the TLS block of the module
+        TLS_DTPREL = 1029, "R_AARCH64_TLS_DTPREL",
+        /// Offset in the initial TLS block
+        TLS_TPREL = 1030, "R_AARCH64_TLS_TPREL",
+        /// TLS descriptor
+        TLSDESC = 1031, "R_AARCH64_TLSDESC",
+        /// Adjust indirectly by the load bias
+        IRELATIVE = 1032, "R_AARCH64_IRELATIVE",
+    }
+}
+
+elf_enum! {
+    /// Relocation types for `EM_RISCV`
+    pub struct RiscvRelocationType(u32) {
+        /// No relocation
+        NONE = 0, "R_RISCV_NONE",
+        /// Direct 32-bit, S + A
+        ABS32 = 1, "R_RISCV_32",
+        /// Direct 64-bit, S + A
+        ABS64 = 2, "R_RISCV_64",
+        /// Adjust by the load bias, B + A
+        RELATIVE = 3, "R_RISCV_RELATIVE",
+        /// Copy the symbol at run time
+        COPY = 4, "R_RISCV_COPY",
+        /// Create a PLT entry, S
+        JUMP_SLOT = 5, "R_RISCV_JUMP_SLOT",
+        /// Module ID of the symbol
+        TLS_DTPMOD32 = 6, "R_RISCV_TLS_DTPMOD32",
+        /// Module ID of the symbol
+        TLS_DTPMOD64 = 7, "R_RISCV_TLS_DTPMOD64",
+        /// Offset in the TLS block of the module
+        TLS_DTPREL32 = 8, "R_RISCV_TLS_DTPREL32",
+        /// Offset in the TLS block of the module
+        TLS_DTPREL64 = 9, "R_RISCV_TLS_DTPREL64",
+        /// Offset in the initial TLS block
+        TLS_TPREL32 = 10, "R_RISCV_TLS_TPREL32",
+        /// Offset in the initial TLS block
+        TLS_TPREL64 = 11, "R_RISCV_TLS_TPREL64",
+        /// PC-relative 12-bit offset for conditional branches
+        BRANCH = 16, "R_RISCV_BRANCH",
+        /// PC-relative 20-bit offset for `JAL`
+        JAL = 17, "R_RISCV_JAL",
+        /// PC-relative call through an `AUIPC` and `JALR` pair
+        CALL = 18, "R_RISCV_CALL",
+        /// Like `CALL`, through the PLT
+        CALL_PLT = 19, "R_RISCV_CALL_PLT",
+        /// High 20 bits of the PC-relative offset to the GOT entry
+        GOT_HI20 = 20, "R_RISCV_GOT_HI20",
+        /// High 20 bits of the PC-relative offset to the IE GOT entry
+        TLS_GOT_HI20 = 21, "R_RISCV_TLS_GOT_HI20",
+        /// High 20 bits of the PC-relative offset to the GD GOT entry
+        TLS_GD_HI20 = 22, "R_RISCV_TLS_GD_HI20",
+        /// High 20 bits of a PC-relative offset, for `AUIPC`
+        PCREL_HI20 = 23, "R_RISCV_PCREL_HI20",
+        /// Low 12 bits of a PC-relative offset, for I-type instructions
+        PCREL_LO12_I = 24, "R_RISCV_PCREL_LO12_I",
+        /// Low 12 bits of a PC-relative offset, for S-type instructions
+        PCREL_LO12_S = 25, "R_RISCV_PCREL_LO12_S",
+        /// High 20 bits of an absolute address, for `LUI`
+        HI20 = 26, "R_RISCV_HI20",
+        /// Low 12 bits of an absolute address, for I-type instructions
+        LO12_I = 27, "R_RISCV_LO12_I",
+        /// Low 12 bits of an absolute address, for S-type instructions
+        LO12_S = 28, "R_RISCV_LO12_S",
+        /// High 20 bits of an offset in the initial TLS block
+        TPREL_HI20 = 29, "R_RISCV_TPREL_HI20",
+        /// Low 12 bits of an offset in the initial TLS block, I-type
+        TPREL_LO12_I = 30, "R_RISCV_TPREL_LO12_I",
+        /// Low 12 bits of an offset in the initial TLS block, S-type
+        TPREL_LO12_S = 31, "R_RISCV_TPREL_LO12_S",
+        /// Marker for the thread pointer addition
+        TPREL_ADD = 32, "R_RISCV_TPREL_ADD",
+        /// 8-bit in-place addition
+        ADD8 = 33, "R_RISCV_ADD8",
+        /// 16-bit in-place addition
+        ADD16 = 34, "R_RISCV_ADD16",
+        /// 32-bit in-place addition
+        ADD32 = 35, "R_RISCV_ADD32",
+        /// 64-bit in-place addition
+        ADD64 = 36, "R_RISCV_ADD64",
+        /// 8-bit in-place subtraction
+        SUB8 = 37, "R_RISCV_SUB8",
+        /// 16-bit in-place subtraction
+        SUB16 = 38, "R_RISCV_SUB16",
+        /// 32-bit in-place subtraction
+        SUB32 = 39, "R_RISCV_SUB32",
+        /// 64-bit in-place subtraction
+        SUB64 = 40, "R_RISCV_SUB64",
+        /// Alignment requirement for linker relaxation
+        ALIGN = 43, "R_RISCV_ALIGN",
+        /// PC-relative 8-bit offset for compressed branches
+        RVC_BRANCH = 44, "R_RISCV_RVC_BRANCH",
+        /// PC-relative 11-bit offset for compressed jumps
+        RVC_JUMP = 45, "R_RISCV_RVC_JUMP",
+        /// The preceding relocation may be relaxed
+        RELAX = 51, "R_RISCV_RELAX",
+        /// 6-bit in-place subtraction
+        SUB6 = 52, "R_RISCV_SUB6",
+        /// 6-bit in-place assignment
+        SET6 = 53, "R_RISCV_SET6",
+        /// 8-bit in-place assignment
+        SET8 = 54, "R_RISCV_SET8",
+        /// 16-bit in-place assignment
+        SET16 = 55, "R_RISCV_SET16",
+        /// 32-bit in-place assignment
+        SET32 = 56, "R_RISCV_SET32",
+        /// PC-relative 32-bit, S + A - P
+        PCREL32 = 57, "R_RISCV_32_PCREL",
+        /// Adjust indirectly by the load bias
+        IRELATIVE = 58, "R_RISCV_IRELATIVE",
+        /// ULEB128 in-place assignment
+        SET_ULEB128 = 60, "R_RISCV_SET_ULEB128",
+        /// ULEB128 in-place subtraction
+        SUB_ULEB128 = 61, "R_RISCV_SUB_ULEB128",
+    }
+}
@@ -0,0 +1,555 @@
+use alloc::borrow::Cow;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+use zerocopy::FromZeros;
+
+use crate::endian::{self, SwapBytes};
+use crate::error::{Error, Result};
+use crate::file::ElfFile;
+use crate::header::Header64;
+use crate::program_header::ProgramHeader64;
+use crate::relocation::{Rel64, RelTable, Rela64, RelaTable};
+use crate::section_header::{SECTION_FLAG_INFO_LINK, SectionHeader64, SectionType};
+use crate::string_table::{StringTable, StringTableBuilder};
+use crate::symbol::{SectionIndex, Symbol64, SymbolBinding, SymbolTable};
+use crate::table::Table;
+
+/// The sections of a file being rewritten
+///
+/// Sections are indexed like the section header table of the original file,
+/// with added sections after the existing ones. Removed sections keep their
+/// slot until the file is written, so indices stay valid while editing.
+///
+/// When the loaded contents are kept, the program headers and every byte
+/// covered by a segment or an existing allocated section stay at their
+/// original offsets, and the other sections are written after them. The
+/// indices held by symbol tables, relocations, groups and section headers
+/// are remapped to the remaining sections.
+#[derive(Debug, Clone)]
+pub(crate) struct SectionRewriter<'a> {
+    file: ElfFile<'a>,
+    pub(crate) headers: Vec<SectionHeader64>,
+    pub(crate) names: Vec<Cow<'a, [u8]>>,
+    pub(crate) contents: Vec<Cow<'a, [u8]>>,
+    pub(crate) removed: Vec<bool>,
+    /// Removes the local symbols that nothing refers to
+    pub(crate) discard_locals: bool,
+    /// Keeps the loaded contents at their offsets; otherwise only the headers
+    /// and allocated notes are kept and segments lose their file contents
+    pub(crate) keep_loaded: bool,
+    /// Number of sections of the original file
+    original: usize,
+    names_index: usize,
+}
+
+impl<'a> SectionRewriter<'a> {
+    pub(crate) fn new(file: &ElfFile<'a>) -> Result<Self> {
+        let headers: Vec<SectionHeader64> = file.section_headers().collect();
+        let original = headers.len();
+        let mut rewriter = Self {
+            file: file.clone(),
+            names: headers
+                .iter()
+                .map(|shdr| Cow::Borrowed(file.section_name(shdr).unwrap_or_default()))
+                .collect(),
+            contents: headers
+                .iter()
+                .enumerate()
+                .map(|(index, shdr)| match index {
+                    // The null section may hold the extended section count in sh_size
+                    0 => Ok(Cow::Borrowed(&[][..])),
+                    _ => file.section_data(shdr).map(Cow::Borrowed),
+                })
+                .collect::<Result<_>>()?,
+            removed: vec![false; original],
+            headers,
+            discard_locals: false,
+            keep_loaded: true,
+            original,
+            names_index: file.section_names_index(),
+        };
+
+        if original == 0 {
+            rewriter.add(b"", SectionHeader64::new_zeroed(), Vec::new());
+            rewriter.names_index = rewriter.add(
+                b".shstrtab",
+                SectionHeader64 {
+                    sh_type: SectionType::STRTAB,
+                    sh_addralign: 1,
+                    ..SectionHeader64::new_zeroed()
+                },
+                Vec::new(),
+            );
+        } else if rewriter.names_index == 0 || rewriter.names_index >= original {
+            return Err(Error::InvalidData("missing section header string table"));
+        }
+
+        Ok(rewriter)
+    }
+
+    /// Returns the index of the section header string table
+    pub(crate) fn names_index(&self) -> usize {
+        self.names_index
+    }
+
+    /// Returns the index of the first remaining section with the given name
+    pub(crate) fn find(&self, name: &[u8]) -> Option<usize> {
+        (1..self.headers.len()).find(|&index| !self.removed[index] && *self.names[index] == *name)
+    }
+
+    /// Adds a section after the existing ones and returns its index
+    ///
+    /// The offset and, unless the section is `SHT_NOBITS`, the size of the
+    /// header are set when the file is written.
+    pub(crate) fn add(&mut self, name: &[u8], header: SectionHeader64, contents: Vec<u8>) -> usize {
+        self.headers.push(header);
+        self.names.push(Cow::Owned(name.to_vec()));
+        self.contents.push(Cow::Owned(contents));
+        self.removed.push(false);
+        self.headers.len() - 1
+    }
+
+    /// Removes the sections that refer to removed ones, such as their
+    /// relocations, and the groups left without members
+    pub(crate) fn remove_dependents(&mut self) -> Result<()> {
+        let encoding = self.file.header().encoding.clone();
+
+        loop {
+            let mut changed = false;
+            for (index, shdr) in self.headers.iter().enumerate().skip(1) {
+                if self.removed[index] {
+                    continue;
+                }
+                let mut members = Table::<u32>::new(&self.contents[index]).iter().skip(1);
+                let empty_group = shdr.sh_type == SectionType::GROUP
+                    && members.len() != 0
+                    && members.all(|member| is_removed(&self.removed, member.convert(&encoding)));
+                if empty_group
+                    || is_removed(&self.removed, shdr.sh_link)
+                    || (info_is_section(shdr) && is_removed(&self.removed, shdr.sh_info))
+                {
+                    if shdr.is_alloc() {
+                        return Err(Error::InvalidData(
+                            "allocated section refers to a removed section",
+                        ));
+                    }
+                    self.removed[index] = true;
+                    changed = true;
+                }
+            }
+            if !changed {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Writes the new file
+    pub(crate) fn write(mut self) -> Result<Vec<u8>> {
+        let mut new_index = vec![None; self.headers.len()];
+        let mut count = 0;
+        for (index, removed) in self.removed.iter().enumerate() {
+            if !removed {
+                new_index[index] = Some(count);
+                count += 1;
+            }
+        }
+
+        self.rewrite_links(&new_index)?;
+
+        // Rebuild the section header string table from the remaining names
+        let mut names = StringTableBuilder::new();
+        for (index, shdr) in self.headers.iter_mut().enumerate() {
+            if new_index[index].is_some() {
+                shdr.sh_name = names.add(&self.names[index]);
+            }
+        }
+        self.contents[self.names_index] = Cow::Owned(names.into_bytes());
+
+        let mut out = self.layout(&new_index)?;
+
+        let encoding = self.file.header().encoding.clone();
+        let mut headers: Vec<SectionHeader64> = self
+            .headers
+            .into_iter()
+            .zip(&new_index)
+            .filter(|(_, index)| index.is_some())
+            .map(|(shdr, _)| shdr)
+            .collect();
+
+        let mut header = self.file.header().clone();
+        let count = headers.len() as u64;
+        let names_index = new_index[self.names_index].unwrap_or(0);
+        if count >= SectionIndex::LORESERVE.as_raw() as u64 {
+            header.section_header_count = 0;
+            headers[0].sh_size = count;
+        } else {
+            header.section_header_count = count as u16;
+            headers[0].sh_size = 0;
+        }
+        if names_index >= SectionIndex::LORESERVE.as_raw() as u32 {
+            header.section_header_string_table_index = SectionIndex::XINDEX.as_raw();
+            headers[0].sh_link = names_index;
+        } else {
+            header.section_header_string_table_index = names_index as u16;
+            headers[0].sh_link = 0;
+        }
+
+        out.resize(out.len().next_multiple_of(8), 0);
+        header.section_header_offset = out.len() as u64;
+        header.section_header_entry_size = size_of::<SectionHeader64>() as u16;
+        for shdr in &headers {
+            endian::write(&mut out, shdr, &encoding);
+        }
+        out[..size_of::<Header64>()].copy_from_slice(&header.to_bytes());
+
+        Ok(out)
+    }
+
+    /// Rewrites symbol tables, relocations and groups for the remaining
+    /// sections and remaps the section indices held by the section headers
+    fn rewrite_links(&mut self, new_index: &[Option<u32>]) -> Result<()> {
+        let mut symbol_maps = Vec::with_capacity(self.headers.len());
+        for index in 0..self.headers.len() {
+            let is_symbol_table =
+                new_index[index].is_some() && self.headers[index].sh_type == SectionType::SYMTAB;
+            symbol_maps.push(if is_symbol_table {
+                Some(self.rewrite_symbols(index, new_index)?)
+            } else {
+                None
+            });
+        }
+
+        let encoding = self.file.header().encoding.clone();
+        for index in 0..self.headers.len() {
+            if new_index[index].is_none() {
+                continue;
+            }
+
+            let shdr = &mut self.headers[index];
+            let symbol_map = symbol_maps
+                .get(shdr.sh_link as usize)
+                .and_then(Option::as_ref);
+
+            if shdr.sh_type == SectionType::GROUP {
+                let words = Table::<u32>::new(&self.contents[index]);
+                let mut data = Vec::new();
+                for (position, word) in words.iter().enumerate() {
+                    let word = word.convert(&encoding);
+                    let word = match position {
+                        0 => Some(word),
+                        _ => new_index.get(word as usize).copied().flatten(),
+                    };
+                    if let Some(word) = word {
+                        endian::write(&mut data, &word, &encoding);
+                    }
+                }
+                if let Some(symbol_map) = symbol_map {
+                    shdr.sh_info = remap_symbol(symbol_map, shdr.sh_info)?;
+                }
+                self.contents[index] = Cow::Owned(data);
+            } else if let Some(symbol_map) = symbol_map {
+                if shdr.sh_type == SectionType::REL {
+                    let relocations =
+                        RelTable::with_entry_size(&self.contents[index], shdr.sh_entsize as usize)?;
+                    let mut data = Vec::new();
+                    for relocation in relocations.iter() {
+                        let symbol = remap_symbol(symbol_map, relocation.symbol())?;
+                        let relocation = Rel64 {
+                            r_info: (symbol as u64) << 32 | relocation.r_type() as u64,
+                            ..relocation
+                        };
+                        endian::write(&mut data, &relocation, &encoding);
+                    }
+                    shdr.sh_entsize = size_of::<Rel64>() as u64;
+                    self.contents[index] = Cow::Owned(data);
+                } else if shdr.sh_type == SectionType::RELA {
+                    let relocations = RelaTable::with_entry_size(
+                        &self.contents[index],
+                        shdr.sh_entsize as usize,
+                    )?;
+                    let mut data = Vec::new();
+                    for relocation in relocations.iter() {
+                        let symbol = remap_symbol(symbol_map, relocation.symbol())?;
+                        let relocation = Rela64::new(
+                            relocation.r_offset,
+                            symbol,
+                            relocation.r_type(),
+                            relocation.r_addend,
+                        );
+                        endian::write(&mut data, &relocation, &encoding);
+                    }
+                    shdr.sh_entsize = size_of::<Rela64>() as u64;
+                    self.contents[index] = Cow::Owned(data);
+                }
+            }
+
+            let remap = |index: u32| new_index.get(index as usize).copied().flatten();
+            shdr.sh_link = remap(shdr.sh_link).unwrap_or(0);
+            if info_is_section(shdr) {
+                shdr.sh_info = remap(shdr.sh_info).unwrap_or(0);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes the symbols of the symbol table at `table` that are defined in
+    /// removed sections and, if requested, the local symbols that no
+    /// relocation or group refers to
+    ///
+    /// Returns the new index of every symbol that remains.
+    fn rewrite_symbols(
+        &mut self,
+        table: usize,
+        new_index: &[Option<u32>],
+    ) -> Result<Vec<Option<u32>>> {
+        let encoding = self.file.header().encoding.clone();
+        let shdr = self.headers[table].clone();
+        let symbol_data = self.contents[table].clone();
+        let symbols = SymbolTable::with_entry_size(&symbol_data, shdr.sh_entsize as usize)?;
+
+        let mut extended = None;
+        let mut referenced = vec![false; symbols.len()];
+        for (index, other) in self.headers.iter().enumerate() {
+            if new_index[index].is_none() || other.sh_link as usize != table {
+                continue;
+            }
+
+            let data = &self.contents[index];
+            let entry_size = other.sh_entsize as usize;
+            let mut mark = |symbol: u32| {
+                if let Some(referenced) = referenced.get_mut(symbol as usize) {
+                    *referenced = true;
+                }
+            };
+            if other.sh_type == SectionType::REL {
+                for relocation in RelTable::with_entry_size(data, entry_size)?.iter() {
+                    mark(relocation.symbol());
+                }
+            } else if other.sh_type == SectionType::RELA {
+                for relocation in RelaTable::with_entry_size(data, entry_size)?.iter() {
+                    mark(relocation.symbol());
+                }
+            } else if other.sh_type == SectionType::GROUP {
+                mark(other.sh_info);
+            } else if other.sh_type == SectionType::SYMTAB_SHNDX {
+                extended = Some((index, data.clone()));
+            }
+        }
+        let extended_table = extended.as_ref().map(|(_, data)| Table::<u32>::new(data));
+
+        let mut symbol_map = Vec::with_capacity(symbols.len());
+        let mut kept = Vec::with_capacity(symbols.len());
+        let mut extended_data = Vec::new();
+
+        for (index, mut symbol) in symbols.iter().enumerate() {
+            let raw = symbol.st_shndx.as_raw();
+            let section = if raw == SectionIndex::XINDEX.as_raw() {
+                extended_table
+                    .as_ref()
+                    .and_then(|table| table.get(index))
+                    .map(|value| value.convert(&encoding))
+            } else if raw == 0 || raw >= SectionIndex::LORESERVE.as_raw() {
+                None
+            } else {
+                Some(raw as u32)
+            };
+
+            let in_removed_section =
+                section.is_some_and(|section| new_index.get(section as usize) == Some(&None));
+            let unneeded_local = self.discard_locals
+                && index != 0
+                && symbol.binding() == SymbolBinding::LOCAL
+                && !referenced[index];
+            if in_removed_section || unneeded_local {
+                if referenced[index] {
+                    return Err(Error::InvalidData(
+                        "relocation refers to a symbol in a removed section",
+                    ));
+                }
+                symbol_map.push(None);
+                continue;
+            }
+
+            let new_section =
+                section.and_then(|section| new_index.get(section as usize).copied().flatten());
+            let mut extended_value = 0;
+            if let Some(new_section) = new_section {
+                if raw == SectionIndex::XINDEX.as_raw() {
+                    extended_value = new_section;
+                } else {
+                    symbol.st_shndx = SectionIndex::from_raw(new_section as u16);
+                }
+            }
+            endian::write(&mut extended_data, &extended_value, &encoding);
+            symbol_map.push(Some(kept.len() as u32));
+            kept.push(symbol);
+        }
+
+        // Drop the names of removed symbols, unless the string table is
+        // shared with the section names
+        let link = shdr.sh_link as usize;
+        if kept.len() < symbols.len()
+            && link != self.names_index
+            && new_index.get(link).is_some_and(Option::is_some)
+        {
+            let strings = StringTable::new(&self.contents[link]);
+            let mut names = StringTableBuilder::new();
+            for symbol in &mut kept {
+                symbol.st_name = names.add(strings.get(symbol.st_name).unwrap_or_default());
+            }
+            self.contents[link] = Cow::Owned(names.into_bytes());
+        }
+
+        let mut data = Vec::new();
+        for symbol in &kept {
+            endian::write(&mut data, symbol, &encoding);
+        }
+        self.headers[table].sh_info = kept
+            .iter()
+            .position(|symbol| symbol.binding() != SymbolBinding::LOCAL)
+            .unwrap_or(kept.len()) as u32;
+        self.headers[table].sh_entsize = size_of::<Symbol64>() as u64;
+        self.contents[table] = Cow::Owned(data);
+        if let Some((index, _)) = extended {
+            self.contents[index] = Cow::Owned(extended_data);
+        }
+
+        Ok(symbol_map)
+    }
+
+    /// Writes the file up to the section header table, updating the offset
+    /// and size of every remaining section
+    ///
+    /// When the loaded contents are kept, everything up to the end of the
+    /// last segment or allocated section is copied unchanged. Otherwise only
+    /// the headers and allocated notes keep their offsets, and the file size
+    /// of every segment is cut at the first byte that is no longer present.
+    fn layout(&mut self, new_index: &[Option<u32>]) -> Result<Vec<u8>> {
+        let data = self.file.data();
+        let header = self.file.header();
+        let keep_loaded = self.keep_loaded;
+        let original = self.original;
+        let mut program_headers: Vec<ProgramHeader64> = self.file.program_headers().collect();
+
+        // Sections whose contents stay at their original offsets
+        let fixed = |index: usize, shdr: &SectionHeader64| {
+            index < original
+                && !program_headers.is_empty()
+                && shdr.is_alloc()
+                && (keep_loaded || shdr.sh_type == SectionType::NOTE)
+        };
+
+        let mut intervals = vec![
+            (0, header.header_size as u64),
+            interval(
+                header.program_header_offset,
+                program_headers.len() as u64 * header.program_header_entry_size as u64,
+            )?,
+        ];
+        for (index, shdr) in self.headers.iter().enumerate() {
+            if new_index[index].is_some() && fixed(index, shdr) {
+                intervals.push(interval(shdr.sh_offset, shdr.file_size())?);
+            }
+        }
+        if keep_loaded {
+            for phdr in &program_headers {
+                intervals.push(interval(phdr.p_offset, phdr.p_filesz)?);
+            }
+        }
+
+        let end = intervals.iter().map(|&(_, end)| end).max().unwrap_or(0);
+        let prefix = data.get(..end as usize).ok_or(Error::OutOfBounds {
+            offset: 0,
+            size: end,
+        })?;
+        let mut out = if keep_loaded {
+            prefix.to_vec()
+        } else {
+            let mut out = vec![0; prefix.len()];
+            for &(start, end) in &intervals {
+                out[start as usize..end as usize]
+                    .copy_from_slice(&prefix[start as usize..end as usize]);
+            }
+            out
+        };
+
+        for (index, shdr) in self.headers.iter_mut().enumerate() {
+            if index == 0 || new_index[index].is_none() || fixed(index, shdr) {
+                continue;
+            }
+            if shdr.sh_type == SectionType::NOBITS {
+                shdr.sh_offset = out.len() as u64;
+                continue;
+            }
+            let offset = u64::try_from(out.len())
+                .ok()
+                .and_then(|offset| offset.checked_next_multiple_of(shdr.sh_addralign.max(1)))
+                .and_then(|offset| usize::try_from(offset).ok())
+                .ok_or(Error::InvalidData("invalid section alignment"))?;
+            out.resize(offset, 0);
+            shdr.sh_offset = out.len() as u64;
+            shdr.sh_size = self.contents[index].len() as u64;
+            out.extend_from_slice(&self.contents[index]);
+        }
+
+        if !keep_loaded {
+            let encoding = header.encoding.clone();
+            let entry_size = header.program_header_entry_size as u64;
+            for (index, phdr) in program_headers.iter_mut().enumerate() {
+                let (start, end) = interval(phdr.p_offset, phdr.p_filesz)?;
+                phdr.p_filesz = covered(&intervals, start, end) - start;
+                let offset = header.program_header_offset + index as u64 * entry_size;
+                endian::write_at(&mut out, offset as usize, phdr, &encoding)?;
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+/// Returns the file range of `size` bytes at `offset`
+fn interval(offset: u64, size: u64) -> Result<(u64, u64)> {
+    let end = offset
+        .checked_add(size)
+        .ok_or(Error::OutOfBounds { offset, size })?;
+    Ok((offset, end))
+}
+
+fn is_removed(removed: &[bool], index: u32) -> bool {
+    removed.get(index as usize).copied().unwrap_or(false)
+}
+
+/// Returns true if `sh_info` holds a section index rather than a count or a
+/// symbol index
+fn info_is_section(shdr: &SectionHeader64) -> bool {
+    shdr.sh_flags & SECTION_FLAG_INFO_LINK != 0
+        || shdr.sh_type == SectionType::REL
+        || shdr.sh_type == SectionType::RELA
+}
+
+fn remap_symbol(symbol_map: &[Option<u32>], symbol: u32) -> Result<u32> {
+    symbol_map
+        .get(symbol as usize)
+        .copied()
+        .flatten()
+        .ok_or(Error::InvalidData("reference to a removed symbol"))
+}
+
+/// Returns the end of the part of `start..end` that the union of
+/// `intervals` covers without a gap from `start`
+fn covered(intervals: &[(u64, u64)], start: u64, end: u64) -> u64 {
+    let mut position = start;
+    while position < end {
+        match intervals
+            .iter()
+            .find(|&&(start, end)| start <= position && position < end)
+        {
+            Some(&(_, end)) => position = end,
+            None => return position,
+        }
+    }
+    end
+}
@@ -0,0 +1,166 @@
+use zerocopy::{FromBytes, Immutable, IntoBytes};
+
+use crate::elf_enum;
+use crate::endian::impl_swap_bytes;
+use crate::table::Table;
+
+/// 64-bit section header (Elf64_Shdr)
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes, IntoBytes, Immutable)]
+#[doc(alias = "Elf64_Shdr")]
+pub struct SectionHeader64 {
+    /// Offset of the section name in the section header string table
+    pub sh_name: u32,
+    /// Section type
+    pub sh_type: SectionType,
+    /// Section flags, a combination of the `SECTION_FLAG_*` constants
+    pub sh_flags: u64,
+    /// Virtual address of the section in memory, or 0
+    pub sh_addr: u64,
+    /// File offset of the section contents
+    pub sh_offset: u64,
+    /// Size of the section
+    pub sh_size: u64,
+    /// Index of an associated section, depending on the type
+    pub sh_link: u32,
+    /// Extra information, depending on the type
+    pub sh_info: u32,
+    /// Required alignment of the section
+    pub sh_addralign: u64,
+    /// Size of each entry, for sections holding a table
+    pub sh_entsize: u64,
+}
+
+impl_swap_bytes!(SectionHeader64 {
+    sh_name,
+    sh_type,
+    sh_flags,
+    sh_addr,
+    sh_offset,
+    sh_size,
+    sh_link,
+    sh_info,
+    sh_addralign,
+    sh_entsize,
+});
+
+impl SectionHeader64 {
+    /// Returns true if the section occupies memory at run time
+    pub const fn is_alloc(&self) -> bool {
+        self.sh_flags & SECTION_FLAG_ALLOC != 0
+    }
+
+    /// Returns the size of the section contents in the file
+    pub fn file_size(&self) -> u64 {
+        if self.sh_type == SectionType::NOBITS {
+            0
+        } else {
+            self.sh_size
+        }
+    }
+}
+
+/// A view over a section header table
+pub type SectionHeaderTable<'a> = Table<'a, SectionHeader64>;
+
+/// Section flag marking data writable at run time
+#[doc(alias = "SHF_WRITE")]
+pub const SECTION_FLAG_WRITE: u64 = 0x1;
+/// Section flag marking a section that occupies memory at run time
+#[doc(alias = "SHF_ALLOC")]
+pub const SECTION_FLAG_ALLOC: u64 = 0x2;
+/// Section flag marking executable instructions
+#[doc(alias = "SHF_EXECINSTR")]
+pub const SECTION_FLAG_EXECINSTR: u64 = 0x4;
+/// Section flag marking data that may be merged to remove duplicates
+#[doc(alias = "SHF_MERGE")]
+pub const SECTION_FLAG_MERGE: u64 = 0x10;
+/// Section flag marking NUL-terminated strings
+#[doc(alias = "SHF_STRINGS")]
+pub const SECTION_FLAG_STRINGS: u64 = 0x20;
+/// Section flag marking an `sh_info` field holding a section index
+#[doc(alias = "SHF_INFO_LINK")]
+pub const SECTION_FLAG_INFO_LINK: u64 = 0x40;
+/// Section flag requiring the order of the linked section to be preserved
+#[doc(alias = "SHF_LINK_ORDER")]
+pub const SECTION_FLAG_LINK_ORDER: u64 = 0x80;
+/// Section flag requiring OS-specific processing
+#[doc(alias = "SHF_OS_NONCONFORMING")]
+pub const SECTION_FLAG_OS_NONCONFORMING: u64 = 0x100;
+/// Section flag marking a member of a section group
+#[doc(alias = "SHF_GROUP")]
+pub const SECTION_FLAG_GROUP: u64 = 0x200;
+/// Section flag marking thread-local storage
+#[doc(alias = "SHF_TLS")]
+pub const SECTION_FLAG_TLS: u64 = 0x400;
+/// Section flag marking compressed contents
+#[doc(alias = "SHF_COMPRESSED")]
+pub const SECTION_FLAG_COMPRESSED: u64 = 0x800;
+/// Section flag excluding the section from links and executables
+#[doc(alias = "SHF_EXCLUDE")]
+pub const SECTION_FLAG_EXCLUDE: u64 = 0x80000000;
+
+elf_enum! {
+    pub struct SectionType(u32) {
+        /// Inactive section header
+        NULL = 0, "NULL",
+        /// Program defined contents
+        PROGBITS = 1, "PROGBITS",
+        /// Symbol table
+        SYMTAB = 2, "SYMTAB",
+        /// String table
+        STRTAB = 3, "STRTAB",
+        /// Relocations with explicit addends
+        RELA = 4, "RELA",
+        /// Symbol hash table
+        HASH = 5, "HASH",
+        /// Dynamic linking information
+        DYNAMIC = 6, "DYNAMIC",
+        /// Notes
+        NOTE = 7, "NOTE",
+        /// Occupies no space in the file, such as `.bss`
+        NOBITS = 8, "NOBITS",
+        /// Relocations without explicit addends
+        REL = 9, "REL",
+        /// Reserved
+        SHLIB = 10, "SHLIB",
+        /// Dynamic symbol table
+        DYNSYM = 11, "DYNSYM",
+        /// Array of pointers to initialization functions
+        INIT_ARRAY = 14, "INIT_ARRAY",
+        /// Array of pointers to termination functions
+        FINI_ARRAY = 15, "FINI_ARRAY",
+        /// Array of pointers to pre-initialization functions
+        PREINIT_ARRAY = 16, "PREINIT_ARRAY",
+        /// Section group
+        GROUP = 17, "GROUP",
+        /// Extended section indices for a symbol table
+        SYMTAB_SHNDX = 18, "SYMTAB SECTION INDICES",
+        /// Relative relocations in compact form
+        RELR = 19, "RELR",
+        /// OS-specific range start
+        LOOS = 0x60000000, "LOOS",
+        /// Object attributes
+        GNU_ATTRIBUTES = 0x6ffffff5, "GNU_ATTRIBUTES",
+        /// GNU style symbol hash table
+        GNU_HASH = 0x6ffffff6, "GNU_HASH",
+        /// Prelink library list
+        GNU_LIBLIST = 0x6ffffff7, "GNU_LIBLIST",
+        /// Symbol version definitions
+        GNU_VERDEF = 0x6ffffffd, "VERDEF",
+        /// Symbol version requirements
+        GNU_VERNEED = 0x6ffffffe, "VERNEED",
+        /// Symbol version table
+        GNU_VERSYM = 0x6fffffff, "VERSYM",
+        /// Processor-specific range start
+        LOPROC = 0x70000000, "LOPROC",
+        /// x86-64 unwind information
+        X86_64_UNWIND = 0x70000001, "X86_64_UNWIND",
+        /// Processor-specific range end
+        HIPROC = 0x7fffffff, "HIPROC",
+        /// Application-specific range start
+        LOUSER = 0x80000000, "LOUSER",
+        /// Application-specific range end
+        HIUSER = 0xffffffff, "HIUSER",
+    }
+}
@@ -0,0 +1,208 @@
+use crate::dynamic::{
+    DYNAMIC_FLAG_1_NOW, DYNAMIC_FLAG_1_PIE, DYNAMIC_FLAG_BIND_NOW, DynamicTable, DynamicTag,
+};
+use crate::file::ElfFile;
+use crate::header::{Machine, ObjectType};
+use crate::note::{
+    PROPERTY_AARCH64_FEATURE_1_AND, PROPERTY_AARCH64_FEATURE_1_BTI, PROPERTY_AARCH64_FEATURE_1_PAC,
+    PROPERTY_X86_FEATURE_1_AND, PROPERTY_X86_FEATURE_1_IBT, PROPERTY_X86_FEATURE_1_SHSTK,
+};
+use crate::program_header::{ProgramType, SEGMENT_FLAG_EXECUTE};
+use crate::section_header::SectionType;
+
+/// Relocation read-only protection of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relro {
+    /// No `PT_GNU_RELRO` segment, so relocated data stays writable
+    None,
+    /// A `PT_GNU_RELRO` segment without immediate binding, so the GOT entries
+    /// of lazily bound functions stay writable
+    Partial,
+    /// A `PT_GNU_RELRO` segment with `DF_BIND_NOW` or `DF_1_NOW`, so the whole
+    /// GOT is made read-only after relocation
+    Full,
+}
+
+/// Whether a file is loaded at a random address
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pie {
+    /// An `ET_EXEC` executable, always loaded at its link address
+    Disabled,
+    /// An `ET_DYN` executable marked with `DF_1_PIE`
+    Enabled,
+    /// An `ET_DYN` file without `DF_1_PIE`, such as a shared library
+    SharedObject,
+    /// A relocatable object or core file, which is never loaded
+    NotApplicable,
+}
+
+/// Hardware control-flow protections a file was built for, from its
+/// `NT_GNU_PROPERTY_TYPE_0` note
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ControlFlow {
+    /// x86 CET indirect branch tracking
+    pub ibt: bool,
+    /// x86 CET shadow stack
+    pub shstk: bool,
+    /// AArch64 branch target identification
+    pub bti: bool,
+    /// AArch64 pointer authentication
+    pub pac: bool,
+}
+
+/// Hardening features of a file, as reported by tools like `checksec`
+///
+/// Everything is derived from the file itself: segments, dynamic entries,
+/// imported symbols and notes. Nothing is loaded or executed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecurityReport<'a> {
+    pub relro: Relro,
+    /// True if a `PT_GNU_STACK` segment marks the stack non-executable
+    ///
+    /// Without the segment, Linux maps the stack executable. `None` for a
+    /// file without program headers, such as a relocatable object, which is
+    /// never loaded.
+    pub nx: Option<bool>,
+    pub pie: Pie,
+    /// True if the file refers to `__stack_chk_fail`, meaning some functions
+    /// are protected by stack canaries
+    pub stack_canary: bool,
+    /// Number of distinct `_FORTIFY_SOURCE` checked functions the file
+    /// refers to, listed by [`fortified_functions`]
+    pub fortified: usize,
+    /// The `DT_RPATH` search path, without its NUL terminator
+    pub rpath: Option<&'a [u8]>,
+    /// The `DT_RUNPATH` search path, without its NUL terminator
+    pub runpath: Option<&'a [u8]>,
+    pub control_flow: ControlFlow,
+    /// True if the file has no `SHT_SYMTAB` symbol table
+    pub stripped: bool,
+}
+
+impl<'a> SecurityReport<'a> {
+    /// Inspects the hardening features of `elf`
+    pub fn from_file(elf: &ElfFile<'a>) -> Self {
+        let dynamic = elf.dynamic().ok();
+        let flags = |tag| {
+            dynamic
+                .as_ref()
+                .and_then(|dynamic: &DynamicTable| dynamic.find(tag))
+                .unwrap_or(0)
+        };
+        let dynamic_string = |tag| {
+            let offset = dynamic.as_ref()?.find(tag)?;
+            elf.dynamic_strings().ok()?.get(u32::try_from(offset).ok()?)
+        };
+
+        let bind_now = flags(DynamicTag::FLAGS) & DYNAMIC_FLAG_BIND_NOW != 0
+            || flags(DynamicTag::FLAGS_1) & DYNAMIC_FLAG_1_NOW != 0;
+        let relro = match elf.find_segment(ProgramType::GNU_RELRO) {
+            None => Relro::None,
+            Some(_) if bind_now => Relro::Full,
+            Some(_) => Relro::Partial,
+        };
+
+        let nx = elf.program_headers().next().map(|_| {
+            elf.find_segment(ProgramType::GNU_STACK)
+                .is_some_and(|phdr| phdr.p_flags & SEGMENT_FLAG_EXECUTE == 0)
+        });
+
+        let pie = match elf.header().object_type {
+            ObjectType::EXEC => Pie::Disabled,
+            ObjectType::DYN if flags(DynamicTag::FLAGS_1) & DYNAMIC_FLAG_1_PIE != 0 => Pie::Enabled,
+            ObjectType::DYN => Pie::SharedObject,
+            _ => Pie::NotApplicable,
+        };
+
+        let stack_canary = referenced_symbols(elf).any(|name| name == b"__stack_chk_fail");
+        let fortified = fortified_functions(elf).count();
+
+        Self {
+            relro,
+            nx,
+            pie,
+            stack_canary,
+            fortified,
+            rpath: dynamic_string(DynamicTag::RPATH),
+            runpath: dynamic_string(DynamicTag::RUNPATH),
+            control_flow: control_flow(elf),
+            stripped: !elf
+                .section_headers()
+                .any(|shdr| shdr.sh_type == SectionType::SYMTAB),
+        }
+    }
+}
+
+/// Iterates over the distinct `_FORTIFY_SOURCE` checked functions, such as
+/// `__memcpy_chk`, the file refers to
+pub fn fortified_functions<'a>(elf: &ElfFile<'a>) -> impl Iterator<Item = &'a [u8]> + 'a {
+    let is_fortified = |name: &[u8]| {
+        name.starts_with(b"__") && name.ends_with(b"_chk") && name != b"__stack_chk_fail"
+    };
+
+    // Versioned copies can repeat a name, so only the first occurrence of
+    // each is kept, without allocating
+    let earlier = referenced_symbols(elf);
+    referenced_symbols(elf)
+        .enumerate()
+        .filter(move |&(index, name)| {
+            is_fortified(name) && !earlier.clone().take(index).any(|other| other == name)
+        })
+        .map(|(_, name)| name)
+}
+
+/// Iterates over the names of the symbols a file imports or, for a file
+/// without dynamic symbols such as a static executable, every symbol of its
+/// symbol table
+fn referenced_symbols<'a>(elf: &ElfFile<'a>) -> impl Iterator<Item = &'a [u8]> + Clone + 'a {
+    let dynamic = elf.dynamic_symbols().ok().map(|symbols| {
+        let strings = *symbols.strings();
+        symbols
+            .symbols()
+            .iter()
+            .filter(|symbol| symbol.is_undefined())
+            .filter_map(move |symbol| strings.get(symbol.st_name))
+    });
+
+    let symtab = match dynamic {
+        Some(_) => None,
+        None => elf
+            .section_headers()
+            .find(|shdr| shdr.sh_type == SectionType::SYMTAB)
+            .and_then(|shdr| Some((elf.symbols(&shdr).ok()?, elf.linked_strings(&shdr).ok()?)))
+            .map(|(symbols, strings)| {
+                symbols
+                    .iter()
+                    .filter_map(move |symbol| strings.get(symbol.st_name))
+            }),
+    };
+
+    dynamic
+        .into_iter()
+        .flatten()
+        .chain(symtab.into_iter().flatten())
+}
+
+fn control_flow(elf: &ElfFile<'_>) -> ControlFlow {
+    let machine = &elf.header().machine;
+    let mut control_flow = ControlFlow::default();
+
+    for property in elf.gnu_properties() {
+        let Some(features) = property.value() else {
+            continue;
+        };
+        match property.pr_type {
+            PROPERTY_X86_FEATURE_1_AND if matches!(*machine, Machine::X86_64 | Machine::_386) => {
+                control_flow.ibt = features & PROPERTY_X86_FEATURE_1_IBT != 0;
+                control_flow.shstk = features & PROPERTY_X86_FEATURE_1_SHSTK != 0;
+            }
+            PROPERTY_AARCH64_FEATURE_1_AND if *machine == Machine::AARCH64 => {
+                control_flow.bti = features & PROPERTY_AARCH64_FEATURE_1_BTI != 0;
+                control_flow.pac = features & PROPERTY_AARCH64_FEATURE_1_PAC != 0;
+            }
+            _ => {}
+        }
+    }
+
+    control_flow
+}
@@ -0,0 +1,98 @@
+#[cfg(feature = "alloc")]
+use alloc::collections::BTreeMap;
+#[cfg(feature = "alloc")]
+use alloc::vec;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// A view over a table of NUL-terminated strings, such as `.dynstr` or `.strtab`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StringTable<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> StringTable<'a> {
+    pub const fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Returns the raw bytes backing the table
+    pub const fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Returns the string starting at `offset`, without its NUL terminator
+    pub fn get(&self, offset: u32) -> Option<&'a [u8]> {
+        let bytes = self.data.get(offset as usize..)?;
+        let end = bytes.iter().position(|&b| b == 0)?;
+        Some(&bytes[..end])
+    }
+
+    /// Returns the string starting at `offset` if it is valid UTF-8
+    pub fn get_str(&self, offset: u32) -> Option<&'a str> {
+        self.get(offset)
+            .and_then(|bytes| core::str::from_utf8(bytes).ok())
+    }
+}
+
+/// Builder for string tables, storing each distinct string once
+///
+/// The table starts with an empty string, so offset 0 always names nothing.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+pub struct StringTableBuilder {
+    data: Vec<u8>,
+    offsets: BTreeMap<Vec<u8>, u32>,
+}
+
+#[cfg(feature = "alloc")]
+impl StringTableBuilder {
+    pub fn new() -> Self {
+        Self {
+            data: vec![0],
+            offsets: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a string, without its NUL terminator, and returns its offset
+    pub fn add(&mut self, string: &[u8]) -> u32 {
+        if string.is_empty() {
+            return 0;
+        }
+
+        if let Some(&offset) = self.offsets.get(string) {
+            return offset;
+        }
+
+        let offset = self.data.len() as u32;
+        self.data.extend_from_slice(string);
+        self.data.push(0);
+        self.offsets.insert(string.to_vec(), offset);
+        offset
+    }
+
+    /// Returns the size of the table in bytes
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns true if the table only holds the empty string
+    pub fn is_empty(&self) -> bool {
+        self.data.len() == 1
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Default for StringTableBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,110 @@
+use alloc::vec::Vec;
+
+use crate::error::Result;
+use crate::file::ElfFile;
+use crate::header::ObjectType;
+use crate::rewrite::SectionRewriter;
+use crate::section_header::SectionType;
+
+/// What [`strip`] removes from a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StripMode {
+    /// Removes debugging sections and the symbols defined in them, like
+    /// `strip --strip-debug`
+    Debug,
+    /// Removes debugging sections and local symbols, like
+    /// `strip --strip-debug --discard-all`
+    DebugAndLocals,
+    /// Removes debugging sections, `.comment` and the symbol table, like
+    /// `strip --strip-all`
+    ///
+    /// The relocations of a relocatable object refer to its symbol table, so
+    /// only the local symbols nothing refers to are removed from it.
+    All,
+    /// Keeps only debugging information, the symbol table and notes, like
+    /// `objcopy --only-keep-debug`
+    ///
+    /// Every other section keeps its header but becomes `SHT_NOBITS`, so the
+    /// result can serve as the separate debug file of the original.
+    OnlyKeepDebug,
+}
+
+/// Returns true if `name` is the name of a section holding debugging
+/// information
+pub fn is_debug_section(name: &[u8]) -> bool {
+    [
+        b".debug".as_slice(),
+        b".zdebug",
+        b".stab",
+        b".gdb_index",
+        b".line",
+    ]
+    .iter()
+    .any(|prefix| name.starts_with(prefix))
+}
+
+/// Removes sections and symbols from `file` according to `mode`
+///
+/// Except with [`StripMode::OnlyKeepDebug`], the program headers and every
+/// byte covered by a segment or an allocated section stay at their original
+/// offsets, so the stripped file loads exactly like the original. The
+/// remaining sections are written after them, followed by a new section
+/// header table. Sections that link to a removed section, such as the
+/// relocations of a debugging section, are removed with it.
+///
+/// Removed sections are expected to follow the allocated ones, as linkers
+/// place them, so that the section indices held by `.dynsym` stay valid.
+pub fn strip(file: &ElfFile, mode: StripMode) -> Result<Vec<u8>> {
+    if file.section_count() == 0 {
+        return Ok(file.data().to_vec());
+    }
+
+    let mut rewriter = SectionRewriter::new(file)?;
+    let names_index = rewriter.names_index();
+    let relocatable = file.header().object_type == ObjectType::REL;
+
+    for index in 1..rewriter.headers.len() {
+        let shdr = &rewriter.headers[index];
+        if index == names_index || shdr.is_alloc() {
+            continue;
+        }
+
+        let name = &rewriter.names[index];
+        let remove = match mode {
+            StripMode::Debug | StripMode::DebugAndLocals => is_debug_section(name),
+            StripMode::All => {
+                is_debug_section(name)
+                    || **name == *b".comment"
+                    || (!relocatable && shdr.sh_type == SectionType::SYMTAB)
+            }
+            StripMode::OnlyKeepDebug => false,
+        };
+        if remove {
+            rewriter.removed[index] = true;
+            // The string table of a removed symbol table goes with it
+            let strings = shdr.sh_link as usize;
+            if shdr.sh_type == SectionType::SYMTAB
+                && strings != names_index
+                && let Some(removed) = rewriter.removed.get_mut(strings)
+            {
+                *removed = true;
+            }
+        }
+    }
+    rewriter.remove_dependents()?;
+
+    match mode {
+        StripMode::Debug => {}
+        StripMode::DebugAndLocals | StripMode::All => rewriter.discard_locals = true,
+        StripMode::OnlyKeepDebug => {
+            rewriter.keep_loaded = false;
+            for shdr in &mut rewriter.headers {
+                if shdr.is_alloc() && shdr.sh_type != SectionType::NOTE {
+                    shdr.sh_type = SectionType::NOBITS;
+                }
+            }
+        }
+    }
+
+    rewriter.write()
+}
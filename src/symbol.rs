@@ -0,0 +1,183 @@
+#[cfg(feature = "demangle")]
+use alloc::{format, string::String};
+
+use zerocopy::{FromBytes, Immutable, IntoBytes};
+
+use crate::elf_enum;
+use crate::endian::impl_swap_bytes;
+use crate::table::Table;
+
+/// 64-bit symbol table entry (Elf64_Sym)
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes, IntoBytes, Immutable)]
+#[doc(alias = "Elf64_Sym")]
+pub struct Symbol64 {
+    /// String table offset of the symbol name
+    pub st_name: u32,
+    /// Symbol binding and type
+    pub st_info: u8,
+    /// Symbol visibility
+    pub st_other: u8,
+    /// Index of the section the symbol is defined in
+    pub st_shndx: SectionIndex,
+    /// Symbol value, usually an address
+    pub st_value: u64,
+    /// Size of the object the symbol refers to
+    pub st_size: u64,
+}
+
+impl_swap_bytes!(Symbol64 {
+    st_name,
+    st_info,
+    st_other,
+    st_shndx,
+    st_value,
+    st_size,
+});
+
+impl Symbol64 {
+    #[doc(alias = "ELF64_ST_BIND")]
+    pub const fn binding(&self) -> SymbolBinding {
+        SymbolBinding::from_raw(self.st_info >> 4)
+    }
+
+    #[doc(alias = "ELF64_ST_TYPE")]
+    pub const fn symbol_type(&self) -> SymbolType {
+        SymbolType::from_raw(self.st_info & 0xf)
+    }
+
+    #[doc(alias = "ELF64_ST_VISIBILITY")]
+    pub const fn visibility(&self) -> SymbolVisibility {
+        SymbolVisibility::from_raw(self.st_other & 0x3)
+    }
+
+    /// Returns true if the symbol refers to a definition in another object
+    pub const fn is_undefined(&self) -> bool {
+        self.st_shndx.as_raw() == SectionIndex::UNDEF.as_raw()
+    }
+}
+
+/// A view over a symbol table, such as `.dynsym` or `.symtab`
+pub type SymbolTable<'a> = Table<'a, Symbol64>;
+
+/// Demangles a Rust or C++ (Itanium ABI) symbol name
+///
+/// Rust names are shown without their trailing hash. Returns `None` for
+/// names that are not mangled, such as those of C functions.
+#[cfg(feature = "demangle")]
+pub fn demangle(name: &[u8]) -> Option<String> {
+    let name = core::str::from_utf8(name).ok()?;
+
+    if let Ok(demangled) = rustc_demangle::try_demangle(name) {
+        return Some(format!("{demangled:#}"));
+    }
+
+    // cpp_demangle also accepts bare type encodings, such as `g` for
+    // `__float128`, so only names with the mangling prefix are tried
+    if !name.starts_with("_Z") && !name.starts_with("__Z") {
+        return None;
+    }
+    cpp_demangle::Symbol::new(name)
+        .ok()?
+        .demangle(&cpp_demangle::DemangleOptions::default())
+        .ok()
+}
+
+elf_enum! {
+    pub struct SymbolBinding(u8) {
+        /// Not visible outside the object file
+        LOCAL = 0, "LOCAL",
+        /// Visible to all object files being combined
+        GLOBAL = 1, "GLOBAL",
+        /// Like a global symbol, with lower precedence
+        WEAK = 2, "WEAK",
+        /// Unique in the whole process (GNU extension)
+        GNU_UNIQUE = 10, "UNIQUE",
+        /// OS-specific range start
+        LOOS = 10, "LOOS",
+        /// OS-specific range end
+        HIOS = 12, "HIOS",
+        /// Processor-specific range start
+        LOPROC = 13, "LOPROC",
+        /// Processor-specific range end
+        HIPROC = 15, "HIPROC",
+    }
+}
+
+elf_enum! {
+    pub struct SymbolType(u8) {
+        /// Unspecified type
+        NOTYPE = 0, "NOTYPE",
+        /// Data object
+        OBJECT = 1, "OBJECT",
+        /// Function or other executable code
+        FUNC = 2, "FUNC",
+        /// Section
+        SECTION = 3, "SECTION",
+        /// Source file name
+        FILE = 4, "FILE",
+        /// Uninitialized common block
+        COMMON = 5, "COMMON",
+        /// Thread-local storage object
+        TLS = 6, "TLS",
+        /// Indirect function (GNU extension)
+        GNU_IFUNC = 10, "IFUNC",
+        /// OS-specific range start
+        LOOS = 10, "LOOS",
+        /// OS-specific range end
+        HIOS = 12, "HIOS",
+        /// Processor-specific range start
+        LOPROC = 13, "LOPROC",
+        /// Processor-specific range end
+        HIPROC = 15, "HIPROC",
+    }
+}
+
+elf_enum! {
+    pub struct SymbolVisibility(u8) {
+        /// Visibility is determined by the binding
+        DEFAULT = 0, "DEFAULT",
+        /// Processor-specific hidden visibility
+        INTERNAL = 1, "INTERNAL",
+        /// Not visible to other components
+        HIDDEN = 2, "HIDDEN",
+        /// Visible but not preemptible
+        PROTECTED = 3, "PROTECTED",
+    }
+}
+
+elf_enum! {
+    pub struct SectionIndex(u16) {
+        /// Undefined section
+        UNDEF = 0, "UND",
+        /// Reserved range start
+        LORESERVE = 0xff00, "LORESERVE",
+        /// Absolute value, not affected by relocation
+        ABS = 0xfff1, "ABS",
+        /// Common block not yet allocated
+        COMMON = 0xfff2, "COM",
+        /// The real index is held in an SHT_SYMTAB_SHNDX section
+        XINDEX = 0xffff, "XINDEX",
+    }
+}
+
+#[cfg(all(test, feature = "demangle"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn demangle_names() {
+        for name in [&b"g"[..], b"t", b"main", b"_start"] {
+            assert_eq!(demangle(name), None);
+        }
+        assert_eq!(demangle(b"_Z3fooi").as_deref(), Some("foo(int)"));
+        assert_eq!(
+            demangle(b"_ZN2ns6Widget4drawEv").as_deref(),
+            Some("ns::Widget::draw()")
+        );
+        assert_eq!(
+            demangle(b"_ZN3foo3bar17h05af221e174051e9E").as_deref(),
+            Some("foo::bar")
+        );
+    }
+}
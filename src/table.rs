@@ -0,0 +1,130 @@
+use core::marker::PhantomData;
+use core::mem::size_of;
+
+use zerocopy::FromBytes;
+
+use crate::error::{Error, Result};
+
+/// A view over a packed array of fixed-size ELF records
+///
+/// Entries are copied out of the underlying bytes on access, so the data does
+/// not need to be aligned for `T`. The entry size may be larger than `T` to
+/// accommodate tables whose `*_entsize` field declares extra padding.
+pub struct Table<'a, T> {
+    data: &'a [u8],
+    entry_size: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<'a, T: FromBytes> Table<'a, T> {
+    /// Creates a table whose entries are exactly `size_of::<T>()` bytes
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            entry_size: size_of::<T>(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Creates a table with an explicit entry size
+    pub fn with_entry_size(data: &'a [u8], entry_size: usize) -> Result<Self> {
+        if entry_size < size_of::<T>() {
+            return Err(Error::InvalidEntrySize {
+                expected: size_of::<T>(),
+                found: entry_size,
+            });
+        }
+
+        Ok(Self {
+            data,
+            entry_size,
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len() / self.entry_size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn entry_size(&self) -> usize {
+        self.entry_size
+    }
+
+    /// Returns the raw bytes backing the table
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    pub fn get(&self, index: usize) -> Option<T> {
+        if index >= self.len() {
+            return None;
+        }
+
+        let start = index * self.entry_size;
+        T::read_from_prefix(&self.data[start..])
+            .ok()
+            .map(|(entry, _)| entry)
+    }
+
+    pub fn iter(&self) -> TableIter<'a, T> {
+        TableIter {
+            table: self.clone(),
+            index: 0,
+        }
+    }
+}
+
+impl<T> Clone for Table<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            data: self.data,
+            entry_size: self.entry_size,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> core::fmt::Debug for Table<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Table")
+            .field("len", &(self.data.len() / self.entry_size))
+            .field("entry_size", &self.entry_size)
+            .finish()
+    }
+}
+
+impl<'a, T: FromBytes> IntoIterator for &Table<'a, T> {
+    type Item = T;
+    type IntoIter = TableIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TableIter<'a, T> {
+    table: Table<'a, T>,
+    index: usize,
+}
+
+impl<T: FromBytes> Iterator for TableIter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.table.get(self.index)?;
+        self.index += 1;
+        Some(entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.table.len().saturating_sub(self.index);
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T: FromBytes> ExactSizeIterator for TableIter<'_, T> {}
@@ -0,0 +1,74 @@
+use core::ffi::c_void;
+use core::ptr::NonNull;
+
+use crate::dynamic::DynamicSymbols;
+use crate::error::Result;
+use crate::image::ElfImage;
+use crate::symbol::{SymbolBinding, SymbolType};
+
+/// A view over the vDSO the kernel maps into every process
+///
+/// The vDSO is located through the `AT_SYSINFO_EHDR` auxiliary vector entry
+/// and is addressed purely through its program headers, since its section
+/// headers are not guaranteed to be mapped.
+#[derive(Debug, Clone)]
+pub struct Vdso<'a> {
+    image: ElfImage<'a>,
+    symbols: DynamicSymbols<'a>,
+}
+
+impl<'a> Vdso<'a> {
+    /// Builds a view over the vDSO mapped at `base`
+    ///
+    /// # Safety
+    ///
+    /// `base` must point to the start of a complete ELF image mapped into
+    /// memory according to its program headers, such as the value of the
+    /// `AT_SYSINFO_EHDR` auxiliary vector entry. The mapping must stay valid
+    /// and unmodified for the lifetime `'a`.
+    pub unsafe fn from_base(base: *const u8) -> Result<Self> {
+        // SAFETY: forwarded to the caller
+        let image = unsafe { ElfImage::from_base(base)? };
+        let symbols = image.dynamic_symbols()?;
+
+        Ok(Self { image, symbols })
+    }
+
+    /// Resolves a function symbol with the given version to its runtime address
+    ///
+    /// If the vDSO carries no version information, the version is ignored.
+    ///
+    /// ```no_run
+    /// # fn example(vdso: elfling::vdso::Vdso<'static>) {
+    /// type ClockGettime = unsafe extern "C" fn(i32, *mut [i64; 2]) -> i32;
+    ///
+    /// if let Some(address) = vdso.lookup(b"__vdso_clock_gettime", b"LINUX_2.6") {
+    ///     let clock_gettime: ClockGettime = unsafe { core::mem::transmute(address) };
+    /// }
+    /// # }
+    /// ```
+    pub fn lookup(&self, name: &[u8], version: &[u8]) -> Option<NonNull<c_void>> {
+        let index = self.symbols.find_with(name, |index, symbol| {
+            symbol.symbol_type() == SymbolType::FUNC
+                && matches!(
+                    symbol.binding(),
+                    SymbolBinding::GLOBAL | SymbolBinding::WEAK
+                )
+                && !symbol.is_undefined()
+                && self.symbols.has_version(index, version)
+        })?;
+
+        let symbol = self.symbols.symbols().get(index)?;
+        NonNull::new(symbol.st_value.wrapping_add(self.image.bias()) as usize as *mut c_void)
+    }
+
+    /// Returns the underlying image
+    pub fn image(&self) -> &ElfImage<'a> {
+        &self.image
+    }
+
+    /// Returns the dynamic symbol table and its lookup tables
+    pub fn symbols(&self) -> &DynamicSymbols<'a> {
+        &self.symbols
+    }
+}
@@ -0,0 +1,331 @@
+use zerocopy::{FromBytes, Immutable, IntoBytes};
+
+use crate::elf_enum;
+use crate::endian::impl_swap_bytes;
+use crate::string_table::StringTable;
+use crate::table::Table;
+
+/// Symbol version definition (Elf64_Verdef)
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes, IntoBytes, Immutable)]
+#[doc(alias = "Elf64_Verdef")]
+pub struct VersionDefinition64 {
+    /// Version revision of this structure
+    pub vd_version: u16,
+    /// Version information flags
+    pub vd_flags: u16,
+    /// Version index, as referenced by the symbol version table
+    pub vd_ndx: u16,
+    /// Number of associated auxiliary entries
+    pub vd_cnt: u16,
+    /// SysV hash of the version name
+    pub vd_hash: u32,
+    /// Offset in bytes from this entry to its first auxiliary entry
+    pub vd_aux: u32,
+    /// Offset in bytes from this entry to the next definition
+    pub vd_next: u32,
+}
+
+impl_swap_bytes!(VersionDefinition64 {
+    vd_version,
+    vd_flags,
+    vd_ndx,
+    vd_cnt,
+    vd_hash,
+    vd_aux,
+    vd_next,
+});
+
+/// Auxiliary symbol version definition (Elf64_Verdaux)
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes, IntoBytes, Immutable)]
+#[doc(alias = "Elf64_Verdaux")]
+pub struct VersionDefinitionAux64 {
+    /// String table offset of the version or dependency name
+    pub vda_name: u32,
+    /// Offset in bytes from this entry to the next one
+    pub vda_next: u32,
+}
+
+impl_swap_bytes!(VersionDefinitionAux64 { vda_name, vda_next });
+
+/// Symbol version requirement (Elf64_Verneed)
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes, IntoBytes, Immutable)]
+#[doc(alias = "Elf64_Verneed")]
+pub struct VersionNeed64 {
+    /// Version revision of this structure
+    pub vn_version: u16,
+    /// Number of associated auxiliary entries
+    pub vn_cnt: u16,
+    /// String table offset of the name of the needed object
+    pub vn_file: u32,
+    /// Offset in bytes from this entry to its first auxiliary entry
+    pub vn_aux: u32,
+    /// Offset in bytes from this entry to the next requirement
+    pub vn_next: u32,
+}
+
+impl_swap_bytes!(VersionNeed64 {
+    vn_version,
+    vn_cnt,
+    vn_file,
+    vn_aux,
+    vn_next,
+});
+
+/// Auxiliary symbol version requirement (Elf64_Vernaux), one per version
+/// needed from an object
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, FromBytes, IntoBytes, Immutable)]
+#[doc(alias = "Elf64_Vernaux")]
+pub struct VersionNeedAux64 {
+    /// SysV hash of the version name
+    pub vna_hash: u32,
+    /// Version information flags
+    pub vna_flags: u16,
+    /// Version index, as referenced by the symbol version table
+    pub vna_other: u16,
+    /// String table offset of the version name
+    pub vna_name: u32,
+    /// Offset in bytes from this entry to the next one
+    pub vna_next: u32,
+}
+
+impl_swap_bytes!(VersionNeedAux64 {
+    vna_hash,
+    vna_flags,
+    vna_other,
+    vna_name,
+    vna_next,
+});
+
+/// Flag in a [`VersionDefinition64`] marking the version of the object itself
+#[doc(alias = "VER_FLG_BASE")]
+pub const VERSION_FLAG_BASE: u16 = 0x1;
+/// Flag marking a weak version reference
+#[doc(alias = "VER_FLG_WEAK")]
+pub const VERSION_FLAG_WEAK: u16 = 0x2;
+
+elf_enum! {
+    /// Entry of the symbol version table (`DT_VERSYM`, `.gnu.version`)
+    pub struct VersionIndex(u16) {
+        /// Symbol is local and not available outside the object
+        LOCAL = 0, "*local*",
+        /// Symbol is global but unversioned
+        GLOBAL = 1, "*global*",
+    }
+}
+
+impl VersionIndex {
+    /// Returns the version index without the hidden bit
+    pub const fn index(&self) -> u16 {
+        self.inner & 0x7fff
+    }
+
+    /// Returns true if the symbol is hidden, meaning it can only be bound to
+    /// by explicitly requesting this version
+    pub const fn is_hidden(&self) -> bool {
+        self.inner & 0x8000 != 0
+    }
+}
+
+/// A view over a symbol version table
+pub type VersionTable<'a> = Table<'a, VersionIndex>;
+
+/// A version definition together with its name
+#[derive(Debug, Clone)]
+pub struct VersionDefinition<'a> {
+    pub definition: VersionDefinition64,
+    /// The name of the version, from the first auxiliary entry
+    pub name: Option<&'a [u8]>,
+    /// Auxiliary entries, the first of which names this version
+    pub auxiliary: VersionDefinitionAuxIter<'a>,
+}
+
+/// Iterator over a chain of version definitions (`DT_VERDEF`, `.gnu.version_d`)
+#[derive(Debug, Clone)]
+pub struct VersionDefinitionIter<'a> {
+    data: &'a [u8],
+    strings: StringTable<'a>,
+    offset: Option<usize>,
+    remaining: usize,
+}
+
+impl<'a> VersionDefinitionIter<'a> {
+    /// Creates an iterator over at most `count` definitions starting at the
+    /// beginning of `data`
+    pub fn new(data: &'a [u8], count: usize, strings: StringTable<'a>) -> Self {
+        Self {
+            data,
+            strings,
+            offset: Some(0),
+            remaining: count,
+        }
+    }
+}
+
+impl<'a> Iterator for VersionDefinitionIter<'a> {
+    type Item = VersionDefinition<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let offset = self.offset?;
+        let (definition, _) =
+            VersionDefinition64::read_from_prefix(self.data.get(offset..)?).ok()?;
+
+        self.remaining -= 1;
+        self.offset = match definition.vd_next {
+            0 => None,
+            next => offset.checked_add(next as usize),
+        };
+
+        let auxiliary = VersionDefinitionAuxIter {
+            data: self.data,
+            strings: self.strings,
+            offset: offset.checked_add(definition.vd_aux as usize),
+            remaining: definition.vd_cnt as usize,
+        };
+
+        let name = auxiliary
+            .clone()
+            .next()
+            .and_then(|(aux, _)| self.strings.get(aux.vda_name));
+
+        Some(VersionDefinition {
+            definition,
+            name,
+            auxiliary,
+        })
+    }
+}
+
+/// Iterator over the auxiliary entries of a version definition
+#[derive(Debug, Clone)]
+pub struct VersionDefinitionAuxIter<'a> {
+    data: &'a [u8],
+    strings: StringTable<'a>,
+    offset: Option<usize>,
+    remaining: usize,
+}
+
+impl<'a> Iterator for VersionDefinitionAuxIter<'a> {
+    type Item = (VersionDefinitionAux64, Option<&'a [u8]>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let offset = self.offset?;
+        let (aux, _) = VersionDefinitionAux64::read_from_prefix(self.data.get(offset..)?).ok()?;
+
+        self.remaining -= 1;
+        self.offset = match aux.vda_next {
+            0 => None,
+            next => offset.checked_add(next as usize),
+        };
+
+        let name = self.strings.get(aux.vda_name);
+        Some((aux, name))
+    }
+}
+
+/// A version requirement together with the name of the needed object
+#[derive(Debug, Clone)]
+pub struct VersionNeed<'a> {
+    pub need: VersionNeed64,
+    /// The name of the needed object
+    pub file: Option<&'a [u8]>,
+    /// Auxiliary entries, one per needed version
+    pub auxiliary: VersionNeedAuxIter<'a>,
+}
+
+/// Iterator over a chain of version requirements (`DT_VERNEED`,
+/// `.gnu.version_r`)
+#[derive(Debug, Clone)]
+pub struct VersionNeedIter<'a> {
+    data: &'a [u8],
+    strings: StringTable<'a>,
+    offset: Option<usize>,
+    remaining: usize,
+}
+
+impl<'a> VersionNeedIter<'a> {
+    /// Creates an iterator over at most `count` requirements starting at the
+    /// beginning of `data`
+    pub fn new(data: &'a [u8], count: usize, strings: StringTable<'a>) -> Self {
+        Self {
+            data,
+            strings,
+            offset: Some(0),
+            remaining: count,
+        }
+    }
+}
+
+impl<'a> Iterator for VersionNeedIter<'a> {
+    type Item = VersionNeed<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let offset = self.offset?;
+        let (need, _) = VersionNeed64::read_from_prefix(self.data.get(offset..)?).ok()?;
+
+        self.remaining -= 1;
+        self.offset = match need.vn_next {
+            0 => None,
+            next => offset.checked_add(next as usize),
+        };
+
+        let auxiliary = VersionNeedAuxIter {
+            data: self.data,
+            strings: self.strings,
+            offset: offset.checked_add(need.vn_aux as usize),
+            remaining: need.vn_cnt as usize,
+        };
+
+        Some(VersionNeed {
+            file: self.strings.get(need.vn_file),
+            need,
+            auxiliary,
+        })
+    }
+}
+
+/// Iterator over the auxiliary entries of a version requirement
+#[derive(Debug, Clone)]
+pub struct VersionNeedAuxIter<'a> {
+    data: &'a [u8],
+    strings: StringTable<'a>,
+    offset: Option<usize>,
+    remaining: usize,
+}
+
+impl<'a> Iterator for VersionNeedAuxIter<'a> {
+    type Item = (VersionNeedAux64, Option<&'a [u8]>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let offset = self.offset?;
+        let (aux, _) = VersionNeedAux64::read_from_prefix(self.data.get(offset..)?).ok()?;
+
+        self.remaining -= 1;
+        self.offset = match aux.vna_next {
+            0 => None,
+            next => offset.checked_add(next as usize),
+        };
+
+        let name = self.strings.get(aux.vna_name);
+        Some((aux, name))
+    }
+}